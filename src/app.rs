@@ -1,36 +1,257 @@
 use std::cmp::Ordering;
+use der::Tag;
 use tui::widgets::ListState;
-use crate::asn1_der::{Asn1Error, ASN1Node, flatten_nodes, parse_asn1};
+use crate::asn1_der::{Asn1Error, ASN1Node, ASN1Value, find_der, flatten_nodes, parse_asn1, parse_asn1_records, parse_tlv_records};
+use crate::decrypt::{attach_plain, decrypt_tree, has_encrypted, legacy, pkcs12, DecryptError};
+use crate::input::pem::PemBlock;
+use crate::input::utils::{InputData, InputFormat, RawInput};
+use crate::schema::{annotate, cdr, SchemaOptions};
 
 #[derive(Debug)]
 pub struct App {
     pub(crate) view: Vec<ASN1Node>,
     pub(crate) nodes: Vec<ASN1Node>,
     pub(crate) state: ListState,
+    pub(crate) status: Option<String>,
+    pub(crate) passphrase_prompt: Option<String>,
+    roots: Vec<ASN1Node>,
+    options: SchemaOptions,
+    /// Labels put in front of the recognized label of each root: the label of the PEM block
+    /// a root was read from or the position of a structure found by a scan.
+    root_labels: Vec<Option<String>>,
+    /// Legacy encrypted PEM blocks by the index of their root.
+    encrypted_pem: Vec<(usize, PemBlock)>,
+    /// The input as it was read and the format it is shown in.
+    source: RawInput,
+    format: InputFormat,
 }
 
 impl App {
-    pub(crate) fn new(input: Vec<u8>) -> Result<Self, Asn1Error> {
-        let mut root = parse_asn1(&input, 0)?;
-        root.visible = true;
-
-        let mut state = ListState::default();
-        state.select(Some(0));
-
-        let mut nodes: Vec<ASN1Node> = flatten_nodes(&root);
-        let mut i = 0;
-        while i < nodes.len() {
-            nodes[i].index = i;
-            i = i + 1;
+    pub(crate) fn new(input: InputData, options: &SchemaOptions) -> Result<Self, Asn1Error> {
+        let mut app = App {
+            view: Vec::new(),
+            nodes: Vec::new(),
+            state: ListState::default(),
+            status: None,
+            passphrase_prompt: None,
+            roots: Vec::new(),
+            options: options.clone(),
+            root_labels: Vec::new(),
+            encrypted_pem: Vec::new(),
+            source: RawInput::default(),
+            format: InputFormat::default(),
+        };
+        app.load(input)?;
+
+        Ok(app)
+    }
+
+    /// Shows the trees of the input in place of the trees shown before.
+    fn load(&mut self, input: InputData) -> Result<(), Asn1Error> {
+        let options = &self.options;
+        let mut root_labels = Vec::new();
+        let mut encrypted_pem = Vec::new();
+        let (mut roots, status) = if let Some(decoding) = &options.decoding {
+            if input.entries.is_empty() {
+                decoding.decode(&input.data)
+            } else {
+                let mut roots = Vec::new();
+                let mut failures = Vec::new();
+                for entry in &input.entries {
+                    let (records, status) = decoding.decode(&entry.data);
+                    root_labels.extend(records.iter().map(|_| Some(format!("[{}]", entry.name))));
+                    roots.extend(records);
+                    failures.extend(status.map(|status| format!("{}: {}", entry.name, status)));
+                }
+                let status = match failures.len() {
+                    0 => format!("{} records, n and N switch between them", input.entries.len()),
+                    1 => failures.remove(0),
+                    count => format!("{} and {} more records failed", failures.remove(0), count - 1),
+                };
+                (roots, Some(status))
+            }
+        } else {
+            let blocks = input.pem.len();
+            let entries = input.entries.len();
+            let hits = options.scan.map(|min_size| find_der(&input.data, min_size));
+            let mut roots = if let Some(hits) = &hits {
+                root_labels = hits.iter().map(|(offset, length)| Some(format!("[{:#x}, {} bytes]", offset, length))).collect();
+                hits.iter().map(|&(offset, length)| parse_asn1(&input.data[offset..offset + length], 0)).collect::<Result<_, _>>()?
+            } else if options.tlv {
+                parse_tlv_records(&input.data)?
+            } else if !input.entries.is_empty() {
+                let mut roots = Vec::new();
+                for entry in input.entries {
+                    let records = records(&entry.data);
+                    root_labels.extend(records.iter().map(|_| Some(format!("[{}]", entry.name))));
+                    roots.extend(records);
+                }
+                roots
+            } else if input.pem.is_empty() {
+                parse_asn1_records(&input.data)?
+            } else {
+                let mut roots = Vec::new();
+                for block in input.pem {
+                    if legacy::is_encrypted(&block) {
+                        // the label of the encrypted node names the block already
+                        root_labels.push(None);
+                        roots.push(legacy::encrypted_node(&block, &block.body));
+                        encrypted_pem.push((roots.len() - 1, block));
+                        continue;
+                    }
+                    let records = records(&block.body);
+                    root_labels.extend(records.iter().map(|_| Some(format!("[{}]", block.label))));
+                    roots.extend(records);
+                }
+                roots
+            };
+            for root in roots.iter_mut() {
+                annotate(root, options);
+            }
+            if options.cdr {
+                cdr::number_records(&mut roots);
+            }
+            let status = match (options.cdr, hits) {
+                (true, _) => Some(format!("{} record(s)", roots.len())),
+                (false, Some(hits)) => Some(format!("{} DER structure(s) found, n and N move between them", hits.len())),
+                (false, None) if entries > 1 => Some(format!("{} entries, n and N switch between them", entries)),
+                (false, None) => (blocks > 1).then(|| format!("{} PEM blocks, n and N switch between them", blocks)),
+            };
+            (roots, status)
+        };
+        for root in roots.iter_mut() {
+            root.visible = true;
         }
 
-        let app = App {
-            view: get_view(nodes.clone()),
-            nodes: nodes.clone(),
-            state,
+        self.roots = roots;
+        self.status = [input.report, status].into_iter().flatten().reduce(|report, status| format!("{}. {}", report, status));
+        self.root_labels = root_labels;
+        self.encrypted_pem = encrypted_pem;
+        self.source = input.source;
+        self.format = input.format;
+        self.label_roots();
+        self.state.select(Some(0));
+        self.rebuild();
+        Ok(())
+    }
+
+    /// Reads the input again in the next input format, the trees stay when the input is not in that format.
+    pub(crate) fn next_input_format(&mut self) {
+        let format = self.format.next();
+        let result = self
+            .source
+            .clone()
+            .decode(format)
+            .map_err(|error| error.to_string())
+            .and_then(|input| self.load(input).map_err(|error| error.to_string()));
+        if let Err(error) = result {
+            // the next key press tries the format after this one
+            self.format = format;
+            self.status = Some(format!("Cannot read the input as {}: {}", format, error));
+        }
+    }
+
+    fn rebuild(&mut self) {
+        let mut nodes: Vec<ASN1Node> = self.roots.iter().flat_map(flatten_nodes).collect();
+        for (i, node) in nodes.iter_mut().enumerate() {
+            node.index = i;
+        }
+
+        self.view = get_view(nodes.clone());
+        self.nodes = nodes;
+        if self.state.selected().map(|selected| selected >= self.view.len()).unwrap_or(true) {
+            self.state.select(Some(0));
+        }
+    }
+
+    /// Returns true while the input contains encrypted keys which are not decrypted yet.
+    pub(crate) fn needs_passphrase(&self) -> bool {
+        self.encrypted_pem.iter().any(|(i, _)| matches!(self.roots[*i].value, ASN1Value::Bytes(_)))
+            || self.roots.iter().any(|root| has_encrypted(root) || pkcs12::has_unverified_mac(root))
+    }
+
+    /// Decrypts the encrypted content with the passphrase and shows the result in the status line.
+    pub(crate) fn decrypt(&mut self, passphrase: &str) {
+        let mac = self.roots.iter_mut().find_map(|root| pkcs12::check_mac(root, passphrase));
+        let result = self.decrypt_pem(passphrase).and_then(|pem| {
+            let mut decrypted = pem;
+            let mut error = None;
+            for root in self.roots.iter_mut() {
+                match decrypt_tree(root, passphrase, &self.options) {
+                    Ok(count) => decrypted += count,
+                    Err(err) => error = Some(err),
+                }
+            }
+            match error {
+                Some(err) if decrypted == 0 => Err(err),
+                _ => Ok(decrypted),
+            }
+        });
+
+        let status = match &result {
+            Ok(0) => "Nothing to decrypt".to_string(),
+            Ok(count) => {
+                // decrypted containers such as PKCS#12 SafeContents are labelled by their parent
+                for root in self.roots.iter_mut() {
+                    annotate(root, &self.options);
+                }
+                self.label_roots();
+                format!("Decrypted {} item(s)", count)
+            }
+            Err(err) => err.to_string(),
         };
+        if mac.is_some() || matches!(result, Ok(count) if count > 0) {
+            self.rebuild();
+        }
+        self.status = Some(match mac {
+            Some(mac) => format!("{}. {}", mac, status),
+            None => status,
+        });
+    }
 
-        Ok(app)
+    fn decrypt_pem(&mut self, passphrase: &str) -> Result<usize, DecryptError> {
+        let mut decrypted = 0;
+        let mut error = None;
+        for (i, block) in &self.encrypted_pem {
+            let ASN1Value::Bytes(data) = &self.roots[*i].value else {
+                continue;
+            };
+            let result = legacy::decrypt(block, data, passphrase)
+                .and_then(|plain| attach_plain(&mut self.roots[*i], &plain, &self.options));
+            match result {
+                Ok(()) => decrypted += 1,
+                Err(err) => error = Some(err),
+            }
+        }
+        match error {
+            Some(err) if decrypted == 0 => Err(err),
+            _ => Ok(decrypted),
+        }
+    }
+
+    /// Puts the PEM label or the position of each root in front of the label it was recognized with.
+    fn label_roots(&mut self) {
+        for (root, prefix) in self.roots.iter_mut().zip(&self.root_labels) {
+            let Some(prefix) = prefix else {
+                continue;
+            };
+            let label = match root.label.take() {
+                Some(label) if label.starts_with(prefix) => label,
+                Some(label) => format!("{} {}", prefix, label),
+                None => prefix.clone(),
+            };
+            root.set_label(&label);
+        }
+    }
+
+    pub(crate) fn start_passphrase_prompt(&mut self) {
+        self.passphrase_prompt = Some(String::new());
+    }
+
+    pub(crate) fn submit_passphrase_prompt(&mut self) {
+        if let Some(passphrase) = self.passphrase_prompt.take() {
+            self.decrypt(&passphrase);
+        }
     }
 
     pub(crate) fn toggle_selected(&mut self) {
@@ -58,16 +279,12 @@ impl App {
                         self.nodes[i].visible = false;
                     } else {
                         self.nodes[i].expanded = false;
-                        if self.nodes[i].level > next_level {
-                            self.nodes[i].visible = false;
-                        } else {
-                            self.nodes[i].visible = true;
-                        }
+                        self.nodes[i].visible = self.nodes[i].level <= next_level;
                     }
                 }
                 Ordering::Less => break,
             };
-            i = i + 1;
+            i += 1;
         }
     }
 
@@ -106,6 +323,32 @@ impl App {
         self.state.select(Some(i));
     }
 
+    /// Moves the selection to the first line of the next top level record, e.g. the next CDR.
+    pub(crate) fn next_record(&mut self) {
+        let selected = self.state.selected().unwrap_or(0);
+        let next = self.view.iter().enumerate().skip(selected + 1).find(|(_, node)| node.level == 0);
+        if let Some((i, _)) = next {
+            self.state.select(Some(i));
+        }
+        self.show_record_position();
+    }
+
+    pub(crate) fn prev_record(&mut self) {
+        let selected = self.state.selected().unwrap_or(0);
+        let prev = self.view[..selected].iter().rposition(|node| node.level == 0);
+        if let Some(i) = prev {
+            self.state.select(Some(i));
+        }
+        self.show_record_position();
+    }
+
+    fn show_record_position(&mut self) {
+        if let Some(selected) = self.state.selected() {
+            let record = self.view[..=selected].iter().filter(|node| node.level == 0).count();
+            self.status = Some(format!("Record {}/{}", record, self.roots.len()));
+        }
+    }
+
     pub(crate) fn first(&mut self) {
         self.state.select(Some(0));
     }
@@ -120,4 +363,14 @@ fn get_view(nodes: Vec<ASN1Node>) -> Vec<ASN1Node> {
         .into_iter()
         .filter(|node| node.visible)
         .collect()
+}
+
+/// Reads the records of a PEM block or an extracted entry, data which is not BER is shown as its
+/// bytes with the error so that the other blocks of the bundle can still be viewed.
+fn records(data: &[u8]) -> Vec<ASN1Node> {
+    parse_asn1_records(data).unwrap_or_else(|error| {
+        let mut node = ASN1Node::new(Tag::OctetString, ASN1Value::Bytes(data.to_vec()), 0, false, false);
+        node.set_annotation(error.to_string());
+        vec![node]
+    })
 }
\ No newline at end of file
@@ -1,8 +1,7 @@
 use std::error::Error;
 use std::fmt::{Display, Formatter};
-use std::mem;
-use der::{Decode, Result, asn1::{Any, ObjectIdentifier}, Tagged, Tag, Reader};
-use der::asn1::{BitString, Ia5String, OctetString, PrintableString, UtcTime};
+use der::{ErrorKind, Length, Result, asn1::{Any, ObjectIdentifier}, Tagged, Tag, TagNumber};
+use der::asn1::{BitString, BmpString, GeneralizedTime, Ia5String, OctetString, PrintableString, TeletexString, UtcTime};
 use log::{warn};
 
 #[derive(Debug)]
@@ -32,50 +31,162 @@ impl Display for Asn1Error {
 pub enum ASN1Value {
     Boolean(bool),
     Integer(u128),
+    BigInteger(Vec<u8>),
     BitString(BitString),
-    OctetString(Box<ASN1Node>),
+    OctetString(Box<ASN1Node>, Vec<u8>),
+    Bytes(Vec<u8>),
     Null,
     ObjectIdentifier(ObjectIdentifier),
     Utf8String(Vec<u8>),
     Sequence(Vec<ASN1Node>),
-    Set(Box<ASN1Node>),
+    Set(Vec<ASN1Node>),
     PrintableString(PrintableString),
     Ia5String(Ia5String),
+    CharacterString(String),
     UtcTime(UtcTime),
-    Application(Box<ASN1Node>),
-    ContextSpecific(Box<ASN1Node>),
-    Private(Box<ASN1Node>),
+    GeneralizedTime(GeneralizedTime),
+    Application(Vec<ASN1Node>),
+    ContextSpecific(Vec<ASN1Node>),
+    Private(Vec<ASN1Node>),
     Other(String),
 }
 
 #[derive(Clone, Debug)]
 pub struct ASN1Node {
     pub(crate) tag: Tag,
+    /// Tag number of the high-tag-number form (above 30), `tag` then holds the class only.
+    pub(crate) tag_number: Option<u32>,
+    /// Identifier octets of a BER-TLV data object such as `0x9F02`, whose meaning comes from a tag dictionary.
+    pub(crate) tlv_tag: Option<u32>,
     pub(crate) value: ASN1Value,
     pub(crate) level: u32,
     pub(crate) expandable: bool,
     pub(crate) expanded: bool,
     pub(crate) visible: bool,
     pub(crate) index: usize,
+    pub(crate) label: Option<String>,
+    pub(crate) annotation: Option<String>,
+    pub(crate) secret: bool,
 }
 
 impl ASN1Node {
     pub(crate) fn new(tag: Tag, value: ASN1Value, level: u32, expandable: bool, visible: bool) -> Self {
         ASN1Node {
             tag,
+            tag_number: None,
+            tlv_tag: None,
             value,
             level,
             expandable,
             expanded: false,
             visible,
-            index: 0
+            index: 0,
+            label: None,
+            annotation: None,
+            secret: false,
+        }
+    }
+
+    pub(crate) fn children(&self) -> &[ASN1Node] {
+        match &self.value {
+            ASN1Value::Sequence(children) |
+            ASN1Value::Set(children) |
+            ASN1Value::ContextSpecific(children) |
+            ASN1Value::Application(children) |
+            ASN1Value::Private(children) => children,
+            ASN1Value::OctetString(child, _) => std::slice::from_ref(child.as_ref()),
+            _ => &[],
+        }
+    }
+
+    pub(crate) fn children_mut(&mut self) -> &mut [ASN1Node] {
+        match &mut self.value {
+            ASN1Value::Sequence(children) |
+            ASN1Value::Set(children) |
+            ASN1Value::ContextSpecific(children) |
+            ASN1Value::Application(children) |
+            ASN1Value::Private(children) => children,
+            ASN1Value::OctetString(child, _) => std::slice::from_mut(child.as_mut()),
+            _ => &mut [],
+        }
+    }
+
+    /// Returns the tag number, including numbers above 30 which `der::Tag` cannot hold.
+    pub(crate) fn tag_number(&self) -> u32 {
+        self.tag_number.unwrap_or_else(|| self.tag.number().value() as u32)
+    }
+
+    fn tag_name(&self) -> String {
+        if let Some(tag) = self.tlv_tag {
+            return format!("{:02X}", tag);
+        }
+        let Some(number) = self.tag_number else {
+            return self.tag.to_string();
+        };
+        let class = match self.tag {
+            Tag::Application { .. } => "APPLICATION",
+            Tag::Private { .. } => "PRIVATE",
+            _ => "CONTEXT-SPECIFIC",
+        };
+        let form = if self.tag.is_constructed() { "constructed" } else { "primitive" };
+        format!("{} [{}] ({})", class, number, form)
+    }
+
+    pub(crate) fn as_oid(&self) -> Option<ObjectIdentifier> {
+        match &self.value {
+            ASN1Value::ObjectIdentifier(oid) => Some(*oid),
+            _ => None,
+        }
+    }
+
+    /// Returns the content octets of an OCTET STRING, whether or not it holds nested DER.
+    pub(crate) fn octets(&self) -> Option<&[u8]> {
+        match &self.value {
+            ASN1Value::OctetString(_, bytes) | ASN1Value::Bytes(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    /// Returns the text of string and time values without quotes.
+    pub(crate) fn as_string(&self) -> Option<String> {
+        match &self.value {
+            ASN1Value::Utf8String(bytes) => Some(String::from_utf8_lossy(bytes).to_string()),
+            ASN1Value::PrintableString(s) => Some(s.to_string()),
+            ASN1Value::Ia5String(s) => Some(s.to_string()),
+            ASN1Value::CharacterString(s) => Some(s.clone()),
+            ASN1Value::UtcTime(utc) => Some(utc.to_date_time().to_string()),
+            ASN1Value::GeneralizedTime(time) => Some(time.to_date_time().to_string()),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_u128(&self) -> Option<u128> {
+        match &self.value {
+            ASN1Value::Integer(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn set_label(&mut self, label: &str) {
+        self.label = Some(label.to_string());
+    }
+
+    pub(crate) fn set_annotation(&mut self, annotation: impl Into<String>) {
+        self.annotation = Some(annotation.into());
+    }
+
+    /// Marks the node and everything below it as secret so that its value is masked.
+    pub(crate) fn mark_secret(&mut self) {
+        self.secret = true;
+        for child in self.children_mut() {
+            child.mark_secret();
         }
     }
 
     pub(crate) fn toggle_expand(&mut self) {
         if let ASN1Value::Sequence(_) |
         ASN1Value::Set(_) |
-        ASN1Value::OctetString(_) |
+        ASN1Value::OctetString(..) |
         ASN1Value::ContextSpecific(_) |
         ASN1Value::Application(_) |
         ASN1Value::Private(_) = self.value {
@@ -84,157 +195,574 @@ impl ASN1Node {
     }
 
     pub(crate) fn display_value(&self) -> String {
+        if self.secret {
+            return match &self.value {
+                ASN1Value::BigInteger(bytes) | ASN1Value::Bytes(bytes) => format!("<redacted, {} bytes>", bytes.len()),
+                _ => "<redacted>".to_string(),
+            };
+        }
+
         match &self.value {
             ASN1Value::Integer(i) => format!("{:?}", i),
-            ASN1Value::OctetString(_) => "".to_string(),
+            ASN1Value::BigInteger(bytes) => format!("0x{}", hex::encode(bytes)),
+            ASN1Value::OctetString(..) => "".to_string(),
+            ASN1Value::Bytes(bytes) => hex::encode(bytes),
             ASN1Value::Sequence(children) => format!("(field(s): {:?})", children.len()),
-            ASN1Value::Set(_) => "".to_string(),
+            ASN1Value::Set(children) => format!("(item(s): {:?})", children.len()),
             ASN1Value::Boolean(b) => format!("{}", b),
             ASN1Value::Null => "Null".to_string(),
-            ASN1Value::ObjectIdentifier(oid) => format!("{}", oid.to_string()),
+            ASN1Value::ObjectIdentifier(oid) => oid.to_string(),
             ASN1Value::Utf8String(vec) => {
                 let string = String::from_utf8(vec.clone()).unwrap_or_else(|_| "".to_string());
                 format!("{:?}", string)
             }
             ASN1Value::PrintableString(s) => format!("{:?}", s.to_string()),
             ASN1Value::Ia5String(s) => format!("{:?}", s.to_string()),
+            ASN1Value::CharacterString(s) => format!("{:?}", s),
             ASN1Value::UtcTime(utc) => format!("{:?}", utc.to_date_time().to_string()),
+            ASN1Value::GeneralizedTime(time) => format!("{:?}", time.to_date_time().to_string()),
             ASN1Value::BitString(bits) => {
-                let bits_string = bits.raw_bytes().iter()
+                bits.raw_bytes().iter()
                     .map(|&byte| format!("{:08b}", byte))
                     .collect::<Vec<String>>()
-                    .join("");
-                format!("{}", bits_string)
+                    .join("")
             }
             ASN1Value::ContextSpecific(_) => "".to_string(),
             ASN1Value::Application(_) => "".to_string(),
             ASN1Value::Private(_) => "".to_string(),
-            ASN1Value::Other(s) => format!("{:?}", s.to_string()),
+            ASN1Value::Other(s) => format!("{:?}", s),
         }
     }
 
     pub(crate) fn get_view_content(&mut self) -> String {
         let level_repeat = " ".repeat((self.level * 2) as usize);
         let expand = if self.expandable { if self.expanded { "- " } else { "+ " } } else { "  " };
-        format!("{} {} {} {}", expand, level_repeat, self.tag.to_string(), self.display_value())
+        let label = self.label.as_ref().map(|label| format!("{}: ", label)).unwrap_or_default();
+        let annotation = self.annotation.as_ref().map(|annotation| format!(" ({})", annotation)).unwrap_or_default();
+        format!("{} {} {}{} {}{}", expand, level_repeat, label, self.tag_name(), self.display_value(), annotation)
     }
 }
 
 pub fn parse_asn1(data: &[u8], level: u32) -> Result<ASN1Node> {
-    let mut data_vec = data.to_vec();
-    match data_vec[0] {
+    match read_element(data, level) {
+        Ok((element, _)) => element.parse(level),
+        Err(_) => parse_any(Any::null(), level),
+    }
+}
+
+/// Parses all consecutive top level elements, e.g. a file of concatenated BER records.
+pub fn parse_asn1_records(data: &[u8]) -> Result<Vec<ASN1Node>> {
+    let mut records = Vec::new();
+    let mut rest = data;
+    while !rest.is_empty() {
+        match read_element(rest, 0) {
+            Ok((element, length)) => {
+                records.push(element.parse(0)?);
+                rest = &rest[length..];
+            }
+            Err(_) if !records.is_empty() => {
+                warn!("Skipped {} trailing byte(s) which are not BER encoded", rest.len());
+                break;
+            }
+            Err(_) => return Ok(vec![parse_asn1(data, 0)?]),
+        }
+    }
+    if records.is_empty() {
+        records.push(parse_asn1(data, 0)?);
+    }
+    Ok(records)
+}
+
+/// Returns true if the data is one or more BER elements with no bytes left over.
+pub(crate) fn is_complete_ber(data: &[u8]) -> bool {
+    !data.is_empty() && complete_ber_prefix(data) == data.len()
+}
+
+/// Returns the length of the BER elements at the start of the data, up to the first one which
+/// is cut off or invalid, such as the messages of a captured stream which ends mid-message.
+pub(crate) fn complete_ber_prefix(data: &[u8]) -> usize {
+    let mut end = 0;
+    while let Ok((_, length)) = read_element(&data[end..], 0) {
+        end += length;
+    }
+    end
+}
+
+/// Deepest nesting of elements which is parsed or followed by `find_der`, deeper input such as a run
+/// of `30 80` headers is rejected instead of overflowing the stack.
+const MAX_DEPTH: u32 = 64;
+
+/// Finds DER structures in binary data such as firmware images: SEQUENCEs of at least `min_size` bytes
+/// with definite lengths whose constructed contents are elements up to their end. Returns the offset and
+/// length of each structure, the structures inside a structure found are not listed.
+pub(crate) fn find_der(data: &[u8], min_size: usize) -> Vec<(usize, usize)> {
+    let mut hits = Vec::new();
+    let mut offset = 0;
+    while offset < data.len() {
+        match data[offset] {
+            0x30 => match der_length(&data[offset..], 0) {
+                Some(length) if length >= min_size => {
+                    hits.push((offset, length));
+                    offset += length;
+                }
+                _ => offset += 1,
+            },
+            _ => offset += 1,
+        }
+    }
+    hits
+}
+
+/// Returns the length of the element at the start of the data when it is plausible DER. End-of-contents
+/// octets are not, so that runs of zero bytes such as padding are not taken for elements.
+fn der_length(data: &[u8], depth: u32) -> Option<usize> {
+    if data.first() == Some(&0) {
+        return None;
+    }
+    let (tag, _, identifier) = read_identifier(data).ok()?;
+    let (length, header) = read_length(data, identifier).ok()?;
+    let end = header.checked_add(length?)?;
+    let content = data.get(header..end)?;
+    if tag.is_constructed() {
+        if depth == MAX_DEPTH {
+            return None;
+        }
+        let mut rest = content;
+        while !rest.is_empty() {
+            rest = &rest[der_length(rest, depth + 1)?..];
+        }
+    }
+    Some(end)
+}
+
+/// Parses BER-TLV data objects such as smart-card responses, where tags carry no ASN.1 type.
+/// A trailing `90 00` status word is shown as a separate node.
+pub fn parse_tlv_records(data: &[u8]) -> Result<Vec<ASN1Node>> {
+    let (objects, status) = match data {
+        [objects @ .., 0x90, 0x00] if !objects.is_empty() => (objects, Some(&data[objects.len()..])),
+        _ => (data, None),
+    };
+    let mut records = parse_tlv(objects, 0)?;
+    if let Some(status) = status {
+        let mut node = ASN1Node::new(Tag::OctetString, ASN1Value::Bytes(status.to_vec()), 0, false, false);
+        node.set_label("SW1-SW2");
+        node.set_annotation("success");
+        records.push(node);
+    }
+    Ok(records)
+}
+
+fn parse_tlv(data: &[u8], level: u32) -> Result<Vec<ASN1Node>> {
+    if level > MAX_DEPTH {
+        return Err(ErrorKind::Overlength.into());
+    }
+    let mut nodes = Vec::new();
+    let mut rest = data;
+    while let Some(&first) = rest.first() {
+        // padding before, between and after data objects
+        if first == 0x00 || first == 0xff {
+            rest = &rest[1..];
+            continue;
+        }
+        let identifier = match first & 0x1f {
+            0x1f => 2 + rest[1..].iter().take_while(|&&byte| byte & 0x80 != 0).count(),
+            _ => 1,
+        };
+        if identifier > size_of::<u32>() {
+            return Err(ErrorKind::TagNumberInvalid.into());
+        }
+        let (length, header) = read_length(rest, identifier)?;
+        let length = length.ok_or(ErrorKind::IndefiniteLength)?;
+        let end = header.checked_add(length).filter(|&end| end <= rest.len()).ok_or(ErrorKind::Incomplete {
+            expected_len: Length::try_from(header.saturating_add(length))?,
+            actual_len: Length::try_from(rest.len())?,
+        })?;
+        let content = &rest[header..end];
+
+        let constructed = first & 0x20 != 0;
+        let tag = match first >> 6 {
+            0b01 => Tag::Application { constructed, number: TagNumber::N30 },
+            0b10 => Tag::ContextSpecific { constructed, number: TagNumber::N30 },
+            0b11 => Tag::Private { constructed, number: TagNumber::N30 },
+            _ if constructed => Tag::Sequence,
+            _ => Tag::OctetString,
+        };
+        let value = match parse_tlv(content, level + 1) {
+            Ok(children) if constructed => match tag {
+                Tag::Application { .. } => ASN1Value::Application(children),
+                Tag::ContextSpecific { .. } => ASN1Value::ContextSpecific(children),
+                Tag::Private { .. } => ASN1Value::Private(children),
+                _ => ASN1Value::Sequence(children),
+            },
+            _ => ASN1Value::Bytes(content.to_vec()),
+        };
+        let expandable = is_node_expandable(&value);
+        let mut node = ASN1Node::new(tag, value, level, expandable, false);
+        node.tlv_tag = Some(rest[..identifier].iter().fold(0, |tag, &byte| tag << 8 | byte as u32));
+        nodes.push(node);
+        rest = &rest[end..];
+    }
+    Ok(nodes)
+}
+
+/// Parses the content octets of a constructed value as a list of elements.
+fn parse_elements(data: &[u8], level: u32) -> Result<Vec<ASN1Node>> {
+    let mut children = Vec::new();
+    let mut rest = data;
+    while !rest.is_empty() {
+        let (element, length) = read_element(rest, level)?;
+        children.push(element.parse(level)?);
+        rest = &rest[length..];
+    }
+    Ok(children)
+}
+
+/// One BER element, `tag_number` is set when the tag needs the high-tag-number form.
+struct Element {
+    any: Any,
+    tag_number: Option<u32>,
+}
+
+impl Element {
+    fn parse(self, level: u32) -> Result<ASN1Node> {
+        let mut node = parse_any(self.any, level)?;
+        node.tag_number = self.tag_number;
+        Ok(node)
+    }
+}
+
+/// Reads one BER element at the nesting `depth` and returns it with its encoded length. Unlike DER this
+/// accepts non-minimal and indefinite lengths and tag numbers above 30, which LDAP, SNMP and CDR encoders produce.
+fn read_element(data: &[u8], depth: u32) -> Result<(Element, usize)> {
+    if depth > MAX_DEPTH {
+        return Err(ErrorKind::Overlength.into());
+    }
+    let (tag, tag_number, identifier) = read_identifier(data)?;
+    let (length, header) = read_length(data, identifier)?;
+
+    let (content, total) = match length {
+        Some(length) => {
+            let end = header.checked_add(length).filter(|&end| end <= data.len()).ok_or(ErrorKind::Incomplete {
+                expected_len: Length::try_from(header.saturating_add(length))?,
+                actual_len: Length::try_from(data.len())?,
+            })?;
+            (&data[header..end], end)
+        }
+        None => {
+            if !tag.is_constructed() {
+                return Err(ErrorKind::IndefiniteLength.into());
+            }
+            // the content runs until the end-of-contents octets of this element
+            let mut position = header;
+            while data.get(position..position + 2) != Some(&[0, 0]) {
+                let (_, length) = read_element(&data[position..], depth + 1)?;
+                position += length;
+            }
+            (&data[header..position], position + 2)
+        }
+    };
+    Ok((Element { any: Any::new(tag, content)?, tag_number }, total))
+}
+
+/// Reads the identifier octets and returns the tag, the tag number of the high-tag-number form
+/// and the length of the identifier.
+fn read_identifier(data: &[u8]) -> Result<(Tag, Option<u32>, usize)> {
+    let incomplete = |actual: usize| ErrorKind::Incomplete {
+        expected_len: Length::try_from(actual + 1).unwrap_or(Length::ONE),
+        actual_len: Length::try_from(actual).unwrap_or(Length::ZERO),
+    };
+    let (&first, rest) = data.split_first().ok_or_else(|| incomplete(0))?;
+    if first & 0x1f != 0x1f {
         // fix der for parsing GeneralString
-        27 => mem::swap(&mut data_vec[0], &mut 19),
-        _ => {}
+        let tag = if first == 27 { Tag::PrintableString } else { Tag::try_from(first)? };
+        return Ok((tag, None, 1));
     }
-    let data = data_vec.as_slice();
 
-    let any: Any = Any::from_der(data).unwrap_or_else(|_| {
-        Any::null()
-    });
+    let mut number = 0u32;
+    for (i, &byte) in rest.iter().enumerate() {
+        if number > u32::MAX >> 7 {
+            return Err(ErrorKind::TagNumberInvalid.into());
+        }
+        number = number << 7 | (byte & 0x7f) as u32;
+        if byte & 0x80 != 0 {
+            continue;
+        }
+        // der::Tag only holds numbers up to 30, larger ones are kept next to it
+        let (tag_number, high) = match u8::try_from(number) {
+            Ok(low) if low <= 30 => (TagNumber::new(low), None),
+            _ => (TagNumber::N30, Some(number)),
+        };
+        let constructed = first & 0x20 != 0;
+        let tag = match first >> 6 {
+            0b01 => Tag::Application { constructed, number: tag_number },
+            0b10 => Tag::ContextSpecific { constructed, number: tag_number },
+            0b11 => Tag::Private { constructed, number: tag_number },
+            _ => return Err(ErrorKind::TagUnknown { byte: first }.into()),
+        };
+        return Ok((tag, high, i + 2));
+    }
+    Err(incomplete(data.len()).into())
+}
+
+/// Reads the length octets after the identifier, `None` stands for the indefinite form.
+fn read_length(data: &[u8], identifier: usize) -> Result<(Option<usize>, usize)> {
+    let incomplete = || ErrorKind::Incomplete {
+        expected_len: Length::try_from(identifier + 1).unwrap_or(Length::ONE),
+        actual_len: Length::try_from(identifier).unwrap_or(Length::ZERO),
+    };
+    let first = *data.get(identifier).ok_or_else(incomplete)?;
+    let start = identifier + 1;
+    match first {
+        0x80 => Ok((None, start)),
+        short if short < 0x80 => Ok((Some(short as usize), start)),
+        long => {
+            let count = (long & 0x7f) as usize;
+            let bytes = data.get(start..start + count).ok_or_else(incomplete)?;
+            if count > size_of::<usize>() {
+                return Err(ErrorKind::Overlength.into());
+            }
+            let length = bytes.iter().fold(0usize, |length, &byte| length << 8 | byte as usize);
+            Ok((Some(length), start + count))
+        }
+    }
+}
+
+/// Parses the content of a constructed value, keeping the raw bytes when they are not valid DER.
+fn parse_constructed(data: &[u8], level: u32) -> Option<Vec<ASN1Node>> {
+    parse_elements(data, level).ok()
+}
 
-    Ok(parse_any(any, level)?)
+/// Parses the content of a primitive OCTET STRING when it holds exactly one complete element.
+fn parse_nested(data: &[u8], level: u32) -> Option<ASN1Node> {
+    match read_element(data, level) {
+        Ok((element, length)) if length == data.len() => element.parse(level).ok(),
+        _ => None,
+    }
 }
 
 fn parse_any(any: Any, level: u32) -> Result<ASN1Node> {
     let tag = any.tag();
     let value = match tag {
-        Tag::Integer => ASN1Value::Integer(any.decode_as::<u128>()?),
+        Tag::Integer => match any.decode_as::<u128>() {
+            Ok(value) => ASN1Value::Integer(value),
+            Err(_) => ASN1Value::BigInteger(any.value().to_vec()),
+        },
+        Tag::Enumerated => match any.value() {
+            bytes if bytes.len() <= 16 => ASN1Value::Integer(bytes.iter().fold(0, |value, &byte| value << 8 | byte as u128)),
+            bytes => ASN1Value::BigInteger(bytes.to_vec()),
+        },
         Tag::OctetString => {
             let inner = any.decode_as::<OctetString>()?;
-            let parsed_inner = parse_asn1(inner.as_bytes(), level + 1)?;
-            ASN1Value::OctetString(Box::new(parsed_inner))
-        }
-        Tag::Sequence => {
-            any.sequence(|decoder| {
-                let mut children: Vec<ASN1Node> = Vec::new();
-                while !decoder.is_finished() {
-                    let child: Any = decoder.decode()?;
-                    let parsed_child: ASN1Node = parse_any(child.clone(), level + 1)?;
-                    children.push(parsed_child);
-                }
-                Ok::<ASN1Value, der::Error>(ASN1Value::Sequence(children))
-            })?
-        }
-        Tag::Set => {
-            let inner: Any = any.decode_as()?;
-            let parsed_inner = parse_asn1(inner.value(), level + 1)?;
-            ASN1Value::Set(Box::new(parsed_inner))
+            match parse_nested(inner.as_bytes(), level + 1) {
+                Some(parsed_inner) => ASN1Value::OctetString(Box::new(parsed_inner), inner.as_bytes().to_vec()),
+                None => ASN1Value::Bytes(inner.as_bytes().to_vec()),
+            }
         }
+        Tag::Sequence => ASN1Value::Sequence(parse_elements(any.value(), level + 1)?),
+        Tag::Set => match parse_constructed(any.value(), level + 1) {
+            Some(children) => ASN1Value::Set(children),
+            None => ASN1Value::Bytes(any.value().to_vec()),
+        },
         Tag::BitString => ASN1Value::BitString(any.decode_as::<BitString>()?),
-        Tag::Boolean => ASN1Value::Boolean(any.decode_as::<bool>()?),
+        // BER allows any non-zero octet for TRUE
+        Tag::Boolean => match any.value() {
+            [value] => ASN1Value::Boolean(*value != 0),
+            _ => return Err(ErrorKind::Length { tag }.into()),
+        },
         Tag::Null => ASN1Value::Null,
         Tag::ObjectIdentifier => ASN1Value::ObjectIdentifier(any.decode_as::<ObjectIdentifier>()?),
         Tag::Utf8String => ASN1Value::Utf8String(String::from_utf8_lossy(any.value()).to_string().into()),
         Tag::Ia5String => ASN1Value::Ia5String(any.decode_as::<Ia5String>()?),
         Tag::UtcTime => ASN1Value::UtcTime(any.decode_as::<UtcTime>()?),
-        Tag::PrintableString => ASN1Value::PrintableString(any.decode_as::<PrintableString>()?),
-        Tag::Application { .. } => {
-            let inner: Any = any.decode_as()?;
-            let parsed_inner = parse_asn1(inner.value(), level + 1)?;
-            Ok::<ASN1Value, der::Error>(ASN1Value::Application(Box::new(parsed_inner)))
-        }?,
-        Tag::ContextSpecific { .. } => {
-            let inner: Any = any.decode_as()?;
-            if inner.tag().is_constructed() {
-                let parsed_inner = parse_asn1(inner.value(), level + 1)?;
-                Ok::<ASN1Value, der::Error>(ASN1Value::ContextSpecific(Box::new(parsed_inner)))
+        Tag::PrintableString => match any.decode_as::<PrintableString>() {
+            Ok(value) => ASN1Value::PrintableString(value),
+            // GeneralString values are read as PrintableString and may use any character
+            Err(_) => ASN1Value::CharacterString(String::from_utf8_lossy(any.value()).to_string()),
+        },
+        Tag::GeneralizedTime => ASN1Value::GeneralizedTime(any.decode_as::<GeneralizedTime>()?),
+        Tag::TeletexString => ASN1Value::CharacterString(any.decode_as::<TeletexString>()?.to_string()),
+        Tag::BmpString => ASN1Value::CharacterString(any.decode_as::<BmpString>()?.to_string()),
+        Tag::VisibleString | Tag::NumericString => {
+            ASN1Value::CharacterString(String::from_utf8_lossy(any.value()).to_string())
+        }
+        Tag::Application { constructed: true, .. } => match parse_constructed(any.value(), level + 1) {
+            Some(children) => ASN1Value::Application(children),
+            None => ASN1Value::Bytes(any.value().to_vec()),
+        },
+        Tag::Application { .. } => ASN1Value::Application(vec![raw_content_node(&any, level)]),
+        Tag::ContextSpecific { constructed, .. } => {
+            if constructed {
+                match parse_constructed(any.value(), level + 1) {
+                    Some(children) => ASN1Value::ContextSpecific(children),
+                    None => ASN1Value::Bytes(any.value().to_vec()),
+                }
             } else {
-                let node = ASN1Node::new(
-                    Tag::Utf8String,
-                    ASN1Value::Utf8String(Vec::from(inner.value())),
-                    level + 1,
-                    false,
-                    false
-                );
-                Ok::<ASN1Value, der::Error>(ASN1Value::ContextSpecific(Box::new(node)))
+                ASN1Value::ContextSpecific(vec![raw_content_node(&any, level)])
             }
-        }?,
-        Tag::Private { .. } => {
-            let inner: Any = any.decode_as()?;
-            let parsed_inner = parse_asn1(inner.value(), level + 1)?;
-            Ok::<ASN1Value, der::Error>(ASN1Value::Private(Box::new(parsed_inner)))
-        }?,
+        }
+        Tag::Private { .. } => match parse_constructed(any.value(), level + 1) {
+            Some(children) => ASN1Value::Private(children),
+            None => ASN1Value::Bytes(any.value().to_vec()),
+        },
         _ => {
             warn!("Unsupported tag: {:?}", tag.to_string());
             ASN1Value::Other(format!("Unsupported tag: {:?}", tag.to_string()))
         }
     };
-    Ok(ASN1Node::new(tag, value, level, is_node_expandable(tag), false))
+    let expandable = is_node_expandable(&value);
+    Ok(ASN1Node::new(tag, value, level, expandable, false))
 }
 
-fn is_node_expandable(tag: Tag) -> bool {
-    match tag {
-        Tag::Sequence |
-        Tag::Set |
-        Tag::OctetString |
-        Tag::ContextSpecific { .. } |
-        Tag::Application { .. } |
-        Tag::Private { .. }
-        => true,
+/// Keeps the content of an IMPLICIT tagged primitive value, its type is only known to the schema.
+fn raw_content_node(any: &Any, level: u32) -> ASN1Node {
+    ASN1Node::new(
+        Tag::Utf8String,
+        ASN1Value::Utf8String(Vec::from(any.value())),
+        level + 1,
+        false,
+        false
+    )
+}
 
-        _ => false,
-    }
+pub(crate) fn is_node_expandable(value: &ASN1Value) -> bool {
+    matches!(
+        value,
+        ASN1Value::Sequence(_) |
+        ASN1Value::Set(_) |
+        ASN1Value::OctetString(..) |
+        ASN1Value::ContextSpecific(_) |
+        ASN1Value::Application(_) |
+        ASN1Value::Private(_)
+    )
 }
 
 pub(crate) fn flatten_nodes(node: &ASN1Node) -> Vec<ASN1Node> {
     let mut nodes = vec![node.clone()];
 
     match &node.value {
-        ASN1Value::Sequence(children) => {
+        ASN1Value::Sequence(children) |
+        ASN1Value::Set(children) |
+        ASN1Value::ContextSpecific(children) |
+        ASN1Value::Application(children) |
+        ASN1Value::Private(children) => {
             for child in children {
-                nodes.extend(flatten_nodes(&child));
+                nodes.extend(flatten_nodes(child));
             }
         }
-        ASN1Value::Set(child) |
-        ASN1Value::OctetString(child) |
-        ASN1Value::ContextSpecific(child) |
-        ASN1Value::Application(child) |
-        ASN1Value::Private(child) => {
+        ASN1Value::OctetString(child, _) => {
             nodes.extend(flatten_nodes(child.as_ref()));
         }
         _ => {}
     }
 
     nodes
+}
+
+/// The lines the viewer shows for the tree with every node expanded, without indentation.
+#[cfg(test)]
+pub(crate) fn view_lines(node: &ASN1Node) -> Vec<String> {
+    flatten_nodes(node)
+        .iter_mut()
+        .map(|node| {
+            node.expandable = false;
+            node.get_view_content().trim_start().to_string()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_indefinite_lengths_and_high_tag_numbers() {
+        let record = [
+            0x30, 0x80, // SEQUENCE with an indefinite length
+            0x02, 0x01, 0x05, // INTEGER 5
+            0x9f, 0x81, 0x00, 0x01, 0x07, // [128] IMPLICIT primitive
+            0xbf, 0x1f, 0x80, 0x04, 0x01, 0xaa, 0x00, 0x00, // [31] constructed, indefinite length
+            0x00, 0x00,
+        ];
+        let mut data = record.to_vec();
+        data.extend_from_slice(&[0x02, 0x01, 0x09]);
+        assert!(is_complete_ber(&data));
+        assert_eq!(complete_ber_prefix(&data[..data.len() - 1]), record.len());
+
+        let records = parse_asn1_records(&data).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].as_u128(), Some(9));
+
+        let fields = records[0].children();
+        assert_eq!(fields.len(), 3);
+        assert_eq!(fields[0].as_u128(), Some(5));
+        assert_eq!(fields[1].tag_number(), 128);
+        assert!(!fields[1].tag.is_constructed());
+        assert!(matches!(&fields[1].children()[0].value, ASN1Value::Utf8String(bytes) if bytes == &[0x07]));
+        assert_eq!(fields[2].tag_number(), 31);
+        assert_eq!(fields[2].children()[0].octets(), Some(&[0xaa][..]));
+    }
+
+    #[test]
+    fn rejects_tlv_lengths_past_the_end() {
+        // a length of usize::MAX used to overflow when added to the header length
+        let data = [0x6f, 0x88, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff];
+        assert!(parse_tlv_records(&data).is_err());
+        assert!(parse_tlv_records(&[0x6f, 0x05, 0x84, 0x01]).is_err());
+    }
+
+    #[test]
+    fn finds_embedded_der_structures() {
+        let cert = include_bytes!("../tests/data/cert.der");
+        // a SEQUENCE header whose length runs past the end comes before the certificate
+        let mut data = vec![0x00, 0x30, 0x82, 0x7f, 0xff, 0x17];
+        data.extend_from_slice(cert);
+        data.extend_from_slice(&[0x30, 0x03, 0x02, 0x01, 0x05, 0xff, 0xff]);
+
+        assert_eq!(find_der(&data, 64), [(6, cert.len())]);
+        assert_eq!(find_der(&data, 5), [(6, cert.len()), (6 + cert.len(), 5)]);
+        assert_eq!(find_der(&data, cert.len() + 1), []);
+    }
+
+    #[test]
+    fn lists_the_outer_structure_only() {
+        let cert = include_bytes!("../tests/data/cert.der");
+        let mut data = vec![0x30, 0x82];
+        data.extend_from_slice(&(cert.len() as u16 + 2).to_be_bytes());
+        data.extend_from_slice(cert);
+        data.extend_from_slice(&[0x05, 0x00]);
+        assert_eq!(find_der(&data, 64), [(0, data.len())]);
+
+        // runs of zero bytes are not taken for elements
+        let mut padded = vec![0x30, 0x04, 0x00, 0x00, 0x00, 0x00];
+        padded.extend_from_slice(cert);
+        assert_eq!(find_der(&padded, 64), [(6, cert.len())]);
+    }
+
+    /// Wraps the content in `depth` SEQUENCEs with definite lengths.
+    fn nested(tag: u8, depth: usize, content: &[u8]) -> Vec<u8> {
+        (0..depth).fold(content.to_vec(), |inner, _| {
+            let length = (inner.len() as u32).to_be_bytes();
+            let mut outer = vec![tag, 0x84];
+            outer.extend_from_slice(&length);
+            outer.extend(inner);
+            outer
+        })
+    }
+
+    #[test]
+    fn stops_at_the_nesting_limit() {
+        // indefinite lengths used to be followed until the stack overflowed
+        let data = [0x30, 0x80].repeat(100_000);
+        assert_eq!(complete_ber_prefix(&data), 0);
+        assert!(parse_asn1(&data, 0).is_ok());
+
+        let data = nested(0x30, 1000, &[0x05, 0x00]);
+        assert!(is_complete_ber(&data));
+        assert!(parse_asn1(&data, 0).is_err());
+        assert!(parse_tlv_records(&nested(0x70, 1000, &[0x5a, 0x01, 0x01])).is_ok());
+
+        let shallow = nested(0x30, 10, &[0x05, 0x00]);
+        let node = parse_asn1(&shallow, 0).unwrap();
+        assert_eq!(flatten_nodes(&node).len(), 11);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,172 @@
+use std::fmt;
+use crate::asn1_module::ModuleError;
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Token {
+    /// Type and value references, keywords and `&field` names.
+    Word(String),
+    Number(i128),
+    /// Character, bit and hex strings as written, such as `'0A'H`.
+    Literal(String),
+    Symbol(&'static str),
+}
+
+/// A token and the line it starts on.
+pub(crate) type Lexed = (Token, usize);
+
+const SYMBOLS: [&str; 21] = [
+    "::=", "...", "..", "{", "}", "(", ")", "[", "]", ",", ";", "|", "^", "@", ".", "!", "<", ">", ":", "=", "*",
+];
+
+/// Splits ASN.1 notation into tokens, dropping `--` and `/* */` comments.
+pub(crate) fn tokenize(text: &str) -> Result<Vec<Lexed>, ModuleError> {
+    let chars = text.chars().collect::<Vec<char>>();
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        if c == '\n' {
+            line += 1;
+            i += 1;
+        } else if c.is_whitespace() {
+            i += 1;
+        } else if c == '-' && next == Some('-') {
+            // a comment ends at the end of the line or at the next "--"
+            i += 2;
+            while i < chars.len() && chars[i] != '\n' && !(chars[i] == '-' && chars.get(i + 1) == Some(&'-')) {
+                i += 1;
+            }
+            if i < chars.len() && chars[i] == '-' {
+                i += 2;
+            }
+        } else if c == '/' && next == Some('*') {
+            let mut depth = 0;
+            while i < chars.len() {
+                match (chars[i], chars.get(i + 1)) {
+                    ('/', Some('*')) => {
+                        depth += 1;
+                        i += 2;
+                    }
+                    ('*', Some('/')) => {
+                        depth -= 1;
+                        i += 2;
+                        if depth == 0 {
+                            break;
+                        }
+                    }
+                    ('\n', _) => {
+                        line += 1;
+                        i += 1;
+                    }
+                    _ => i += 1,
+                }
+            }
+        } else if c.is_ascii_digit() || (c == '-' && next.is_some_and(|next| next.is_ascii_digit())) {
+            let start = i;
+            i += 1;
+            while i < chars.len() && chars[i].is_ascii_digit() {
+                i += 1;
+            }
+            let number = chars[start..i].iter().collect::<String>();
+            let number = number.parse().map_err(|_| ModuleError::syntax(line, format!("number {} is too large", number)))?;
+            tokens.push((Token::Number(number), line));
+        } else if c.is_alphabetic() || c == '&' {
+            let start = i;
+            i += 1;
+            while i < chars.len() {
+                let part = chars[i].is_alphanumeric() || chars[i] == '_';
+                let hyphen = chars[i] == '-' && chars.get(i + 1).is_some_and(|next| next.is_alphanumeric());
+                if !part && !hyphen {
+                    break;
+                }
+                i += 1;
+            }
+            tokens.push((Token::Word(chars[start..i].iter().collect()), line));
+        } else if c == '"' || c == '\'' {
+            let start = i;
+            let start_line = line;
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    None => return Err(ModuleError::syntax(start_line, "unterminated string")),
+                    // a doubled quote stands for the quote itself
+                    Some(&quote) if quote == c && chars.get(i + 1) == Some(&c) && c == '"' => i += 2,
+                    Some(&quote) if quote == c => break,
+                    Some('\n') => {
+                        line += 1;
+                        i += 1;
+                    }
+                    Some(_) => i += 1,
+                }
+            }
+            i += 1;
+            if c == '\'' && chars.get(i).is_some_and(|suffix| matches!(suffix, 'B' | 'H')) {
+                i += 1;
+            }
+            tokens.push((Token::Literal(chars[start..i].iter().collect()), start_line));
+        } else {
+            let rest = &chars[i..];
+            let symbol = SYMBOLS
+                .iter()
+                .find(|symbol| rest.iter().take(symbol.len()).copied().eq(symbol.chars()))
+                .ok_or_else(|| ModuleError::syntax(line, format!("unexpected character {:?}", c)))?;
+            tokens.push((Token::Symbol(symbol), line));
+            i += symbol.len();
+        }
+    }
+    Ok(tokens)
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Word(word) => write!(f, "{}", word),
+            Token::Number(number) => write!(f, "{}", number),
+            Token::Literal(literal) => write!(f, "{}", literal),
+            Token::Symbol(symbol) => write!(f, "\"{}\"", symbol),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_notation_into_tokens() {
+        let text = "Version ::= INTEGER { v1(0) } -- a comment -- (-1..2, ...)\n/* a /* nested */ comment\n*/ &id 'A0'H \"a \"\"b\"\"\"";
+        let tokens = tokenize(text).unwrap();
+        let expected = [
+            (Token::Word("Version".into()), 1),
+            (Token::Symbol("::="), 1),
+            (Token::Word("INTEGER".into()), 1),
+            (Token::Symbol("{"), 1),
+            (Token::Word("v1".into()), 1),
+            (Token::Symbol("("), 1),
+            (Token::Number(0), 1),
+            (Token::Symbol(")"), 1),
+            (Token::Symbol("}"), 1),
+            (Token::Symbol("("), 1),
+            (Token::Number(-1), 1),
+            (Token::Symbol(".."), 1),
+            (Token::Number(2), 1),
+            (Token::Symbol(","), 1),
+            (Token::Symbol("..."), 1),
+            (Token::Symbol(")"), 1),
+            (Token::Word("&id".into()), 3),
+            (Token::Literal("'A0'H".into()), 3),
+            (Token::Literal("\"a \"\"b\"\"\"".into()), 3),
+        ];
+        assert_eq!(tokens, expected);
+    }
+
+    #[test]
+    fn reports_the_line_of_errors() {
+        let error = tokenize("A ::= INTEGER\nB ::= IA5String (SIZE (1..#))").unwrap_err();
+        assert_eq!(error.to_string(), "line 2: unexpected character '#'");
+        let error = tokenize("A ::= INTEGER\n\nb A ::= \"open").unwrap_err();
+        assert_eq!(error.to_string(), "line 3: unterminated string");
+    }
+}
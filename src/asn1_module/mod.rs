@@ -0,0 +1,240 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use crate::asn1_module::lexer::{tokenize, Lexed};
+use crate::asn1_module::parser::Parser;
+
+pub mod lexer;
+pub mod parser;
+
+#[derive(Debug)]
+pub enum ModuleError {
+    Syntax { line: usize, message: String },
+}
+
+impl ModuleError {
+    pub(crate) fn syntax(line: usize, message: impl Into<String>) -> Self {
+        ModuleError::Syntax { line, message: message.into() }
+    }
+}
+
+impl fmt::Display for ModuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ModuleError::Syntax { line, message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl Error for ModuleError {}
+
+/// Definitions read from one or more ASN.1 modules, which share a single namespace.
+#[derive(Clone, Debug, Default)]
+pub struct Modules {
+    pub(crate) types: HashMap<String, TypeAssignment>,
+    pub(crate) values: HashMap<String, Value>,
+    pub(crate) classes: HashMap<String, Class>,
+    pub(crate) objects: HashMap<String, Object>,
+    pub(crate) object_sets: HashMap<String, Vec<SetElement>>,
+    /// Information objects and object sets kept as tokens until their class is known.
+    pub(crate) pending: Vec<Pending>,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Pending {
+    pub(crate) name: String,
+    pub(crate) class: String,
+    pub(crate) tokens: Vec<Lexed>,
+    pub(crate) set: bool,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct TypeAssignment {
+    pub(crate) parameters: Vec<Parameter>,
+    pub(crate) ty: Type,
+}
+
+/// A formal parameter of a parameterized type, `governed` when written as `Governor : name`.
+#[derive(Clone, Debug)]
+pub(crate) struct Parameter {
+    pub(crate) name: String,
+    pub(crate) governed: bool,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum Type {
+    Boolean,
+    Null,
+    Integer { named: Vec<(String, i128)>, constraint: Constraint },
+    Enumerated { root: Vec<(String, i128)>, extension: Option<Vec<(String, i128)>> },
+    BitString { named: Vec<(String, i128)>, constraint: Constraint },
+    OctetString { constraint: Constraint },
+    String { kind: StringKind, constraint: Constraint },
+    ObjectIdentifier,
+    RelativeOid,
+    Real,
+    Sequence { set: bool, root: Vec<Component>, extension: Option<Vec<Addition>> },
+    SequenceOf { set: bool, item: Box<Type>, constraint: Constraint },
+    Choice { root: Vec<Component>, extension: Option<Vec<Component>> },
+    Reference { name: String, arguments: Vec<Argument>, constraint: Constraint },
+    /// `CLASS.&field ({ObjectSet}{@component})`, the object set and the component holding the object key.
+    ClassField { class: String, field: String, set: Option<String>, relation: Option<String> },
+    /// `ANY` and other open types whose type is not known.
+    Open,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum StringKind {
+    Ia5,
+    Printable,
+    Visible,
+    Numeric,
+    Bmp,
+    Universal,
+    Utf8,
+    /// General, Graphic, Teletex and Videotex strings, which have no fixed character size.
+    Other,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Component {
+    pub(crate) name: String,
+    pub(crate) ty: Type,
+    /// `OPTIONAL` or `DEFAULT`, the component has a presence bit.
+    pub(crate) optional: bool,
+    /// `COMPONENTS OF Type`, the components of the type are included here.
+    pub(crate) components_of: bool,
+    /// The tag written before the type, which selects a CHOICE alternative in OER.
+    pub(crate) tag: Option<(der::Class, u32)>,
+}
+
+/// An extension addition of a SEQUENCE, `[[ ]]` groups hold several components.
+#[derive(Clone, Debug)]
+pub(crate) struct Addition {
+    pub(crate) components: Vec<Component>,
+    pub(crate) group: bool,
+}
+
+/// The PER-visible parts of the constraints of a type.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Constraint {
+    pub(crate) value: Vec<(Bound, Bound)>,
+    pub(crate) value_extensible: bool,
+    pub(crate) size: Vec<(Bound, Bound)>,
+    pub(crate) size_extensible: bool,
+    /// `CONTAINING Type` of an OCTET STRING or BIT STRING.
+    pub(crate) containing: Option<Box<Type>>,
+}
+
+impl Constraint {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.value.is_empty() && self.size.is_empty() && self.containing.is_none()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum Bound {
+    Min,
+    Max,
+    Value(i128),
+    /// A value reference or a value parameter.
+    Reference(String),
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum Value {
+    Integer(i128),
+    /// An identifier, either a value reference or a named value such as an enumeration item.
+    Name(String),
+    Other,
+}
+
+/// An actual parameter of a parameterized type reference.
+#[derive(Clone, Debug)]
+pub(crate) enum Argument {
+    Type(Type),
+    Value(Value),
+    Set(Vec<SetElement>),
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum SetElement {
+    Object(Object),
+    ObjectReference(String),
+    SetReference(String),
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Class {
+    pub(crate) fields: Vec<(String, FieldKind)>,
+    /// The `WITH SYNTAX` notation for defining objects of the class.
+    pub(crate) syntax: Option<Vec<SyntaxItem>>,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum FieldKind {
+    /// `&Type`, a type field which makes an open type.
+    Type,
+    /// `&value Type`, a value field of a fixed type.
+    Value(Type),
+    Other,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum SyntaxItem {
+    Literal(lexer::Token),
+    Field(String),
+    Optional(Vec<SyntaxItem>),
+}
+
+/// The settings of an information object by field name, such as `&id` or `&Value`.
+pub(crate) type Object = HashMap<String, Setting>;
+
+#[derive(Clone, Debug)]
+pub(crate) enum Setting {
+    Type(Type),
+    Value(Value),
+}
+
+impl Modules {
+    /// Reads the modules of an ASN.1 file and adds their definitions.
+    pub(crate) fn load(&mut self, text: &str) -> Result<(), ModuleError> {
+        let tokens = tokenize(text)?;
+        Parser::new(&tokens).parse_modules(self)
+    }
+
+    /// Parses the information objects and object sets once the classes of all modules are known.
+    pub(crate) fn link(&mut self) -> Result<(), ModuleError> {
+        for pending in std::mem::take(&mut self.pending) {
+            let Some(class) = self.classes.get(&pending.class) else {
+                continue;
+            };
+            let mut parser = Parser::new(&pending.tokens);
+            if pending.set {
+                let elements = parser.parse_object_set(Some(class))?;
+                self.object_sets.insert(pending.name, elements);
+            } else {
+                let object = parser.parse_object(class)?;
+                self.objects.insert(pending.name, object);
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn has_type(&self, name: &str) -> bool {
+        self.types.contains_key(name)
+    }
+
+    /// Returns the integer of a value reference, following references to other values.
+    pub(crate) fn integer(&self, name: &str) -> Option<i128> {
+        let mut name = name;
+        for _ in 0..16 {
+            match self.values.get(name)? {
+                Value::Integer(value) => return Some(*value),
+                Value::Name(other) => name = other,
+                Value::Other => return None,
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,908 @@
+use der::Class as TagClass;
+use crate::asn1_module::lexer::{Lexed, Token};
+use crate::asn1_module::{
+    Addition, Argument, Bound, Class, Component, Constraint, FieldKind, ModuleError, Modules, Object, Parameter,
+    Pending, SetElement, Setting, StringKind, SyntaxItem, Type, TypeAssignment, Value,
+};
+
+type Result<T> = std::result::Result<T, ModuleError>;
+
+/// Reads type, value, class and object assignments. Tags other than those of components, table
+/// constraints and other parts which do not change PER or OER encodings are skipped.
+pub(crate) struct Parser<'a> {
+    tokens: &'a [Lexed],
+    position: usize,
+}
+
+/// Returns true for type, class and object set references, `&Type` fields count by the letter after `&`.
+pub(crate) fn is_upper(name: &str) -> bool {
+    name.trim_start_matches('&').starts_with(|c: char| c.is_uppercase())
+}
+
+impl<'a> Parser<'a> {
+    pub(crate) fn new(tokens: &'a [Lexed]) -> Self {
+        Parser { tokens, position: 0 }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.peek_at(0)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<&Token> {
+        self.tokens.get(self.position + offset).map(|(token, _)| token)
+    }
+
+    fn advance(&mut self) -> Option<Token> {
+        let token = self.peek().cloned();
+        self.position += 1;
+        token
+    }
+
+    fn error(&self, message: impl Into<String>) -> ModuleError {
+        let line = self.tokens.get(self.position).or(self.tokens.last()).map(|(_, line)| *line).unwrap_or(1);
+        ModuleError::syntax(line, message)
+    }
+
+    fn unexpected(&self, expected: &str) -> ModuleError {
+        match self.peek() {
+            Some(token) => self.error(format!("expected {}, found {}", expected, token)),
+            None => self.error(format!("expected {}, found the end of the file", expected)),
+        }
+    }
+
+    fn is_symbol(&self, symbol: &str) -> bool {
+        matches!(self.peek(), Some(Token::Symbol(found)) if *found == symbol)
+    }
+
+    fn is_word(&self, word: &str) -> bool {
+        matches!(self.peek(), Some(Token::Word(found)) if found == word)
+    }
+
+    fn eat_symbol(&mut self, symbol: &str) -> bool {
+        let found = self.is_symbol(symbol);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn eat_word(&mut self, word: &str) -> bool {
+        let found = self.is_word(word);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn expect_symbol(&mut self, symbol: &str) -> Result<()> {
+        if self.eat_symbol(symbol) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("\"{}\"", symbol)))
+        }
+    }
+
+    fn expect_word(&mut self, word: &str) -> Result<()> {
+        if self.eat_word(word) {
+            Ok(())
+        } else {
+            Err(self.unexpected(word))
+        }
+    }
+
+    fn word(&mut self) -> Result<String> {
+        match self.peek() {
+            Some(Token::Word(word)) => {
+                let word = word.clone();
+                self.position += 1;
+                Ok(word)
+            }
+            _ => Err(self.unexpected("a name")),
+        }
+    }
+
+    /// Skips one token, or a whole `{ }`, `( )` or `[ ]` group, and returns the tokens inside the group.
+    fn skip_balanced(&mut self) -> Result<Vec<Lexed>> {
+        let close = match self.peek() {
+            Some(Token::Symbol("{")) => "}",
+            Some(Token::Symbol("(")) => ")",
+            Some(Token::Symbol("[")) => "]",
+            Some(_) => {
+                self.position += 1;
+                return Ok(vec![]);
+            }
+            None => return Err(self.unexpected("a value")),
+        };
+        let start = self.position + 1;
+        let mut depth = 0;
+        while let Some(token) = self.advance() {
+            match token {
+                Token::Symbol("{" | "(" | "[") => depth += 1,
+                Token::Symbol("}" | ")" | "]") => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Ok(self.tokens[start..self.position - 1].to_vec());
+                    }
+                }
+                _ => {}
+            }
+        }
+        Err(self.error(format!("missing \"{}\"", close)))
+    }
+
+    fn skip_past(&mut self, symbol: &str) -> Result<()> {
+        while !self.eat_symbol(symbol) {
+            if self.peek().is_none() {
+                return Err(self.unexpected(&format!("\"{}\"", symbol)));
+            }
+            self.skip_balanced()?;
+        }
+        Ok(())
+    }
+
+    /// Reads the modules of a file, or plain assignments when the file has no module header.
+    pub(crate) fn parse_modules(&mut self, modules: &mut Modules) -> Result<()> {
+        let has_header = self.tokens.iter().any(|(token, _)| matches!(token, Token::Word(word) if word == "BEGIN"));
+        if !has_header {
+            while self.peek().is_some() {
+                self.parse_assignment(modules)?;
+            }
+            return Ok(());
+        }
+        while self.peek().is_some() {
+            while !self.eat_word("BEGIN") {
+                if self.advance().is_none() {
+                    return Err(self.unexpected("BEGIN"));
+                }
+            }
+            if self.eat_word("EXPORTS") {
+                self.skip_past(";")?;
+            }
+            if self.eat_word("IMPORTS") {
+                self.skip_past(";")?;
+            }
+            while !self.eat_word("END") {
+                if self.peek().is_none() {
+                    return Err(self.unexpected("END"));
+                }
+                self.parse_assignment(modules)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn parse_assignment(&mut self, modules: &mut Modules) -> Result<()> {
+        let name = self.word()?;
+        let upper = is_upper(&name);
+        if upper && self.eat_symbol("::=") {
+            if self.eat_word("CLASS") {
+                let class = self.parse_class()?;
+                modules.classes.insert(name, class);
+            } else {
+                let ty = self.parse_type()?;
+                modules.types.insert(name, TypeAssignment { parameters: vec![], ty });
+            }
+            return Ok(());
+        }
+        let parameters = if self.is_symbol("{") { self.parse_parameters()? } else { vec![] };
+        if self.eat_symbol("::=") {
+            let ty = self.parse_type()?;
+            modules.types.insert(name, TypeAssignment { parameters, ty });
+            return Ok(());
+        }
+
+        // `value Type ::= value`, `object CLASS ::= { ... }` or `ObjectSet CLASS ::= { ... }`
+        let governor = self.parse_type()?;
+        self.expect_symbol("::=")?;
+        if self.is_symbol("{") {
+            let tokens = self.skip_balanced()?;
+            if let Type::Reference { name: class, .. } = governor {
+                modules.pending.push(Pending { name, class, tokens, set: upper });
+            }
+        } else {
+            let value = self.parse_value()?;
+            if !upper {
+                modules.values.insert(name, value);
+            }
+        }
+        Ok(())
+    }
+
+    /// `{ Type, Governor : name, ... }` after the name of a parameterized assignment.
+    fn parse_parameters(&mut self) -> Result<Vec<Parameter>> {
+        let tokens = self.skip_balanced()?;
+        let parameters = tokens
+            .split(|(token, _)| *token == Token::Symbol(","))
+            .filter_map(|parameter| {
+                let name = parameter.iter().rev().find_map(|(token, _)| match token {
+                    Token::Word(word) => Some(word.clone()),
+                    _ => None,
+                })?;
+                let governed = parameter.iter().any(|(token, _)| *token == Token::Symbol(":"));
+                Some(Parameter { name, governed })
+            })
+            .collect();
+        Ok(parameters)
+    }
+
+    pub(crate) fn parse_type(&mut self) -> Result<Type> {
+        // tags of types do not change PER encodings, OER reads the tags of CHOICE alternatives from their components
+        while self.is_symbol("[") {
+            self.skip_balanced()?;
+            let _ = self.eat_word("IMPLICIT") || self.eat_word("EXPLICIT");
+        }
+        let word = self.word()?;
+        let string = |kind| Type::String { kind, constraint: Constraint::default() };
+        let mut ty = match word.as_str() {
+            "BOOLEAN" => Type::Boolean,
+            "NULL" => Type::Null,
+            "INTEGER" => Type::Integer { named: self.parse_named_numbers()?, constraint: Constraint::default() },
+            "ENUMERATED" => self.parse_enumerated()?,
+            "BIT" => {
+                self.expect_word("STRING")?;
+                Type::BitString { named: self.parse_named_numbers()?, constraint: Constraint::default() }
+            }
+            "OCTET" => {
+                self.expect_word("STRING")?;
+                Type::OctetString { constraint: Constraint::default() }
+            }
+            "OBJECT" => {
+                self.expect_word("IDENTIFIER")?;
+                Type::ObjectIdentifier
+            }
+            "RELATIVE-OID" => Type::RelativeOid,
+            "REAL" => Type::Real,
+            "ANY" => {
+                if self.eat_word("DEFINED") {
+                    self.expect_word("BY")?;
+                    self.word()?;
+                }
+                Type::Open
+            }
+            "SEQUENCE" | "SET" => self.parse_sequence(word == "SET")?,
+            "CHOICE" => {
+                let (root, extension) = self.parse_components()?;
+                let extension = extension.map(|additions| additions.into_iter().flat_map(|addition| addition.components).collect());
+                Type::Choice { root, extension }
+            }
+            "IA5String" => string(StringKind::Ia5),
+            "PrintableString" => string(StringKind::Printable),
+            "VisibleString" | "ISO646String" | "UTCTime" | "GeneralizedTime" => string(StringKind::Visible),
+            "NumericString" => string(StringKind::Numeric),
+            "BMPString" => string(StringKind::Bmp),
+            "UniversalString" => string(StringKind::Universal),
+            "UTF8String" => string(StringKind::Utf8),
+            "GeneralString" | "GraphicString" | "TeletexString" | "T61String" | "VideotexString" | "ObjectDescriptor" => {
+                string(StringKind::Other)
+            }
+            "EXTERNAL" | "EMBEDDED" | "CHARACTER" | "INSTANCE" => {
+                return Err(self.error(format!("{} types are not supported", word)));
+            }
+            _ if is_upper(&word) => {
+                // `Module.Type` or `CLASS.&field`
+                let name = if self.eat_symbol(".") { Some(self.word()?) } else { None };
+                match name {
+                    Some(field) if field.starts_with('&') => {
+                        let (set, relation) = self.parse_table_constraint()?;
+                        Type::ClassField { class: word, field, set, relation }
+                    }
+                    name => {
+                        let arguments = if self.is_symbol("{") { self.parse_arguments()? } else { vec![] };
+                        Type::Reference { name: name.unwrap_or(word), arguments, constraint: Constraint::default() }
+                    }
+                }
+            }
+            _ => {
+                self.position -= 1;
+                return Err(self.unexpected("a type"));
+            }
+        };
+        while self.is_symbol("(") {
+            let constraint = self.parse_constraint()?;
+            apply_constraint(&mut ty, constraint);
+        }
+        Ok(ty)
+    }
+
+    /// `INTEGER { name(1), ... }` and `BIT STRING { name(0), ... }`
+    fn parse_named_numbers(&mut self) -> Result<Vec<(String, i128)>> {
+        if !self.is_symbol("{") {
+            return Ok(vec![]);
+        }
+        let tokens = self.skip_balanced()?;
+        let named = tokens
+            .split(|(token, _)| *token == Token::Symbol(","))
+            .filter_map(|item| match item {
+                [(Token::Word(name), _), (Token::Symbol("("), _), (Token::Number(number), _), ..] => Some((name.clone(), *number)),
+                _ => None,
+            })
+            .collect();
+        Ok(named)
+    }
+
+    /// `ENUMERATED { a, b(5), ..., c }`, items without a number get the lowest unused ones.
+    fn parse_enumerated(&mut self) -> Result<Type> {
+        self.expect_symbol("{")?;
+        let mut root: Vec<(String, Option<i128>)> = vec![];
+        let mut extension: Option<Vec<(String, Option<i128>)>> = None;
+        while !self.eat_symbol("}") {
+            if self.eat_symbol("...") {
+                extension = Some(vec![]);
+                if self.eat_symbol("!") {
+                    self.skip_balanced()?;
+                }
+            } else {
+                let name = self.word()?;
+                let number = if self.eat_symbol("(") {
+                    let number = match self.advance() {
+                        Some(Token::Number(number)) => number,
+                        _ => return Err(self.error("expected an enumeration number")),
+                    };
+                    self.expect_symbol(")")?;
+                    Some(number)
+                } else {
+                    None
+                };
+                extension.as_mut().unwrap_or(&mut root).push((name, number));
+            }
+            if !self.eat_symbol(",") && !self.is_symbol("}") {
+                return Err(self.unexpected("\",\" or \"}\""));
+            }
+        }
+
+        let numbered = root.iter().filter_map(|(_, number)| *number).collect::<Vec<i128>>();
+        let mut next = 0;
+        let mut items = vec![];
+        for (name, number) in root {
+            let number = number.unwrap_or_else(|| {
+                while numbered.contains(&next) || items.iter().any(|(_, used)| *used == next) {
+                    next += 1;
+                }
+                next
+            });
+            items.push((name, number));
+        }
+        items.sort_by_key(|(_, number)| *number);
+        let extension = extension.map(|additions| {
+            let mut last = items.iter().map(|(_, number)| *number).max().unwrap_or(-1);
+            additions
+                .into_iter()
+                .map(|(name, number)| {
+                    last = number.unwrap_or(last + 1);
+                    (name, last)
+                })
+                .collect()
+        });
+        Ok(Type::Enumerated { root: items, extension })
+    }
+
+    /// `SEQUENCE { ... }`, `SEQUENCE (SIZE (..)) OF Type` or `SEQUENCE SIZE (..) OF Type`
+    fn parse_sequence(&mut self, set: bool) -> Result<Type> {
+        if self.is_symbol("{") {
+            let (root, extension) = self.parse_components()?;
+            return Ok(Type::Sequence { set, root, extension });
+        }
+        let mut constraint = Constraint::default();
+        if self.is_symbol("(") {
+            constraint = self.parse_constraint()?;
+        } else if self.eat_word("SIZE") {
+            let size = self.parse_constraint()?;
+            constraint.size = size.value;
+            constraint.size_extensible = size.value_extensible;
+        }
+        self.expect_word("OF")?;
+        // `SEQUENCE OF item Type` names the items
+        if matches!(self.peek(), Some(Token::Word(word)) if !is_upper(word)) {
+            self.position += 1;
+        }
+        let item = Box::new(self.parse_type()?);
+        Ok(Type::SequenceOf { set, item, constraint })
+    }
+
+    /// The components of a SEQUENCE, SET or CHOICE, with the extension additions after `...`.
+    fn parse_components(&mut self) -> Result<(Vec<Component>, Option<Vec<Addition>>)> {
+        self.expect_symbol("{")?;
+        let mut root = vec![];
+        let mut extension: Option<Vec<Addition>> = None;
+        let mut ellipses = 0;
+        while !self.eat_symbol("}") {
+            if self.eat_symbol("...") {
+                ellipses += 1;
+                extension.get_or_insert_with(Vec::new);
+                if self.eat_symbol("!") {
+                    self.skip_balanced()?;
+                }
+            } else if self.is_symbol("[") && matches!(self.peek_at(1), Some(Token::Symbol("["))) {
+                self.position += 2;
+                // `[[2: ...]]` version numbers
+                if matches!((self.peek(), self.peek_at(1)), (Some(Token::Number(_)), Some(Token::Symbol(":")))) {
+                    self.position += 2;
+                }
+                let mut components = vec![];
+                loop {
+                    components.push(self.parse_component()?);
+                    if !self.eat_symbol(",") {
+                        break;
+                    }
+                }
+                self.expect_symbol("]")?;
+                self.expect_symbol("]")?;
+                extension.get_or_insert_with(Vec::new).push(Addition { components, group: true });
+            } else {
+                let component = self.parse_component()?;
+                match (&mut extension, ellipses) {
+                    (Some(additions), 1) => additions.push(Addition { components: vec![component], group: false }),
+                    _ => root.push(component),
+                }
+            }
+            if !self.eat_symbol(",") && !self.is_symbol("}") {
+                return Err(self.unexpected("\",\" or \"}\""));
+            }
+        }
+        Ok((root, extension))
+    }
+
+    fn parse_component(&mut self) -> Result<Component> {
+        if self.eat_word("COMPONENTS") {
+            self.expect_word("OF")?;
+            let ty = self.parse_type()?;
+            return Ok(Component { name: String::new(), ty, optional: false, components_of: true, tag: None });
+        }
+        let name = self.word()?;
+        let tag = self.parse_tag()?;
+        let ty = self.parse_type()?;
+        let optional = if self.eat_word("OPTIONAL") {
+            true
+        } else if self.eat_word("DEFAULT") {
+            self.parse_value()?;
+            true
+        } else {
+            false
+        };
+        Ok(Component { name, ty, optional, components_of: false, tag })
+    }
+
+    /// `[n]`, `[APPLICATION n]` or `[PRIVATE n]` before the type of a component. A tag number
+    /// given as a value reference is skipped like the tags of types.
+    fn parse_tag(&mut self) -> Result<Option<(TagClass, u32)>> {
+        if !self.eat_symbol("[") {
+            return Ok(None);
+        }
+        let class = if self.eat_word("APPLICATION") {
+            TagClass::Application
+        } else if self.eat_word("PRIVATE") {
+            TagClass::Private
+        } else if self.eat_word("UNIVERSAL") {
+            TagClass::Universal
+        } else {
+            TagClass::ContextSpecific
+        };
+        let number = match self.advance() {
+            Some(Token::Number(number)) => u32::try_from(number).ok(),
+            Some(Token::Word(_)) => None,
+            _ => {
+                self.position -= 1;
+                return Err(self.unexpected("a tag number"));
+            }
+        };
+        self.expect_symbol("]")?;
+        let _ = self.eat_word("IMPLICIT") || self.eat_word("EXPLICIT");
+        Ok(number.map(|number| (class, number)))
+    }
+
+    /// `({ObjectSet})` or `({ObjectSet}{@component})` after a class field type.
+    fn parse_table_constraint(&mut self) -> Result<(Option<String>, Option<String>)> {
+        if !(self.is_symbol("(") && matches!(self.peek_at(1), Some(Token::Symbol("{")))) {
+            return Ok((None, None));
+        }
+        self.position += 2;
+        let set = self.word()?;
+        self.expect_symbol("}")?;
+        let mut relation = None;
+        if self.eat_symbol("{") {
+            // `@id`, `@.id` or `@..outer.id`, the last name is the component
+            while !self.eat_symbol("}") {
+                match self.advance() {
+                    Some(Token::Word(word)) => relation = Some(word),
+                    Some(_) => {}
+                    None => return Err(self.unexpected("\"}\"")),
+                }
+            }
+        }
+        self.expect_symbol(")")?;
+        Ok((Some(set), relation))
+    }
+
+    fn parse_constraint(&mut self) -> Result<Constraint> {
+        self.expect_symbol("(")?;
+        self.parse_constraint_body()
+    }
+
+    /// Reads value ranges and SIZE constraints up to the closing parenthesis. Unions widen the range,
+    /// elements after `...` only make the constraint extensible and other constraints are skipped.
+    fn parse_constraint_body(&mut self) -> Result<Constraint> {
+        let mut constraint = Constraint::default();
+        let mut extensible = false;
+        let mut except = false;
+        loop {
+            let Some(token) = self.peek().cloned() else {
+                return Err(self.unexpected("\")\""));
+            };
+            let root = !extensible && !except;
+            match token {
+                Token::Symbol(")") => {
+                    self.position += 1;
+                    break;
+                }
+                Token::Symbol("...") => {
+                    self.position += 1;
+                    extensible = true;
+                }
+                Token::Symbol("|" | "," | "^") => {
+                    self.position += 1;
+                    except = false;
+                }
+                Token::Word(word) if word == "UNION" || word == "INTERSECTION" => {
+                    self.position += 1;
+                    except = false;
+                }
+                Token::Word(word) if word == "EXCEPT" => {
+                    self.position += 1;
+                    except = true;
+                }
+                Token::Word(word) if word == "SIZE" => {
+                    self.position += 1;
+                    let size = self.parse_constraint()?;
+                    if root {
+                        constraint.size.extend(size.value);
+                        constraint.size_extensible |= size.value_extensible;
+                    }
+                }
+                Token::Symbol("(") => {
+                    let inner = self.parse_constraint()?;
+                    if root {
+                        constraint.value.extend(inner.value);
+                        constraint.value_extensible |= inner.value_extensible;
+                        constraint.size.extend(inner.size);
+                        constraint.size_extensible |= inner.size_extensible;
+                        constraint.containing = inner.containing.or(constraint.containing);
+                    }
+                }
+                Token::Word(word) if word == "CONTAINING" => {
+                    self.position += 1;
+                    constraint.containing = Some(Box::new(self.parse_type()?));
+                }
+                Token::Word(word) if word == "ENCODED" => {
+                    self.position += 1;
+                    self.expect_word("BY")?;
+                    self.parse_value()?;
+                }
+                Token::Word(word) if matches!(word.as_str(), "FROM" | "WITH" | "PATTERN" | "SETTINGS" | "INCLUDES") => {
+                    self.position += 1;
+                    let _ = self.eat_word("COMPONENTS") || self.eat_word("COMPONENT");
+                    self.skip_balanced()?;
+                }
+                Token::Number(_) | Token::Word(_) if self.is_bound() => {
+                    let lower = self.parse_bound()?;
+                    let _ = self.eat_symbol("<");
+                    let upper = if self.eat_symbol("..") {
+                        let _ = self.eat_symbol("<");
+                        self.parse_bound()?
+                    } else {
+                        lower.clone()
+                    };
+                    if root {
+                        constraint.value.push((lower, upper));
+                    }
+                }
+                _ => {
+                    self.skip_balanced()?;
+                }
+            }
+        }
+        if extensible {
+            if constraint.value.is_empty() && !constraint.size.is_empty() {
+                constraint.size_extensible = true;
+            } else {
+                constraint.value_extensible = true;
+            }
+        }
+        Ok(constraint)
+    }
+
+    fn is_bound(&self) -> bool {
+        match self.peek() {
+            Some(Token::Number(_)) => true,
+            Some(Token::Word(word)) => word == "MIN" || word == "MAX" || !(is_upper(word) || word.starts_with('&')),
+            _ => false,
+        }
+    }
+
+    fn parse_bound(&mut self) -> Result<Bound> {
+        match self.advance() {
+            Some(Token::Number(number)) => Ok(Bound::Value(number)),
+            Some(Token::Word(word)) if word == "MIN" => Ok(Bound::Min),
+            Some(Token::Word(word)) if word == "MAX" => Ok(Bound::Max),
+            Some(Token::Word(word)) if !is_upper(&word) => Ok(Bound::Reference(word)),
+            _ => {
+                self.position -= 1;
+                Err(self.unexpected("a value"))
+            }
+        }
+    }
+
+    pub(crate) fn parse_value(&mut self) -> Result<Value> {
+        match self.peek() {
+            Some(Token::Number(number)) => {
+                let number = *number;
+                self.position += 1;
+                Ok(Value::Integer(number))
+            }
+            Some(Token::Word(word)) if !word.starts_with('&') => {
+                let word = word.clone();
+                self.position += 1;
+                Ok(Value::Name(word))
+            }
+            Some(Token::Literal(_) | Token::Symbol("{")) => {
+                self.skip_balanced()?;
+                Ok(Value::Other)
+            }
+            _ => Err(self.unexpected("a value")),
+        }
+    }
+
+    /// `{ Type, value, {ObjectSet} }` after a parameterized type reference.
+    fn parse_arguments(&mut self) -> Result<Vec<Argument>> {
+        self.expect_symbol("{")?;
+        let mut arguments = vec![];
+        while !self.eat_symbol("}") {
+            let argument = match self.peek() {
+                Some(Token::Symbol("{")) => {
+                    self.position += 1;
+                    Argument::Set(self.parse_set_elements(None, Some("}"))?)
+                }
+                Some(Token::Number(_) | Token::Literal(_)) => Argument::Value(self.parse_value()?),
+                Some(Token::Word(word)) if !is_upper(word) => Argument::Value(self.parse_value()?),
+                _ => Argument::Type(self.parse_type()?),
+            };
+            arguments.push(argument);
+            if !self.eat_symbol(",") && !self.is_symbol("}") {
+                return Err(self.unexpected("\",\" or \"}\""));
+            }
+        }
+        Ok(arguments)
+    }
+
+    /// Reads the elements of an object set body, the tokens between its braces.
+    pub(crate) fn parse_object_set(&mut self, class: Option<&Class>) -> Result<Vec<SetElement>> {
+        self.parse_set_elements(class, None)
+    }
+
+    /// Reads object set elements up to the closing symbol, inline objects need the class to be read.
+    fn parse_set_elements(&mut self, class: Option<&Class>, close: Option<&str>) -> Result<Vec<SetElement>> {
+        let mut elements = vec![];
+        loop {
+            match self.peek() {
+                None if close.is_none() => break,
+                None => return Err(self.unexpected("\"}\"")),
+                Some(Token::Symbol(symbol)) if Some(*symbol) == close => {
+                    self.position += 1;
+                    break;
+                }
+                Some(Token::Symbol("{")) => {
+                    let tokens = self.skip_balanced()?;
+                    if let Some(class) = class {
+                        elements.push(SetElement::Object(Parser::new(&tokens).parse_object(class)?));
+                    }
+                }
+                Some(Token::Word(word)) if word != "UNION" => {
+                    let element = if is_upper(word) {
+                        SetElement::SetReference(word.clone())
+                    } else {
+                        SetElement::ObjectReference(word.clone())
+                    };
+                    self.position += 1;
+                    // `Set.&field` extracts objects of a field, which is not followed
+                    if self.eat_symbol(".") {
+                        self.word()?;
+                    } else {
+                        elements.push(element);
+                    }
+                }
+                _ => {
+                    self.skip_balanced()?;
+                }
+            }
+        }
+        Ok(elements)
+    }
+
+    /// Reads the tokens of an object definition with the `WITH SYNTAX` notation of its class.
+    pub(crate) fn parse_object(&mut self, class: &Class) -> Result<Object> {
+        let mut object = Object::new();
+        match &class.syntax {
+            Some(syntax) => {
+                self.match_syntax(syntax, class, &mut object, true)?;
+            }
+            None => {
+                while matches!(self.peek(), Some(Token::Word(word)) if word.starts_with('&')) {
+                    let field = self.word()?;
+                    let setting = self.parse_setting(class, &field)?;
+                    object.insert(field, setting);
+                    let _ = self.eat_symbol(",");
+                }
+            }
+        }
+        Ok(object)
+    }
+
+    /// Matches the syntax items in order, an optional group is skipped when its first literal is missing.
+    fn match_syntax(&mut self, items: &[SyntaxItem], class: &Class, object: &mut Object, required: bool) -> Result<bool> {
+        for (i, item) in items.iter().enumerate() {
+            match item {
+                SyntaxItem::Literal(literal) => {
+                    if self.peek() == Some(literal) {
+                        self.position += 1;
+                    } else if i == 0 && !required {
+                        return Ok(false);
+                    } else {
+                        return Err(self.unexpected(&literal.to_string()));
+                    }
+                }
+                SyntaxItem::Field(field) => {
+                    if i == 0 && !required && self.peek().is_none() {
+                        return Ok(false);
+                    }
+                    let setting = self.parse_setting(class, field)?;
+                    object.insert(field.clone(), setting);
+                }
+                SyntaxItem::Optional(group) => {
+                    self.match_syntax(group, class, object, false)?;
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    fn parse_setting(&mut self, class: &Class, field: &str) -> Result<Setting> {
+        match class.fields.iter().find(|(name, _)| name == field).map(|(_, kind)| kind) {
+            Some(FieldKind::Type) => Ok(Setting::Type(self.parse_type()?)),
+            Some(FieldKind::Value(_)) => Ok(Setting::Value(self.parse_value()?)),
+            _ => {
+                self.skip_balanced()?;
+                Ok(Setting::Value(Value::Other))
+            }
+        }
+    }
+
+    /// `CLASS { &field ..., } [WITH SYNTAX { ... }]`
+    fn parse_class(&mut self) -> Result<Class> {
+        self.expect_symbol("{")?;
+        let mut fields = vec![];
+        while !self.eat_symbol("}") {
+            let name = self.word()?;
+            if !name.starts_with('&') {
+                return Err(self.error(format!("expected a class field, found {}", name)));
+            }
+            let kind = match self.peek() {
+                _ if is_upper(&name) => FieldKind::Type,
+                Some(Token::Symbol("," | "}")) => FieldKind::Other,
+                Some(Token::Word(word)) if word.starts_with('&') => FieldKind::Other,
+                _ => FieldKind::Value(self.parse_type()?),
+            };
+            // UNIQUE, OPTIONAL and DEFAULT settings
+            while !self.is_symbol(",") && !self.is_symbol("}") {
+                self.skip_balanced()?;
+            }
+            let _ = self.eat_symbol(",");
+            fields.push((name, kind));
+        }
+        let syntax = if self.eat_word("WITH") {
+            self.expect_word("SYNTAX")?;
+            self.expect_symbol("{")?;
+            Some(self.parse_syntax("}")?)
+        } else {
+            None
+        };
+        Ok(Class { fields, syntax })
+    }
+
+    fn parse_syntax(&mut self, close: &str) -> Result<Vec<SyntaxItem>> {
+        let mut items = vec![];
+        loop {
+            let item = match self.advance() {
+                None => return Err(self.unexpected(&format!("\"{}\"", close))),
+                Some(Token::Symbol(symbol)) if symbol == close => break,
+                Some(Token::Symbol("[")) => SyntaxItem::Optional(self.parse_syntax("]")?),
+                Some(Token::Word(word)) if word.starts_with('&') => SyntaxItem::Field(word),
+                Some(token) => SyntaxItem::Literal(token),
+            };
+            items.push(item);
+        }
+        Ok(items)
+    }
+}
+
+/// Applies a constraint written after a type, a later constraint replaces the ranges of an earlier one.
+pub(crate) fn apply_constraint(ty: &mut Type, constraint: Constraint) {
+    let target = match ty {
+        Type::Integer { constraint, .. }
+        | Type::BitString { constraint, .. }
+        | Type::OctetString { constraint }
+        | Type::String { constraint, .. }
+        | Type::SequenceOf { constraint, .. }
+        | Type::Reference { constraint, .. } => constraint,
+        _ => return,
+    };
+    if !constraint.value.is_empty() {
+        target.value = constraint.value;
+        target.value_extensible = constraint.value_extensible;
+    }
+    if !constraint.size.is_empty() {
+        target.size = constraint.size;
+        target.size_extensible = constraint.size_extensible;
+    }
+    if constraint.containing.is_some() {
+        target.containing = constraint.containing;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::asn1_module::{Bound, Modules, Type, Value};
+
+    const MODULE: &str = "
+        Example { 1 2 3 } DEFINITIONS AUTOMATIC TAGS ::= BEGIN
+        IMPORTS Other FROM Elsewhere;
+        maxItems INTEGER ::= 8
+        ub-items INTEGER ::= maxItems
+        Message ::= SEQUENCE {
+            id [0] INTEGER (0..maxItems),
+            name UTF8String (SIZE (1..64)) OPTIONAL,
+            items SEQUENCE (SIZE (0..ub-items)) OF Item,
+            ...,
+            [[ flag BOOLEAN, note IA5String ]]
+        }
+        Item ::= CHOICE { number INTEGER, text VisibleString, ... }
+        Wrapped { Type } ::= SEQUENCE { value Type }
+        ATTRIBUTE ::= CLASS { &id OBJECT IDENTIFIER UNIQUE, &Type } WITH SYNTAX { TYPE &Type ID &id }
+        Attributes ATTRIBUTE ::= { { TYPE INTEGER ID { 1 2 } } | { TYPE BOOLEAN ID { 1 3 } } }
+        END";
+
+    #[test]
+    fn reads_assignments() {
+        let mut modules = Modules::default();
+        modules.load(MODULE).unwrap();
+        modules.link().unwrap();
+
+        assert_eq!(modules.integer("ub-items"), Some(8));
+        assert!(matches!(modules.values.get("maxItems"), Some(Value::Integer(8))));
+        assert!(!modules.has_type("Other"));
+        let Type::Sequence { set: false, root, extension: Some(extension) } = &modules.types["Message"].ty else {
+            panic!("Message is not a SEQUENCE with extensions");
+        };
+        let names = root.iter().map(|component| component.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, ["id", "name", "items"]);
+        assert_eq!(root[0].tag, Some((der::Class::ContextSpecific, 0)));
+        assert!(root[1].optional);
+        let Type::Integer { constraint, .. } = &root[0].ty else {
+            panic!("id is not an INTEGER");
+        };
+        assert!(matches!(constraint.value[..], [(Bound::Value(0), Bound::Reference(ref name))] if name == "maxItems"));
+        assert_eq!(extension.len(), 1);
+        assert!(extension[0].group);
+        assert_eq!(extension[0].components.len(), 2);
+        assert!(matches!(&modules.types["Item"].ty, Type::Choice { root, extension: Some(_) } if root.len() == 2));
+        assert_eq!(modules.types["Wrapped"].parameters[0].name, "Type");
+        assert_eq!(modules.object_sets["Attributes"].len(), 2);
+    }
+
+    #[test]
+    fn reports_the_line_of_errors() {
+        let mut modules = Modules::default();
+        let error = modules.load("M DEFINITIONS ::= BEGIN\nA ::= INTEGER\nB ::= SEQUENCE { a INTEGER,\n").unwrap_err();
+        assert!(error.to_string().starts_with("line 3: "), "{}", error);
+        let error = Modules::default().load("M DEFINITIONS ::= BEGIN\nA ::= INTEGER\n").unwrap_err();
+        assert_eq!(error.to_string(), "line 2: expected END, found the end of the file");
+    }
+}
@@ -1,5 +1,7 @@
 use std::path::PathBuf;
 use clap::Parser;
+use crate::encoding::Encoding;
+use crate::input::utils::{Framing, InputFormat};
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -11,4 +13,86 @@ pub struct Cli {
     /// ASN.1 specification string in base64 or hex
     #[arg(short = 'a', long)]
     pub(crate) asn1: Option<String>,
+
+    /// Format of the input, detected when it is not given
+    #[arg(long, value_enum, value_name = "FORMAT", default_value = "auto")]
+    pub(crate) input_format: InputFormat,
+
+    /// Show only the part of the decoded input from this byte offset, decimal or 0x hex
+    #[arg(long, value_name = "OFFSET", value_parser = parse_number)]
+    pub(crate) offset: Option<usize>,
+
+    /// Show only this many bytes of the decoded input, from the --offset
+    #[arg(long, value_name = "BYTES", value_parser = parse_number)]
+    pub(crate) length: Option<usize>,
+
+    /// Split the input into records which are decoded one by one: length-prefixed records of the
+    /// decoded input, or lines of text which are each base64, hex or binary
+    #[arg(long, value_enum, value_name = "FRAMING", conflicts_with_all = ["scan", "cdr"])]
+    pub(crate) frame: Option<Framing>,
+
+    /// Search binary input such as a firmware image for DER structures and list them
+    #[arg(long, conflicts_with_all = ["cdr", "tlv", "type_name"])]
+    pub(crate) scan: bool,
+
+    /// Smallest DER structure listed by --scan, in bytes
+    #[arg(long, value_name = "BYTES", default_value_t = 64, requires = "scan")]
+    pub(crate) min_size: usize,
+
+    /// Show secret key components instead of masking them
+    #[arg(long)]
+    pub(crate) show_secrets: bool,
+
+    /// Read the passphrase for encrypted keys from the first line of a file
+    #[arg(long, value_name = "FILE", conflicts_with = "passphrase_env")]
+    pub(crate) passphrase_file: Option<PathBuf>,
+
+    /// Read the passphrase for encrypted keys from an environment variable
+    #[arg(long, value_name = "VAR")]
+    pub(crate) passphrase_env: Option<String>,
+
+    /// Name object identifiers in SNMP varbinds from a file of "OID name" lines
+    #[arg(long, value_name = "FILE")]
+    pub(crate) oid_map: Option<PathBuf>,
+
+    /// Browse a file of 3GPP TS 32.298 charging data records (CDR)
+    #[arg(long)]
+    pub(crate) cdr: bool,
+
+    /// Skip a file header of this many bytes before the first CDR
+    #[arg(long, value_name = "BYTES", requires = "cdr")]
+    pub(crate) cdr_header: Option<usize>,
+
+    /// Read the input as BER-TLV data objects such as EMV card responses
+    #[arg(long, conflicts_with = "cdr")]
+    pub(crate) tlv: bool,
+
+    /// Name BER-TLV tags from a file of "TAG name" lines, may be given more than once
+    #[arg(long, value_name = "FILE", requires = "tlv")]
+    pub(crate) tag_dictionary: Vec<PathBuf>,
+
+    /// Compare the EF.SOD data group hashes with this DG file, may be given more than once
+    #[arg(long, value_name = "FILE")]
+    pub(crate) data_group: Vec<PathBuf>,
+
+    /// Load type definitions from an ASN.1 module file, may be given more than once
+    #[arg(long, value_name = "FILE")]
+    pub(crate) module: Vec<PathBuf>,
+
+    /// Decode the input as a value of this type from the loaded modules
+    #[arg(long = "type", value_name = "TYPE", requires = "module", conflicts_with_all = ["cdr", "tlv"])]
+    pub(crate) type_name: Option<String>,
+
+    /// Encoding of the input, APER when only a --type is given. XER and JER are read without a type too
+    #[arg(long, value_enum, value_name = "ENCODING", conflicts_with_all = ["cdr", "tlv"])]
+    pub(crate) encoding: Option<Encoding>,
+}
+
+/// Parses a decimal number or a hex number with a `0x` prefix.
+fn parse_number(text: &str) -> Result<usize, String> {
+    let result = match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => usize::from_str_radix(hex, 16),
+        None => text.parse(),
+    };
+    result.map_err(|error| error.to_string())
 }
\ No newline at end of file
@@ -0,0 +1,71 @@
+use der::Tag;
+use md5::{Digest, Md5};
+use crate::asn1_der::{ASN1Node, ASN1Value};
+use crate::decrypt::{CbcCipher, DecryptError};
+use crate::input::pem::PemBlock;
+
+/// Returns true if the PEM block carries `Proc-Type: 4,ENCRYPTED` (RFC 1421 style encryption).
+pub(crate) fn is_encrypted(block: &PemBlock) -> bool {
+    block
+        .header("Proc-Type")
+        .map(|value| value.ends_with("ENCRYPTED"))
+        .unwrap_or(false)
+}
+
+/// Decrypts the body of a legacy OpenSSL encrypted PEM block described by its `DEK-Info` header.
+pub(crate) fn decrypt(block: &PemBlock, data: &[u8], passphrase: &str) -> Result<Vec<u8>, DecryptError> {
+    let dek_info = block
+        .header("DEK-Info")
+        .ok_or(DecryptError::InvalidParameters("missing DEK-Info header"))?;
+    let (name, iv) = dek_info
+        .split_once(',')
+        .ok_or(DecryptError::InvalidParameters("DEK-Info"))?;
+    let cipher = CbcCipher::from_name(name.trim())
+        .ok_or_else(|| DecryptError::UnsupportedAlgorithm(name.trim().to_string()))?;
+    let iv = hex::decode(iv.trim()).map_err(|_| DecryptError::InvalidParameters("DEK-Info iv"))?;
+    if iv.len() < 8 {
+        return Err(DecryptError::InvalidParameters("DEK-Info iv"));
+    }
+
+    let key = bytes_to_key(passphrase.as_bytes(), &iv[..8], cipher.key_len());
+    cipher.decrypt(&key, &iv, data)
+}
+
+/// OpenSSL `EVP_BytesToKey` with MD5 and a single iteration.
+fn bytes_to_key(passphrase: &[u8], salt: &[u8], key_len: usize) -> Vec<u8> {
+    let mut key = Vec::with_capacity(key_len);
+    let mut block: Vec<u8> = Vec::new();
+    while key.len() < key_len {
+        block = Md5::new()
+            .chain_update(&block)
+            .chain_update(passphrase)
+            .chain_update(salt)
+            .finalize()
+            .to_vec();
+        key.extend_from_slice(&block);
+    }
+    key.truncate(key_len);
+    key
+}
+
+/// Builds the node shown for an encrypted PEM block until it is decrypted.
+pub(crate) fn encrypted_node(block: &PemBlock, data: &[u8]) -> ASN1Node {
+    let mut node = ASN1Node::new(Tag::OctetString, ASN1Value::Bytes(data.to_vec()), 0, false, true);
+    node.set_label(&format!("encrypted {}", block.label));
+    if let Some(dek_info) = block.header("DEK-Info") {
+        node.set_annotation(format!("DEK-Info: {}", dek_info));
+    }
+    node
+}
+
+#[cfg(test)]
+mod tests {
+    use super::bytes_to_key;
+
+    #[test]
+    fn derives_keys_like_openssl() {
+        // openssl enc -aes-256-cbc -md md5 -pass pass:password -S 0102030405060708 -P
+        let key = bytes_to_key(b"password", &[1, 2, 3, 4, 5, 6, 7, 8], 32);
+        assert_eq!(hex::encode(key), "e7b0971e52ca5cc8d0539fb3412f6316f7ba2e6ee293d9f3457b99436b51ce02");
+    }
+}
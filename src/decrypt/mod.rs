@@ -0,0 +1,201 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use aes::{Aes128, Aes192, Aes256};
+use cbc::cipher::{BlockDecryptMut, KeyIvInit};
+use cbc::cipher::block_padding::Pkcs7;
+use cbc::Decryptor;
+use der::asn1::ObjectIdentifier;
+use des::{Des, TdesEde3};
+use crate::asn1_der::{ASN1Node, ASN1Value, parse_asn1};
+use crate::schema::{annotate, implicit_octets, is_context, SchemaOptions};
+use crate::schema::keys::{is_encrypted_private_key_info, is_pbe_algorithm};
+
+pub mod legacy;
+pub mod pkcs12;
+pub mod pkcs5;
+
+#[derive(Debug)]
+pub enum DecryptError {
+    UnsupportedAlgorithm(String),
+    InvalidParameters(&'static str),
+    BadDecrypt,
+}
+
+impl Display for DecryptError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecryptError::UnsupportedAlgorithm(name) => write!(f, "Unsupported encryption algorithm: {}", name),
+            DecryptError::InvalidParameters(what) => write!(f, "Invalid encryption parameters: {}", what),
+            DecryptError::BadDecrypt => write!(f, "Decryption failed, wrong passphrase?"),
+        }
+    }
+}
+
+impl Error for DecryptError {}
+
+/// Block ciphers in CBC mode used by password based encryption schemes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum CbcCipher {
+    Des,
+    DesEde3,
+    Aes128,
+    Aes192,
+    Aes256,
+}
+
+impl CbcCipher {
+    pub(crate) fn from_oid(oid: &ObjectIdentifier) -> Option<CbcCipher> {
+        match oid.to_string().as_str() {
+            "1.3.14.3.2.7" => Some(CbcCipher::Des),
+            "1.2.840.113549.3.7" => Some(CbcCipher::DesEde3),
+            "2.16.840.1.101.3.4.1.2" => Some(CbcCipher::Aes128),
+            "2.16.840.1.101.3.4.1.22" => Some(CbcCipher::Aes192),
+            "2.16.840.1.101.3.4.1.42" => Some(CbcCipher::Aes256),
+            _ => None,
+        }
+    }
+
+    /// Maps the cipher names used by the `DEK-Info` header of legacy PEM files.
+    pub(crate) fn from_name(name: &str) -> Option<CbcCipher> {
+        match name.to_ascii_uppercase().as_str() {
+            "DES-CBC" => Some(CbcCipher::Des),
+            "DES-EDE3-CBC" => Some(CbcCipher::DesEde3),
+            "AES-128-CBC" => Some(CbcCipher::Aes128),
+            "AES-192-CBC" => Some(CbcCipher::Aes192),
+            "AES-256-CBC" => Some(CbcCipher::Aes256),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn key_len(self) -> usize {
+        match self {
+            CbcCipher::Des => 8,
+            CbcCipher::DesEde3 => 24,
+            CbcCipher::Aes128 => 16,
+            CbcCipher::Aes192 => 24,
+            CbcCipher::Aes256 => 32,
+        }
+    }
+
+    pub(crate) fn decrypt(self, key: &[u8], iv: &[u8], data: &[u8]) -> Result<Vec<u8>, DecryptError> {
+        match self {
+            CbcCipher::Des => cbc_decrypt::<Des>(key, iv, data),
+            CbcCipher::DesEde3 => cbc_decrypt::<TdesEde3>(key, iv, data),
+            CbcCipher::Aes128 => cbc_decrypt::<Aes128>(key, iv, data),
+            CbcCipher::Aes192 => cbc_decrypt::<Aes192>(key, iv, data),
+            CbcCipher::Aes256 => cbc_decrypt::<Aes256>(key, iv, data),
+        }
+    }
+}
+
+fn cbc_decrypt<C>(key: &[u8], iv: &[u8], data: &[u8]) -> Result<Vec<u8>, DecryptError>
+where
+    C: cbc::cipher::BlockDecrypt + cbc::cipher::BlockCipher + cbc::cipher::KeyInit,
+{
+    let decryptor = Decryptor::<C>::new_from_slices(key, iv)
+        .map_err(|_| DecryptError::InvalidParameters("key or iv length"))?;
+    decryptor
+        .decrypt_padded_vec_mut::<Pkcs7>(data)
+        .map_err(|_| DecryptError::BadDecrypt)
+}
+
+/// Decrypts data protected with the algorithm described by the AlgorithmIdentifier node.
+pub(crate) fn decrypt_with_algorithm(algorithm: &ASN1Node, data: &[u8], passphrase: &str) -> Result<Vec<u8>, DecryptError> {
+    let oid = algorithm
+        .children()
+        .first()
+        .and_then(ASN1Node::as_oid)
+        .ok_or(DecryptError::InvalidParameters("missing algorithm"))?;
+    let params = algorithm.children().get(1);
+    if pkcs12::is_pkcs12_pbe(&oid) {
+        pkcs12::decrypt(&oid, params, data, passphrase)
+    } else {
+        pkcs5::decrypt(&oid, params, data, passphrase)
+    }
+}
+
+/// `EncryptedContentInfo` protected with a password, its `[0] IMPLICIT` content not decrypted yet.
+fn is_password_encrypted_content(node: &ASN1Node) -> bool {
+    match node.children() {
+        [content_type, algorithm, content] => {
+            content_type.as_oid().is_some()
+                && algorithm.children().first().and_then(ASN1Node::as_oid).map(|oid| is_pbe_algorithm(&oid)).unwrap_or(false)
+                && is_context(content, 0)
+                && implicit_octets(content).is_some()
+        }
+        _ => false,
+    }
+}
+
+/// Returns true if the tree contains encrypted content which is not decrypted yet.
+pub(crate) fn has_encrypted(node: &ASN1Node) -> bool {
+    if is_encrypted_private_key_info(node) && matches!(node.children()[1].value, ASN1Value::Bytes(_)) {
+        return true;
+    }
+    if is_password_encrypted_content(node) {
+        return true;
+    }
+    node.children().iter().any(has_encrypted)
+}
+
+/// Decrypts every encrypted private key and password encrypted content in the tree and shows
+/// the plain structure as a child of the encrypted node. Returns the number of decrypted nodes.
+pub(crate) fn decrypt_tree(node: &mut ASN1Node, passphrase: &str, options: &SchemaOptions) -> Result<usize, DecryptError> {
+    if is_password_encrypted_content(node) {
+        let [_, algorithm, content] = node.children_mut() else {
+            return Ok(0);
+        };
+        let data = implicit_octets(content).map(<[u8]>::to_vec).unwrap_or_default();
+        let plain = decrypt_with_algorithm(algorithm, &data, passphrase)?;
+        attach_plain(content, &plain, options)?;
+        // the decrypted content may hold encrypted keys of its own, e.g. PKCS#12 shrouded key bags
+        return Ok(1 + decrypt_tree(content, passphrase, options).unwrap_or(0));
+    }
+    if is_encrypted_private_key_info(node) {
+        let (algorithm, encrypted) = match node.children_mut() {
+            [algorithm, encrypted] => (algorithm, encrypted),
+            _ => return Ok(0),
+        };
+        let ASN1Value::Bytes(data) = &encrypted.value else {
+            return Ok(0);
+        };
+        let plain = decrypt_with_algorithm(algorithm, data, passphrase)?;
+        attach_plain(encrypted, &plain, options)?;
+        return Ok(1);
+    }
+
+    let mut decrypted = 0;
+    let mut error = None;
+    for child in node.children_mut() {
+        match decrypt_tree(child, passphrase, options) {
+            Ok(count) => decrypted += count,
+            Err(err) => error = Some(err),
+        }
+    }
+    match error {
+        Some(err) if decrypted == 0 => Err(err),
+        _ => Ok(decrypted),
+    }
+}
+
+/// Replaces the raw encrypted bytes of the node with the parsed decrypted structure.
+pub(crate) fn attach_plain(node: &mut ASN1Node, plain: &[u8], options: &SchemaOptions) -> Result<(), DecryptError> {
+    let mut inner = parse_asn1(plain, node.level + 1).map_err(|_| DecryptError::BadDecrypt)?;
+    if matches!(inner.value, ASN1Value::Null) {
+        return Err(DecryptError::BadDecrypt);
+    }
+    annotate(&mut inner, options);
+
+    match std::mem::replace(&mut node.value, ASN1Value::Null) {
+        ASN1Value::Bytes(encrypted) => node.value = ASN1Value::OctetString(Box::new(inner), encrypted),
+        // the raw content of an IMPLICIT tagged value is replaced by the decrypted structure
+        ASN1Value::ContextSpecific(_) => node.value = ASN1Value::ContextSpecific(vec![inner]),
+        value => {
+            node.value = value;
+            return Err(DecryptError::InvalidParameters("node is not encrypted"));
+        }
+    }
+    node.expandable = true;
+    node.set_annotation("decrypted");
+    Ok(())
+}
@@ -0,0 +1,228 @@
+use std::fmt::{Display, Formatter};
+use cbc::cipher::{BlockDecryptMut, InnerIvInit};
+use cbc::cipher::block_padding::Pkcs7;
+use der::asn1::ObjectIdentifier;
+use hmac::{Mac, SimpleHmac};
+use hmac::digest::core_api::BlockSizeUser;
+use rc2::Rc2;
+use sha1::{Digest, Sha1};
+use sha2::{Sha224, Sha256, Sha384, Sha512};
+use crate::asn1_der::{ASN1Node, ASN1Value};
+use crate::decrypt::{CbcCipher, DecryptError};
+use crate::decrypt::pkcs5::unsupported;
+use crate::schema::pkcs12::is_pfx;
+
+const PBE_SHA1_3DES: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.12.1.3");
+const PBE_SHA1_2DES: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.12.1.4");
+const PBE_SHA1_RC2_128: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.12.1.5");
+const PBE_SHA1_RC2_40: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.12.1.6");
+
+const SHA1: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.14.3.2.26");
+const SHA224: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.2.4");
+const SHA256: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.2.1");
+const SHA384: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.2.2");
+const SHA512: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.2.3");
+
+/// Diversifier values of the PKCS#12 key derivation (RFC 7292, appendix B.3).
+const KEY_ID: u8 = 1;
+const IV_ID: u8 = 2;
+const MAC_ID: u8 = 3;
+
+/// Returns true if the OID is one of the PKCS#12 `pbeWithSHAAnd...` algorithms.
+pub(crate) fn is_pkcs12_pbe(oid: &ObjectIdentifier) -> bool {
+    oid.to_string().starts_with("1.2.840.113549.1.12.1.")
+}
+
+/// Decrypts data encrypted with a PKCS#12 password based encryption algorithm.
+pub(crate) fn decrypt(oid: &ObjectIdentifier, params: Option<&ASN1Node>, data: &[u8], passphrase: &str) -> Result<Vec<u8>, DecryptError> {
+    let params = params.ok_or(DecryptError::InvalidParameters("missing parameters"))?;
+    let (salt, iterations) = match params.children() {
+        [salt, iterations, ..] => (
+            salt.octets().ok_or(DecryptError::InvalidParameters("salt"))?,
+            iterations
+                .as_u128()
+                .and_then(|count| u32::try_from(count).ok())
+                .ok_or(DecryptError::InvalidParameters("iteration count"))?,
+        ),
+        _ => return Err(DecryptError::InvalidParameters("pkcs-12PbeParams")),
+    };
+    let password = bmp_password(passphrase);
+    let derive = |id: u8, len: usize| derive_key::<Sha1>(&password, salt, iterations, id, len);
+
+    match *oid {
+        PBE_SHA1_3DES => CbcCipher::DesEde3.decrypt(&derive(KEY_ID, 24), &derive(IV_ID, 8), data),
+        PBE_SHA1_2DES => {
+            // two-key triple DES uses the first key again as the third one
+            let mut key = derive(KEY_ID, 16);
+            key.extend_from_within(..8);
+            CbcCipher::DesEde3.decrypt(&key, &derive(IV_ID, 8), data)
+        }
+        PBE_SHA1_RC2_128 => rc2_decrypt(&derive(KEY_ID, 16), &derive(IV_ID, 8), data),
+        PBE_SHA1_RC2_40 => rc2_decrypt(&derive(KEY_ID, 5), &derive(IV_ID, 8), data),
+        _ => Err(unsupported(oid)),
+    }
+}
+
+fn rc2_decrypt(key: &[u8], iv: &[u8], data: &[u8]) -> Result<Vec<u8>, DecryptError> {
+    let cipher = Rc2::new_with_eff_key_len(key, key.len() * 8);
+    let decryptor = cbc::Decryptor::<Rc2>::inner_iv_slice_init(cipher, iv)
+        .map_err(|_| DecryptError::InvalidParameters("iv length"))?;
+    decryptor
+        .decrypt_padded_vec_mut::<Pkcs7>(data)
+        .map_err(|_| DecryptError::BadDecrypt)
+}
+
+/// PKCS#12 passwords are BMPStrings (big-endian UTF-16) with a terminating zero.
+fn bmp_password(passphrase: &str) -> Vec<u8> {
+    passphrase
+        .encode_utf16()
+        .chain(std::iter::once(0))
+        .flat_map(u16::to_be_bytes)
+        .collect()
+}
+
+/// The PKCS#12 key derivation function from RFC 7292, appendix B.2.
+fn derive_key<D: Digest + BlockSizeUser>(password: &[u8], salt: &[u8], iterations: u32, id: u8, len: usize) -> Vec<u8> {
+    let v = D::block_size();
+    let stretch = |data: &[u8]| -> Vec<u8> {
+        if data.is_empty() {
+            return Vec::new();
+        }
+        data.iter().cycle().take(v * data.len().div_ceil(v)).copied().collect()
+    };
+    let mut input = stretch(salt);
+    input.extend(stretch(password));
+
+    let mut derived = Vec::with_capacity(len);
+    while derived.len() < len {
+        let mut block = D::new().chain_update(vec![id; v]).chain_update(&input).finalize().to_vec();
+        for _ in 1..iterations {
+            block = D::digest(&block).to_vec();
+        }
+        derived.extend_from_slice(&block);
+
+        // adds B + 1 to every v-byte chunk of the input, B being the hash repeated to v bytes
+        let b: Vec<u8> = block.iter().cycle().take(v).copied().collect();
+        for chunk in input.chunks_mut(v) {
+            let mut carry = 1u16;
+            for (byte, add) in chunk.iter_mut().zip(&b).rev() {
+                let sum = *byte as u16 + *add as u16 + carry;
+                *byte = sum as u8;
+                carry = sum >> 8;
+            }
+        }
+    }
+    derived.truncate(len);
+    derived
+}
+
+/// Result of checking the integrity MAC of a PFX.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum MacStatus {
+    Verified,
+    Failed,
+    Unsupported,
+}
+
+impl Display for MacStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MacStatus::Verified => write!(f, "MAC verified"),
+            MacStatus::Failed => write!(f, "MAC verification failed"),
+            MacStatus::Unsupported => write!(f, "Unsupported MAC algorithm"),
+        }
+    }
+}
+
+/// Returns true if the root is a PFX whose MAC has not been checked yet.
+pub(crate) fn has_unverified_mac(root: &ASN1Node) -> bool {
+    is_pfx(root) && root.children().get(2).map(|mac_data| mac_data.annotation.is_none()).unwrap_or(false)
+}
+
+/// Verifies the MAC of a PFX root and annotates `macData` with the result.
+pub(crate) fn check_mac(root: &mut ASN1Node, passphrase: &str) -> Option<MacStatus> {
+    if !is_pfx(root) {
+        return None;
+    }
+    let status = verify_mac(root, passphrase)?;
+    if let Some(mac_data) = root.children_mut().get_mut(2) {
+        mac_data.set_annotation(status.to_string());
+    }
+    Some(status)
+}
+
+/// Verifies `PFX.macData` over the content of `authSafe` with the passphrase.
+/// Returns `None` when the PFX has no MAC.
+fn verify_mac(pfx: &ASN1Node, passphrase: &str) -> Option<MacStatus> {
+    let [_, auth_safe, mac_data] = pfx.children() else {
+        return None;
+    };
+    let content = auth_safe.children().get(1)?.children().first()?;
+    let data = match &content.value {
+        ASN1Value::OctetString(_, octets) | ASN1Value::Bytes(octets) => octets,
+        _ => return None,
+    };
+    let (digest_info, salt) = match mac_data.children() {
+        [digest_info, salt, ..] => (digest_info, salt.octets()?),
+        _ => return None,
+    };
+    let iterations = match mac_data.children().get(2) {
+        Some(iterations) => u32::try_from(iterations.as_u128()?).ok()?,
+        None => 1,
+    };
+    let (algorithm, expected) = match digest_info.children() {
+        [algorithm, digest] => (algorithm.children().first()?.as_oid()?, digest.octets()?),
+        _ => return None,
+    };
+
+    // an empty password is encoded either as two zero bytes or as no bytes at all
+    let mut passwords = vec![bmp_password(passphrase)];
+    if passphrase.is_empty() {
+        passwords.push(Vec::new());
+    }
+    let verify = |password: &[u8]| match algorithm {
+        SHA1 => Some(mac_matches::<Sha1>(password, salt, iterations, data, expected)),
+        SHA224 => Some(mac_matches::<Sha224>(password, salt, iterations, data, expected)),
+        SHA256 => Some(mac_matches::<Sha256>(password, salt, iterations, data, expected)),
+        SHA384 => Some(mac_matches::<Sha384>(password, salt, iterations, data, expected)),
+        SHA512 => Some(mac_matches::<Sha512>(password, salt, iterations, data, expected)),
+        _ => None,
+    };
+    let mut status = MacStatus::Failed;
+    for password in passwords {
+        match verify(&password) {
+            Some(true) => return Some(MacStatus::Verified),
+            Some(false) => {}
+            None => status = MacStatus::Unsupported,
+        }
+    }
+    Some(status)
+}
+
+fn mac_matches<D: Digest + BlockSizeUser>(password: &[u8], salt: &[u8], iterations: u32, data: &[u8], expected: &[u8]) -> bool {
+    let key = derive_key::<D>(password, salt, iterations, MAC_ID, <D as Digest>::output_size());
+    let Ok(mut mac) = <SimpleHmac<D> as Mac>::new_from_slice(&key) else {
+        return false;
+    };
+    mac.update(data);
+    mac.verify_slice(expected).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn derives_keys_with_the_pkcs12_kdf() {
+        let key = derive_key::<Sha1>(&bmp_password("smeg"), &hex::decode("0a58cf64530d823f").unwrap(), 1, KEY_ID, 24);
+        assert_eq!(hex::encode(key), "8aaae6297b6cb04642ab5b077851284eb7128f1a2a7fbca3");
+
+        let key = derive_key::<Sha1>(&bmp_password("queeg"), &hex::decode("3d83c0e4546ac140").unwrap(), 1000, MAC_ID, 20);
+        assert_eq!(hex::encode(key), "17b9e78ea534fc2b6a35512d03799d9ea3c461c0");
+    }
+
+    #[test]
+    fn encodes_passwords_as_bmp_strings() {
+        assert_eq!(bmp_password("smeg"), [0, b's', 0, b'm', 0, b'e', 0, b'g', 0, 0]);
+    }
+}
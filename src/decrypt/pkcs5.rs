@@ -0,0 +1,228 @@
+use der::asn1::ObjectIdentifier;
+use md5::Md5;
+use sha1::{Digest, Sha1};
+use sha2::{Sha224, Sha256, Sha384, Sha512};
+use crate::asn1_der::ASN1Node;
+use crate::decrypt::{CbcCipher, DecryptError};
+use crate::schema::oid::oid_name;
+
+const PBE_MD5_DES_CBC: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.5.3");
+const PBE_SHA1_DES_CBC: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.5.10");
+const PBES2: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.5.13");
+const PBKDF2: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.5.12");
+const SCRYPT: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.6.1.4.1.11591.4.11");
+
+const HMAC_WITH_SHA1: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.2.7");
+const HMAC_WITH_SHA224: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.2.8");
+const HMAC_WITH_SHA256: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.2.9");
+const HMAC_WITH_SHA384: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.2.10");
+const HMAC_WITH_SHA512: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.2.11");
+
+/// Largest amount of memory an scrypt key derivation may ask for.
+const MAX_SCRYPT_MEMORY: u128 = 1 << 30;
+
+/// Decrypts data encrypted with a PKCS#5 (RFC 8018) password based encryption scheme.
+pub(crate) fn decrypt(oid: &ObjectIdentifier, params: Option<&ASN1Node>, data: &[u8], passphrase: &str) -> Result<Vec<u8>, DecryptError> {
+    let params = params.ok_or(DecryptError::InvalidParameters("missing parameters"))?;
+    match *oid {
+        PBES2 => decrypt_pbes2(params, data, passphrase),
+        PBE_MD5_DES_CBC => decrypt_pbes1::<Md5>(params, data, passphrase),
+        PBE_SHA1_DES_CBC => decrypt_pbes1::<Sha1>(params, data, passphrase),
+        _ => Err(unsupported(oid)),
+    }
+}
+
+pub(crate) fn unsupported(oid: &ObjectIdentifier) -> DecryptError {
+    DecryptError::UnsupportedAlgorithm(oid_name(oid).map(str::to_string).unwrap_or_else(|| oid.to_string()))
+}
+
+/// PBES1 derives the DES key and IV with PBKDF1 from `PBEParameter ::= SEQUENCE { salt, iterationCount }`.
+fn decrypt_pbes1<D: Digest>(params: &ASN1Node, data: &[u8], passphrase: &str) -> Result<Vec<u8>, DecryptError> {
+    let (salt, iterations) = match params.children() {
+        [salt, iterations, ..] => (
+            salt.octets().ok_or(DecryptError::InvalidParameters("salt"))?,
+            iterations
+                .as_u128()
+                .and_then(|count| u32::try_from(count).ok())
+                .ok_or(DecryptError::InvalidParameters("iteration count"))?,
+        ),
+        _ => return Err(DecryptError::InvalidParameters("PBEParameter")),
+    };
+
+    let mut derived = D::new().chain_update(passphrase.as_bytes()).chain_update(salt).finalize().to_vec();
+    for _ in 1..iterations {
+        derived = D::digest(&derived).to_vec();
+    }
+    CbcCipher::Des.decrypt(&derived[..8], &derived[8..16], data)
+}
+
+fn decrypt_pbes2(params: &ASN1Node, data: &[u8], passphrase: &str) -> Result<Vec<u8>, DecryptError> {
+    let (kdf, scheme) = match params.children() {
+        [kdf, scheme] => (kdf, scheme),
+        _ => return Err(DecryptError::InvalidParameters("PBES2-params")),
+    };
+
+    let scheme_oid = scheme
+        .children()
+        .first()
+        .and_then(ASN1Node::as_oid)
+        .ok_or(DecryptError::InvalidParameters("encryptionScheme"))?;
+    let cipher = CbcCipher::from_oid(&scheme_oid).ok_or_else(|| unsupported(&scheme_oid))?;
+    let iv = scheme
+        .children()
+        .get(1)
+        .and_then(ASN1Node::octets)
+        .ok_or(DecryptError::InvalidParameters("iv"))?;
+
+    let key = derive_key(kdf, passphrase, cipher.key_len())?;
+    cipher.decrypt(&key, iv, data)
+}
+
+fn derive_key(kdf: &ASN1Node, passphrase: &str, key_len: usize) -> Result<Vec<u8>, DecryptError> {
+    let (oid, params) = match kdf.children() {
+        [oid, params] => (
+            oid.as_oid().ok_or(DecryptError::InvalidParameters("keyDerivationFunc"))?,
+            params.children(),
+        ),
+        _ => return Err(DecryptError::InvalidParameters("keyDerivationFunc")),
+    };
+
+    match oid {
+        PBKDF2 => pbkdf2_key(params, passphrase, key_len),
+        SCRYPT => scrypt_key(params, passphrase, key_len),
+        _ => Err(unsupported(&oid)),
+    }
+}
+
+fn pbkdf2_key(params: &[ASN1Node], passphrase: &str, key_len: usize) -> Result<Vec<u8>, DecryptError> {
+    let salt = params
+        .first()
+        .and_then(ASN1Node::octets)
+        .ok_or(DecryptError::InvalidParameters("salt"))?;
+    let iterations = params
+        .get(1)
+        .and_then(ASN1Node::as_u128)
+        .and_then(|count| u32::try_from(count).ok())
+        .ok_or(DecryptError::InvalidParameters("iteration count"))?;
+    // the prf defaults to hmacWithSHA1 when it is omitted
+    let prf = params
+        .iter()
+        .skip(2)
+        .find_map(|param| param.children().first().and_then(ASN1Node::as_oid))
+        .unwrap_or(HMAC_WITH_SHA1);
+
+    let mut key = vec![0u8; key_len];
+    let password = passphrase.as_bytes();
+    match prf {
+        HMAC_WITH_SHA1 => pbkdf2::pbkdf2_hmac::<Sha1>(password, salt, iterations, &mut key),
+        HMAC_WITH_SHA224 => pbkdf2::pbkdf2_hmac::<Sha224>(password, salt, iterations, &mut key),
+        HMAC_WITH_SHA256 => pbkdf2::pbkdf2_hmac::<Sha256>(password, salt, iterations, &mut key),
+        HMAC_WITH_SHA384 => pbkdf2::pbkdf2_hmac::<Sha384>(password, salt, iterations, &mut key),
+        HMAC_WITH_SHA512 => pbkdf2::pbkdf2_hmac::<Sha512>(password, salt, iterations, &mut key),
+        _ => return Err(unsupported(&prf)),
+    }
+    Ok(key)
+}
+
+fn scrypt_key(params: &[ASN1Node], passphrase: &str, key_len: usize) -> Result<Vec<u8>, DecryptError> {
+    let (salt, cost, block_size, parallelization) = match params {
+        [salt, cost, block_size, parallelization, ..] => (
+            salt.octets().ok_or(DecryptError::InvalidParameters("salt"))?,
+            cost.as_u128().ok_or(DecryptError::InvalidParameters("cost parameter"))?,
+            block_size
+                .as_u128()
+                .and_then(|value| u32::try_from(value).ok())
+                .ok_or(DecryptError::InvalidParameters("block size"))?,
+            parallelization
+                .as_u128()
+                .and_then(|value| u32::try_from(value).ok())
+                .ok_or(DecryptError::InvalidParameters("parallelization"))?,
+        ),
+        _ => return Err(DecryptError::InvalidParameters("scrypt-params")),
+    };
+    if !cost.is_power_of_two() {
+        return Err(DecryptError::InvalidParameters("cost parameter"));
+    }
+    // scrypt uses 128 * r * N bytes for each of the p lanes
+    let memory = [cost, block_size as u128, parallelization as u128]
+        .iter()
+        .try_fold(128u128, |memory, &factor| memory.checked_mul(factor));
+    if memory.is_none_or(|memory| memory > MAX_SCRYPT_MEMORY) {
+        return Err(DecryptError::InvalidParameters("scrypt memory above 1 GiB"));
+    }
+
+    // the scrypt crate derives at least 10 bytes, DES takes the first 8 of them
+    let params = scrypt::Params::new(
+        cost.trailing_zeros() as u8,
+        block_size,
+        parallelization,
+        key_len.max(10),
+    )
+    .map_err(|_| DecryptError::InvalidParameters("scrypt-params"))?;
+    let mut key = vec![0u8; key_len.max(10)];
+    scrypt::scrypt(passphrase.as_bytes(), salt, &params, &mut key)
+        .map_err(|_| DecryptError::InvalidParameters("key length"))?;
+    key.truncate(key_len);
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asn1_der::parse_asn1;
+
+    fn params(der: &str) -> ASN1Node {
+        parse_asn1(&hex::decode(der).unwrap(), 0).unwrap()
+    }
+
+    #[test]
+    fn derives_pbkdf2_keys() {
+        // RFC 6070, "password" and "salt" with two iterations
+        let key = pbkdf2_key(params("3009040473616c74020102").children(), "password", 20).unwrap();
+        assert_eq!(hex::encode(key), "ea6c014dc72d6f8ccd1ed92ace1d41f0d8de8957");
+
+        let with_sha256 = params("3017040473616c74020102300c06082a864886f70d02090500");
+        let key = pbkdf2_key(with_sha256.children(), "password", 32).unwrap();
+        assert_eq!(hex::encode(key), "ae4d0c95af6b46d32d0adff928f06dd02a303f8ef3c251dfd6e2d85a95474c43");
+    }
+
+    #[test]
+    fn derives_scrypt_keys() {
+        let key = scrypt_key(params("300f04044e61436c020110020101020101").children(), "password", 64).unwrap();
+        assert_eq!(
+            hex::encode(key),
+            "aec6b7483ed26e08802b41f4032086a0e886be7ac48fcfd92ff0cef8109752f4ac74b077263256a65a99701b7a304d46611c8aa391e799ce10a27753e7e9c09a"
+        );
+    }
+
+    #[test]
+    fn derives_short_scrypt_keys() {
+        // DES-CBC takes an 8-byte key, the start of the longer key
+        let key = scrypt_key(params("300f04044e61436c020110020101020101").children(), "password", 8).unwrap();
+        assert_eq!(hex::encode(key), "aec6b7483ed26e08");
+    }
+
+    #[test]
+    fn rejects_scrypt_parameters_using_too_much_memory() {
+        // N = 2^40 is a power of two but would need 128 TiB
+        let scrypt = params("301404044e61436c020601000000000002010102010f");
+        assert!(matches!(
+            scrypt_key(scrypt.children(), "password", 16),
+            Err(DecryptError::InvalidParameters("scrypt memory above 1 GiB"))
+        ));
+    }
+
+    #[test]
+    fn rejects_parameters_which_do_not_fit() {
+        // a block size of 2^33 used to be truncated to zero
+        let scrypt = params("301304044e61436c02011002050200000000020101");
+        assert!(matches!(scrypt_key(scrypt.children(), "password", 64), Err(DecryptError::InvalidParameters("block size"))));
+
+        // 2^32 iterations of PBKDF1
+        let pbes1 = params("30110408010203040506070802050100000000");
+        assert!(matches!(
+            decrypt_pbes1::<Md5>(&pbes1, &[0; 16], "password"),
+            Err(DecryptError::InvalidParameters("iteration count"))
+        ));
+    }
+}
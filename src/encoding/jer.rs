@@ -0,0 +1,335 @@
+use der::asn1::ObjectIdentifier;
+use der::Tag;
+use serde_json::Value as Json;
+use crate::asn1_der::{ASN1Node, ASN1Value};
+use crate::asn1_module::parser::is_upper;
+use crate::asn1_module::{Addition, Argument, Component, Constraint, FieldKind, Modules, StringKind, Type};
+use crate::encoding::schema::{annotate_open_type, annotate_reference, annotate_sequence, Env, Schema};
+use crate::encoding::{bit_string, choice_node, error, finish, integer_node, labelled, leaf, name_bits, recovered, string_node, DecodeError, Result};
+
+struct Decoder<'a> {
+    schema: Schema<'a>,
+}
+
+/// Decodes a JER document as a value of the type, or as it is written when no type is given.
+pub(crate) fn decode(data: &[u8], modules: &Modules, type_name: Option<&str>) -> (Vec<ASN1Node>, Option<String>) {
+    let mut decoder = Decoder { schema: Schema::new(modules) };
+    let json = serde_json::from_slice::<Json>(data).map_err(|json_error| error(format!("invalid JSON: {}", json_error)));
+    let name = type_name.unwrap_or("JSON");
+    let result = json.and_then(|json| match type_name {
+        Some(type_name) => {
+            let ty = Type::Reference { name: type_name.to_string(), arguments: vec![], constraint: Constraint::default() };
+            labelled(decoder.decode_value(&json, &ty, &Env::default(), 0), Some(type_name), 0)
+        }
+        None => Ok(decoder.decode_untyped(&json, 0)),
+    });
+    finish(result, 0, name, &decoder.schema.errors)
+}
+
+fn expected(json: &Json, what: &str) -> Box<DecodeError> {
+    error(format!("{} is not {}", json, what))
+}
+
+fn integer(json: &Json) -> Option<i128> {
+    json.as_i64().map(i128::from).or_else(|| json.as_u64().map(i128::from))
+}
+
+fn hex_bytes(json: &Json) -> Result<Vec<u8>> {
+    json.as_str().and_then(|text| hex::decode(text).ok()).ok_or_else(|| expected(json, "a hex string"))
+}
+
+impl Decoder<'_> {
+    /// Decodes a component or item, a value which fails is shown with its error and decoding goes on.
+    fn decode_labelled(&mut self, json: &Json, ty: &Type, env: &Env, level: u32, label: Option<&str>) -> ASN1Node {
+        let result = labelled(self.decode_value(json, ty, env, level), label, level);
+        recovered(result, &mut self.schema.errors, level)
+    }
+
+    fn decode_value(&mut self, json: &Json, ty: &Type, env: &Env, level: u32) -> Result<ASN1Node> {
+        match ty {
+            Type::Reference { name, arguments, constraint } => self.decode_reference(json, name, arguments, constraint, env, level),
+            Type::ClassField { class, field, set, relation } => {
+                self.decode_class_field(json, class, field, set.as_deref(), relation.as_deref(), env, level)
+            }
+            Type::Boolean => {
+                let value = json.as_bool().ok_or_else(|| expected(json, "a BOOLEAN"))?;
+                Ok(leaf(Tag::Boolean, ASN1Value::Boolean(value), level))
+            }
+            Type::Null => Ok(leaf(Tag::Null, ASN1Value::Null, level)),
+            Type::Integer { named, .. } => {
+                let number = integer(json).ok_or_else(|| expected(json, "an INTEGER"))?;
+                let mut node = integer_node(Tag::Integer, number, level);
+                if let Some((name, _)) = named.iter().find(|(_, named)| *named == number) {
+                    node.set_annotation(name.clone());
+                }
+                Ok(node)
+            }
+            Type::Enumerated { root, extension } => {
+                let (name, number) = root
+                    .iter()
+                    .chain(extension.iter().flatten())
+                    .find(|(name, _)| json.as_str() == Some(name))
+                    .ok_or_else(|| expected(json, "an item of the ENUMERATED"))?;
+                let mut node = integer_node(Tag::Enumerated, *number, level);
+                node.set_annotation(name.clone());
+                Ok(node)
+            }
+            Type::BitString { named, constraint } => {
+                // a hex string, with the number of bits when the size is not fixed
+                let fixed = match self.schema.range(&constraint.size, env)? {
+                    (Some(lower), Some(upper)) if lower == upper && !constraint.size_extensible => usize::try_from(upper).ok(),
+                    _ => None,
+                };
+                let (bytes, length) = match json {
+                    Json::Object(members) => {
+                        let bytes = hex_bytes(members.get("value").unwrap_or(&Json::Null))?;
+                        let length = members.get("length").and_then(Json::as_u64).ok_or_else(|| expected(json, "a BIT STRING"))?;
+                        (bytes, length as usize)
+                    }
+                    _ => {
+                        let bytes = hex_bytes(json)?;
+                        let length = fixed.unwrap_or(bytes.len() * 8);
+                        (bytes, length)
+                    }
+                };
+                let bits = (0..length.min(bytes.len() * 8)).map(|i| bytes[i / 8] & 0x80 >> (i % 8) != 0).collect::<Vec<bool>>();
+                let mut node = leaf(Tag::BitString, bit_string(&bits)?, level);
+                name_bits(&mut node, named, &bits);
+                Ok(node)
+            }
+            Type::OctetString { constraint } => {
+                let value = match (&constraint.containing, json) {
+                    // a contained value is written as JSON
+                    (Some(inner), Json::Object(_) | Json::Array(_)) => {
+                        let node = self.decode_labelled(json, inner, env, level + 1, None);
+                        ASN1Value::OctetString(Box::new(node), vec![])
+                    }
+                    _ => ASN1Value::Bytes(hex_bytes(json)?),
+                };
+                Ok(leaf(Tag::OctetString, value, level))
+            }
+            Type::String { kind, .. } => {
+                let text = json.as_str().ok_or_else(|| expected(json, "a string"))?;
+                Ok(string_node(*kind, text.to_string(), level))
+            }
+            Type::ObjectIdentifier => {
+                let oid = json.as_str().and_then(|text| ObjectIdentifier::new(text).ok()).ok_or_else(|| expected(json, "an OBJECT IDENTIFIER"))?;
+                Ok(leaf(Tag::ObjectIdentifier, ASN1Value::ObjectIdentifier(oid), level))
+            }
+            Type::RelativeOid => {
+                let text = json.as_str().ok_or_else(|| expected(json, "a RELATIVE-OID"))?;
+                Ok(leaf(Tag::OctetString, ASN1Value::CharacterString(text.to_string()), level))
+            }
+            // a number, or "INF", "-INF", "NaN" and "-0" as strings
+            Type::Real => {
+                let text = json.as_str().map(str::to_string).unwrap_or_else(|| json.to_string());
+                Ok(leaf(Tag::Real, ASN1Value::CharacterString(text), level))
+            }
+            Type::Sequence { set, root, extension } => self.decode_sequence(json, *set, root, extension.as_deref(), env, level),
+            Type::SequenceOf { set, item, .. } => {
+                let tag = if *set { Tag::Set } else { Tag::Sequence };
+                let items = json.as_array().ok_or_else(|| expected(json, "an array"))?;
+                let children = items.iter().map(|item_json| self.decode_labelled(item_json, item, env, level + 1, None)).collect();
+                Ok(leaf(tag, ASN1Value::Set(children), level))
+            }
+            Type::Choice { root, extension } => {
+                let Some((chosen, value)) = json.as_object().and_then(|members| members.iter().next()) else {
+                    return Err(expected(json, "a CHOICE"));
+                };
+                let alternatives = root.iter().chain(extension.iter().flatten());
+                let node = match alternatives.enumerate().find(|(_, alternative)| alternative.name == *chosen) {
+                    Some((index, alternative)) => {
+                        let node = self.decode_labelled(value, &alternative.ty, env, level + 1, Some(&alternative.name));
+                        choice_node(index, node, level)
+                    }
+                    None => {
+                        let mut node = self.decode_untyped(value, level + 1);
+                        node.set_label(chosen);
+                        node.set_annotation("unknown alternative");
+                        node
+                    }
+                };
+                Ok(node)
+            }
+            Type::Open => Ok(self.decode_untyped(json, level)),
+        }
+    }
+
+    fn decode_reference(&mut self, json: &Json, name: &str, arguments: &[Argument], constraint: &Constraint, env: &Env, level: u32) -> Result<ASN1Node> {
+        let (ty, inner_env) = self.schema.resolve(name, arguments, constraint, env)?;
+        self.schema.depth += 1;
+        let result = self.decode_value(json, &ty, &inner_env, level);
+        self.schema.depth -= 1;
+        result.map(|mut node| {
+            annotate_reference(&mut node, name);
+            node
+        })
+    }
+
+    fn decode_sequence(&mut self, json: &Json, set: bool, root: &[Component], extension: Option<&[Addition]>, env: &Env, level: u32) -> Result<ASN1Node> {
+        let members = json.as_object().ok_or_else(|| expected(json, "an object"))?;
+        let root = self.schema.expand(root)?;
+        let additions = extension.unwrap_or_default().iter().flat_map(|addition| &addition.components);
+        let components = root.iter().chain(additions).collect::<Vec<&Component>>();
+        self.schema.scopes.push(vec![]);
+        let mut children = vec![];
+        for (name, value) in members {
+            let node = match components.iter().find(|component| component.name == *name) {
+                Some(component) => {
+                    let node = self.decode_labelled(value, &component.ty, env, level + 1, Some(&component.name));
+                    self.schema.record(component, &node);
+                    node
+                }
+                None => {
+                    let mut node = self.decode_untyped(value, level + 1);
+                    node.set_label(name);
+                    node.set_annotation("unknown component");
+                    node
+                }
+            };
+            children.push(node);
+        }
+        self.schema.scopes.pop();
+
+        let tag = if set { Tag::Set } else { Tag::Sequence };
+        let mut node = leaf(tag, ASN1Value::Sequence(vec![]), level);
+        annotate_sequence(&mut node, &root, &children);
+        node.value = ASN1Value::Sequence(children);
+        Ok(node)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn decode_class_field(
+        &mut self,
+        json: &Json,
+        class: &str,
+        field: &str,
+        set: Option<&str>,
+        relation: Option<&str>,
+        env: &Env,
+        level: u32,
+    ) -> Result<ASN1Node> {
+        match self.schema.field_kind(class, field) {
+            Some(FieldKind::Value(ty)) => self.decode_value(json, ty, &Env::default(), level),
+            _ if is_upper(field) => {
+                let Some(ty) = self.schema.object_type(set, relation, field, env) else {
+                    let mut node = self.decode_untyped(json, level);
+                    node.set_annotation("open type");
+                    return Ok(node);
+                };
+                let scopes = std::mem::take(&mut self.schema.scopes);
+                let mut node = self.decode_labelled(json, &ty, &Env::default(), level, None);
+                self.schema.scopes = scopes;
+                annotate_open_type(&mut node, &ty);
+                Ok(node)
+            }
+            _ => Err(error(format!("class field {}.{} is not supported", class, field))),
+        }
+    }
+
+    /// Shows JSON without a type: objects are SEQUENCEs labelled by member, arrays are
+    /// SEQUENCE OFs and numbers which are not integers are REALs.
+    fn decode_untyped(&mut self, json: &Json, level: u32) -> ASN1Node {
+        match json {
+            Json::Null => leaf(Tag::Null, ASN1Value::Null, level),
+            Json::Bool(value) => leaf(Tag::Boolean, ASN1Value::Boolean(*value), level),
+            Json::Number(number) => match integer(json) {
+                Some(value) => integer_node(Tag::Integer, value, level),
+                None => leaf(Tag::Real, ASN1Value::CharacterString(number.to_string()), level),
+            },
+            Json::String(text) => string_node(StringKind::Utf8, text.clone(), level),
+            Json::Array(items) => {
+                let children = items.iter().map(|item| self.decode_untyped(item, level + 1)).collect();
+                leaf(Tag::Sequence, ASN1Value::Set(children), level)
+            }
+            Json::Object(members) => {
+                let children = members
+                    .iter()
+                    .map(|(name, value)| {
+                        let mut node = self.decode_untyped(value, level + 1);
+                        node.set_label(name);
+                        node
+                    })
+                    .collect();
+                leaf(Tag::Sequence, ASN1Value::Sequence(children), level)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::asn1_der::view_lines;
+    use super::*;
+
+    const MODULE: &str = "M DEFINITIONS AUTOMATIC TAGS ::= BEGIN
+        Msg ::= SEQUENCE {
+            flag BOOLEAN,
+            count INTEGER (-5..10),
+            bits BIT STRING { first(0), second(1), third(2) } (SIZE (3)),
+            vbits BIT STRING (SIZE (1..20)),
+            octets OCTET STRING,
+            text IA5String,
+            list SEQUENCE (SIZE (1..4)) OF INTEGER (0..7),
+            color ENUMERATED { red, green },
+            choice CHOICE { a INTEGER, b BOOLEAN },
+            opt INTEGER OPTIONAL,
+            ...
+        }
+        END";
+
+    const MSG: &str = r#"{"flag": true, "count": 3, "bits": "A0", "vbits": {"value": "B0", "length": 5}, "octets": "0102",
+        "text": "Hi & bye", "list": [1, 7], "color": "green", "choice": {"b": false}, "extra": [1.5, null]}"#;
+
+    fn decoded(text: &str, type_name: Option<&str>) -> (Vec<String>, Option<String>) {
+        let mut modules = Modules::default();
+        modules.load(MODULE).unwrap();
+        let (nodes, status) = decode(text.as_bytes(), &modules, type_name);
+        (nodes.iter().flat_map(view_lines).collect(), status)
+    }
+
+    #[test]
+    fn decodes_typed_documents() {
+        let (lines, status) = decoded(MSG, Some("Msg"));
+        assert_eq!(status, None);
+        assert_eq!(
+            lines,
+            [
+                "Msg: SEQUENCE (field(s): 10) (Msg)",
+                "flag: BOOLEAN true",
+                "count: INTEGER 3",
+                "bits: BIT STRING 10100000 (first, third)",
+                "vbits: BIT STRING 10110000",
+                "octets: OCTET STRING 0102",
+                "text: IA5String \"Hi & bye\"",
+                "list: SEQUENCE (item(s): 2)",
+                "INTEGER 1",
+                "INTEGER 7",
+                "color: ENUMERATED 1 (green)",
+                "choice: CONTEXT-SPECIFIC [1] (constructed)  (b)",
+                "b: BOOLEAN false",
+                "extra: SEQUENCE (item(s): 2) (unknown component)",
+                "REAL \"1.5\"",
+                "NULL Null",
+            ]
+        );
+    }
+
+    #[test]
+    fn decodes_untyped_documents() {
+        let (lines, status) = decoded(MSG, None);
+        assert_eq!(status, None);
+        assert_eq!(lines[..4], ["SEQUENCE (field(s): 10)", "flag: BOOLEAN true", "count: INTEGER 3", "bits: UTF8String \"A0\""]);
+        assert_eq!(lines[4..7], ["vbits: SEQUENCE (field(s): 2)", "value: UTF8String \"B0\"", "length: INTEGER 5"]);
+        assert_eq!(lines[13..15], ["choice: SEQUENCE (field(s): 1)", "b: BOOLEAN false"]);
+    }
+
+    #[test]
+    fn reports_values_of_the_wrong_type() {
+        let (lines, status) = decoded(r#"{"flag": 1}"#, Some("Msg"));
+        assert_eq!(lines, ["Msg: SEQUENCE (field(s): 1) (Msg)", "flag: NULL \"1 is not a BOOLEAN\""]);
+        assert_eq!(status.as_deref(), Some("1 value(s) could not be decoded: 1 is not a BOOLEAN"));
+        let (_, status) = decoded("{\"flag\": ", None);
+        assert!(status.is_some_and(|status| status.starts_with("decoding stopped: invalid JSON: ")));
+    }
+}
@@ -0,0 +1,199 @@
+use clap::ValueEnum;
+use der::{Tag, TagNumber};
+use crate::asn1_der::{is_node_expandable, ASN1Node, ASN1Value};
+use crate::asn1_module::{Modules, StringKind};
+
+pub mod jer;
+pub mod oer;
+pub mod per;
+pub mod schema;
+pub mod xer;
+
+/// Encodings which are not BER, most of them need the ASN.1 type of the value to be decoded.
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum Encoding {
+    /// Aligned packed encoding rules (APER)
+    Aper,
+    /// Unaligned packed encoding rules (UPER)
+    Uper,
+    /// Octet encoding rules (OER and canonical OER)
+    Oer,
+    /// XML encoding rules, read without a type too
+    Xer,
+    /// JSON encoding rules, read without a type too
+    Jer,
+}
+
+impl Encoding {
+    /// XER and JER input is text, which is read as it is instead of being decoded from base64 or hex.
+    pub(crate) fn is_text(self) -> bool {
+        matches!(self, Encoding::Xer | Encoding::Jer)
+    }
+}
+
+/// Decodes the input as a value of a type from the loaded ASN.1 modules instead of reading it as BER.
+/// XER and JER input without a type is shown with the element and member names it has.
+#[derive(Clone, Debug)]
+pub struct TypeDecoding {
+    pub(crate) modules: Modules,
+    pub(crate) type_name: Option<String>,
+    pub(crate) encoding: Encoding,
+}
+
+impl TypeDecoding {
+    /// Returns the decoded tree and a status line when decoding stopped early, failed for
+    /// some open types or left bytes over. A failed value is shown as far as it was decoded.
+    pub(crate) fn decode(&self, data: &[u8]) -> (Vec<ASN1Node>, Option<String>) {
+        let type_name = self.type_name.as_deref();
+        match (self.encoding, type_name) {
+            (Encoding::Aper, Some(type_name)) => per::decode(data, &self.modules, type_name, true),
+            (Encoding::Uper, Some(type_name)) => per::decode(data, &self.modules, type_name, false),
+            (Encoding::Oer, Some(type_name)) => oer::decode(data, &self.modules, type_name),
+            (Encoding::Xer, _) => xer::decode(data, &self.modules, type_name),
+            (Encoding::Jer, _) => jer::decode(data, &self.modules, type_name),
+            (_, None) => (vec![], Some("the input needs a --type to be decoded with".to_string())),
+        }
+    }
+}
+
+pub(crate) type Result<T> = std::result::Result<T, Box<DecodeError>>;
+
+/// Why decoding stopped, with the value decoded up to the failing node.
+pub(crate) struct DecodeError {
+    message: String,
+    partial: Option<ASN1Node>,
+}
+
+fn error(message: impl Into<String>) -> Box<DecodeError> {
+    Box::new(DecodeError { message: message.into(), partial: None })
+}
+
+fn partial(message: String, node: ASN1Node) -> Box<DecodeError> {
+    Box::new(DecodeError { message, partial: Some(node) })
+}
+
+/// Builds the status line from the outcome of decoding the whole input, the number of bytes
+/// left after the value and the values which failed while decoding went on.
+fn finish(result: Result<ASN1Node>, rest: usize, name: &str, errors: &[String]) -> (Vec<ASN1Node>, Option<String>) {
+    let (root, status) = match result {
+        Ok(root) => (root, (rest > 0).then(|| format!("{} byte(s) left after the {} value", rest, name))),
+        Err(error) => {
+            let root = error.partial.unwrap_or_else(|| error_node(&error.message, 0));
+            (root, Some(format!("decoding stopped: {}", error.message)))
+        }
+    };
+    let status = match (status, errors.first()) {
+        (None, Some(first)) => Some(format!("{} value(s) could not be decoded: {}", errors.len(), first)),
+        (status, _) => status,
+    };
+    (vec![root], status)
+}
+
+/// Labels a decoded value, a failed value is labelled too so that it shows where decoding stopped.
+fn labelled(result: Result<ASN1Node>, label: Option<&str>, level: u32) -> Result<ASN1Node> {
+    let label_node = |node: &mut ASN1Node| {
+        if let Some(label) = label {
+            node.set_label(label);
+        }
+    };
+    match result {
+        Ok(mut node) => {
+            label_node(&mut node);
+            Ok(node)
+        }
+        Err(mut error) => {
+            let mut partial = error.partial.take().unwrap_or_else(|| error_node(&error.message, level));
+            label_node(&mut partial);
+            error.partial = Some(partial);
+            Err(error)
+        }
+    }
+}
+
+/// Returns a value whose failure does not stop decoding, such as an open type of a known length,
+/// and keeps the error for the status line.
+fn recovered(result: Result<ASN1Node>, errors: &mut Vec<String>, level: u32) -> ASN1Node {
+    match result {
+        Ok(node) => node,
+        Err(error) => {
+            let node = error.partial.unwrap_or_else(|| error_node(&error.message, level));
+            errors.push(error.message);
+            node
+        }
+    }
+}
+
+fn leaf(tag: Tag, value: ASN1Value, level: u32) -> ASN1Node {
+    let expandable = is_node_expandable(&value);
+    ASN1Node::new(tag, value, level, expandable, false)
+}
+
+fn error_node(message: &str, level: u32) -> ASN1Node {
+    leaf(Tag::Null, ASN1Value::Other(message.to_string()), level)
+}
+
+/// Integers which do not fit `u128` are kept as two's complement bytes and annotated in decimal.
+fn integer_node(tag: Tag, value: i128, level: u32) -> ASN1Node {
+    match u128::try_from(value) {
+        Ok(value) => leaf(tag, ASN1Value::Integer(value), level),
+        Err(_) => {
+            let bytes = value.to_be_bytes();
+            let start = (0..15).take_while(|&i| bytes[i] == 0xff && bytes[i + 1] & 0x80 != 0).count();
+            let mut node = leaf(tag, ASN1Value::BigInteger(bytes[start..].to_vec()), level);
+            node.set_annotation(value.to_string());
+            node
+        }
+    }
+}
+
+/// Wraps the chosen alternative of a CHOICE in a node tagged with its index, like automatic tagging.
+fn choice_node(index: usize, alternative: ASN1Node, level: u32) -> ASN1Node {
+    let (number, high) = match u8::try_from(index) {
+        Ok(low) if low <= 30 => (TagNumber::new(low), None),
+        _ => (TagNumber::N30, Some(index as u32)),
+    };
+    let tag = Tag::ContextSpecific { constructed: true, number };
+    let name = alternative.label.clone();
+    let mut node = leaf(tag, ASN1Value::ContextSpecific(vec![alternative]), level);
+    node.tag_number = high;
+    if let Some(name) = name {
+        node.set_annotation(name);
+    }
+    node
+}
+
+/// A character string read as text, as XER and JER send it.
+fn string_node(kind: StringKind, text: String, level: u32) -> ASN1Node {
+    let tag = match kind {
+        StringKind::Ia5 => Tag::Ia5String,
+        StringKind::Printable => Tag::PrintableString,
+        StringKind::Visible => Tag::VisibleString,
+        StringKind::Numeric => Tag::NumericString,
+        StringKind::Bmp => Tag::BmpString,
+        StringKind::Universal | StringKind::Utf8 => Tag::Utf8String,
+        StringKind::Other => Tag::TeletexString,
+    };
+    leaf(tag, ASN1Value::CharacterString(text), level)
+}
+
+/// Annotates a value with the names of the bits which are set.
+fn name_bits(node: &mut ASN1Node, named: &[(String, i128)], bits: &[bool]) {
+    let names = named
+        .iter()
+        .filter(|(_, bit)| usize::try_from(*bit).ok().and_then(|bit| bits.get(bit)) == Some(&true))
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<&str>>();
+    if !names.is_empty() {
+        node.set_annotation(names.join(", "));
+    }
+}
+
+/// Packs bits into a BIT STRING value.
+fn bit_string(bits: &[bool]) -> Result<ASN1Value> {
+    let mut bytes = vec![0u8; bits.len().div_ceil(8)];
+    for (i, _) in bits.iter().enumerate().filter(|(_, bit)| **bit) {
+        bytes[i / 8] |= 0x80 >> (i % 8);
+    }
+    let unused = (bytes.len() * 8 - bits.len()) as u8;
+    der::asn1::BitString::new(unused, bytes).map(ASN1Value::BitString).map_err(|_| error("invalid BIT STRING"))
+}
@@ -0,0 +1,574 @@
+use der::asn1::ObjectIdentifier;
+use der::{Class as TagClass, Tag};
+use crate::asn1_der::{ASN1Node, ASN1Value};
+use crate::asn1_module::parser::is_upper;
+use crate::asn1_module::{Addition, Argument, Component, Constraint, FieldKind, Modules, StringKind, Type};
+use crate::encoding::schema::{annotate_open_type, annotate_reference, annotate_sequence, Env, Schema};
+use crate::encoding::{bit_string, choice_node, error, error_node, finish, integer_node, labelled, leaf, name_bits, partial, recovered, Result};
+
+struct ByteReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        ByteReader { data, position: 0 }
+    }
+
+    fn bytes(&mut self, count: usize) -> Result<&'a [u8]> {
+        let data: &'a [u8] = self.data;
+        match self.position.checked_add(count) {
+            Some(end) if end <= data.len() => {
+                let bytes = &data[self.position..end];
+                self.position = end;
+                Ok(bytes)
+            }
+            _ => Err(error(format!("unexpected end of data at byte {}", self.position))),
+        }
+    }
+
+    fn byte(&mut self) -> Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    /// Reads a preamble or bitmap of `count` bits, which fills whole octets.
+    fn bits(&mut self, count: usize) -> Result<Vec<bool>> {
+        let bytes = self.bytes(count.div_ceil(8))?;
+        Ok((0..count).map(|i| bytes[i / 8] & 0x80 >> (i % 8) != 0).collect())
+    }
+}
+
+struct Decoder<'a> {
+    schema: Schema<'a>,
+}
+
+pub(crate) fn decode(data: &[u8], modules: &Modules, type_name: &str) -> (Vec<ASN1Node>, Option<String>) {
+    let mut decoder = Decoder { schema: Schema::new(modules) };
+    let mut reader = ByteReader::new(data);
+    let ty = Type::Reference { name: type_name.to_string(), arguments: vec![], constraint: Constraint::default() };
+    let result = decoder.decode_labelled(&mut reader, &ty, &Env::default(), 0, Some(type_name));
+    let rest = data.len().saturating_sub(reader.position);
+    finish(result, rest, type_name, &decoder.schema.errors)
+}
+
+fn unsigned(bytes: &[u8]) -> Result<i128> {
+    if bytes.len() > 15 {
+        return Err(error(format!("a {} byte integer is too large", bytes.len())));
+    }
+    Ok(bytes.iter().fold(0i128, |value, &byte| value << 8 | byte as i128))
+}
+
+fn signed(bytes: &[u8]) -> Result<i128> {
+    if bytes.len() > 16 {
+        return Err(error(format!("a {} byte integer is too large", bytes.len())));
+    }
+    if bytes.is_empty() {
+        return Ok(0);
+    }
+    let value = bytes.iter().fold(0u128, |value, &byte| value << 8 | byte as u128);
+    let shift = 128 - bytes.len() * 8;
+    Ok(((value << shift) as i128) >> shift)
+}
+
+/// X.696 10.2 and 10.3, the octets of an integer whose bounds fit one of the fixed sizes.
+fn fixed_size(lower: i128, upper: i128) -> Option<usize> {
+    let sizes = [1usize, 2, 4, 8];
+    if lower >= 0 {
+        sizes.into_iter().find(|size| upper < 1i128 << (size * 8))
+    } else {
+        sizes.into_iter().find(|size| lower >= -(1i128 << (size * 8 - 1)) && upper < 1i128 << (size * 8 - 1))
+    }
+}
+
+impl Decoder<'_> {
+    fn decode_labelled(&mut self, reader: &mut ByteReader, ty: &Type, env: &Env, level: u32, label: Option<&str>) -> Result<ASN1Node> {
+        let result = self.decode_type(reader, ty, env, level);
+        labelled(result, label, level)
+    }
+
+    /// Decodes a value sent in its own octets, such as an open type or an extension addition.
+    /// Decoding goes on after a failure since the length of the octets is known.
+    fn decode_octets(&mut self, bytes: &[u8], ty: &Type, env: &Env, level: u32) -> ASN1Node {
+        let mut reader = ByteReader::new(bytes);
+        let scopes = std::mem::take(&mut self.schema.scopes);
+        let result = self.decode_labelled(&mut reader, ty, env, level, None);
+        self.schema.scopes = scopes;
+        recovered(result, &mut self.schema.errors, level)
+    }
+
+    fn decode_type(&mut self, reader: &mut ByteReader, ty: &Type, env: &Env, level: u32) -> Result<ASN1Node> {
+        match ty {
+            Type::Reference { name, arguments, constraint } => self.decode_reference(reader, name, arguments, constraint, env, level),
+            Type::ClassField { class, field, set, relation } => {
+                self.decode_class_field(reader, class, field, set.as_deref(), relation.as_deref(), env, level)
+            }
+            Type::Boolean => Ok(leaf(Tag::Boolean, ASN1Value::Boolean(reader.byte()? != 0), level)),
+            Type::Null => Ok(leaf(Tag::Null, ASN1Value::Null, level)),
+            Type::Integer { named, constraint } => {
+                let value = self.decode_integer(reader, constraint, env)?;
+                let mut node = integer_node(Tag::Integer, value, level);
+                if let Some((name, _)) = named.iter().find(|(_, number)| *number == value) {
+                    node.set_annotation(name.clone());
+                }
+                Ok(node)
+            }
+            Type::Enumerated { root, extension } => {
+                // the value itself, in one octet up to 127 or after the number of its octets
+                let first = reader.byte()?;
+                let value = match first & 0x80 {
+                    0 => first as i128,
+                    _ => signed(reader.bytes((first & 0x7f) as usize)?)?,
+                };
+                let mut node = integer_node(Tag::Enumerated, value, level);
+                let name = root.iter().chain(extension.iter().flatten()).find(|(_, number)| *number == value);
+                node.set_annotation(name.map(|(name, _)| name.clone()).unwrap_or_else(|| "unknown item".to_string()));
+                Ok(node)
+            }
+            Type::BitString { named, constraint } => {
+                let bits = match self.fixed_size(constraint, env)? {
+                    Some(count) => {
+                        let bytes = reader.bytes(count.div_ceil(8))?;
+                        (0..count).map(|i| bytes[i / 8] & 0x80 >> (i % 8) != 0).collect()
+                    }
+                    None => {
+                        let length = self.length(reader)?;
+                        let bytes = reader.bytes(length)?;
+                        let Some((&unused, bytes)) = bytes.split_first() else {
+                            return Err(error("BIT STRING without the unused bits octet"));
+                        };
+                        let count = (bytes.len() * 8).checked_sub(unused as usize).ok_or_else(|| error("invalid BIT STRING"))?;
+                        (0..count).map(|i| bytes[i / 8] & 0x80 >> (i % 8) != 0).collect::<Vec<bool>>()
+                    }
+                };
+                let mut node = leaf(Tag::BitString, bit_string(&bits)?, level);
+                name_bits(&mut node, named, &bits);
+                Ok(node)
+            }
+            Type::OctetString { constraint } => {
+                let bytes = match self.fixed_size(constraint, env)? {
+                    Some(count) => reader.bytes(count)?.to_vec(),
+                    None => self.octets(reader)?,
+                };
+                let value = match &constraint.containing {
+                    Some(inner) => ASN1Value::OctetString(Box::new(self.decode_octets(&bytes, inner, env, level + 1)), bytes),
+                    None => ASN1Value::Bytes(bytes),
+                };
+                Ok(leaf(Tag::OctetString, value, level))
+            }
+            Type::String { kind, constraint } => self.decode_string(reader, *kind, constraint, env, level),
+            Type::ObjectIdentifier => {
+                let bytes = self.octets(reader)?;
+                let value = match ObjectIdentifier::from_bytes(&bytes) {
+                    Ok(oid) => ASN1Value::ObjectIdentifier(oid),
+                    Err(_) => ASN1Value::Bytes(bytes),
+                };
+                Ok(leaf(Tag::ObjectIdentifier, value, level))
+            }
+            Type::RelativeOid | Type::Real => {
+                let bytes = self.octets(reader)?;
+                let tag = if matches!(ty, Type::Real) { Tag::Real } else { Tag::OctetString };
+                Ok(leaf(tag, ASN1Value::Bytes(bytes), level))
+            }
+            Type::Sequence { set, root, extension } => self.decode_sequence(reader, *set, root, extension.as_deref(), env, level),
+            Type::SequenceOf { set, item, .. } => {
+                let tag = if *set { Tag::Set } else { Tag::Sequence };
+                let mut children = vec![];
+                let result = self.decode_items(reader, item, env, level, &mut children);
+                let node = leaf(tag, ASN1Value::Set(children), level);
+                match result {
+                    Ok(()) => Ok(node),
+                    Err(error) => Err(partial(error.message, node)),
+                }
+            }
+            Type::Choice { root, extension } => self.decode_choice(reader, root, extension.as_deref(), env, level),
+            Type::Open => {
+                let mut node = leaf(Tag::OctetString, ASN1Value::Bytes(self.octets(reader)?), level);
+                node.set_annotation("open type");
+                Ok(node)
+            }
+        }
+    }
+
+    fn decode_reference(
+        &mut self,
+        reader: &mut ByteReader,
+        name: &str,
+        arguments: &[Argument],
+        constraint: &Constraint,
+        env: &Env,
+        level: u32,
+    ) -> Result<ASN1Node> {
+        let (ty, inner_env) = self.schema.resolve(name, arguments, constraint, env)?;
+        self.schema.depth += 1;
+        let result = self.decode_type(reader, &ty, &inner_env, level);
+        self.schema.depth -= 1;
+        result.map(|mut node| {
+            annotate_reference(&mut node, name);
+            node
+        })
+    }
+
+    /// X.696 8.6, a length in one octet up to 127 or after the number of its octets.
+    fn length(&mut self, reader: &mut ByteReader) -> Result<usize> {
+        let first = reader.byte()?;
+        if first & 0x80 == 0 {
+            return Ok(first as usize);
+        }
+        let octets = (first & 0x7f) as usize;
+        if octets == 0 || octets > 8 {
+            return Err(error(format!("invalid length at byte {}", reader.position - 1)));
+        }
+        let length = reader.bytes(octets)?.iter().fold(0u64, |length, &byte| length << 8 | byte as u64);
+        usize::try_from(length).map_err(|_| error("length out of range"))
+    }
+
+    /// Reads octets after their length, as sent for open types and unconstrained values.
+    fn octets(&mut self, reader: &mut ByteReader) -> Result<Vec<u8>> {
+        let length = self.length(reader)?;
+        Ok(reader.bytes(length)?.to_vec())
+    }
+
+    /// Returns the size of a string with a fixed SIZE, which is sent without a length.
+    /// Extensible constraints are not OER-visible.
+    fn fixed_size(&self, constraint: &Constraint, env: &Env) -> Result<Option<usize>> {
+        if constraint.size_extensible {
+            return Ok(None);
+        }
+        match self.schema.range(&constraint.size, env)? {
+            (Some(lower), Some(upper)) if lower == upper => Ok(usize::try_from(upper).ok()),
+            _ => Ok(None),
+        }
+    }
+
+    fn decode_integer(&mut self, reader: &mut ByteReader, constraint: &Constraint, env: &Env) -> Result<i128> {
+        let (lower, upper) = match constraint.value_extensible {
+            true => (None, None),
+            false => self.schema.range(&constraint.value, env)?,
+        };
+        match (lower, upper.zip(lower).and_then(|(upper, lower)| fixed_size(lower, upper))) {
+            (Some(lower), Some(size)) if lower >= 0 => unsigned(reader.bytes(size)?),
+            (Some(_), Some(size)) => signed(reader.bytes(size)?),
+            (Some(lower), None) if lower >= 0 => unsigned(&self.octets(reader)?),
+            _ => signed(&self.octets(reader)?),
+        }
+    }
+
+    fn decode_string(&mut self, reader: &mut ByteReader, kind: StringKind, constraint: &Constraint, env: &Env, level: u32) -> Result<ASN1Node> {
+        let (tag, unit) = match kind {
+            StringKind::Ia5 => (Tag::Ia5String, 1),
+            StringKind::Printable => (Tag::PrintableString, 1),
+            StringKind::Visible => (Tag::VisibleString, 1),
+            StringKind::Numeric => (Tag::NumericString, 1),
+            StringKind::Bmp => (Tag::BmpString, 2),
+            StringKind::Universal => (Tag::Utf8String, 4),
+            StringKind::Utf8 => (Tag::Utf8String, 0),
+            StringKind::Other => (Tag::TeletexString, 0),
+        };
+        // only strings of a fixed character size have a length in characters
+        let fixed = if unit > 0 { self.fixed_size(constraint, env)? } else { None };
+        let bytes = match fixed {
+            Some(count) => reader.bytes(count.checked_mul(unit).ok_or_else(|| error("string too large"))?)?.to_vec(),
+            None => self.octets(reader)?,
+        };
+        let text = match unit {
+            2 | 4 => bytes
+                .chunks(unit)
+                .map(|code| char::from_u32(code.iter().fold(0u32, |code, &byte| code << 8 | byte as u32)).unwrap_or('?'))
+                .collect(),
+            _ => String::from_utf8_lossy(&bytes).to_string(),
+        };
+        Ok(leaf(tag, ASN1Value::CharacterString(text), level))
+    }
+
+    fn decode_sequence(
+        &mut self,
+        reader: &mut ByteReader,
+        set: bool,
+        root: &[Component],
+        extension: Option<&[Addition]>,
+        env: &Env,
+        level: u32,
+    ) -> Result<ASN1Node> {
+        let mut children = vec![];
+        self.schema.scopes.push(vec![]);
+        let result = self.decode_sequence_components(reader, root, extension, env, level, &mut children);
+        self.schema.scopes.pop();
+
+        let tag = if set { Tag::Set } else { Tag::Sequence };
+        let mut node = leaf(tag, ASN1Value::Sequence(vec![]), level);
+        annotate_sequence(&mut node, root, &children);
+        node.value = ASN1Value::Sequence(children);
+        match result {
+            Ok(()) => Ok(node),
+            Err(message) => Err(partial(message, node)),
+        }
+    }
+
+    /// Decodes the components into `children`, returning the error message of a failed component.
+    fn decode_sequence_components(
+        &mut self,
+        reader: &mut ByteReader,
+        root: &[Component],
+        extension: Option<&[Addition]>,
+        env: &Env,
+        level: u32,
+        children: &mut Vec<ASN1Node>,
+    ) -> std::result::Result<(), String> {
+        let extended = self.decode_components(reader, root, extension.is_some(), env, level, children)?;
+        if !extended {
+            return Ok(());
+        }
+
+        // the extension bitmap is sent like a BIT STRING
+        let bitmap = self.octets(reader).map_err(|error| error.message)?;
+        let Some((&unused, bitmap)) = bitmap.split_first() else {
+            return Err("extension bitmap without the unused bits octet".to_string());
+        };
+        let count = (bitmap.len() * 8).saturating_sub(unused as usize);
+        let present = (0..count).filter(|i| bitmap[i / 8] & 0x80 >> (i % 8) != 0);
+        let additions = extension.unwrap_or_default();
+        for index in present {
+            let bytes = self.octets(reader).map_err(|error| error.message)?;
+            let Some(addition) = additions.get(index) else {
+                let mut node = leaf(Tag::OctetString, ASN1Value::Bytes(bytes), level + 1);
+                node.set_label(&format!("extension {}", index + 1));
+                node.set_annotation("unknown extension");
+                children.push(node);
+                continue;
+            };
+            let mut inner = ByteReader::new(&bytes);
+            let result = match addition.group {
+                true => self.decode_components(&mut inner, &addition.components, false, env, level, children).map(|_| ()),
+                false => self.decode_component(&mut inner, &addition.components[0], env, level, children),
+            };
+            if let Err(message) = result {
+                self.schema.errors.push(message);
+            }
+        }
+        Ok(())
+    }
+
+    /// Decodes components after the preamble holding the extension bit and the presence bits of
+    /// the OPTIONAL and DEFAULT components, returns whether extension additions follow.
+    fn decode_components(
+        &mut self,
+        reader: &mut ByteReader,
+        components: &[Component],
+        extensible: bool,
+        env: &Env,
+        level: u32,
+        children: &mut Vec<ASN1Node>,
+    ) -> std::result::Result<bool, String> {
+        let components = self.schema.expand(components).map_err(|error| error.message)?;
+        let optional = components.iter().filter(|component| component.optional).count();
+        let preamble = reader.bits(extensible as usize + optional).map_err(|error| error.message)?;
+        let extended = extensible && preamble[0];
+        let mut present = preamble.into_iter().skip(extensible as usize);
+        for component in components.iter() {
+            if component.optional && !present.next().unwrap_or(false) {
+                continue;
+            }
+            self.decode_component(reader, component, env, level, children)?;
+        }
+        Ok(extended)
+    }
+
+    fn decode_component(
+        &mut self,
+        reader: &mut ByteReader,
+        component: &Component,
+        env: &Env,
+        level: u32,
+        children: &mut Vec<ASN1Node>,
+    ) -> std::result::Result<(), String> {
+        match self.decode_labelled(reader, &component.ty, env, level + 1, Some(&component.name)) {
+            Ok(child) => {
+                self.schema.record(component, &child);
+                children.push(child);
+                Ok(())
+            }
+            Err(error) => {
+                children.extend(error.partial);
+                Err(error.message)
+            }
+        }
+    }
+
+    /// X.696 20, the number of items is sent as an unsigned integer after the number of its octets.
+    fn decode_items(&mut self, reader: &mut ByteReader, item: &Type, env: &Env, level: u32, children: &mut Vec<ASN1Node>) -> Result<()> {
+        let octets = self.length(reader)?;
+        let count = unsigned(reader.bytes(octets)?)?;
+        for _ in 0..count {
+            match self.decode_labelled(reader, item, env, level + 1, None) {
+                Ok(child) => children.push(child),
+                Err(mut error) => {
+                    children.extend(error.partial.take());
+                    return Err(error);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// X.696 8.7, the tag of the alternative in the class bits and 6 bits of number, larger
+    /// numbers follow in base 128.
+    fn tag(&mut self, reader: &mut ByteReader) -> Result<(TagClass, u32)> {
+        let first = reader.byte()?;
+        let class = match first >> 6 {
+            0 => TagClass::Universal,
+            1 => TagClass::Application,
+            2 => TagClass::ContextSpecific,
+            _ => TagClass::Private,
+        };
+        if first & 0x3f != 0x3f {
+            return Ok((class, (first & 0x3f) as u32));
+        }
+        let mut number = 0u32;
+        for _ in 0..5 {
+            let byte = reader.byte()?;
+            number = number.checked_shl(7).ok_or_else(|| error("tag number too large"))? | (byte & 0x7f) as u32;
+            if byte & 0x80 == 0 {
+                return Ok((class, number));
+            }
+        }
+        Err(error("tag number too large"))
+    }
+
+    fn decode_choice(&mut self, reader: &mut ByteReader, root: &[Component], extension: Option<&[Component]>, env: &Env, level: u32) -> Result<ASN1Node> {
+        let tag = self.tag(reader)?;
+        let alternatives = root.iter().chain(extension.unwrap_or_default()).collect::<Vec<&Component>>();
+        // without any tag written in the module the alternatives are tagged automatically
+        let automatic = alternatives.iter().all(|alternative| alternative.tag.is_none());
+        let index = alternatives.iter().enumerate().position(|(index, alternative)| match automatic {
+            true => tag == (TagClass::ContextSpecific, index as u32),
+            false => alternative.tag == Some(tag),
+        });
+        let (index, alternative, result) = match index {
+            Some(index) if index < root.len() => {
+                let alternative = alternatives[index];
+                match self.decode_labelled(reader, &alternative.ty, env, level + 1, Some(&alternative.name)) {
+                    Ok(node) => (index, node, Ok(())),
+                    Err(error) => {
+                        let node = error.partial.unwrap_or_else(|| error_node(&error.message, level + 1));
+                        (index, node, Err(error.message))
+                    }
+                }
+            }
+            // extension alternatives are sent as open types
+            Some(index) => {
+                let bytes = self.octets(reader)?;
+                let mut node = self.decode_octets(&bytes, &alternatives[index].ty, env, level + 1);
+                node.set_label(&alternatives[index].name);
+                (index, node, Ok(()))
+            }
+            None if extension.is_some() => {
+                let mut node = leaf(Tag::OctetString, ASN1Value::Bytes(self.octets(reader)?), level + 1);
+                node.set_annotation("unknown alternative");
+                (tag.1 as usize, node, Ok(()))
+            }
+            None => return Err(error(format!("no alternative has the tag {:?} {}", tag.0, tag.1))),
+        };
+        let node = choice_node(index, alternative, level);
+        match result {
+            Ok(()) => Ok(node),
+            Err(message) => Err(partial(message, node)),
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn decode_class_field(
+        &mut self,
+        reader: &mut ByteReader,
+        class: &str,
+        field: &str,
+        set: Option<&str>,
+        relation: Option<&str>,
+        env: &Env,
+        level: u32,
+    ) -> Result<ASN1Node> {
+        match self.schema.field_kind(class, field) {
+            Some(FieldKind::Value(ty)) => self.decode_type(reader, ty, &Env::default(), level),
+            _ if is_upper(field) => {
+                let bytes = self.octets(reader)?;
+                let Some(ty) = self.schema.object_type(set, relation, field, env) else {
+                    let mut node = leaf(Tag::OctetString, ASN1Value::Bytes(bytes), level);
+                    node.set_annotation("open type");
+                    return Ok(node);
+                };
+                let mut node = self.decode_octets(&bytes, &ty, &Env::default(), level);
+                annotate_open_type(&mut node, &ty);
+                Ok(node)
+            }
+            _ => Err(error(format!("class field {}.{} is not supported", class, field))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::asn1_der::view_lines;
+    use super::*;
+
+    const MODULE: &str = "M DEFINITIONS AUTOMATIC TAGS ::= BEGIN
+        Msg ::= SEQUENCE {
+            flag BOOLEAN,
+            count INTEGER (-5..10),
+            bits BIT STRING { first(0), second(1), third(2) } (SIZE (3)),
+            octets OCTET STRING,
+            text IA5String,
+            list SEQUENCE (SIZE (1..4)) OF INTEGER (0..7),
+            color ENUMERATED { red, green },
+            choice CHOICE { a INTEGER, b BOOLEAN },
+            opt INTEGER OPTIONAL,
+            ...
+        }
+        END";
+
+    fn decoded(data: &str) -> (Vec<String>, Option<String>) {
+        let mut modules = Modules::default();
+        modules.load(MODULE).unwrap();
+        let (nodes, status) = decode(&hex::decode(data).unwrap(), &modules, "Msg");
+        (nodes.iter().flat_map(view_lines).collect(), status)
+    }
+
+    const MSG: &str = "00ff03a0020102084869202620627965010201070181";
+
+    #[test]
+    fn decodes_values() {
+        let (lines, status) = decoded(&format!("{}00", MSG));
+        assert_eq!(status, None);
+        assert_eq!(
+            lines,
+            [
+                "Msg: SEQUENCE (field(s): 8) (Msg)",
+                "flag: BOOLEAN true",
+                "count: INTEGER 3",
+                "bits: BIT STRING 10100000 (first, third)",
+                "octets: OCTET STRING 0102",
+                "text: IA5String \"Hi & bye\"",
+                "list: SEQUENCE (item(s): 2)",
+                "INTEGER 1",
+                "INTEGER 7",
+                "color: ENUMERATED 1 (green)",
+                "choice: CONTEXT-SPECIFIC [1] (constructed)  (b)",
+                "b: BOOLEAN false",
+            ]
+        );
+
+        // the presence bit of opt follows the extension bit in the preamble
+        let (lines, status) = decoded(&format!("40{}000105", &MSG[2..]));
+        assert_eq!(status, None);
+        assert_eq!(lines.last().map(String::as_str), Some("opt: INTEGER 5"));
+    }
+
+    #[test]
+    fn reports_truncated_values() {
+        let (lines, status) = decoded(MSG);
+        assert_eq!(status.as_deref(), Some("decoding stopped: unexpected end of data at byte 22"));
+        assert_eq!(lines.len(), 12);
+        assert_eq!(lines[11], "b: NULL \"unexpected end of data at byte 22\"");
+        let (_, status) = decoded(&format!("{}00ff", MSG));
+        assert_eq!(status.as_deref(), Some("1 byte(s) left after the Msg value"));
+    }
+}
@@ -0,0 +1,635 @@
+use der::asn1::ObjectIdentifier;
+use der::Tag;
+use crate::asn1_der::{ASN1Node, ASN1Value};
+use crate::asn1_module::parser::is_upper;
+use crate::asn1_module::{Addition, Argument, Component, Constraint, FieldKind, Modules, StringKind, Type};
+use crate::encoding::schema::{annotate_open_type, annotate_reference, annotate_sequence, Env, Schema};
+use crate::encoding::{bit_string, choice_node, error, error_node, finish, integer_node, labelled, leaf, name_bits, partial, recovered, Result};
+
+/// Lengths from 16K on are sent in fragments of 16K to 64K units.
+const FRAGMENT: usize = 16384;
+const K64: i128 = 65536;
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        BitReader { data, position: 0 }
+    }
+
+    fn ensure(&self, bits: usize) -> Result<()> {
+        match self.position.checked_add(bits) {
+            Some(end) if end <= self.data.len() * 8 => Ok(()),
+            _ => Err(error(format!("unexpected end of data at bit {}", self.position))),
+        }
+    }
+
+    fn bit(&mut self) -> Result<bool> {
+        Ok(self.bits(1)? == 1)
+    }
+
+    fn bits(&mut self, count: usize) -> Result<u128> {
+        if count > 128 {
+            return Err(error(format!("a {} bit field at bit {} is too large", count, self.position)));
+        }
+        self.ensure(count)?;
+        let mut value = 0;
+        for _ in 0..count {
+            let bit = self.data[self.position / 8] >> (7 - self.position % 8) & 1;
+            value = value << 1 | bit as u128;
+            self.position += 1;
+        }
+        Ok(value)
+    }
+
+    fn align(&mut self) {
+        self.position = self.position.div_ceil(8) * 8;
+    }
+}
+
+struct Decoder<'a> {
+    schema: Schema<'a>,
+    aligned: bool,
+}
+
+pub(crate) fn decode(data: &[u8], modules: &Modules, type_name: &str, aligned: bool) -> (Vec<ASN1Node>, Option<String>) {
+    let mut decoder = Decoder { schema: Schema::new(modules), aligned };
+    let mut reader = BitReader::new(data);
+    let ty = Type::Reference { name: type_name.to_string(), arguments: vec![], constraint: Constraint::default() };
+    let result = decoder.decode_labelled(&mut reader, &ty, &Env::default(), 0, Some(type_name));
+    let rest = data.len().saturating_sub(reader.position.div_ceil(8));
+    finish(result, rest, type_name, &decoder.schema.errors)
+}
+
+/// Number of bits holding the numbers `0..range`.
+fn bits_for(range: u128) -> usize {
+    (128 - (range - 1).leading_zeros()) as usize
+}
+
+impl Decoder<'_> {
+    /// Decodes a value and labels it, a failed value is labelled too so that it shows where decoding stopped.
+    fn decode_labelled(&mut self, reader: &mut BitReader, ty: &Type, env: &Env, level: u32, label: Option<&str>) -> Result<ASN1Node> {
+        let result = self.decode_type(reader, ty, env, level);
+        labelled(result, label, level)
+    }
+
+    /// Decodes a value sent in its own octets, such as an open type or a contained value. Decoding
+    /// goes on after a failure since the length of the octets is known.
+    fn decode_octets(&mut self, bytes: &[u8], ty: &Type, env: &Env, level: u32) -> ASN1Node {
+        let mut reader = BitReader::new(bytes);
+        let scopes = std::mem::take(&mut self.schema.scopes);
+        let result = self.decode_labelled(&mut reader, ty, env, level, None);
+        self.schema.scopes = scopes;
+        recovered(result, &mut self.schema.errors, level)
+    }
+
+    fn decode_type(&mut self, reader: &mut BitReader, ty: &Type, env: &Env, level: u32) -> Result<ASN1Node> {
+        match ty {
+            Type::Reference { name, arguments, constraint } => self.decode_reference(reader, name, arguments, constraint, env, level),
+            Type::ClassField { class, field, set, relation } => {
+                self.decode_class_field(reader, class, field, set.as_deref(), relation.as_deref(), env, level)
+            }
+            Type::Boolean => Ok(leaf(Tag::Boolean, ASN1Value::Boolean(reader.bit()?), level)),
+            Type::Null => Ok(leaf(Tag::Null, ASN1Value::Null, level)),
+            Type::Integer { named, constraint } => {
+                let value = self.decode_integer(reader, constraint, env)?;
+                let mut node = integer_node(Tag::Integer, value, level);
+                if let Some((name, _)) = named.iter().find(|(_, number)| *number == value) {
+                    node.set_annotation(name.clone());
+                }
+                Ok(node)
+            }
+            Type::Enumerated { root, extension } => {
+                let item = match extension {
+                    Some(extension) if reader.bit()? => {
+                        let index = self.normally_small(reader)?;
+                        extension.get(index).cloned().unwrap_or_else(|| (format!("unknown extension {}", index), (root.len() + index) as i128))
+                    }
+                    _ => {
+                        let index = self.constrained_whole_number(reader, 0, root.len() as i128 - 1)?;
+                        root.get(index as usize).cloned().ok_or_else(|| error("ENUMERATED index out of range"))?
+                    }
+                };
+                let mut node = integer_node(Tag::Enumerated, item.1, level);
+                node.set_annotation(item.0);
+                Ok(node)
+            }
+            Type::BitString { named, constraint } => {
+                let bits = self.string_units(reader, constraint, env, 1, false)?;
+                let bits = bits.into_iter().map(|bit| bit == 1).collect::<Vec<bool>>();
+                let mut node = leaf(Tag::BitString, bit_string(&bits)?, level);
+                name_bits(&mut node, named, &bits);
+                Ok(node)
+            }
+            Type::OctetString { constraint } => {
+                let bytes = self.string_units(reader, constraint, env, 8, false)?.into_iter().map(|unit| unit as u8).collect::<Vec<u8>>();
+                let value = match &constraint.containing {
+                    Some(inner) => ASN1Value::OctetString(Box::new(self.decode_octets(&bytes, inner, env, level + 1)), bytes),
+                    None => ASN1Value::Bytes(bytes),
+                };
+                Ok(leaf(Tag::OctetString, value, level))
+            }
+            Type::String { kind, constraint } => self.decode_string(reader, *kind, constraint, env, level),
+            Type::ObjectIdentifier => {
+                let bytes = self.open_type_octets(reader)?;
+                let value = match ObjectIdentifier::from_bytes(&bytes) {
+                    Ok(oid) => ASN1Value::ObjectIdentifier(oid),
+                    Err(_) => ASN1Value::Bytes(bytes),
+                };
+                Ok(leaf(Tag::ObjectIdentifier, value, level))
+            }
+            Type::RelativeOid | Type::Real => {
+                let bytes = self.open_type_octets(reader)?;
+                let tag = if matches!(ty, Type::Real) { Tag::Real } else { Tag::OctetString };
+                Ok(leaf(tag, ASN1Value::Bytes(bytes), level))
+            }
+            Type::Sequence { set, root, extension } => self.decode_sequence(reader, *set, root, extension.as_deref(), env, level),
+            Type::SequenceOf { set, item, constraint } => self.decode_sequence_of(reader, *set, item, constraint, env, level),
+            Type::Choice { root, extension } => self.decode_choice(reader, root, extension.as_deref(), env, level),
+            Type::Open => {
+                let mut node = leaf(Tag::OctetString, ASN1Value::Bytes(self.open_type_octets(reader)?), level);
+                node.set_annotation("open type");
+                Ok(node)
+            }
+        }
+    }
+
+    fn decode_reference(
+        &mut self,
+        reader: &mut BitReader,
+        name: &str,
+        arguments: &[Argument],
+        constraint: &Constraint,
+        env: &Env,
+        level: u32,
+    ) -> Result<ASN1Node> {
+        let (ty, inner_env) = self.schema.resolve(name, arguments, constraint, env)?;
+        self.schema.depth += 1;
+        let result = self.decode_type(reader, &ty, &inner_env, level);
+        self.schema.depth -= 1;
+        result.map(|mut node| {
+            annotate_reference(&mut node, name);
+            node
+        })
+    }
+
+    fn decode_integer(&mut self, reader: &mut BitReader, constraint: &Constraint, env: &Env) -> Result<i128> {
+        let (lower, upper) = self.schema.range(&constraint.value, env)?;
+        if constraint.value_extensible && reader.bit()? {
+            return self.unconstrained_whole_number(reader, None);
+        }
+        match (lower, upper) {
+            (Some(lower), Some(upper)) => self.constrained_whole_number(reader, lower, upper),
+            (Some(lower), None) => self.unconstrained_whole_number(reader, Some(lower)),
+            _ => self.unconstrained_whole_number(reader, None),
+        }
+    }
+
+    /// X.691 11.5, a number within `lower..=upper` sent as the offset from the lower bound.
+    fn constrained_whole_number(&mut self, reader: &mut BitReader, lower: i128, upper: i128) -> Result<i128> {
+        let range = upper
+            .checked_sub(lower)
+            .filter(|range| *range >= 0)
+            .ok_or_else(|| error(format!("empty range {}..{}", lower, upper)))? as u128
+            + 1;
+        if range == 1 {
+            return Ok(lower);
+        }
+        let offset = if !self.aligned || range <= 255 {
+            reader.bits(bits_for(range))?
+        } else if range == 256 {
+            reader.align();
+            reader.bits(8)?
+        } else if range <= K64 as u128 {
+            reader.align();
+            reader.bits(16)?
+        } else {
+            // the number of octets comes first
+            let octets = bits_for(range).div_ceil(8) as i128;
+            let length = self.constrained_whole_number(reader, 1, octets)? as usize;
+            reader.align();
+            reader.bits(length * 8)?
+        };
+        // the bits read can hold numbers above the upper bound, which a valid encoding does not send
+        if offset >= range {
+            return Err(error(format!("{} is above the upper bound {}", lower.saturating_add_unsigned(offset), upper)));
+        }
+        i128::try_from(offset).ok().and_then(|offset| lower.checked_add(offset)).ok_or_else(|| error("integer out of range"))
+    }
+
+    /// X.691 11.7 and 11.8, an offset from the lower bound or a two's complement number after a length.
+    fn unconstrained_whole_number(&mut self, reader: &mut BitReader, lower: Option<i128>) -> Result<i128> {
+        let bytes = self.open_type_octets(reader)?;
+        if bytes.len() > 16 {
+            return Err(error(format!("a {} byte integer is too large", bytes.len())));
+        }
+        let unsigned = bytes.iter().fold(0u128, |value, &byte| value << 8 | byte as u128);
+        match lower {
+            Some(lower) => i128::try_from(unsigned)
+                .ok()
+                .and_then(|unsigned| lower.checked_add(unsigned))
+                .ok_or_else(|| error("integer out of range")),
+            None if bytes.is_empty() => Ok(0),
+            None => {
+                let shift = 128 - bytes.len() * 8;
+                Ok(((unsigned << shift) as i128) >> shift)
+            }
+        }
+    }
+
+    /// X.691 11.6, used for CHOICE and ENUMERATED extension indexes.
+    fn normally_small(&mut self, reader: &mut BitReader) -> Result<usize> {
+        if !reader.bit()? {
+            return Ok(reader.bits(6)? as usize);
+        }
+        usize::try_from(self.unconstrained_whole_number(reader, Some(0))?).map_err(|_| error("index out of range"))
+    }
+
+    /// X.691 11.9, returns the length and whether it is a fragment which more fragments follow.
+    fn length(&mut self, reader: &mut BitReader, lower: i128, upper: Option<i128>) -> Result<(usize, bool)> {
+        if let Some(upper) = upper.filter(|upper| *upper < K64) {
+            let length = self.constrained_whole_number(reader, lower, upper)?;
+            return usize::try_from(length).map(|length| (length, false)).map_err(|_| error("negative length"));
+        }
+        if self.aligned {
+            reader.align();
+        }
+        let first = reader.bits(8)? as usize;
+        if first & 0x80 == 0 {
+            Ok((first, false))
+        } else if first & 0x40 == 0 {
+            Ok(((first & 0x3f) << 8 | reader.bits(8)? as usize, false))
+        } else {
+            match first & 0x3f {
+                fragments @ 1..=4 => Ok((fragments * FRAGMENT, true)),
+                _ => Err(error(format!("invalid length fragment at bit {}", reader.position - 8))),
+            }
+        }
+    }
+
+    /// Reads units of `unit` bits after a length determinant, following fragments.
+    fn units(&mut self, reader: &mut BitReader, lower: i128, upper: Option<i128>, unit: usize, align: bool) -> Result<Vec<u128>> {
+        let mut units = vec![];
+        loop {
+            let (count, more) = self.length(reader, lower, upper)?;
+            if self.aligned && align && count > 0 {
+                reader.align();
+            }
+            reader.ensure(count.saturating_mul(unit))?;
+            for _ in 0..count {
+                units.push(reader.bits(unit)?);
+            }
+            if !more {
+                return Ok(units);
+            }
+        }
+    }
+
+    /// Reads the octets of an open type, also used for unconstrained numbers and object identifiers.
+    fn open_type_octets(&mut self, reader: &mut BitReader) -> Result<Vec<u8>> {
+        Ok(self.units(reader, 0, None, 8, true)?.into_iter().map(|unit| unit as u8).collect())
+    }
+
+    /// Reads the units of a BIT STRING, OCTET STRING or character string with its SIZE constraint.
+    /// Fixed sizes up to 16 bits are not aligned, neither are character strings of up to 16 bits.
+    fn string_units(&mut self, reader: &mut BitReader, constraint: &Constraint, env: &Env, unit: usize, characters: bool) -> Result<Vec<u128>> {
+        let (lower, upper) = self.schema.range(&constraint.size, env)?;
+        let extended = constraint.size_extensible && reader.bit()?;
+        let (lower, upper) = if extended { (0, None) } else { (lower.unwrap_or(0).max(0), upper) };
+        match upper {
+            Some(upper) if upper == lower && upper < K64 => {
+                let count = upper as usize;
+                if self.aligned && count * unit > 16 {
+                    reader.align();
+                }
+                reader.ensure(count * unit)?;
+                (0..count).map(|_| reader.bits(unit)).collect()
+            }
+            _ => {
+                let align = !characters || upper.is_none_or(|upper| upper as usize * unit > 16);
+                self.units(reader, lower, upper, unit, align)
+            }
+        }
+    }
+
+    fn decode_string(&mut self, reader: &mut BitReader, kind: StringKind, constraint: &Constraint, env: &Env, level: u32) -> Result<ASN1Node> {
+        let (tag, unit) = match kind {
+            StringKind::Ia5 => (Tag::Ia5String, 7),
+            StringKind::Printable => (Tag::PrintableString, 7),
+            StringKind::Visible => (Tag::VisibleString, 7),
+            StringKind::Numeric => (Tag::NumericString, 4),
+            StringKind::Bmp => (Tag::BmpString, 16),
+            StringKind::Universal => (Tag::Utf8String, 32),
+            StringKind::Utf8 | StringKind::Other => {
+                // the size of these strings is not known in octets, so their length is unconstrained
+                let bytes = self.open_type_octets(reader)?;
+                let tag = if kind == StringKind::Utf8 { Tag::Utf8String } else { Tag::TeletexString };
+                return Ok(leaf(tag, ASN1Value::CharacterString(String::from_utf8_lossy(&bytes).to_string()), level));
+            }
+        };
+        // ALIGNED rounds the character size up to a power of two
+        let unit = if self.aligned && unit == 7 { 8 } else { unit };
+        let codes = self.string_units(reader, constraint, env, unit, true)?;
+        let text = codes
+            .into_iter()
+            .map(|code| match kind {
+                StringKind::Numeric => " 0123456789".chars().nth(code as usize).unwrap_or('?'),
+                _ => u32::try_from(code).ok().and_then(char::from_u32).unwrap_or('?'),
+            })
+            .collect::<String>();
+        Ok(leaf(tag, ASN1Value::CharacterString(text), level))
+    }
+
+    fn decode_sequence(
+        &mut self,
+        reader: &mut BitReader,
+        set: bool,
+        root: &[Component],
+        extension: Option<&[Addition]>,
+        env: &Env,
+        level: u32,
+    ) -> Result<ASN1Node> {
+        let mut children = vec![];
+        self.schema.scopes.push(vec![]);
+        let result = self.decode_sequence_components(reader, root, extension, env, level, &mut children);
+        self.schema.scopes.pop();
+
+        let tag = if set { Tag::Set } else { Tag::Sequence };
+        let mut node = leaf(tag, ASN1Value::Sequence(vec![]), level);
+        annotate_sequence(&mut node, root, &children);
+        node.value = ASN1Value::Sequence(children);
+        match result {
+            Ok(()) => Ok(node),
+            Err(message) => Err(partial(message, node)),
+        }
+    }
+
+    /// Decodes the components into `children`, returning the error message of a failed component.
+    fn decode_sequence_components(
+        &mut self,
+        reader: &mut BitReader,
+        root: &[Component],
+        extension: Option<&[Addition]>,
+        env: &Env,
+        level: u32,
+        children: &mut Vec<ASN1Node>,
+    ) -> std::result::Result<(), String> {
+        let extended = extension.is_some() && reader.bit().map_err(|error| error.message)?;
+        self.decode_components(reader, root, env, level, children)?;
+        if !extended {
+            return Ok(());
+        }
+
+        let count = match reader.bit() {
+            Ok(false) => reader.bits(6).map(|count| count as usize + 1),
+            Ok(true) => self.length(reader, 0, None).map(|(count, _)| count),
+            Err(error) => Err(error),
+        }
+        .map_err(|error| error.message)?;
+        let present = (0..count).map(|_| reader.bit()).collect::<Result<Vec<bool>>>().map_err(|error| error.message)?;
+        let additions = extension.unwrap_or_default();
+        for (index, _) in present.iter().enumerate().filter(|(_, present)| **present) {
+            let bytes = self.open_type_octets(reader).map_err(|error| error.message)?;
+            let Some(addition) = additions.get(index) else {
+                let mut node = leaf(Tag::OctetString, ASN1Value::Bytes(bytes), level + 1);
+                node.set_label(&format!("extension {}", index + 1));
+                node.set_annotation("unknown extension");
+                children.push(node);
+                continue;
+            };
+            let mut inner = BitReader::new(&bytes);
+            let components = if addition.group { &addition.components[..] } else { &addition.components[..1] };
+            let result = match addition.group {
+                true => self.decode_components(&mut inner, components, env, level, children),
+                false => self.decode_component(&mut inner, &components[0], env, level, children),
+            };
+            if let Err(message) = result {
+                self.schema.errors.push(message);
+            }
+        }
+        Ok(())
+    }
+
+    /// Decodes root components after the presence bitmap of their OPTIONAL and DEFAULT components.
+    fn decode_components(
+        &mut self,
+        reader: &mut BitReader,
+        components: &[Component],
+        env: &Env,
+        level: u32,
+        children: &mut Vec<ASN1Node>,
+    ) -> std::result::Result<(), String> {
+        let components = self.schema.expand(components).map_err(|error| error.message)?;
+        let optional = components.iter().filter(|component| component.optional).count();
+        let present = (0..optional).map(|_| reader.bit()).collect::<Result<Vec<bool>>>().map_err(|error| error.message)?;
+        let mut present = present.into_iter();
+        for component in components.iter() {
+            if component.optional && !present.next().unwrap_or(false) {
+                continue;
+            }
+            self.decode_component(reader, component, env, level, children)?;
+        }
+        Ok(())
+    }
+
+    fn decode_component(
+        &mut self,
+        reader: &mut BitReader,
+        component: &Component,
+        env: &Env,
+        level: u32,
+        children: &mut Vec<ASN1Node>,
+    ) -> std::result::Result<(), String> {
+        match self.decode_labelled(reader, &component.ty, env, level + 1, Some(&component.name)) {
+            Ok(child) => {
+                self.schema.record(component, &child);
+                children.push(child);
+                Ok(())
+            }
+            Err(error) => {
+                children.extend(error.partial);
+                Err(error.message)
+            }
+        }
+    }
+
+    fn decode_sequence_of(&mut self, reader: &mut BitReader, set: bool, item: &Type, constraint: &Constraint, env: &Env, level: u32) -> Result<ASN1Node> {
+        let tag = if set { Tag::Set } else { Tag::Sequence };
+        let mut children = vec![];
+        let result = self.decode_items(reader, item, constraint, env, level, &mut children);
+        let node = leaf(tag, ASN1Value::Set(children), level);
+        match result {
+            Ok(()) => Ok(node),
+            Err(error) => Err(partial(error.message, node)),
+        }
+    }
+
+    fn decode_items(&mut self, reader: &mut BitReader, item: &Type, constraint: &Constraint, env: &Env, level: u32, children: &mut Vec<ASN1Node>) -> Result<()> {
+        let (lower, upper) = self.schema.range(&constraint.size, env)?;
+        let extended = constraint.size_extensible && reader.bit()?;
+        let (lower, upper) = if extended { (0, None) } else { (lower.unwrap_or(0).max(0), upper) };
+        loop {
+            let (count, more) = match upper {
+                Some(upper) if upper == lower && upper < K64 => (upper as usize, false),
+                _ => self.length(reader, lower, upper)?,
+            };
+            for _ in 0..count {
+                match self.decode_labelled(reader, item, env, level + 1, None) {
+                    Ok(child) => children.push(child),
+                    Err(mut error) => {
+                        children.extend(error.partial.take());
+                        return Err(error);
+                    }
+                }
+            }
+            if !more {
+                return Ok(());
+            }
+        }
+    }
+
+    fn decode_choice(&mut self, reader: &mut BitReader, root: &[Component], extension: Option<&[Component]>, env: &Env, level: u32) -> Result<ASN1Node> {
+        let (index, alternative, result) = match extension {
+            Some(extension) if reader.bit()? => {
+                let index = self.normally_small(reader)?;
+                let bytes = self.open_type_octets(reader)?;
+                let alternative = match extension.get(index) {
+                    Some(alternative) => {
+                        let mut node = self.decode_octets(&bytes, &alternative.ty, env, level + 1);
+                        node.set_label(&alternative.name);
+                        node
+                    }
+                    None => {
+                        let mut node = leaf(Tag::OctetString, ASN1Value::Bytes(bytes), level + 1);
+                        node.set_annotation("unknown alternative");
+                        node
+                    }
+                };
+                (root.len() + index, alternative, Ok(()))
+            }
+            _ => {
+                if root.is_empty() {
+                    return Err(error("CHOICE without alternatives"));
+                }
+                let index = self.constrained_whole_number(reader, 0, root.len() as i128 - 1)? as usize;
+                let alternative = root.get(index).ok_or_else(|| error("CHOICE index out of range"))?;
+                match self.decode_labelled(reader, &alternative.ty, env, level + 1, Some(&alternative.name)) {
+                    Ok(node) => (index, node, Ok(())),
+                    Err(error) => {
+                        let node = error.partial.unwrap_or_else(|| error_node(&error.message, level + 1));
+                        (index, node, Err(error.message))
+                    }
+                }
+            }
+        };
+        let node = choice_node(index, alternative, level);
+        match result {
+            Ok(()) => Ok(node),
+            Err(message) => Err(partial(message, node)),
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn decode_class_field(
+        &mut self,
+        reader: &mut BitReader,
+        class: &str,
+        field: &str,
+        set: Option<&str>,
+        relation: Option<&str>,
+        env: &Env,
+        level: u32,
+    ) -> Result<ASN1Node> {
+        match self.schema.field_kind(class, field) {
+            Some(FieldKind::Value(ty)) => self.decode_type(reader, ty, &Env::default(), level),
+            _ if is_upper(field) => {
+                let bytes = self.open_type_octets(reader)?;
+                let Some(ty) = self.schema.object_type(set, relation, field, env) else {
+                    let mut node = leaf(Tag::OctetString, ASN1Value::Bytes(bytes), level);
+                    node.set_annotation("open type");
+                    return Ok(node);
+                };
+                let mut node = self.decode_octets(&bytes, &ty, &Env::default(), level);
+                annotate_open_type(&mut node, &ty);
+                Ok(node)
+            }
+            _ => Err(error(format!("class field {}.{} is not supported", class, field))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::asn1_der::view_lines;
+    use crate::asn1_module::Modules;
+    use super::decode;
+
+    /// The PersonnelRecord of X.691 annex A.1. Tags of types are not read from modules, so the SET
+    /// components are written in the canonical order of their tags, the order PER sends them in.
+    const PERSONNEL: &str = "
+        Personnel DEFINITIONS ::= BEGIN
+        PersonnelRecord ::= [APPLICATION 0] IMPLICIT SET {
+            name Name,
+            number EmployeeNumber,
+            title [0] VisibleString,
+            dateOfHire [1] Date,
+            nameOfSpouse [2] Name,
+            children [3] IMPLICIT SEQUENCE OF ChildInformation DEFAULT {} }
+        ChildInformation ::= SET { name Name, dateOfBirth [0] Date }
+        Name ::= [APPLICATION 1] IMPLICIT SEQUENCE { givenName VisibleString, initial VisibleString, familyName VisibleString }
+        EmployeeNumber ::= [APPLICATION 2] IMPLICIT INTEGER
+        Date ::= [APPLICATION 3] IMPLICIT VisibleString
+        END";
+
+    fn decoded(module: &str, type_name: &str, data: &str, aligned: bool) -> (Vec<String>, Option<String>) {
+        let mut modules = Modules::default();
+        modules.load(module).unwrap();
+        modules.link().unwrap();
+        let (nodes, status) = decode(&hex::decode(data).unwrap(), &modules, type_name, aligned);
+        (nodes.iter().flat_map(view_lines).collect(), status)
+    }
+
+    #[test]
+    fn decodes_the_personnel_record() {
+        let aligned = concat!(
+            "80044a6f686e015005536d6974680133084469726563746f72083139373130393137044d617279015405536d697468",
+            "020552616c7068015405536d69746808313935373131313105537573616e0142054a6f6e6573083139353930373137",
+        );
+        let unaligned = concat!(
+            "824adfa3700d005a7b74f4d0026611134f2cb8fa6fe410c5cb762c1cb16e09370f2f20350169edd3d340102d2c3b38",
+            "6801a80b4f6e9e9a0218b96add8b162c4169f5e787700c20595bf765e610c5cb572c1bb16e",
+        );
+        for (data, aligned) in [(aligned, true), (unaligned, false)] {
+            let (lines, status) = decoded(PERSONNEL, "PersonnelRecord", data, aligned);
+            assert_eq!(status, None);
+            assert_eq!(lines.len(), 25);
+            assert_eq!(lines[2], "givenName: VisibleString \"John\"");
+            assert_eq!(lines[5], "number: INTEGER 51");
+            assert_eq!(lines[6], "title: VisibleString \"Director\"");
+            assert_eq!(lines[12], "children: SEQUENCE (item(s): 2)");
+            assert_eq!(lines[24], "dateOfBirth: VisibleString \"19590717\"");
+        }
+    }
+
+    #[test]
+    fn rejects_indexes_past_the_root() {
+        let module = "M DEFINITIONS AUTOMATIC TAGS ::= BEGIN
+            C ::= CHOICE { a NULL, b NULL, c NULL }
+            E ::= ENUMERATED { x, y, z }
+            N ::= INTEGER (0..2)
+            END";
+        // two bits hold the index 3 although there are three alternatives, items or numbers
+        for type_name in ["C", "E", "N"] {
+            for aligned in [true, false] {
+                let (_, status) = decoded(module, type_name, "c0", aligned);
+                assert_eq!(status.as_deref(), Some("decoding stopped: 3 is above the upper bound 2"));
+            }
+        }
+        let (lines, status) = decoded(module, "C", "80", true);
+        assert_eq!(status, None);
+        assert_eq!(lines, ["C: CONTEXT-SPECIFIC [2] (constructed)  (c)", "c: NULL Null"]);
+    }
+}
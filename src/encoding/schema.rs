@@ -0,0 +1,222 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::rc::Rc;
+use crate::asn1_der::{ASN1Node, ASN1Value};
+use crate::asn1_module::parser::{apply_constraint, is_upper};
+use crate::asn1_module::{Argument, Bound, Component, Constraint, FieldKind, Modules, Object, Parameter, SetElement, Setting, Type, Value};
+use crate::encoding::{error, Result};
+
+/// Type references are followed this deep before a loop in the module is assumed.
+const MAX_DEPTH: usize = 512;
+
+/// Bindings of the formal parameters of a parameterized type.
+#[derive(Clone, Default)]
+pub(crate) struct Env(Rc<HashMap<String, Binding>>);
+
+pub(crate) enum Binding {
+    Type(Type, Env),
+    Value(i128),
+    Set(Vec<SetElement>, Env),
+}
+
+/// The state shared by the decoders of all encodings while they walk the types of the modules.
+pub(crate) struct Schema<'a> {
+    pub(crate) modules: &'a Modules,
+    /// Integer components of the enclosing SEQUENCEs, with the class field they were read from,
+    /// which select the information object of an open type.
+    pub(crate) scopes: Vec<Vec<(String, i128, Option<String>)>>,
+    /// Open types and contained values which failed, decoding went on after them.
+    pub(crate) errors: Vec<String>,
+    pub(crate) depth: usize,
+}
+
+impl<'a> Schema<'a> {
+    pub(crate) fn new(modules: &'a Modules) -> Self {
+        Schema { modules, scopes: vec![], errors: vec![], depth: 0 }
+    }
+
+    /// Returns the type a reference stands for with the constraint of the reference applied, and
+    /// the bindings of its parameters.
+    pub(crate) fn resolve<'e>(&self, name: &str, arguments: &[Argument], constraint: &Constraint, env: &'e Env) -> Result<(Cow<'e, Type>, Env)>
+    where
+        'a: 'e,
+    {
+        if self.depth >= MAX_DEPTH {
+            return Err(error(format!("type {} refers to itself", name)));
+        }
+        let modules: &'a Modules = self.modules;
+        let (ty, inner_env) = match env.0.get(name) {
+            Some(Binding::Type(ty, bound_env)) => (ty, bound_env.clone()),
+            _ => {
+                let assignment = modules.types.get(name).ok_or_else(|| error(format!("type {} is not defined", name)))?;
+                let bindings = assignment
+                    .parameters
+                    .iter()
+                    .zip(arguments)
+                    .filter_map(|(parameter, argument)| Some((parameter.name.clone(), self.bind(parameter, argument, env)?)))
+                    .collect();
+                (&assignment.ty, Env(Rc::new(bindings)))
+            }
+        };
+        if constraint.is_empty() {
+            return Ok((Cow::Borrowed(ty), inner_env));
+        }
+        let mut ty = ty.clone();
+        apply_constraint(&mut ty, constraint.clone());
+        Ok((Cow::Owned(ty), inner_env))
+    }
+
+    fn bind(&self, parameter: &Parameter, argument: &Argument, env: &Env) -> Option<Binding> {
+        let binding = match argument {
+            Argument::Set(elements) => Binding::Set(elements.clone(), env.clone()),
+            Argument::Type(Type::Reference { name, arguments, .. }) if parameter.governed && arguments.is_empty() => {
+                Binding::Set(vec![SetElement::SetReference(name.clone())], env.clone())
+            }
+            Argument::Type(ty) => Binding::Type(ty.clone(), env.clone()),
+            Argument::Value(value) => Binding::Value(self.value(value, env)?),
+        };
+        Some(binding)
+    }
+
+    pub(crate) fn value(&self, value: &Value, env: &Env) -> Option<i128> {
+        match value {
+            Value::Integer(value) => Some(*value),
+            Value::Name(name) => match env.0.get(name) {
+                Some(Binding::Value(value)) => Some(*value),
+                _ => self.modules.integer(name),
+            },
+            Value::Other => None,
+        }
+    }
+
+    /// Returns the lowest and highest bound of the ranges, `None` when a bound is MIN, MAX or missing.
+    pub(crate) fn range(&self, ranges: &[(Bound, Bound)], env: &Env) -> Result<(Option<i128>, Option<i128>)> {
+        if ranges.is_empty() {
+            return Ok((None, None));
+        }
+        let bound = |bound: &Bound| match bound {
+            Bound::Min | Bound::Max => Ok(None),
+            Bound::Value(value) => Ok(Some(*value)),
+            Bound::Reference(name) => self
+                .value(&Value::Name(name.clone()), env)
+                .map(Some)
+                .ok_or_else(|| error(format!("value {} is not defined", name))),
+        };
+        let (mut lower, mut upper) = (Some(i128::MAX), Some(i128::MIN));
+        for (low, high) in ranges {
+            lower = lower.zip(bound(low)?).map(|(a, b)| a.min(b));
+            upper = upper.zip(bound(high)?).map(|(a, b)| a.max(b));
+        }
+        Ok((lower, upper))
+    }
+
+    /// Replaces `COMPONENTS OF Type` with the root components of the referenced SEQUENCE.
+    pub(crate) fn expand<'c>(&self, components: &'c [Component]) -> Result<Cow<'c, [Component]>> {
+        if !components.iter().any(|component| component.components_of) {
+            return Ok(Cow::Borrowed(components));
+        }
+        let mut expanded = vec![];
+        for component in components {
+            if !component.components_of {
+                expanded.push(component.clone());
+                continue;
+            }
+            let included = match &component.ty {
+                Type::Reference { name, .. } => self.modules.types.get(name).map(|assignment| &assignment.ty),
+                ty => Some(ty),
+            };
+            match included {
+                Some(Type::Sequence { root, .. }) => expanded.extend(self.expand(root)?.iter().cloned()),
+                _ => return Err(error("COMPONENTS OF needs a SEQUENCE type")),
+            }
+        }
+        Ok(Cow::Owned(expanded))
+    }
+
+    /// Keeps the value of an integer component for the open types which depend on it.
+    pub(crate) fn record(&mut self, component: &Component, node: &ASN1Node) {
+        let Some(value) = node.as_u128().and_then(|value| i128::try_from(value).ok()) else {
+            return;
+        };
+        let field = match &component.ty {
+            Type::ClassField { field, .. } => Some(field.clone()),
+            _ => None,
+        };
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.push((component.name.clone(), value, field));
+        }
+    }
+
+    /// Returns the kind of a class field, a value field of a fixed type or a type field.
+    pub(crate) fn field_kind(&self, class: &str, field: &str) -> Option<&'a FieldKind> {
+        let modules: &'a Modules = self.modules;
+        let class = modules.classes.get(class)?;
+        class.fields.iter().find(|(name, _)| name == field).map(|(_, kind)| kind)
+    }
+
+    /// Finds the type of an open type in the object set, from the object whose key field holds
+    /// the value of the related component.
+    pub(crate) fn object_type(&self, set: Option<&str>, relation: Option<&str>, field: &str, env: &Env) -> Option<Type> {
+        let (set, relation) = set.zip(relation)?;
+        let (key, key_field) = self.scopes.iter().rev().find_map(|scope| {
+            scope.iter().rev().find(|(name, _, _)| name == relation).map(|(_, key, field)| (*key, field.as_deref()))
+        })?;
+        self.find_in_set(set, env, key, key_field, field, 0)
+    }
+
+    fn find_in_set(&self, name: &str, env: &Env, key: i128, key_field: Option<&str>, field: &str, depth: usize) -> Option<Type> {
+        if depth > 32 {
+            return None;
+        }
+        let (elements, set_env) = match env.0.get(name) {
+            Some(Binding::Set(elements, set_env)) => (elements, set_env.clone()),
+            _ => (self.modules.object_sets.get(name)?, Env::default()),
+        };
+        elements.iter().find_map(|element| match element {
+            SetElement::Object(object) => self.match_object(object, key, key_field, field),
+            SetElement::ObjectReference(name) => self.match_object(self.modules.objects.get(name)?, key, key_field, field),
+            SetElement::SetReference(name) => self.find_in_set(name, &set_env, key, key_field, field, depth + 1),
+        })
+    }
+
+    fn match_object(&self, object: &Object, key: i128, key_field: Option<&str>, field: &str) -> Option<Type> {
+        let is_key = |setting: &Setting| matches!(setting, Setting::Value(value) if self.value(value, &Env::default()) == Some(key));
+        let matched = match key_field {
+            Some(key_field) => object.get(key_field).is_some_and(is_key),
+            None => object.values().any(is_key),
+        };
+        match object.get(field) {
+            Some(Setting::Type(ty)) if matched => Some(ty.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// Names a SEQUENCE or SET value after the type it was decoded as, unless it already has a name.
+pub(crate) fn annotate_reference(node: &mut ASN1Node, name: &str) {
+    if node.annotation.is_none() && matches!(node.value, ASN1Value::Sequence(_) | ASN1Value::Set(_)) {
+        node.set_annotation(name);
+    }
+}
+
+/// Names the value of an open type after its type, keeping the name of a resolved inner open type.
+pub(crate) fn annotate_open_type(node: &mut ASN1Node, ty: &Type) {
+    if let Type::Reference { name, .. } = ty {
+        let annotation = match node.annotation.take() {
+            Some(annotation) if annotation != *name => format!("{}: {}", name, annotation),
+            _ => name.clone(),
+        };
+        node.set_annotation(annotation);
+    }
+}
+
+/// Annotates a SEQUENCE with the type of a resolved open type component, such as the IE type of a protocol IE field.
+pub(crate) fn annotate_sequence(node: &mut ASN1Node, root: &[Component], children: &[ASN1Node]) {
+    let open_types = root.iter().filter(|component| matches!(&component.ty, Type::ClassField { field, .. } if is_upper(field)));
+    for component in open_types {
+        let child = children.iter().find(|child| child.label.as_deref() == Some(component.name.as_str()));
+        if let Some(annotation) = child.and_then(|child| child.annotation.clone()) {
+            node.set_annotation(annotation);
+        }
+    }
+}
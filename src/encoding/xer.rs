@@ -0,0 +1,492 @@
+use der::asn1::ObjectIdentifier;
+use der::Tag;
+use xmlparser::{ElementEnd, Token, Tokenizer};
+use crate::asn1_der::{ASN1Node, ASN1Value};
+use crate::asn1_module::parser::is_upper;
+use crate::asn1_module::{Addition, Argument, Component, Constraint, FieldKind, Modules, StringKind, Type};
+use crate::encoding::schema::{annotate_open_type, annotate_reference, annotate_sequence, Env, Schema};
+use crate::encoding::{bit_string, choice_node, error, finish, integer_node, labelled, leaf, name_bits, recovered, string_node, Result};
+
+/// Deepest nesting of XML elements, the nesting `serde_json` allows in JER documents.
+const MAX_NESTING: usize = 128;
+
+/// An XML element with its child elements and the text directly inside it.
+#[derive(Clone, Debug, Default)]
+struct Element {
+    name: String,
+    children: Vec<Element>,
+    text: String,
+}
+
+impl Element {
+    /// Returns the name of the only child element of an element without text, such as `<true/>` inside `<flag>`.
+    fn identifier(&self) -> Option<&str> {
+        match &self.children[..] {
+            [child] if child.children.is_empty() && child.text.trim().is_empty() => Some(&child.name),
+            _ => None,
+        }
+    }
+
+    /// Returns the text of the element, or the identifier which EXTENDED-XER writes as text.
+    fn value(&self) -> &str {
+        self.identifier().unwrap_or_else(|| self.text.trim())
+    }
+}
+
+fn parse_xml(text: &str) -> Result<Element> {
+    let mut stack = vec![Element::default()];
+    for token in Tokenizer::from(text) {
+        let token = token.map_err(|xml_error| error(format!("invalid XML: {}", xml_error)))?;
+        match token {
+            Token::ElementStart { local, .. } => {
+                // the document itself is the first element of the stack
+                if stack.len() > MAX_NESTING {
+                    return Err(error(format!("invalid XML: elements nested deeper than {}", MAX_NESTING)));
+                }
+                stack.push(Element { name: local.to_string(), ..Element::default() });
+            }
+            Token::ElementEnd { end: ElementEnd::Close(..) | ElementEnd::Empty, .. } => {
+                let element = stack.pop().filter(|_| !stack.is_empty()).ok_or_else(|| error("invalid XML: unexpected end tag"))?;
+                stack.last_mut().unwrap_or_else(|| unreachable!()).children.push(element);
+            }
+            Token::Text { text } | Token::Cdata { text, .. } => {
+                if let Some(element) = stack.last_mut() {
+                    element.text.push_str(&unescape(&text));
+                }
+            }
+            _ => {}
+        }
+    }
+    if let Some(open) = stack.get(1..).and_then(|open| open.last()) {
+        return Err(error(format!("invalid XML: element {} is not closed", open.name)));
+    }
+    match stack.pop().map(|document| document.children) {
+        Some(mut children) if children.len() == 1 => Ok(children.remove(0)),
+        _ => Err(error("invalid XML: the document needs one root element")),
+    }
+}
+
+/// Replaces the predefined entities and character references.
+fn unescape(text: &str) -> String {
+    let mut result = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find(';').map(|end| start + end) else {
+            break;
+        };
+        let entity = &rest[start + 1..end];
+        let character = match entity {
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "amp" => Some('&'),
+            "apos" => Some('\''),
+            "quot" => Some('"'),
+            _ => entity
+                .strip_prefix("#x")
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| entity.strip_prefix('#').map(|decimal| decimal.parse()))
+                .and_then(|code| code.ok())
+                .and_then(char::from_u32),
+        };
+        match character {
+            Some(character) => result.push(character),
+            None => result.push_str(&rest[start..=end]),
+        }
+        rest = &rest[end + 1..];
+    }
+    result.push_str(rest);
+    result
+}
+
+/// The types named by the XML element names of X.693 for values which have no identifier,
+/// such as the items of a `SEQUENCE OF INTEGER`.
+fn builtin_type(name: &str) -> Option<Type> {
+    let string = |kind| Type::String { kind, constraint: Constraint::default() };
+    let ty = match name {
+        "BOOLEAN" => Type::Boolean,
+        "NULL" => Type::Null,
+        "INTEGER" => Type::Integer { named: vec![], constraint: Constraint::default() },
+        "BIT_STRING" => Type::BitString { named: vec![], constraint: Constraint::default() },
+        "OCTET_STRING" => Type::OctetString { constraint: Constraint::default() },
+        "OBJECT_IDENTIFIER" => Type::ObjectIdentifier,
+        "RELATIVE_OID" => Type::RelativeOid,
+        "REAL" => Type::Real,
+        "IA5String" => string(StringKind::Ia5),
+        "PrintableString" => string(StringKind::Printable),
+        "VisibleString" | "UTCTime" | "GeneralizedTime" => string(StringKind::Visible),
+        "NumericString" => string(StringKind::Numeric),
+        "BMPString" => string(StringKind::Bmp),
+        "UniversalString" => string(StringKind::Universal),
+        "UTF8String" => string(StringKind::Utf8),
+        _ => return None,
+    };
+    Some(ty)
+}
+
+struct Decoder<'a> {
+    schema: Schema<'a>,
+}
+
+/// Decodes an XER document as a value of the type, or as it is written when no type is given.
+pub(crate) fn decode(data: &[u8], modules: &Modules, type_name: Option<&str>) -> (Vec<ASN1Node>, Option<String>) {
+    let mut decoder = Decoder { schema: Schema::new(modules) };
+    let text = String::from_utf8_lossy(data);
+    let root = parse_xml(&text);
+    let name = type_name.unwrap_or("XML");
+    let result = root.and_then(|root| match type_name {
+        Some(type_name) => {
+            let ty = Type::Reference { name: type_name.to_string(), arguments: vec![], constraint: Constraint::default() };
+            labelled(decoder.decode_element(&root, &ty, &Env::default(), 0), Some(type_name), 0)
+        }
+        None => Ok(decoder.decode_untyped(&root, 0)),
+    });
+    finish(result, 0, name, &decoder.schema.errors)
+}
+
+impl Decoder<'_> {
+    /// Decodes a component or item, a value which fails is shown with its error and decoding goes on.
+    fn decode_labelled(&mut self, element: &Element, ty: &Type, env: &Env, level: u32, label: Option<&str>) -> ASN1Node {
+        let result = labelled(self.decode_element(element, ty, env, level), label, level);
+        recovered(result, &mut self.schema.errors, level)
+    }
+
+    fn decode_element(&mut self, element: &Element, ty: &Type, env: &Env, level: u32) -> Result<ASN1Node> {
+        match ty {
+            Type::Reference { name, arguments, constraint } => self.decode_reference(element, name, arguments, constraint, env, level),
+            Type::ClassField { class, field, set, relation } => {
+                self.decode_class_field(element, class, field, set.as_deref(), relation.as_deref(), env, level)
+            }
+            Type::Boolean => match element.value() {
+                "true" | "1" => Ok(leaf(Tag::Boolean, ASN1Value::Boolean(true), level)),
+                "false" | "0" => Ok(leaf(Tag::Boolean, ASN1Value::Boolean(false), level)),
+                value => Err(error(format!("{:?} is not a BOOLEAN", value))),
+            },
+            Type::Null => Ok(leaf(Tag::Null, ASN1Value::Null, level)),
+            Type::Integer { named, .. } => {
+                let value = element.value();
+                let number = named.iter().find(|(name, _)| name == value).map(|(_, number)| *number);
+                let number = match number {
+                    Some(number) => number,
+                    None => value.parse().map_err(|_| error(format!("{:?} is not an INTEGER", value)))?,
+                };
+                let mut node = integer_node(Tag::Integer, number, level);
+                if let Some((name, _)) = named.iter().find(|(_, named)| *named == number) {
+                    node.set_annotation(name.clone());
+                }
+                Ok(node)
+            }
+            Type::Enumerated { root, extension } => {
+                let value = element.value();
+                let (name, number) = root
+                    .iter()
+                    .chain(extension.iter().flatten())
+                    .find(|(name, _)| name == value)
+                    .ok_or_else(|| error(format!("{:?} is not an item of the ENUMERATED", value)))?;
+                let mut node = integer_node(Tag::Enumerated, *number, level);
+                node.set_annotation(name.clone());
+                Ok(node)
+            }
+            Type::BitString { named, .. } => {
+                // the bits as 0 and 1, or the names of the bits which are set
+                let bits = match element.children.is_empty() {
+                    true => element
+                        .text
+                        .chars()
+                        .filter(|c| !c.is_whitespace())
+                        .map(|c| match c {
+                            '0' => Ok(false),
+                            '1' => Ok(true),
+                            _ => Err(error(format!("{:?} is not a BIT STRING", element.text.trim()))),
+                        })
+                        .collect::<Result<Vec<bool>>>()?,
+                    false => {
+                        let set = element
+                            .children
+                            .iter()
+                            .map(|child| named.iter().find(|(name, _)| *name == child.name).and_then(|(_, bit)| usize::try_from(*bit).ok()))
+                            .collect::<Option<Vec<usize>>>()
+                            .ok_or_else(|| error("unknown named bit"))?;
+                        let mut bits = vec![false; set.iter().max().map_or(0, |max| max + 1)];
+                        set.into_iter().for_each(|bit| bits[bit] = true);
+                        bits
+                    }
+                };
+                let mut node = leaf(Tag::BitString, bit_string(&bits)?, level);
+                name_bits(&mut node, named, &bits);
+                Ok(node)
+            }
+            Type::OctetString { constraint } => {
+                let value = match (&constraint.containing, element.children.first()) {
+                    // a contained value is written as XML
+                    (Some(inner), Some(_)) => {
+                        let node = self.decode_labelled(element, inner, env, level + 1, None);
+                        ASN1Value::OctetString(Box::new(node), vec![])
+                    }
+                    _ => {
+                        let digits = element.text.chars().filter(|c| !c.is_whitespace()).collect::<String>();
+                        ASN1Value::Bytes(hex::decode(&digits).map_err(|_| error(format!("{:?} is not hex", digits)))?)
+                    }
+                };
+                Ok(leaf(Tag::OctetString, value, level))
+            }
+            Type::String { kind, .. } => Ok(string_node(*kind, element.text.clone(), level)),
+            Type::ObjectIdentifier => {
+                let text = element.text.trim();
+                let oid = ObjectIdentifier::new(text).map_err(|_| error(format!("{:?} is not an OBJECT IDENTIFIER", text)))?;
+                Ok(leaf(Tag::ObjectIdentifier, ASN1Value::ObjectIdentifier(oid), level))
+            }
+            Type::RelativeOid => Ok(leaf(Tag::OctetString, ASN1Value::CharacterString(element.text.trim().to_string()), level)),
+            Type::Real => Ok(leaf(Tag::Real, ASN1Value::CharacterString(element.value().to_string()), level)),
+            Type::Sequence { set, root, extension } => self.decode_sequence(element, *set, root, extension.as_deref(), env, level),
+            Type::SequenceOf { set, item, .. } => {
+                let tag = if *set { Tag::Set } else { Tag::Sequence };
+                // BOOLEAN, ENUMERATED and NULL items are written without an element around them
+                let bare = matches!(self.base_type(item, env), Some(Type::Boolean | Type::Enumerated { .. } | Type::Null));
+                let children = element
+                    .children
+                    .iter()
+                    .map(|child| match bare {
+                        true => {
+                            let wrapper = Element { children: vec![child.clone()], ..Element::default() };
+                            self.decode_labelled(&wrapper, item, env, level + 1, None)
+                        }
+                        false => self.decode_labelled(child, item, env, level + 1, None),
+                    })
+                    .collect();
+                Ok(leaf(tag, ASN1Value::Set(children), level))
+            }
+            Type::Choice { root, extension } => {
+                let Some(chosen) = element.children.first() else {
+                    return Err(error("CHOICE without an alternative"));
+                };
+                let alternatives = root.iter().chain(extension.iter().flatten());
+                let node = match alternatives.enumerate().find(|(_, alternative)| alternative.name == chosen.name) {
+                    Some((index, alternative)) => {
+                        let node = self.decode_labelled(chosen, &alternative.ty, env, level + 1, Some(&alternative.name));
+                        choice_node(index, node, level)
+                    }
+                    None => {
+                        let mut node = self.decode_untyped(chosen, level + 1);
+                        node.set_annotation("unknown alternative");
+                        node
+                    }
+                };
+                Ok(node)
+            }
+            Type::Open => Ok(self.decode_untyped(element, level)),
+        }
+    }
+
+    fn decode_reference(
+        &mut self,
+        element: &Element,
+        name: &str,
+        arguments: &[Argument],
+        constraint: &Constraint,
+        env: &Env,
+        level: u32,
+    ) -> Result<ASN1Node> {
+        let (ty, inner_env) = self.schema.resolve(name, arguments, constraint, env)?;
+        self.schema.depth += 1;
+        let result = self.decode_element(element, &ty, &inner_env, level);
+        self.schema.depth -= 1;
+        result.map(|mut node| {
+            annotate_reference(&mut node, name);
+            node
+        })
+    }
+
+    /// Follows type references to the type they stand for.
+    fn base_type(&self, ty: &Type, env: &Env) -> Option<Type> {
+        let mut ty = ty.clone();
+        for _ in 0..32 {
+            match &ty {
+                Type::Reference { name, arguments, constraint } => ty = self.schema.resolve(name, arguments, constraint, env).ok()?.0.into_owned(),
+                _ => return Some(ty),
+            }
+        }
+        None
+    }
+
+    fn decode_sequence(&mut self, element: &Element, set: bool, root: &[Component], extension: Option<&[Addition]>, env: &Env, level: u32) -> Result<ASN1Node> {
+        let root = self.schema.expand(root)?;
+        let additions = extension.unwrap_or_default().iter().flat_map(|addition| &addition.components);
+        let components = root.iter().chain(additions).collect::<Vec<&Component>>();
+        self.schema.scopes.push(vec![]);
+        let mut children = vec![];
+        for child in &element.children {
+            let node = match components.iter().find(|component| component.name == child.name) {
+                Some(component) => {
+                    let node = self.decode_labelled(child, &component.ty, env, level + 1, Some(&component.name));
+                    self.schema.record(component, &node);
+                    node
+                }
+                None => {
+                    let mut node = self.decode_untyped(child, level + 1);
+                    node.set_annotation("unknown component");
+                    node
+                }
+            };
+            children.push(node);
+        }
+        self.schema.scopes.pop();
+
+        let tag = if set { Tag::Set } else { Tag::Sequence };
+        let mut node = leaf(tag, ASN1Value::Sequence(vec![]), level);
+        annotate_sequence(&mut node, &root, &children);
+        node.value = ASN1Value::Sequence(children);
+        Ok(node)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn decode_class_field(
+        &mut self,
+        element: &Element,
+        class: &str,
+        field: &str,
+        set: Option<&str>,
+        relation: Option<&str>,
+        env: &Env,
+        level: u32,
+    ) -> Result<ASN1Node> {
+        match self.schema.field_kind(class, field) {
+            Some(FieldKind::Value(ty)) => self.decode_element(element, ty, &Env::default(), level),
+            _ if is_upper(field) => {
+                let Some(ty) = self.schema.object_type(set, relation, field, env) else {
+                    let mut node = self.decode_untyped(element, level);
+                    node.set_annotation("open type");
+                    return Ok(node);
+                };
+                // the value is written inside an element named after its type
+                let inner = match (&ty, &element.children[..]) {
+                    (Type::Reference { name, .. }, [child]) if child.name == *name => child,
+                    _ => element,
+                };
+                let scopes = std::mem::take(&mut self.schema.scopes);
+                let mut node = self.decode_labelled(inner, &ty, &Env::default(), level, None);
+                self.schema.scopes = scopes;
+                annotate_open_type(&mut node, &ty);
+                Ok(node)
+            }
+            _ => Err(error(format!("class field {}.{} is not supported", class, field))),
+        }
+    }
+
+    /// Shows an element without a type: elements with children are SEQUENCEs, `<true/>` and
+    /// `<false/>` are BOOLEANs, other identifiers are ENUMERATED items and text is an INTEGER,
+    /// an OBJECT IDENTIFIER or a string as it looks.
+    fn decode_untyped(&mut self, element: &Element, level: u32) -> ASN1Node {
+        if let Some(ty) = builtin_type(&element.name) {
+            return self.decode_labelled(element, &ty, &Env::default(), level, None);
+        }
+        let text = element.text.trim();
+        let mut node = match (element.identifier(), &element.children[..]) {
+            (Some(value @ ("true" | "false")), _) => leaf(Tag::Boolean, ASN1Value::Boolean(value == "true"), level),
+            (Some(value), _) => leaf(Tag::Enumerated, ASN1Value::Other(value.to_string()), level),
+            (None, []) if text.is_empty() => leaf(Tag::Null, ASN1Value::Null, level),
+            (None, []) => match (text.parse::<i128>(), ObjectIdentifier::new(text)) {
+                (Ok(number), _) => integer_node(Tag::Integer, number, level),
+                (_, Ok(oid)) if text.contains('.') => leaf(Tag::ObjectIdentifier, ASN1Value::ObjectIdentifier(oid), level),
+                _ => string_node(StringKind::Utf8, element.text.clone(), level),
+            },
+            (None, children) => {
+                let children = children.iter().map(|child| self.decode_untyped(child, level + 1)).collect();
+                leaf(Tag::Sequence, ASN1Value::Sequence(children), level)
+            }
+        };
+        node.set_label(&element.name);
+        node
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::asn1_der::view_lines;
+    use super::*;
+
+    const MODULE: &str = "M DEFINITIONS AUTOMATIC TAGS ::= BEGIN
+        Msg ::= SEQUENCE {
+            flag BOOLEAN,
+            count INTEGER (-5..10),
+            bits BIT STRING { first(0), second(1), third(2) } (SIZE (3)),
+            octets OCTET STRING,
+            text IA5String,
+            list SEQUENCE (SIZE (1..4)) OF INTEGER (0..7),
+            color ENUMERATED { red, green },
+            choice CHOICE { a INTEGER, b BOOLEAN },
+            opt INTEGER OPTIONAL,
+            ...
+        }
+        END";
+
+    const MSG: &str = "<?xml version=\"1.0\"?>\n<!-- a comment -->\n<Msg>
+        <flag><true/></flag>
+        <count>3</count>
+        <bits><first/><third/></bits>
+        <octets>01 02</octets>
+        <text>Hi &amp; bye</text>
+        <list><INTEGER>1</INTEGER><INTEGER>7</INTEGER></list>
+        <color><green/></color>
+        <choice><b><false/></b></choice>
+        </Msg>";
+
+    fn decoded(text: &str, type_name: Option<&str>) -> (Vec<String>, Option<String>) {
+        let mut modules = Modules::default();
+        modules.load(MODULE).unwrap();
+        let (nodes, status) = decode(text.as_bytes(), &modules, type_name);
+        (nodes.iter().flat_map(view_lines).collect(), status)
+    }
+
+    #[test]
+    fn decodes_typed_documents() {
+        let (lines, status) = decoded(MSG, Some("Msg"));
+        assert_eq!(status, None);
+        assert_eq!(
+            lines,
+            [
+                "Msg: SEQUENCE (field(s): 8) (Msg)",
+                "flag: BOOLEAN true",
+                "count: INTEGER 3",
+                "bits: BIT STRING 10100000 (first, third)",
+                "octets: OCTET STRING 0102",
+                "text: IA5String \"Hi & bye\"",
+                "list: SEQUENCE (item(s): 2)",
+                "INTEGER 1",
+                "INTEGER 7",
+                "color: ENUMERATED 1 (green)",
+                "choice: CONTEXT-SPECIFIC [1] (constructed)  (b)",
+                "b: BOOLEAN false",
+            ]
+        );
+    }
+
+    #[test]
+    fn decodes_untyped_documents() {
+        let (lines, status) = decoded(MSG, None);
+        assert_eq!(status, None);
+        assert_eq!(lines[..6], ["Msg: SEQUENCE (field(s): 8)", "flag: BOOLEAN true", "count: INTEGER 3", "bits: SEQUENCE (field(s): 2)", "first: NULL Null", "third: NULL Null"]);
+        assert_eq!(lines[6..9], ["octets: UTF8String \"01 02\"", "text: UTF8String \"Hi & bye\"", "list: SEQUENCE (field(s): 2)"]);
+        assert_eq!(lines[11], "color: ENUMERATED \"green\"");
+    }
+
+    #[test]
+    fn rejects_invalid_documents() {
+        for (text, message) in [
+            ("<a><b></a>", "invalid XML: "),
+            ("<a><b/>", "invalid XML: element a is not closed"),
+            ("<?xml version=\"1.0\"?>", "invalid XML: the document needs one root element"),
+        ] {
+            let (_, status) = decoded(text, None);
+            assert!(status.as_deref().is_some_and(|status| status.starts_with(&format!("decoding stopped: {}", message))), "{:?}", status);
+        }
+    }
+
+    #[test]
+    fn limits_the_nesting_of_elements() {
+        let nested = |depth: usize| format!("{}{}", "<a>".repeat(depth), "</a>".repeat(depth));
+        assert_eq!(decoded(&nested(MAX_NESTING), None).1, None);
+        let (_, status) = decoded(&nested(100_000), None);
+        assert_eq!(status.as_deref(), Some("decoding stopped: invalid XML: elements nested deeper than 128"));
+    }
+}
@@ -0,0 +1,168 @@
+use crate::extract::zip::Archive;
+use crate::extract::{u32_at, u64_at, Entry};
+
+const MAGIC: &[u8] = b"APK Sig Block 42";
+
+/// IDs of the signature schemes in the APK Signing Block whose signers carry certificates.
+const SCHEMES: [(u32, &str); 3] = [
+    (0x7109871a, "APK Signature Scheme v2"),
+    (0xf05368c0, "APK Signature Scheme v3"),
+    (0x1b93ad61, "APK Signature Scheme v3.1"),
+];
+
+/// Returns the signer certificates of the APK Signing Block which is placed right before
+/// the central directory of an APK.
+pub(crate) fn certificates(archive: &Archive) -> Vec<Entry> {
+    signing_block(archive)
+        .map(|pairs| {
+            pairs
+                .into_iter()
+                .filter_map(|(id, value)| SCHEMES.iter().find(|(scheme, _)| *scheme == id).map(|(_, name)| (*name, value)))
+                .flat_map(|(name, value)| scheme_certificates(name, value))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Returns the ID-value pairs of the block: its size, the pairs, its size again and the magic.
+fn signing_block<'a>(archive: &Archive<'a>) -> Option<Vec<(u32, &'a [u8])>> {
+    let data = archive.data;
+    let end = archive.central_directory;
+    if data.get(end.checked_sub(16)?..end)? != MAGIC {
+        return None;
+    }
+    let size = usize::try_from(u64_at(data, end - 24)?).ok()?;
+    let start = end.checked_sub(size.checked_add(8)?)?;
+    let mut pairs = data.get(start + 8..end - 24)?;
+
+    let mut result = Vec::new();
+    while pairs.len() >= 12 {
+        let length = usize::try_from(u64_at(pairs, 0)?).ok()?;
+        let value = pairs.get(12..length.checked_add(8)?)?;
+        result.push((u32_at(pairs, 8)?, value));
+        pairs = &pairs[8 + length..];
+    }
+    Some(result)
+}
+
+/// The signers of v2 and v3 schemes share the layout of their signed data, whose
+/// second field is the sequence of DER certificates.
+fn scheme_certificates(scheme: &str, value: &[u8]) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    let mut signers = prefixed(value).unwrap_or_default();
+    let mut signer_number = 0;
+    while let Some((signer, rest)) = next_prefixed(signers) {
+        signers = rest;
+        signer_number += 1;
+        let Some(mut certificates) = prefixed(signer).and_then(|signed_data| next_prefixed(signed_data)).and_then(|(_, rest)| prefixed(rest)) else {
+            continue;
+        };
+        let mut certificate_number = 0;
+        while let Some((certificate, rest)) = next_prefixed(certificates) {
+            certificates = rest;
+            certificate_number += 1;
+            let name = format!("{} signer {} certificate {}", scheme, signer_number, certificate_number);
+            entries.push(Entry { name, data: certificate.to_vec() });
+        }
+    }
+    entries
+}
+
+/// Returns the content of a value prefixed with its 32-bit little-endian length.
+fn prefixed(data: &[u8]) -> Option<&[u8]> {
+    next_prefixed(data).map(|(value, _)| value)
+}
+
+fn next_prefixed(data: &[u8]) -> Option<(&[u8], &[u8])> {
+    let length = u32_at(data, 0)? as usize;
+    let value = data.get(4..length.checked_add(4)?)?;
+    Some((value, &data[4 + length..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extract::zip::stored_archive;
+
+    fn prefixed(values: &[&[u8]]) -> Vec<u8> {
+        values.iter().flat_map(|value| [(value.len() as u32).to_le_bytes().as_slice(), value].concat()).collect()
+    }
+
+    /// A v2 or v3 scheme value: the signers, each with its signed data, signatures and public key.
+    fn scheme(signers: &[&[&[u8]]]) -> Vec<u8> {
+        let signers = signers
+            .iter()
+            .map(|certificates| {
+                let signed_data = prefixed(&[&prefixed(&[b"digest"]), &prefixed(certificates), &[]]);
+                prefixed(&[&signed_data, &prefixed(&[b"signature"]), b"public key"])
+            })
+            .collect::<Vec<_>>();
+        prefixed(&[&prefixed(&signers.iter().map(Vec::as_slice).collect::<Vec<_>>())])
+    }
+
+    /// An APK whose signing block holding the pairs is placed before the central directory.
+    fn apk(pairs: &[(u32, &[u8])]) -> Vec<u8> {
+        let mut block = Vec::new();
+        for (id, value) in pairs {
+            block.extend_from_slice(&(value.len() as u64 + 4).to_le_bytes());
+            block.extend_from_slice(&id.to_le_bytes());
+            block.extend_from_slice(value);
+        }
+        let size = (block.len() + 24) as u64;
+        block.splice(0..0, size.to_le_bytes());
+        block.extend_from_slice(&size.to_le_bytes());
+        block.extend_from_slice(MAGIC);
+
+        let mut data = stored_archive(&[("AndroidManifest.xml", b"manifest")]);
+        let end = data.len() - 22;
+        let central_directory = u32_at(&data, end + 16).unwrap() as usize;
+        data.splice(central_directory..central_directory, block.iter().copied());
+        let end = data.len() - 22;
+        data[end + 16..end + 20].copy_from_slice(&((central_directory + block.len()) as u32).to_le_bytes());
+        data
+    }
+
+    fn names(data: &[u8]) -> Vec<(String, Vec<u8>)> {
+        certificates(&Archive::new(data).unwrap()).into_iter().map(|entry| (entry.name, entry.data)).collect()
+    }
+
+    #[test]
+    fn reads_the_signer_certificates() {
+        let v2 = scheme(&[&[&[0x30, 0x00], &[0x30, 0x03, 0x02, 0x01, 0x05]], &[&[0x30, 0x01, 0x00]]]);
+        let v3 = scheme(&[&[&[0x30, 0x00]]]);
+        // padding and other pairs such as the verity padding are skipped
+        let data = apk(&[(0x42726577, &[0; 8]), (0x7109871a, &v2), (0xf05368c0, &v3)]);
+        assert_eq!(
+            names(&data),
+            [
+                ("APK Signature Scheme v2 signer 1 certificate 1".to_string(), vec![0x30, 0x00]),
+                ("APK Signature Scheme v2 signer 1 certificate 2".to_string(), vec![0x30, 0x03, 0x02, 0x01, 0x05]),
+                ("APK Signature Scheme v2 signer 2 certificate 1".to_string(), vec![0x30, 0x01, 0x00]),
+                ("APK Signature Scheme v3 signer 1 certificate 1".to_string(), vec![0x30, 0x00]),
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_truncated_blocks() {
+        assert!(names(&stored_archive(&[("AndroidManifest.xml", b"manifest")])).is_empty());
+
+        // a certificate whose length runs past its signer is left out
+        let mut v2 = scheme(&[&[&[0x30, 0x00], &[0x30, 0x03, 0x02, 0x01, 0x05]]]);
+        let position = v2.windows(5).position(|window| window == [0x30, 0x03, 0x02, 0x01, 0x05]).unwrap();
+        v2[position - 4] = 0x50;
+        let data = apk(&[(0x7109871a, &v2)]);
+        assert_eq!(names(&data), [("APK Signature Scheme v2 signer 1 certificate 1".to_string(), vec![0x30, 0x00])]);
+
+        // a pair whose length runs past the block ends the block
+        let mut data = apk(&[(0x7109871a, &scheme(&[&[&[0x30, 0x00]]]))]);
+        let magic = data.windows(16).position(|window| window == MAGIC).unwrap();
+        let size = u64_at(&data, magic - 8).unwrap() as usize;
+        let first_pair = magic + 16 - size;
+        data[first_pair..first_pair + 8].copy_from_slice(&0xffffu64.to_le_bytes());
+        assert!(names(&data).is_empty());
+        // a block size larger than the data
+        data[magic - 8..magic].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(names(&data).is_empty());
+    }
+}
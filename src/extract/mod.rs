@@ -0,0 +1,75 @@
+pub mod apk;
+pub mod pcap;
+pub mod pe;
+pub mod tar;
+pub mod tls;
+pub mod zip;
+
+/// A structure taken out of a container file, shown as a top level entry named after where it was found.
+#[derive(Clone, Debug)]
+pub(crate) struct Entry {
+    pub(crate) name: String,
+    pub(crate) data: Vec<u8>,
+}
+
+/// A container file and the entries taken out of it.
+#[derive(Debug)]
+pub(crate) struct Extracted {
+    /// What the container is, such as "a signed PE file".
+    pub(crate) kind: &'static str,
+    pub(crate) entries: Vec<Entry>,
+}
+
+/// Takes the signatures and certificates out of signed executables and archives, and the
+/// certificates and BER messages out of packet captures. Returns `None` when the data is not
+/// such a container or nothing was found in it.
+pub(crate) fn extract(data: &[u8]) -> Option<Extracted> {
+    let extracted = if let Some(entries) = pe::signatures(data) {
+        Extracted { kind: "a signed PE file", entries }
+    } else if let Some(entries) = pcap::payloads(data) {
+        Extracted { kind: "a packet capture", entries }
+    } else if let Some(archive) = zip::Archive::new(data) {
+        let mut entries = zip::signature_files(&archive);
+        entries.extend(apk::certificates(&archive));
+        Extracted { kind: "a signed JAR or APK", entries }
+    } else {
+        return None;
+    };
+    (!extracted.entries.is_empty()).then_some(extracted)
+}
+
+/// Lists the files of a tar or ZIP archive. Returns `None` when the data is not an archive or
+/// no file was read from it.
+pub(crate) fn archive_files(data: &[u8]) -> Option<Extracted> {
+    let extracted = if let Some(entries) = tar::files(data) {
+        Extracted { kind: "a tar archive", entries }
+    } else if let Some(archive) = zip::Archive::new(data) {
+        Extracted { kind: "a ZIP archive", entries: zip::files(&archive) }
+    } else {
+        return None;
+    };
+    (!extracted.entries.is_empty()).then_some(extracted)
+}
+
+/// Little-endian integers of the container formats.
+fn u16_at(data: &[u8], offset: usize) -> Option<u16> {
+    data.get(offset..offset.checked_add(2)?).map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+fn u32_at(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset.checked_add(4)?).map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+fn u64_at(data: &[u8], offset: usize) -> Option<u64> {
+    let bytes = data.get(offset..offset.checked_add(8)?)?;
+    Some(u64::from_le_bytes(bytes.try_into().ok()?))
+}
+
+/// Big-endian integers of the network protocols.
+fn be16_at(data: &[u8], offset: usize) -> Option<u16> {
+    data.get(offset..offset.checked_add(2)?).map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+fn be32_at(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset.checked_add(4)?).map(|bytes| u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
@@ -0,0 +1,360 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+use crate::asn1_der::{complete_ber_prefix, is_complete_ber};
+use crate::extract::{be16_at, be32_at, tls, u16_at, u32_at, Entry};
+
+const PCAPNG_SECTION_HEADER: u32 = 0x0a0d0d0a;
+const PCAPNG_BYTE_ORDER: u32 = 0x1a2b3c4d;
+const PCAPNG_INTERFACE: u32 = 1;
+const PCAPNG_SIMPLE_PACKET: u32 = 3;
+const PCAPNG_ENHANCED_PACKET: u32 = 6;
+
+const LINKTYPE_NULL: u32 = 0;
+const LINKTYPE_ETHERNET: u32 = 1;
+/// Raw IP has several link type numbers: DLT_RAW of most systems, of OpenBSD, LINKTYPE_RAW,
+/// LINKTYPE_IPV4 and LINKTYPE_IPV6.
+const LINKTYPE_RAW: [u32; 5] = [12, 14, 101, 228, 229];
+const LINKTYPE_LINUX_SLL: u32 = 113;
+const LINKTYPE_LINUX_SLL2: u32 = 276;
+
+const TCP: u8 = 6;
+const UDP: u8 = 17;
+
+/// Ports of the protocols whose BER messages are sent in clear.
+const LDAP_PORTS: [u16; 2] = [389, 3268];
+const KERBEROS_PORTS: [u16; 2] = [88, 464];
+const SNMP_PORTS: [u16; 2] = [161, 162];
+
+/// One direction of a TCP connection or the addresses of a UDP datagram, written like tcpdump does.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct Flow {
+    source: SocketAddr,
+    destination: SocketAddr,
+}
+
+impl Flow {
+    fn has_port(&self, ports: &[u16]) -> bool {
+        ports.contains(&self.source.port()) || ports.contains(&self.destination.port())
+    }
+}
+
+impl fmt::Display for Flow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} > {}", self.source, self.destination)
+    }
+}
+
+enum Segment<'a> {
+    Tcp { flow: Flow, sequence: u32, syn: bool, payload: &'a [u8] },
+    Udp { flow: Flow, payload: &'a [u8] },
+}
+
+/// The segments of one direction of a TCP connection, by sequence number.
+#[derive(Default)]
+struct Stream<'a> {
+    /// The sequence number of the first byte, known when the SYN was captured.
+    start: Option<u32>,
+    segments: Vec<(u32, &'a [u8])>,
+}
+
+impl Stream<'_> {
+    /// Joins the segments in sequence order. Retransmitted bytes are taken once and the stream
+    /// ends at the first segment which was not captured. Without the SYN the stream starts at the
+    /// lowest sequence number, compared in serial number arithmetic as sequence numbers wrap around.
+    fn reassemble(&self) -> Vec<u8> {
+        let lowest = || {
+            self.segments
+                .iter()
+                .map(|(sequence, _)| *sequence)
+                .reduce(|lowest, sequence| if lowest.wrapping_sub(sequence) < 1 << 31 { sequence } else { lowest })
+        };
+        let Some(start) = self.start.or_else(lowest) else {
+            return vec![];
+        };
+        let mut segments = self
+            .segments
+            .iter()
+            .map(|(sequence, payload)| (sequence.wrapping_sub(start) as usize, *payload))
+            .filter(|(offset, _)| *offset < 1 << 31)
+            .collect::<Vec<(usize, &[u8])>>();
+        segments.sort_by_key(|(offset, _)| *offset);
+
+        let mut data = Vec::new();
+        for (offset, payload) in segments {
+            if offset > data.len() {
+                break;
+            }
+            data.extend_from_slice(payload.get(data.len() - offset..).unwrap_or_default());
+        }
+        data
+    }
+}
+
+/// What the entries are taken from, in the order of the first packet of each.
+enum Item<'a> {
+    Stream(Flow),
+    Datagram(Flow, &'a [u8]),
+}
+
+/// Reads a pcap or pcapng capture and returns the certificates of TLS handshakes and the BER
+/// messages of LDAP, Kerberos and SNMP, named after their flow. TCP streams are reassembled,
+/// IP fragments are not. `None` when the data is not a capture.
+pub(crate) fn payloads(data: &[u8]) -> Option<Vec<Entry>> {
+    let frames = pcap_frames(data).or_else(|| pcapng_frames(data))?;
+    let mut items = Vec::new();
+    let mut streams: HashMap<Flow, Stream> = HashMap::new();
+    for (link_type, frame) in frames {
+        match ip_packet(link_type, frame).and_then(segment) {
+            Some(Segment::Tcp { flow, sequence, syn, payload }) => {
+                let stream = streams.entry(flow).or_insert_with(|| {
+                    items.push(Item::Stream(flow));
+                    Stream::default()
+                });
+                if syn {
+                    stream.start = Some(sequence.wrapping_add(1));
+                } else if !payload.is_empty() {
+                    stream.segments.push((sequence, payload));
+                }
+            }
+            Some(Segment::Udp { flow, payload }) => items.push(Item::Datagram(flow, payload)),
+            None => {}
+        }
+    }
+
+    let mut entries = Vec::new();
+    for item in items {
+        match item {
+            Item::Stream(flow) => entries.extend(stream_entries(flow, &streams[&flow].reassemble())),
+            Item::Datagram(flow, payload) => entries.extend(datagram_entry(flow, payload)),
+        }
+    }
+    Some(entries)
+}
+
+fn stream_entries(flow: Flow, stream: &[u8]) -> Vec<Entry> {
+    if tls::is_tls(stream) {
+        return tls::certificates(stream)
+            .into_iter()
+            .enumerate()
+            .map(|(i, data)| Entry { name: format!("{} TLS certificate {}", flow, i + 1), data })
+            .collect();
+    }
+    let (protocol, messages) = if flow.has_port(&KERBEROS_PORTS) {
+        ("Kerberos", record_marked(stream))
+    } else if flow.has_port(&LDAP_PORTS) {
+        ("LDAP", stream.to_vec())
+    } else {
+        return vec![];
+    };
+    // a capture may end in the middle of a message
+    let end = complete_ber_prefix(&messages);
+    match end {
+        0 => vec![],
+        _ => vec![Entry { name: format!("{} {}", flow, protocol), data: messages[..end].to_vec() }],
+    }
+}
+
+/// Kerberos over TCP prefixes each message with its 32-bit length.
+fn record_marked(stream: &[u8]) -> Vec<u8> {
+    let mut messages = Vec::new();
+    let mut rest = stream;
+    while let Some(length) = be32_at(rest, 0) {
+        let Some(message) = rest.get(4..).and_then(|rest| rest.get(..length as usize)) else {
+            break;
+        };
+        messages.extend_from_slice(message);
+        rest = &rest[4 + message.len()..];
+    }
+    messages
+}
+
+fn datagram_entry(flow: Flow, payload: &[u8]) -> Option<Entry> {
+    let protocols = [(SNMP_PORTS, "SNMP"), (KERBEROS_PORTS, "Kerberos"), (LDAP_PORTS, "CLDAP")];
+    let (_, protocol) = protocols.iter().find(|(ports, _)| flow.has_port(ports))?;
+    is_complete_ber(payload).then(|| Entry { name: format!("{} {}", flow, protocol), data: payload.to_vec() })
+}
+
+/// The byte order of a capture file, which is the one of the machine that wrote it.
+#[derive(Clone, Copy)]
+struct ByteOrder {
+    big_endian: bool,
+}
+
+impl ByteOrder {
+    fn u16_at(self, data: &[u8], offset: usize) -> Option<u16> {
+        match self.big_endian {
+            true => be16_at(data, offset),
+            false => u16_at(data, offset),
+        }
+    }
+
+    fn u32_at(self, data: &[u8], offset: usize) -> Option<u32> {
+        match self.big_endian {
+            true => be32_at(data, offset),
+            false => u32_at(data, offset),
+        }
+    }
+}
+
+/// Returns the frames of a pcap file with the link type of the capture, with microsecond or
+/// nanosecond timestamps.
+fn pcap_frames(data: &[u8]) -> Option<Vec<(u32, &[u8])>> {
+    let order = match data.get(..4)? {
+        [0xd4, 0xc3, 0xb2, 0xa1] | [0x4d, 0x3c, 0xb2, 0xa1] => ByteOrder { big_endian: false },
+        [0xa1, 0xb2, 0xc3, 0xd4] | [0xa1, 0xb2, 0x3c, 0x4d] => ByteOrder { big_endian: true },
+        _ => return None,
+    };
+    // the upper bits of the link type hold the length of the frame check sequence
+    let link_type = order.u32_at(data, 20)? & 0x0fff_ffff;
+    let mut frames = Vec::new();
+    let mut position = 24;
+    while let Some(captured) = order.u32_at(data, position + 8) {
+        let Some(frame) = data.get(position + 16..).and_then(|rest| rest.get(..captured as usize)) else {
+            break;
+        };
+        frames.push((link_type, frame));
+        position += 16 + frame.len();
+    }
+    Some(frames)
+}
+
+/// Returns the frames of the packet blocks of a pcapng file with the link type of their interface.
+/// Each section has its own byte order and interfaces.
+fn pcapng_frames(data: &[u8]) -> Option<Vec<(u32, &[u8])>> {
+    if be32_at(data, 0)? != PCAPNG_SECTION_HEADER {
+        return None;
+    }
+    let mut order = ByteOrder { big_endian: false };
+    let mut interfaces = Vec::new();
+    let mut frames = Vec::new();
+    let mut position = 0;
+    // the block type of a section header reads the same in both byte orders
+    while let Some(kind) = order.u32_at(data, position) {
+        if kind == PCAPNG_SECTION_HEADER {
+            order.big_endian = match u32_at(data, position + 8) {
+                Some(PCAPNG_BYTE_ORDER) => false,
+                Some(magic) if magic == PCAPNG_BYTE_ORDER.swap_bytes() => true,
+                _ => break,
+            };
+            interfaces.clear();
+        }
+        let Some(block) = order
+            .u32_at(data, position + 4)
+            .filter(|length| *length >= 12)
+            .and_then(|length| data.get(position..).and_then(|rest| rest.get(..length as usize)))
+        else {
+            break;
+        };
+        match kind {
+            PCAPNG_INTERFACE => interfaces.push(order.u16_at(block, 8).map_or(u32::MAX, u32::from)),
+            PCAPNG_ENHANCED_PACKET => {
+                let interface = order.u32_at(block, 8).and_then(|interface| interfaces.get(interface as usize));
+                let frame = order.u32_at(block, 20).and_then(|captured| block.get(28..).and_then(|rest| rest.get(..captured as usize)));
+                if let (Some(&link_type), Some(frame)) = (interface, frame) {
+                    frames.push((link_type, frame));
+                }
+            }
+            PCAPNG_SIMPLE_PACKET => {
+                // the packet is cut to the snapshot length of the first interface, padding follows it
+                let length = order.u32_at(block, 8).map_or(0, |length| length as usize).min(block.len().saturating_sub(16));
+                if let (Some(&link_type), Some(frame)) = (interfaces.first(), block.get(12..12 + length)) {
+                    frames.push((link_type, frame));
+                }
+            }
+            _ => {}
+        }
+        position += block.len();
+    }
+    Some(frames)
+}
+
+/// Returns the IP packet of a frame, `None` for other protocols such as ARP.
+fn ip_packet(link_type: u32, frame: &[u8]) -> Option<&[u8]> {
+    let (ether_type, packet) = match link_type {
+        LINKTYPE_ETHERNET => {
+            let mut offset = 12;
+            // 802.1Q and 802.1ad VLAN tags
+            while matches!(be16_at(frame, offset)?, 0x8100 | 0x88a8) {
+                offset += 4;
+            }
+            (Some(be16_at(frame, offset)?), frame.get(offset + 2..)?)
+        }
+        LINKTYPE_LINUX_SLL => (Some(be16_at(frame, 14)?), frame.get(16..)?),
+        LINKTYPE_LINUX_SLL2 => (Some(be16_at(frame, 0)?), frame.get(20..)?),
+        LINKTYPE_NULL => (None, frame.get(4..)?),
+        link_type if LINKTYPE_RAW.contains(&link_type) => (None, frame),
+        _ => return None,
+    };
+    match ether_type {
+        None | Some(0x0800) | Some(0x86dd) => Some(packet),
+        Some(_) => None,
+    }
+}
+
+/// Reads the TCP segment or UDP datagram of an IPv4 or IPv6 packet, the version is read from the
+/// packet as some link types do not tell it.
+fn segment(packet: &[u8]) -> Option<Segment<'_>> {
+    let (source, destination, protocol, payload) = match packet.first()? >> 4 {
+        4 => {
+            let header = (packet[0] & 0x0f) as usize * 4;
+            let end = (be16_at(packet, 2)? as usize).min(packet.len());
+            // the offset or the more fragments flag of a fragment
+            if be16_at(packet, 6)? & 0x3fff != 0 {
+                return None;
+            }
+            let source: [u8; 4] = packet.get(12..16)?.try_into().ok()?;
+            let destination: [u8; 4] = packet.get(16..20)?.try_into().ok()?;
+            (IpAddr::from(source), IpAddr::from(destination), packet[9], packet.get(header..end)?)
+        }
+        6 => {
+            let end = (40 + be16_at(packet, 4)? as usize).min(packet.len());
+            let source: [u8; 16] = packet.get(8..24)?.try_into().ok()?;
+            let destination: [u8; 16] = packet.get(24..40)?.try_into().ok()?;
+            let mut protocol = packet[6];
+            let mut payload = packet.get(40..end)?;
+            // hop-by-hop options, routing and destination options headers
+            while matches!(protocol, 0 | 43 | 60) {
+                protocol = *payload.first()?;
+                payload = payload.get((*payload.get(1)? as usize + 1) * 8..)?;
+            }
+            (IpAddr::from(source), IpAddr::from(destination), protocol, payload)
+        }
+        _ => return None,
+    };
+    let flow = Flow {
+        source: SocketAddr::new(source, be16_at(payload, 0)?),
+        destination: SocketAddr::new(destination, be16_at(payload, 2)?),
+    };
+    match protocol {
+        TCP => {
+            let header = (*payload.get(12)? >> 4) as usize * 4;
+            let syn = *payload.get(13)? & 0x02 != 0;
+            Some(Segment::Tcp { flow, sequence: be32_at(payload, 4)?, syn, payload: payload.get(header..)? })
+        }
+        UDP => {
+            let end = (be16_at(payload, 4)? as usize).min(payload.len());
+            Some(Segment::Udp { flow, payload: payload.get(8..end)? })
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Stream;
+
+    #[test]
+    fn reassembles_segments_out_of_order() {
+        let stream = Stream { start: None, segments: vec![(5, b"cd"), (3, b"ab"), (5, b"cd"), (7, b"ef")] };
+        assert_eq!(stream.reassemble(), b"abcdef");
+
+        // the sequence numbers wrap around, the segment at 0xffffffff comes first
+        let stream = Stream { start: None, segments: vec![(1, b"cd"), (u32::MAX, b"ab")] };
+        assert_eq!(stream.reassemble(), b"abcd");
+
+        // the stream ends at a segment which was not captured
+        let stream = Stream { start: Some(1), segments: vec![(1, b"ab"), (5, b"ef")] };
+        assert_eq!(stream.reassemble(), b"ab");
+    }
+}
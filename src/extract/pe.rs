@@ -0,0 +1,125 @@
+use crate::extract::{u16_at, u32_at, Entry};
+
+/// Index of the certificate table in the data directories of the optional header.
+const SECURITY_DIRECTORY: usize = 4;
+const WIN_CERT_TYPE_PKCS_SIGNED_DATA: u16 = 0x0002;
+
+/// Returns the entries of the Authenticode certificate table (`WIN_CERTIFICATE`) of a PE file,
+/// `None` when the data is not a PE file. The table is addressed by file offset, not by RVA.
+pub(crate) fn signatures(data: &[u8]) -> Option<Vec<Entry>> {
+    if !data.starts_with(b"MZ") {
+        return None;
+    }
+    let pe = u32_at(data, 0x3c)? as usize;
+    if data.get(pe..pe.checked_add(4)?)? != b"PE\0\0" {
+        return None;
+    }
+    let optional = pe + 24;
+    let (count_offset, directories) = match u16_at(data, optional)? {
+        0x10b => (optional + 92, optional + 96),
+        0x20b => (optional + 108, optional + 112),
+        _ => return None,
+    };
+    if (u32_at(data, count_offset)? as usize) <= SECURITY_DIRECTORY {
+        return Some(vec![]);
+    }
+    let offset = u32_at(data, directories + SECURITY_DIRECTORY * 8)? as usize;
+    let size = u32_at(data, directories + SECURITY_DIRECTORY * 8 + 4)? as usize;
+    let table = data.get(offset..offset.checked_add(size)?).unwrap_or_default();
+
+    let mut entries = Vec::new();
+    let mut position = 0;
+    // each WIN_CERTIFICATE is dwLength, wRevision, wCertificateType and the certificate, padded to 8 bytes
+    while let (Some(length), Some(kind)) = (u32_at(table, position), u16_at(table, position + 6)) {
+        let length = length as usize;
+        let Some(certificate) = table.get(position + 8..position + length.max(8)) else {
+            break;
+        };
+        let name = match kind {
+            WIN_CERT_TYPE_PKCS_SIGNED_DATA => format!("Authenticode signature {}", entries.len() + 1),
+            kind => format!("WIN_CERTIFICATE {} of type {:#06x}", entries.len() + 1, kind),
+        };
+        entries.push(Entry { name, data: certificate.to_vec() });
+        position += length.max(8).next_multiple_of(8);
+    }
+    Some(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A PE file made of the headers, with the certificate table after them.
+    fn pe_file(magic: u16, table: &[u8], table_size: usize) -> Vec<u8> {
+        let optional = 0x40 + 24;
+        let directories = optional + if magic == 0x10b { 96 } else { 112 };
+        let mut data = vec![0; directories + 16 * 8];
+        data[..2].copy_from_slice(b"MZ");
+        data[0x3c..0x40].copy_from_slice(&0x40u32.to_le_bytes());
+        data[0x40..0x44].copy_from_slice(b"PE\0\0");
+        data[optional..optional + 2].copy_from_slice(&magic.to_le_bytes());
+        data[directories - 4..directories].copy_from_slice(&16u32.to_le_bytes());
+        let (entry, offset) = (directories + SECURITY_DIRECTORY * 8, data.len() as u32);
+        data[entry..entry + 4].copy_from_slice(&offset.to_le_bytes());
+        data[entry + 4..entry + 8].copy_from_slice(&(table_size as u32).to_le_bytes());
+        data.extend_from_slice(table);
+        data
+    }
+
+    fn win_certificate(kind: u16, certificate: &[u8]) -> Vec<u8> {
+        let mut entry = ((8 + certificate.len()) as u32).to_le_bytes().to_vec();
+        entry.extend_from_slice(&0x0200u16.to_le_bytes());
+        entry.extend_from_slice(&kind.to_le_bytes());
+        entry.extend_from_slice(certificate);
+        entry.resize(entry.len().next_multiple_of(8), 0);
+        entry
+    }
+
+    fn names(entries: &[Entry]) -> Vec<(&str, &[u8])> {
+        entries.iter().map(|entry| (entry.name.as_str(), entry.data.as_slice())).collect()
+    }
+
+    #[test]
+    fn reads_the_certificate_table() {
+        let mut table = win_certificate(WIN_CERT_TYPE_PKCS_SIGNED_DATA, &[0x30, 0x03, 0x02, 0x01, 0x05]);
+        table.extend(win_certificate(0x0001, &[0x30, 0x00]));
+        for magic in [0x10b, 0x20b] {
+            let entries = signatures(&pe_file(magic, &table, table.len())).unwrap();
+            assert_eq!(
+                names(&entries),
+                [("Authenticode signature 1", [0x30, 0x03, 0x02, 0x01, 0x05].as_slice()), ("WIN_CERTIFICATE 2 of type 0x0001", [0x30, 0x00].as_slice())]
+            );
+        }
+    }
+
+    #[test]
+    fn stops_at_truncated_tables() {
+        let table = win_certificate(WIN_CERT_TYPE_PKCS_SIGNED_DATA, &[0x30, 0x03, 0x02, 0x01, 0x05]);
+        // the size of the table runs past the end of the file
+        assert!(signatures(&pe_file(0x10b, &table, table.len() + 1)).unwrap().is_empty());
+        // the length of the second entry runs past the end of the table
+        let mut truncated = table.clone();
+        truncated.extend_from_slice(&[0xff, 0, 0, 0, 0, 2, 2, 0, 0x30]);
+        let entries = signatures(&pe_file(0x10b, &truncated, truncated.len())).unwrap();
+        assert_eq!(names(&entries), [("Authenticode signature 1", [0x30, 0x03, 0x02, 0x01, 0x05].as_slice())]);
+        // a header without a length field ends the table
+        let mut short = table.clone();
+        short.extend_from_slice(&[8, 0]);
+        assert_eq!(signatures(&pe_file(0x10b, &short, short.len())).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn skips_files_which_are_not_signed_pe_files() {
+        let unsigned = pe_file(0x10b, &[], 0);
+        assert!(signatures(&unsigned).unwrap().is_empty());
+        let mut few_directories = pe_file(0x20b, &win_certificate(WIN_CERT_TYPE_PKCS_SIGNED_DATA, &[0x30, 0x00]), 16);
+        few_directories[0x58 + 108..0x58 + 112].copy_from_slice(&4u32.to_le_bytes());
+        assert!(signatures(&few_directories).unwrap().is_empty());
+
+        let mut not_pe = unsigned.clone();
+        not_pe[0x40..0x44].copy_from_slice(b"NE\0\0");
+        assert!(signatures(&not_pe).is_none());
+        assert!(signatures(&unsigned[..0x3e]).is_none());
+        assert!(signatures(b"MAMCAQU=").is_none());
+    }
+}
@@ -0,0 +1,27 @@
+use std::io::Read;
+use crate::extract::Entry;
+
+/// Returns the regular files of a POSIX or GNU tar archive, `None` when the data is not one.
+/// Reading stops at the first file which is cut off.
+pub(crate) fn files(data: &[u8]) -> Option<Vec<Entry>> {
+    if data.get(257..262)? != b"ustar" {
+        return None;
+    }
+    let mut archive = ::tar::Archive::new(data);
+    let mut files = Vec::new();
+    for entry in archive.entries().ok()? {
+        let Ok(mut entry) = entry else {
+            break;
+        };
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let name = entry.path().map(|path| path.display().to_string()).unwrap_or_default();
+        let mut content = Vec::new();
+        if entry.read_to_end(&mut content).is_err() {
+            break;
+        }
+        files.push(Entry { name, data: content });
+    }
+    Some(files)
+}
@@ -0,0 +1,56 @@
+use crate::extract::be16_at;
+
+const CHANGE_CIPHER_SPEC: u8 = 20;
+const HANDSHAKE: u8 = 22;
+const CERTIFICATE: u8 = 11;
+
+/// Returns true if the stream starts with a TLS handshake record.
+pub(crate) fn is_tls(stream: &[u8]) -> bool {
+    stream.starts_with(&[HANDSHAKE, 0x03])
+}
+
+/// Returns the DER certificates of the Certificate handshake messages of one direction of a
+/// TLS connection. Handshake messages may span records, so the handshake records are joined
+/// before the messages are read. Records after ChangeCipherSpec are encrypted, which is why
+/// the certificates of TLS 1.3 are not found.
+pub(crate) fn certificates(stream: &[u8]) -> Vec<Vec<u8>> {
+    let mut handshake = Vec::new();
+    let mut records = stream;
+    while let (Some(&kind), Some(length)) = (records.first(), be16_at(records, 3)) {
+        let Some(fragment) = records.get(5..5 + length as usize) else {
+            break;
+        };
+        match kind {
+            CHANGE_CIPHER_SPEC => break,
+            HANDSHAKE => handshake.extend_from_slice(fragment),
+            _ => {}
+        }
+        records = &records[5 + length as usize..];
+    }
+
+    let mut certificates = Vec::new();
+    let mut messages = handshake.as_slice();
+    while let (Some(&kind), Some(length)) = (messages.first(), be24_at(messages, 1)) {
+        let Some(body) = messages.get(4..4 + length) else {
+            break;
+        };
+        if kind == CERTIFICATE {
+            // the list of certificates, each one prefixed with its 24-bit length
+            let mut list = body.get(3..).unwrap_or_default();
+            while let Some(length) = be24_at(list, 0) {
+                let Some(certificate) = list.get(3..3 + length) else {
+                    break;
+                };
+                certificates.push(certificate.to_vec());
+                list = &list[3 + length..];
+            }
+        }
+        messages = &messages[4 + length..];
+    }
+    certificates
+}
+
+fn be24_at(data: &[u8], offset: usize) -> Option<usize> {
+    let bytes = data.get(offset..offset.checked_add(3)?)?;
+    Some(u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]) as usize)
+}
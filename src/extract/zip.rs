@@ -0,0 +1,201 @@
+use std::io::Read;
+use flate2::read::DeflateDecoder;
+use crate::extract::{u16_at, u32_at, Entry};
+
+const LOCAL_HEADER: &[u8] = b"PK\x03\x04";
+const CENTRAL_HEADER: &[u8] = b"PK\x01\x02";
+const END_OF_CENTRAL_DIRECTORY: &[u8] = b"PK\x05\x06";
+/// The end of central directory record is 22 bytes followed by a comment of up to 65535 bytes.
+const MAX_END_SEARCH: usize = 22 + 0xffff;
+
+const STORED: u16 = 0;
+const DEFLATED: u16 = 8;
+
+/// A file of a ZIP archive as listed in the central directory.
+#[derive(Clone, Debug)]
+pub(crate) struct File {
+    pub(crate) name: String,
+    method: u16,
+    compressed_size: usize,
+    local_header: usize,
+}
+
+/// A ZIP archive, such as a JAR or an APK, read through its central directory.
+pub(crate) struct Archive<'a> {
+    pub(crate) data: &'a [u8],
+    pub(crate) files: Vec<File>,
+    /// Offset of the central directory, the APK Signing Block ends there.
+    pub(crate) central_directory: usize,
+}
+
+impl<'a> Archive<'a> {
+    /// Reads the central directory, `None` when the data is not a ZIP archive.
+    pub(crate) fn new(data: &'a [u8]) -> Option<Self> {
+        if !data.starts_with(LOCAL_HEADER) && !data.starts_with(END_OF_CENTRAL_DIRECTORY) {
+            return None;
+        }
+        let search = data.len().saturating_sub(MAX_END_SEARCH);
+        let end = search + data[search..].windows(4).rposition(|window| window == END_OF_CENTRAL_DIRECTORY)?;
+        let count = u16_at(data, end + 10)? as usize;
+        let central_directory = u32_at(data, end + 16)? as usize;
+
+        let mut files = Vec::with_capacity(count);
+        let mut position = central_directory;
+        for _ in 0..count {
+            if data.get(position..position + 4)? != CENTRAL_HEADER {
+                return None;
+            }
+            let name_length = u16_at(data, position + 28)? as usize;
+            let extra_length = u16_at(data, position + 30)? as usize;
+            let comment_length = u16_at(data, position + 32)? as usize;
+            let name = data.get(position + 46..position + 46 + name_length)?;
+            files.push(File {
+                name: String::from_utf8_lossy(name).to_string(),
+                method: u16_at(data, position + 10)?,
+                compressed_size: u32_at(data, position + 20)? as usize,
+                local_header: u32_at(data, position + 42)? as usize,
+            });
+            position += 46 + name_length + extra_length + comment_length;
+        }
+        Some(Archive { data, files, central_directory })
+    }
+
+    /// Returns the content of a stored or deflated file.
+    pub(crate) fn read(&self, file: &File) -> Option<Vec<u8>> {
+        let header = file.local_header;
+        if self.data.get(header..header + 4)? != LOCAL_HEADER {
+            return None;
+        }
+        let start = header + 30 + u16_at(self.data, header + 26)? as usize + u16_at(self.data, header + 28)? as usize;
+        let compressed = self.data.get(start..start.checked_add(file.compressed_size)?)?;
+        match file.method {
+            STORED => Some(compressed.to_vec()),
+            DEFLATED => {
+                let mut content = Vec::new();
+                DeflateDecoder::new(compressed).read_to_end(&mut content).ok()?;
+                Some(content)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Returns the files of the archive, directories are left out and so are files which cannot be read.
+pub(crate) fn files(archive: &Archive) -> Vec<Entry> {
+    archive
+        .files
+        .iter()
+        .filter(|file| !file.name.ends_with('/'))
+        .filter_map(|file| Some(Entry { name: file.name.clone(), data: archive.read(file)? }))
+        .collect()
+}
+
+/// Returns the PKCS#7 signature files of a signed JAR: `META-INF/*.RSA`, `*.DSA` and `*.EC`.
+pub(crate) fn signature_files(archive: &Archive) -> Vec<Entry> {
+    archive
+        .files
+        .iter()
+        .filter(|file| is_signature_file(&file.name))
+        .filter_map(|file| Some(Entry { name: file.name.clone(), data: archive.read(file)? }))
+        .collect()
+}
+
+fn is_signature_file(name: &str) -> bool {
+    let name = name.to_ascii_uppercase();
+    let Some(file) = name.strip_prefix("META-INF/") else {
+        return false;
+    };
+    !file.contains('/') && [".RSA", ".DSA", ".EC"].iter().any(|extension| file.ends_with(extension))
+}
+
+/// Builds a ZIP archive of stored files for the tests of the formats read from archives.
+#[cfg(test)]
+pub(crate) fn stored_archive(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut data = Vec::new();
+    let mut central_directory = Vec::new();
+    for (name, content) in files {
+        let local_header = data.len() as u32;
+        let sizes = [content.len() as u32; 2].map(u32::to_le_bytes).concat();
+        data.extend_from_slice(LOCAL_HEADER);
+        data.extend_from_slice(&[20, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        data.extend_from_slice(&sizes);
+        data.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        data.extend_from_slice(&[0, 0]);
+        data.extend_from_slice(name.as_bytes());
+        data.extend_from_slice(content);
+
+        central_directory.extend_from_slice(CENTRAL_HEADER);
+        central_directory.extend_from_slice(&[20, 0, 20, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        central_directory.extend_from_slice(&sizes);
+        central_directory.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        central_directory.extend_from_slice(&[0; 12]);
+        central_directory.extend_from_slice(&local_header.to_le_bytes());
+        central_directory.extend_from_slice(name.as_bytes());
+    }
+    let offset = data.len() as u32;
+    let count = (files.len() as u16).to_le_bytes();
+    data.extend_from_slice(&central_directory);
+    data.extend_from_slice(END_OF_CENTRAL_DIRECTORY);
+    data.extend_from_slice(&[0, 0, 0, 0, count[0], count[1], count[0], count[1]]);
+    data.extend_from_slice(&(central_directory.len() as u32).to_le_bytes());
+    data.extend_from_slice(&offset.to_le_bytes());
+    data.extend_from_slice(&[0, 0]);
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(entries: &[Entry]) -> Vec<&str> {
+        entries.iter().map(|entry| entry.name.as_str()).collect()
+    }
+
+    #[test]
+    fn reads_stored_and_deflated_files() {
+        let mut data = stored_archive(&[("dir/", b""), ("dir/a.der", &[0x30, 0x00]), ("b.txt", b"hello hello hello")]);
+        let archive = Archive::new(&data).unwrap();
+        assert_eq!(names(&files(&archive)), ["dir/a.der", "b.txt"]);
+        assert_eq!(files(&archive)[1].data, b"hello hello hello");
+
+        // deflated with fixed Huffman codes, the method is read from the central directory
+        let deflated = [0xcb, 0x48, 0xcd, 0xc9, 0xc9, 0x57, 0xc8, 0x40, 0x90, 0x00];
+        data = stored_archive(&[("b.txt", &deflated)]);
+        let position = data.windows(4).position(|window| window == CENTRAL_HEADER).unwrap();
+        data[position + 10] = DEFLATED as u8;
+        let archive = Archive::new(&data).unwrap();
+        assert_eq!(files(&archive)[0].data, b"hello hello hello");
+    }
+
+    #[test]
+    fn lists_the_signature_files_of_jars() {
+        let data = stored_archive(&[
+            ("META-INF/MANIFEST.MF", b"Manifest-Version: 1.0\n"),
+            ("META-INF/SIGNER.SF", b"Signature-Version: 1.0\n"),
+            ("META-INF/SIGNER.RSA", &[0x30, 0x00]),
+            ("META-INF/other.ec", &[0x30, 0x00]),
+            ("META-INF/sub/NESTED.DSA", &[0x30, 0x00]),
+            ("CERT.RSA", &[0x30, 0x00]),
+        ]);
+        let archive = Archive::new(&data).unwrap();
+        assert_eq!(names(&signature_files(&archive)), ["META-INF/SIGNER.RSA", "META-INF/other.ec"]);
+    }
+
+    #[test]
+    fn skips_truncated_archives() {
+        let data = stored_archive(&[("a.der", &[0x30, 0x03, 0x02, 0x01, 0x05]), ("b.der", &[0x30, 0x00])]);
+        // the central directory lists two files but holds one
+        let end = data.len() - 22;
+        let mut cut = data[..end - 46 - "b.der".len()].to_vec();
+        cut.extend_from_slice(&data[end..]);
+        assert!(Archive::new(&cut).is_none());
+        // a file whose size runs past the end of the data is left out
+        let mut oversized = data.clone();
+        let position = oversized.windows(4).position(|window| window == CENTRAL_HEADER).unwrap();
+        oversized[position + 20..position + 24].copy_from_slice(&0xffffu32.to_le_bytes());
+        let archive = Archive::new(&oversized).unwrap();
+        assert_eq!(names(&files(&archive)), ["b.der"]);
+        assert!(Archive::new(b"PK\x03\x04").is_none());
+        assert!(Archive::new(b"MAMCAQU=").is_none());
+    }
+}
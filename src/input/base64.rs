@@ -1,15 +1,81 @@
+use std::fmt;
 use base64::DecodeError;
-use base64::engine::general_purpose;
-use base64::prelude::*;
+use base64::alphabet;
+use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+use base64::Engine;
 
-pub fn base64_decode(input: Vec<u8>) -> Result<Vec<u8>, DecodeError> {
-    Ok(BASE64_STANDARD.decode(input.as_slice())?)
+const CONFIG: GeneralPurposeConfig = GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent);
+const STANDARD: GeneralPurpose = GeneralPurpose::new(&alphabet::STANDARD, CONFIG);
+const URL_SAFE: GeneralPurpose = GeneralPurpose::new(&alphabet::URL_SAFE, CONFIG);
+
+/// How base64 input was written: the alphabet, whether padding was left out and whether
+/// whitespace such as MIME line breaks was removed before decoding.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Base64Variant {
+    url_safe: bool,
+    unpadded: bool,
+    wrapped: bool,
+}
+
+impl fmt::Display for Base64Variant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let alphabet = if self.url_safe { "URL-safe base64" } else { "base64" };
+        let details = [(self.unpadded, "without padding"), (self.wrapped, "line-wrapped")]
+            .iter()
+            .filter(|(set, _)| *set)
+            .map(|(_, detail)| *detail)
+            .collect::<Vec<&str>>();
+        match details.is_empty() {
+            true => write!(f, "{}", alphabet),
+            false => write!(f, "{} ({})", alphabet, details.join(", ")),
+        }
+    }
+}
+
+/// Decodes standard or URL-safe base64 with or without padding, whitespace is skipped.
+pub fn base64_decode(input: &[u8]) -> Result<(Vec<u8>, Base64Variant), DecodeError> {
+    // whitespace around the text, such as the new line at the end of a file, is not wrapping
+    let input = input.trim_ascii();
+    let text = input.iter().copied().filter(|b| !b.is_ascii_whitespace()).collect::<Vec<u8>>();
+    let variant = |url_safe| Base64Variant {
+        url_safe,
+        unpadded: !text.len().is_multiple_of(4),
+        wrapped: text.len() != input.len(),
+    };
+    match STANDARD.decode(&text) {
+        Ok(data) => Ok((data, variant(false))),
+        Err(error) => URL_SAFE.decode(&text).map(|data| (data, variant(true))).map_err(|_| error),
+    }
 }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variant(text: &str) -> String {
+        let (data, variant) = base64_decode(text.as_bytes()).unwrap();
+        assert_eq!(data, [0xfb, 0xff, 0x30, 0x03, 0x02, 0x01, 0x05], "{}", text);
+        variant.to_string()
+    }
+
+    #[test]
+    fn names_the_variant() {
+        assert_eq!(variant("+/8wAwIBBQ=="), "base64");
+        assert_eq!(variant("-_8wAwIBBQ=="), "URL-safe base64");
+        assert_eq!(variant("+/8wAwIBBQ"), "base64 (without padding)");
+        assert_eq!(variant("-_8wAwIBBQ"), "URL-safe base64 (without padding)");
+        assert_eq!(variant("+/8wAwIB\r\nBQ==\r\n"), "base64 (line-wrapped)");
+        assert_eq!(variant("-_8wAw\nIBBQ"), "URL-safe base64 (without padding, line-wrapped)");
+    }
+
+    #[test]
+    fn does_not_count_surrounding_whitespace_as_wrapping() {
+        assert_eq!(variant("+/8wAwIBBQ==\n"), "base64");
+        assert_eq!(variant("  -_8wAwIBBQ\r\n\r\n"), "URL-safe base64 (without padding)");
+    }
 
-pub fn is_valid_base64(data: Vec<u8>) -> bool {
-    if let Ok(decoded) = general_purpose::STANDARD.decode(data.clone()) {
-        decoded != data
-    } else {
-        false
+    #[test]
+    fn rejects_mixed_alphabets() {
+        assert!(base64_decode(b"+_8wAwIBBQ==").is_err());
+        assert!(base64_decode(b"+/8w*wIBBQ==").is_err());
     }
 }
\ No newline at end of file
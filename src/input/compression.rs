@@ -0,0 +1,108 @@
+use std::fmt;
+use std::io::Read;
+use bzip2::read::MultiBzDecoder;
+use flate2::read::MultiGzDecoder;
+use ruzstd::decoding::StreamingDecoder;
+use crate::input::error::InputError;
+
+/// A compression of the input, recognized by its magic bytes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Compression {
+    Gzip,
+    Zstd,
+    Bzip2,
+    Xz,
+}
+
+impl Compression {
+    fn detect(data: &[u8]) -> Option<Self> {
+        match data {
+            [0x1f, 0x8b, 0x08, ..] => Some(Compression::Gzip),
+            [0x28, 0xb5, 0x2f, 0xfd, ..] => Some(Compression::Zstd),
+            // the block size and the magic of the first block, or of the end of an empty stream
+            [b'B', b'Z', b'h', b'1'..=b'9', 0x31, 0x41, 0x59, 0x26, 0x53, 0x59, ..] => Some(Compression::Bzip2),
+            [b'B', b'Z', b'h', b'1'..=b'9', 0x17, 0x72, 0x45, 0x38, 0x50, 0x90, ..] => Some(Compression::Bzip2),
+            [0xfd, b'7', b'z', b'X', b'Z', 0x00, ..] => Some(Compression::Xz),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Compression::Gzip => write!(f, "gzip"),
+            Compression::Zstd => write!(f, "zstd"),
+            Compression::Bzip2 => write!(f, "bzip2"),
+            Compression::Xz => write!(f, "xz"),
+        }
+    }
+}
+
+/// Decompresses gzip, zstd, bzip2 or xz data, data which is not compressed is returned as it is.
+/// Concatenated gzip and bzip2 streams are read to the end.
+pub(crate) fn decompress(data: Vec<u8>) -> Result<(Vec<u8>, Option<Compression>), InputError> {
+    let Some(compression) = Compression::detect(&data) else {
+        return Ok((data, None));
+    };
+    let mut content = Vec::new();
+    let result = match compression {
+        Compression::Gzip => MultiGzDecoder::new(data.as_slice()).read_to_end(&mut content).map_err(|error| error.to_string()),
+        Compression::Bzip2 => MultiBzDecoder::new(data.as_slice()).read_to_end(&mut content).map_err(|error| error.to_string()),
+        Compression::Zstd => StreamingDecoder::new(data.as_slice())
+            .map_err(|error| error.to_string())
+            .and_then(|mut decoder| decoder.read_to_end(&mut content).map_err(|error| error.to_string())),
+        Compression::Xz => lzma_rs::xz_decompress(&mut data.as_slice(), &mut content).map(|_| content.len()).map_err(|error| error.to_string()),
+    };
+    match result {
+        Ok(_) => Ok((content, Some(compression))),
+        Err(error) => Err(InputError::DecompressError(compression, error)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use super::*;
+
+    const DATA: &[u8] = b"0\x03\x02\x01\x05 and some text which compresses";
+
+    fn round_trip(compressed: Vec<u8>, compression: Compression) {
+        assert_eq!(Compression::detect(&compressed), Some(compression));
+        let (data, detected) = decompress(compressed).unwrap();
+        assert_eq!(data, DATA);
+        assert_eq!(detected, Some(compression));
+    }
+
+    #[test]
+    fn decompresses_every_format() {
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gzip.write_all(DATA).unwrap();
+        round_trip(gzip.finish().unwrap(), Compression::Gzip);
+
+        let mut bzip2 = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+        bzip2.write_all(DATA).unwrap();
+        round_trip(bzip2.finish().unwrap(), Compression::Bzip2);
+
+        let zstd = ruzstd::encoding::compress_to_vec(DATA, ruzstd::encoding::CompressionLevel::Fastest);
+        round_trip(zstd, Compression::Zstd);
+
+        let mut xz = Vec::new();
+        lzma_rs::xz_compress(&mut &DATA[..], &mut xz).unwrap();
+        round_trip(xz, Compression::Xz);
+    }
+
+    #[test]
+    fn keeps_data_which_is_not_compressed() {
+        assert_eq!(decompress(DATA.to_vec()).unwrap(), (DATA.to_vec(), None));
+    }
+
+    #[test]
+    fn reports_truncated_input() {
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gzip.write_all(DATA).unwrap();
+        let mut compressed = gzip.finish().unwrap();
+        compressed.truncate(compressed.len() / 2);
+        assert!(matches!(decompress(compressed), Err(InputError::DecompressError(Compression::Gzip, _))));
+    }
+}
@@ -0,0 +1,149 @@
+use serde::Deserialize;
+use serde_json::Value;
+use crate::asn1_der::is_complete_ber;
+use crate::extract::Entry;
+use crate::input::base64::base64_decode;
+use crate::input::pem::parse_pem;
+
+/// Reads a JSON or YAML document, such as a kubeconfig file, a Kubernetes Secret, a JWK set or
+/// a Terraform state, and returns its string values which hold DER or PEM, named by their JSON
+/// path, together with the language of the document. `None` when the text is not a JSON object
+/// or array, nor YAML mappings or sequences, or when none of its strings hold DER or PEM.
+pub(crate) fn document_values(text: &str) -> Option<(Vec<Entry>, &'static str)> {
+    let (documents, language) = match serde_json::from_str::<Value>(text) {
+        Ok(document) => (vec![document], "JSON"),
+        Err(_) => (yaml_documents(text)?, "YAML"),
+    };
+    if !documents.iter().all(|document| document.is_object() || document.is_array()) {
+        return None;
+    }
+
+    let mut entries = Vec::new();
+    for (i, document) in documents.iter().enumerate() {
+        // the documents of a YAML stream are told apart by their number
+        let root = match documents.len() {
+            1 => "$".to_string(),
+            _ => format!("${}", i + 1),
+        };
+        collect(document, root, &mut entries);
+    }
+    (!entries.is_empty()).then_some((entries, language))
+}
+
+/// Reads every document of a YAML stream, documents are separated by `---` lines.
+fn yaml_documents(text: &str) -> Option<Vec<Value>> {
+    serde_yaml::Deserializer::from_str(text)
+        .map(|document| Value::deserialize(document).ok())
+        .filter(|document| !matches!(document, Some(Value::Null)))
+        .collect()
+}
+
+fn collect(value: &Value, path: String, entries: &mut Vec<Entry>) {
+    match value {
+        Value::Object(members) => {
+            for (key, member) in members {
+                collect(member, member_path(&path, key), entries);
+            }
+        }
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                collect(item, format!("{}[{}]", path, i), entries);
+            }
+        }
+        Value::String(text) => entries.extend(string_values(text, &path)),
+        _ => {}
+    }
+}
+
+/// Keys which are not plain names, such as `tls.crt`, are written in brackets.
+fn member_path(path: &str, key: &str) -> String {
+    match !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+        true => format!("{}.{}", path, key),
+        false => format!("{}[{:?}]", path, key),
+    }
+}
+
+/// A string holds PEM text, base64 of DER such as an `x5c` certificate, or base64 of PEM text such
+/// as the `certificate-authority-data` of a kubeconfig. Base64 which decodes to anything but a
+/// constructed BER value is taken for an ordinary string.
+fn string_values(text: &str, path: &str) -> Vec<Entry> {
+    if let Some(entries) = pem_values(text.as_bytes(), path) {
+        return entries;
+    }
+    let Ok((data, _)) = base64_decode(text.as_bytes()) else {
+        return vec![];
+    };
+    if is_constructed_ber(&data) {
+        return vec![Entry { name: path.to_string(), data }];
+    }
+    pem_values(&data, path).unwrap_or_default()
+}
+
+fn pem_values(text: &[u8], path: &str) -> Option<Vec<Entry>> {
+    let blocks = parse_pem(text).ok().filter(|blocks| !blocks.is_empty())?;
+    let entries = blocks
+        .into_iter()
+        .map(|block| Entry {
+            name: format!("{} {}", path, block.label),
+            data: base64_decode(&block.body).map(|(body, _)| body).unwrap_or(block.body),
+        })
+        .collect();
+    Some(entries)
+}
+
+fn is_constructed_ber(data: &[u8]) -> bool {
+    data.first().is_some_and(|identifier| identifier & 0x20 != 0) && is_complete_ber(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DER: [u8; 5] = [0x30, 0x03, 0x02, 0x01, 0x05];
+
+    fn names(text: &str) -> Option<(Vec<String>, &'static str)> {
+        let (entries, language) = document_values(text)?;
+        assert!(entries.iter().all(|entry| entry.data == DER));
+        Some((entries.into_iter().map(|entry| entry.name).collect(), language))
+    }
+
+    #[test]
+    fn finds_der_and_pem_in_json_strings() {
+        let text = r#"{
+            "keys": [{"kty": "EC", "x5c": ["MAMCAQU=", "AgEF"]}],
+            "data": {"tls.crt": "-----BEGIN CERTIFICATE-----\nMAMCAQU=\n-----END CERTIFICATE-----\n-----BEGIN CERTIFICATE-----\nMAMCAQU=\n-----END CERTIFICATE-----\n"},
+            "certificate-authority-data": "LS0tLS1CRUdJTiBDRVJUSUZJQ0FURS0tLS0tCk1BTUNBUVU9Ci0tLS0tRU5EIENFUlRJRklDQVRFLS0tLS0K",
+            "name": "abcd",
+            "count": 3
+        }"#;
+        let (names, language) = names(text).unwrap();
+        assert_eq!(language, "JSON");
+        assert_eq!(
+            names,
+            [
+                "$.keys[0].x5c[0]",
+                "$.data[\"tls.crt\"] CERTIFICATE",
+                "$.data[\"tls.crt\"] CERTIFICATE",
+                "$.certificate-authority-data CERTIFICATE",
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_der_in_yaml_documents() {
+        let nested = "apiVersion: v1\nkind: Secret\ndata:\n  tls.crt: MAMCAQU=\n  list:\n    - name: a\n      value: MAMCAQU=\n";
+        assert_eq!(names(nested), Some((vec!["$.data[\"tls.crt\"]".to_string(), "$.data.list[0].value".to_string()], "YAML")));
+
+        // the documents of a stream are numbered, empty documents are skipped
+        let stream = "---\nca: MAMCAQU=\n---\n---\n- plain\n- MAMCAQU=\n";
+        assert_eq!(names(stream), Some((vec!["$1.ca".to_string(), "$2[1]".to_string()], "YAML")));
+    }
+
+    #[test]
+    fn skips_documents_without_der() {
+        assert!(document_values(r#"{"name": "abcd", "number": "AgEF"}"#).is_none());
+        assert!(document_values("\"MAMCAQU=\"").is_none());
+        assert!(document_values("MAMCAQU=").is_none());
+        assert!(document_values("a: MAMCAQU=\n---\nplain text\n").is_none());
+    }
+}
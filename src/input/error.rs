@@ -1,14 +1,25 @@
 use std::{fmt, io};
 use std::error::Error;
 use base64::DecodeError;
-use hex::FromHexError;
+use crate::asn1_module::ModuleError;
+use crate::input::compression::Compression;
+use crate::input::hex::HexError;
+use crate::input::utils::InputFormat;
 
 #[derive(Debug)]
 pub enum InputError {
     IoError(io::Error),
     DecodeBase64Error(DecodeError),
-    DecodeHexError(FromHexError),
+    DecodeHexError(HexError),
+    DecompressError(Compression, String),
     NoInputProvided,
+    MissingEnvVar(String),
+    InvalidModule(String, ModuleError),
+    UnknownType(String),
+    MissingType,
+    WrongFormat(InputFormat),
+    OutOfRange { offset: usize, size: usize },
+    SlicedEntries(usize),
 }
 
 impl fmt::Display for InputError {
@@ -17,7 +28,17 @@ impl fmt::Display for InputError {
             InputError::IoError(e) => write!(f, "IO error: {}", e),
             InputError::DecodeBase64Error(e) => write!(f, "Base64 decode error: {}", e),
             InputError::DecodeHexError(e) => write!(f, "Hex decode error: {}", e),
+            InputError::DecompressError(compression, e) => write!(f, "Cannot decompress the {} input: {}", compression, e),
             InputError::NoInputProvided => write!(f, "No input provided"),
+            InputError::MissingEnvVar(name) => write!(f, "Environment variable {} is not set", name),
+            InputError::InvalidModule(file, e) => write!(f, "ASN.1 module {}, {}", file, e),
+            InputError::UnknownType(name) => write!(f, "Type {} is not defined in the ASN.1 modules", name),
+            InputError::MissingType => write!(f, "PER and OER input is decoded with a --type from the ASN.1 modules"),
+            InputError::WrongFormat(format) => write!(f, "Input is not {}", format),
+            InputError::OutOfRange { offset, size } => write!(f, "The bytes at offset {} are not all in the {} byte input", offset, size),
+            InputError::SlicedEntries(count) => {
+                write!(f, "--offset, --length and --cdr-header apply to a single structure, the input holds {} blocks or entries", count)
+            }
         }
     }
 }
@@ -36,8 +57,8 @@ impl From<DecodeError> for InputError {
     }
 }
 
-impl From<FromHexError> for InputError {
-    fn from(error: FromHexError) -> Self {
+impl From<HexError> for InputError {
+    fn from(error: HexError) -> Self {
         InputError::DecodeHexError(error)
     }
 }
\ No newline at end of file
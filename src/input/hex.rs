@@ -1,9 +1,303 @@
-use hex::FromHexError;
+use std::error::Error;
+use std::fmt;
 
-pub fn is_valid_hex(data: &[u8]) -> bool {
-    data.len() % 2 == 0 && data.iter().all(|&b| b.is_ascii_hexdigit())
+/// Most bytes the `*` lines of a dump may repeat, a larger offset after one is rejected instead
+/// of filling memory.
+const MAX_REPEAT: usize = 64 << 20;
+
+/// Why text could not be read as hex, lines and columns are counted from 1.
+#[derive(Debug)]
+pub enum HexError {
+    InvalidCharacter { character: char, line: usize, column: usize },
+    OddLength { line: usize, column: usize },
+    /// The offset after a `*` line is further than `MAX_REPEAT` bytes.
+    RepeatTooLong { line: usize },
+}
+
+impl fmt::Display for HexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HexError::InvalidCharacter { character, line, column } => {
+                write!(f, "invalid character {:?} at line {}, column {}", character, line, column)
+            }
+            HexError::OddLength { line, column } => write!(f, "odd number of hex digits at line {}, column {}", line, column),
+            HexError::RepeatTooLong { line } => write!(f, "the offset at line {} repeats more than {} bytes", line, MAX_REPEAT),
+        }
+    }
+}
+
+impl Error for HexError {}
+
+/// How hex input was laid out.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HexLayout {
+    Digits,
+    Dump,
+    CArray,
+}
+
+impl fmt::Display for HexLayout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HexLayout::Digits => write!(f, "hex digits"),
+            HexLayout::Dump => write!(f, "a hex dump with offsets"),
+            HexLayout::CArray => write!(f, "a C array"),
+        }
+    }
+}
+
+/// Returns true if the text starts like one of the hex formats read by `hex_decode`, so that an
+/// invalid character later in the text is reported instead of the text being read as BER.
+pub fn is_valid_hex(text: &str) -> bool {
+    if is_c_array(text) {
+        return true;
+    }
+    let Some(token) = text.split_whitespace().next() else {
+        return false;
+    };
+    let token = token.strip_prefix("0x").or_else(|| token.strip_prefix("0X")).unwrap_or(token);
+    let digits = token.split("\\x").collect::<String>();
+    digits.chars().any(|c| c.is_ascii_hexdigit()) && digits.chars().all(|c| c.is_ascii_hexdigit() || matches!(c, ':' | ',' | ';'))
+}
+
+/// Reads hex bytes written as plain digits, separated by spaces, new lines, `:` or `,`, with `0x`
+/// or `\x` prefixes, as a C array literal or as an `xxd`, `hexdump -C` or `od -x` dump whose offset
+/// column and ASCII gutter are skipped.
+pub fn hex_decode(text: &str) -> Result<(Vec<u8>, HexLayout), HexError> {
+    let c_array = is_c_array(text);
+    let text = match c_array {
+        true => array_content(text),
+        false => text.to_string(),
+    };
+    let lines = text.lines().map(|line| line.chars().collect::<Vec<char>>()).collect::<Vec<_>>();
+    let is_dump = lines
+        .iter()
+        .find(|line| line.iter().any(|c| !c.is_whitespace()))
+        .is_some_and(|line| is_dump_line(line));
+
+    let mut data = Vec::new();
+    let mut dump = Dump::default();
+    for (number, line) in lines.iter().enumerate() {
+        if is_dump {
+            dump.read_line(line, number + 1, &mut data)?;
+        } else {
+            read_bytes(line, 0, number + 1, false, &mut data)?;
+        }
+    }
+    if let Some(end) = dump.end.filter(|end| *end <= data.len()) {
+        // od pads the last word with zeros, the last offset is the length of the data
+        data.truncate(end);
+    }
+    let layout = match (c_array, is_dump) {
+        (true, _) => HexLayout::CArray,
+        (false, true) => HexLayout::Dump,
+        (false, false) => HexLayout::Digits,
+    };
+    Ok((data, layout))
+}
+
+/// `unsigned char data[] = { 0x30, 0x82, ... };` as written by `xxd -i`.
+fn is_c_array(text: &str) -> bool {
+    text.find('{').is_some_and(|open| text[open..].contains("0x"))
+}
+
+/// Blanks everything outside the braces of the array, keeping the lines and columns of the bytes.
+fn array_content(text: &str) -> String {
+    let open = text.find('{').unwrap_or(0);
+    let close = text[open..].find('}').map(|close| open + close).unwrap_or(text.len());
+    text.char_indices()
+        .map(|(i, c)| if (open < i && i < close) || c == '\n' { c } else { ' ' })
+        .collect()
+}
+
+/// Dumps start with an offset of zeros followed by the first bytes.
+fn is_dump_line(line: &[char]) -> bool {
+    let offset = line.iter().skip_while(|c| c.is_whitespace()).take_while(|c| !c.is_whitespace()).collect::<String>();
+    let digits = offset.strip_suffix(':').unwrap_or(&offset);
+    digits.len() >= 4 && digits.chars().all(|c| c == '0') && line.iter().filter(|c| !c.is_whitespace()).count() > offset.len()
+}
+
+/// State of a dump across its lines: the radix of the offsets, which `od` writes in octal, the bytes
+/// of the previous line which a `*` line repeats, and the length given by the last offset.
+#[derive(Default)]
+struct Dump {
+    radix: Option<u32>,
+    previous: Vec<u8>,
+    repeat: bool,
+    end: Option<usize>,
+}
+
+impl Dump {
+    fn read_line(&mut self, line: &[char], number: usize, data: &mut Vec<u8>) -> Result<(), HexError> {
+        let start = line.iter().take_while(|c| c.is_whitespace()).count();
+        let offset_end = start + line[start..].iter().take_while(|c| !c.is_whitespace()).count();
+        let offset = line[start..offset_end].iter().collect::<String>();
+        if offset.is_empty() {
+            return Ok(());
+        }
+        if offset == "*" {
+            self.repeat = true;
+            return Ok(());
+        }
+        let xxd = offset.ends_with(':');
+        let digits = offset.trim_end_matches(':');
+        if let Some(i) = digits.chars().position(|c| !c.is_ascii_hexdigit()) {
+            let character = digits.chars().nth(i).unwrap_or(' ');
+            return Err(HexError::InvalidCharacter { character, line: number, column: start + i + 1 });
+        }
+
+        // the ASCII gutter is between bars or angle brackets, xxd puts two spaces before it
+        let mut end = line.iter().position(|c| matches!(c, '|' | '>')).unwrap_or(line.len()).max(offset_end);
+        if xxd {
+            let hex_start = (offset_end + 1).min(end);
+            if let Some(gap) = line[hex_start..end].windows(2).position(|pair| pair == [' ', ' ']) {
+                end = hex_start + gap;
+            }
+        }
+        let mut bytes = Vec::new();
+        read_bytes(&line[..end], offset_end, number, !xxd, &mut bytes)?;
+
+        if self.radix.is_none() && !data.is_empty() && !self.repeat {
+            let octal = usize::from_str_radix(digits, 8).ok();
+            self.radix = Some(if octal == Some(data.len()) && usize::from_str_radix(digits, 16).ok() != Some(data.len()) { 8 } else { 16 });
+        }
+        let position = usize::from_str_radix(digits, self.radix.unwrap_or(16)).ok();
+        if std::mem::take(&mut self.repeat) {
+            if position.is_some_and(|position| position.saturating_sub(data.len()) > MAX_REPEAT) {
+                return Err(HexError::RepeatTooLong { line: number });
+            }
+            while !self.previous.is_empty() && position.is_some_and(|position| data.len() + self.previous.len() <= position) {
+                data.extend_from_slice(&self.previous);
+            }
+        }
+        if bytes.is_empty() {
+            self.end = position;
+        } else {
+            data.extend_from_slice(&bytes);
+            self.previous = bytes;
+        }
+        Ok(())
+    }
+}
+
+/// Reads the bytes of a line from the column `from`, words of dumps written in host order
+/// such as `od -x` output are little-endian.
+fn read_bytes(line: &[char], from: usize, number: usize, little_endian: bool, data: &mut Vec<u8>) -> Result<(), HexError> {
+    let mut i = from;
+    while i < line.len() {
+        let prefixed = matches!(line[i..], ['\\', 'x', ..]) || matches!(line[i..], ['0', 'x' | 'X', c, ..] if c.is_ascii_hexdigit());
+        if prefixed {
+            i += 2;
+        } else if is_separator(line[i]) {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < line.len() && !is_separator(line[i]) && (i == start || line[i] != '\\') {
+            i += 1;
+        }
+        let token = &line[start..i];
+        if let Some(k) = token.iter().position(|c| !c.is_ascii_hexdigit()) {
+            return Err(HexError::InvalidCharacter { character: token[k], line: number, column: start + k + 1 });
+        }
+        let mut digits = token.iter().collect::<String>();
+        if digits.is_empty() {
+            continue;
+        }
+        if digits.len() % 2 == 1 {
+            if !prefixed {
+                return Err(HexError::OddLength { line: number, column: start + 1 });
+            }
+            // a prefixed number such as `0x1` in a C array
+            digits.insert(0, '0');
+        }
+        let mut bytes = hex::decode(&digits).map_err(|_| HexError::OddLength { line: number, column: start + 1 })?;
+        if little_endian {
+            bytes.reverse();
+        }
+        data.extend_from_slice(&bytes);
+    }
+    Ok(())
 }
 
-pub fn hex_decode(input: Vec<u8>) -> Result<Vec<u8>, FromHexError> {
-    Ok(hex::decode(input)?)
+fn is_separator(c: char) -> bool {
+    c.is_whitespace() || matches!(c, ',' | ':' | ';')
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BYTES: [u8; 5] = [0x30, 0x03, 0x02, 0x01, 0x05];
+
+    #[test]
+    fn reads_digits() {
+        for text in ["3003020105", "30 03\n02 01 05\n", "30:03:02:01:05", "0x30 0x03 0x02 0x01 0x05", "0X30, 0X03, 0X02, 0X01, 0X05", "\\x30\\x03\\x02\\x01\\x05"] {
+            assert!(is_valid_hex(text), "{}", text);
+            let (data, layout) = hex_decode(text).unwrap();
+            assert_eq!(data, BYTES, "{}", text);
+            assert_eq!(layout, HexLayout::Digits);
+        }
+    }
+
+    #[test]
+    fn reads_c_arrays() {
+        let text = "unsigned char data[] = {\n  0x30, 0x03, 0x02, 0x1, 0x05\n};\nunsigned int data_len = 5;\n";
+        assert!(is_valid_hex(text));
+        assert_eq!(hex_decode(text).unwrap(), (BYTES.to_vec(), HexLayout::CArray));
+    }
+
+    #[test]
+    fn reads_dumps() {
+        let xxd = "00000000: 3003 0201 05                             0....\n";
+        let hexdump = "00000000  30 03 02 01 05                                    |0....|\n00000005\n";
+        let od = "000000 30 03 02 01 05  >0....<\n000005\n";
+        for text in [xxd, hexdump, od] {
+            assert_eq!(hex_decode(text).unwrap(), (BYTES.to_vec(), HexLayout::Dump), "{}", text);
+        }
+    }
+
+    #[test]
+    fn swaps_the_words_of_od_dumps() {
+        // od -x writes little-endian words and octal offsets, the last offset drops the padding byte
+        let text = "0000000 4430 0102 3001 3031 060d 6009 4886 6501\n0000020 0403 0005\n0000023\n";
+        let (data, layout) = hex_decode(text).unwrap();
+        assert_eq!(hex::encode(data), "30440201013031300d06096086480165030405");
+        assert_eq!(layout, HexLayout::Dump);
+    }
+
+    #[test]
+    fn repeats_lines_marked_with_a_star() {
+        let hexdump = concat!(
+            "00000000  04 40 00 00 00 00 00 00  00 00 00 00 00 00 00 00  |.@..............|\n",
+            "00000010  00 00 00 00 00 00 00 00  00 00 00 00 00 00 00 00  |................|\n",
+            "*\n",
+            "00000040  00 00 05 00                                       |....|\n",
+            "00000044\n",
+        );
+        let od = "0000000 4004 0000 0000 0000 0000 0000 0000 0000\n0000020 0000 0000 0000 0000 0000 0000 0000 0000\n*\n0000100 0000 0005\n0000104\n";
+        for text in [hexdump, od] {
+            let (data, _) = hex_decode(text).unwrap();
+            assert_eq!(data.len(), 0x44, "{}", text);
+            assert_eq!(data[..2], [0x04, 0x40]);
+            assert!(data[2..0x42].iter().all(|byte| *byte == 0));
+            assert_eq!(data[0x42..], [0x05, 0x00]);
+        }
+    }
+
+    #[test]
+    fn rejects_repeats_past_the_limit() {
+        let text = "00000000  00 00 00 00 00 00 00 00  00 00 00 00 00 00 00 00\n*\nffffffff  00\n";
+        assert!(matches!(hex_decode(text), Err(HexError::RepeatTooLong { line: 3 })));
+    }
+
+    #[test]
+    fn reports_where_input_is_invalid() {
+        let error = hex_decode("30 03\n02 0g 05").unwrap_err();
+        assert!(matches!(error, HexError::InvalidCharacter { character: 'g', line: 2, column: 5 }), "{:?}", error);
+        assert_eq!(error.to_string(), "invalid character 'g' at line 2, column 5");
+        let error = hex_decode("30 03\n\n02 1 05").unwrap_err();
+        assert!(matches!(error, HexError::OddLength { line: 3, column: 4 }), "{:?}", error);
+        let error = hex_decode("00000000  30 03\n00000002  0x 05\n").unwrap_err();
+        assert!(matches!(error, HexError::InvalidCharacter { character: 'x', line: 2, column: 12 }), "{:?}", error);
+    }
 }
\ No newline at end of file
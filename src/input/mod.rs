@@ -1,4 +1,7 @@
 pub mod base64;
+pub mod compression;
+pub mod document;
 pub mod hex;
 pub mod error;
+pub mod pem;
 pub mod utils;
\ No newline at end of file
@@ -1,41 +1,332 @@
+use std::fmt;
 use std::fs::File;
 use std::io;
-use std::io::{BufRead, BufReader, IsTerminal, Read};
+use std::io::{IsTerminal, Read};
 use std::path::Path;
-use clap::Parser;
+use clap::ValueEnum;
+use crate::asn1_der::is_complete_ber;
+use crate::asn1_module::Modules;
 use crate::cli::Cli;
-use crate::input::base64::{base64_decode, is_valid_base64};
+use crate::encoding::{Encoding, TypeDecoding};
+use crate::extract::{archive_files, extract, Entry};
+use crate::input::base64::base64_decode;
+use crate::input::compression::{decompress, Compression};
+use crate::input::document::document_values;
 use crate::input::error::InputError;
 use crate::input::hex::{hex_decode, is_valid_hex};
+use crate::input::pem::{parse_pem, PemBlock};
+use crate::schema::emv::TagDictionary;
+use crate::schema::oid::OidMap;
 
-enum InputSource<'a> {
-    File(&'a Path),
-    Stdin,
+/// Formats the input is read in, `auto` detects the format and shows why in the status line.
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum InputFormat {
+    /// Detect the format from the input
+    #[default]
+    Auto,
+    /// Binary BER or DER as it is read
+    #[value(alias = "der")]
+    Raw,
+    /// PEM blocks, each decoded separately
+    Pem,
+    /// Standard or URL-safe base64, with or without padding
+    Base64,
+    /// Hex digits, hex dumps or a C array
+    Hex,
+    /// JSON or YAML, the strings holding DER or PEM are listed by their path
+    #[value(alias = "yaml")]
+    Json,
 }
 
-fn read_content(source: InputSource) -> io::Result<Vec<u8>> {
-    let buffer = match source {
-        InputSource::File(path) => read_file(path)?,
-        InputSource::Stdin => read_stdin()?,
+impl InputFormat {
+    /// The format after this one, in the order the viewer switches between them.
+    pub(crate) fn next(self) -> Self {
+        match self {
+            InputFormat::Auto => InputFormat::Raw,
+            InputFormat::Raw => InputFormat::Pem,
+            InputFormat::Pem => InputFormat::Base64,
+            InputFormat::Base64 => InputFormat::Hex,
+            InputFormat::Hex => InputFormat::Json,
+            InputFormat::Json => InputFormat::Auto,
+        }
+    }
+}
+
+impl fmt::Display for InputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputFormat::Auto => write!(f, "auto"),
+            InputFormat::Raw => write!(f, "raw bytes"),
+            InputFormat::Pem => write!(f, "PEM"),
+            InputFormat::Base64 => write!(f, "base64"),
+            InputFormat::Hex => write!(f, "hex"),
+            InputFormat::Json => write!(f, "JSON or YAML"),
+        }
+    }
+}
+
+/// How a stream of records is split, each record is decoded on its own and shown as a root.
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum Framing {
+    /// Each record is prefixed with its 16-bit big-endian length
+    U16be,
+    /// Each record is prefixed with its 32-bit big-endian length, as Kerberos over TCP
+    U32be,
+    /// Each line is a record, such as a log of base64 or hex messages
+    Lines,
+}
+
+impl Framing {
+    /// The size of the length prefix, `None` for lines.
+    fn prefix_size(self) -> Option<usize> {
+        match self {
+            Framing::U16be => Some(2),
+            Framing::U32be => Some(4),
+            Framing::Lines => None,
+        }
+    }
+}
+
+impl fmt::Display for Framing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Framing::U16be => write!(f, "16-bit big-endian lengths"),
+            Framing::U32be => write!(f, "32-bit big-endian lengths"),
+            Framing::Lines => write!(f, "lines"),
+        }
+    }
+}
+
+/// The input as it was read, kept so that it can be read again in another format.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct RawInput {
+    bytes: Vec<u8>,
+    /// XER and JER documents are decoded as they are read.
+    text: bool,
+    /// Bytes skipped before the first record, such as a CDR file header.
+    header: usize,
+    /// Part of the decoded input which is shown, from `--offset` and `--length`.
+    offset: usize,
+    length: Option<usize>,
+    frame: Option<Framing>,
+    compression: Option<Compression>,
+}
+
+/// Decoded input together with the PEM blocks it was taken from, if any. The bodies of
+/// the blocks are decoded one by one and `data` holds them one after another.
+#[derive(Debug, Default)]
+pub(crate) struct InputData {
+    pub(crate) data: Vec<u8>,
+    pub(crate) pem: Vec<PemBlock>,
+    /// Structures taken out of a container file such as a signed executable, the files of an archive,
+    /// the strings of a JSON or YAML document or the records of a framed stream, each with the name
+    /// of where it was found.
+    pub(crate) entries: Vec<Entry>,
+    /// How the input was read and why it was detected as that format, shown in the status line.
+    pub(crate) report: Option<String>,
+    pub(crate) format: InputFormat,
+    pub(crate) source: RawInput,
+}
+
+impl RawInput {
+    /// Decodes the input in the format, a format which does not fit the input is an error.
+    pub(crate) fn decode(self, format: InputFormat) -> Result<InputData, InputError> {
+        if self.text {
+            return Ok(InputData { data: self.bytes.clone(), source: self, ..InputData::default() });
+        }
+        let mut input = match self.frame {
+            Some(Framing::Lines) => decode_lines(&self.bytes, format),
+            _ => decode_bytes(&self.bytes, format)?,
+        };
+        // PEM blocks and entries are shown one by one, a part of the bytes they were read from is not
+        let entries = input.pem.len().max(input.entries.len());
+        if entries > 0 && (self.header > 0 || self.offset > 0 || self.length.is_some()) {
+            return Err(InputError::SlicedEntries(entries));
+        }
+        let data = &mut input.data;
+        data.drain(..self.header.min(data.len()));
+        let end = self.length.map_or(Some(data.len()), |length| self.offset.checked_add(length));
+        match end.filter(|end| self.offset <= *end && *end <= data.len()) {
+            Some(end) => {
+                data.truncate(end);
+                data.drain(..self.offset);
+            }
+            None => return Err(InputError::OutOfRange { offset: self.offset, size: data.len() }),
+        }
+
+        // length-prefixed records are split once the stream is decoded, such as from a hex dump
+        if let Some((framing, size)) = self.frame.and_then(|framing| Some((framing, framing.prefix_size()?))) {
+            input.entries = split_records(&input.data, size);
+            input.report = input.report.map(|report| format!("{}, framed by {}", report, framing));
+        }
+        if let Some(compression) = self.compression {
+            input.report = input.report.map(|report| format!("Decompressed {}. {}", compression, report));
+        }
+
+        input.format = format;
+        input.source = self;
+        Ok(input)
+    }
+}
+
+/// Decodes the bytes in the format, a format which does not fit them is an error.
+fn decode_bytes(bytes: &[u8], format: InputFormat) -> Result<InputData, InputError> {
+    let input = match format {
+        InputFormat::Auto => detect(bytes)?,
+        InputFormat::Raw => InputData::read(bytes.to_vec(), "Read as raw bytes".to_string()),
+        InputFormat::Pem => {
+            let blocks = parse_pem(bytes).ok().filter(|blocks| !blocks.is_empty()).ok_or(InputError::WrongFormat(format))?;
+            decode_pem(blocks, "Read as PEM")
+        }
+        InputFormat::Base64 => {
+            let (data, variant) = base64_decode(&text_content(bytes).ok_or(InputError::WrongFormat(format))?)?;
+            InputData::read(data, format!("Read as {}", variant))
+        }
+        InputFormat::Hex => {
+            let text = text_content(bytes).ok_or(InputError::WrongFormat(format))?;
+            let (data, layout) = hex_decode(&String::from_utf8_lossy(&text))?;
+            InputData::read(data, format!("Read as hex, {}", layout))
+        }
+        InputFormat::Json => {
+            let text = std::str::from_utf8(bytes).map_err(|_| InputError::WrongFormat(format))?;
+            let (entries, language) = document_values(text).ok_or(InputError::WrongFormat(format))?;
+            let report = format!("Read as {}: {} DER or PEM value(s) in its strings", language, entries.len());
+            InputData { entries, ..InputData::read(bytes.to_vec(), report) }
+        }
     };
+    Ok(input)
+}
 
-    let content = process_text_content(&buffer).unwrap_or_else(|_| buffer);
+/// Decodes each line of a record stream on its own, in the format or in the format detected for
+/// the line. A line which is not in the format is kept as it is, to be shown with the error.
+fn decode_lines(bytes: &[u8], format: InputFormat) -> InputData {
+    let mut entries = Vec::new();
+    for (i, line) in bytes.split(|&byte| byte == b'\n').enumerate() {
+        let line = line.trim_ascii();
+        if line.is_empty() {
+            continue;
+        }
+        let data = decode_bytes(line, format).map_or_else(|_| line.to_vec(), |input| input.data);
+        entries.push(Entry { name: format!("line {}", i + 1), data });
+    }
+    let data = entries.iter().flat_map(|entry| entry.data.iter().copied()).collect();
+    let report = match format {
+        InputFormat::Auto => "Read lines, the format of each one is detected".to_string(),
+        format => format!("Read lines as {}", format),
+    };
+    InputData { entries, ..InputData::read(data, report) }
+}
+
+/// Splits a stream of records prefixed with their big-endian length. Bytes after the last whole
+/// record are kept as a record of their own, to be shown with the error.
+fn split_records(data: &[u8], size: usize) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    let mut offset = 0;
+    while offset < data.len() {
+        let record = data
+            .get(offset..offset + size)
+            .map(|prefix| prefix.iter().fold(0, |length, &byte| length << 8 | byte as usize))
+            .and_then(|length| data.get(offset + size..).and_then(|rest| rest.get(..length)));
+        let Some(record) = record else {
+            let name = format!("{} byte(s) left at {:#x}", data.len() - offset, offset);
+            entries.push(Entry { name, data: data[offset..].to_vec() });
+            break;
+        };
+        entries.push(Entry { name: format!("record {} at {:#x}", entries.len() + 1, offset), data: record.to_vec() });
+        offset += size + record.len();
+    }
+    entries
+}
 
-    Ok(content)
+impl InputData {
+    fn read(data: Vec<u8>, report: String) -> Self {
+        InputData { data, report: Some(report), ..InputData::default() }
+    }
+}
+
+/// Decodes the base64 body of each PEM block, `data` holds the bodies one after another.
+fn decode_pem(mut blocks: Vec<PemBlock>, report: &str) -> InputData {
+    let mut data = Vec::new();
+    for block in blocks.iter_mut() {
+        if let Ok((body, _)) = base64_decode(&block.body) {
+            block.body = body;
+        }
+        data.extend_from_slice(&block.body);
+    }
+    InputData { pem: blocks, ..InputData::read(data, report.to_string()) }
 }
 
-fn process_text_content(data: &[u8]) -> io::Result<Vec<u8>> {
-    let reader = BufReader::new(data);
-    let mut result = String::new();
+/// Detects the format of the input and returns the decoded data with the reason for the format.
+/// Signed executables and archives are recognized by their headers, input which is BER as it is
+/// stays raw, so that DER made of text characters is not decoded. JSON and YAML documents are read
+/// before hex, as keys such as `data:` look like hex. Hex comes before base64 as short hex strings
+/// are valid base64 too, unless only base64 decodes to BER.
+fn detect(bytes: &[u8]) -> Result<InputData, InputError> {
+    if let Some(extracted) = extract(bytes) {
+        let report = format!("Detected {}: {} structure(s) taken out of it", extracted.kind, extracted.entries.len());
+        return Ok(InputData { entries: extracted.entries, ..InputData::read(bytes.to_vec(), report) });
+    }
+    if let Some(archive) = archive_files(bytes) {
+        let report = format!("Detected {}: {} file(s)", archive.kind, archive.entries.len());
+        return Ok(InputData { entries: open_files(archive.entries), ..InputData::read(bytes.to_vec(), report) });
+    }
+    let blocks = parse_pem(bytes).unwrap_or_default();
+    if !blocks.is_empty() {
+        return Ok(decode_pem(blocks, "Detected PEM: BEGIN and END lines"));
+    }
+    if is_complete_ber(bytes) {
+        return Ok(InputData::read(bytes.to_vec(), "Detected raw bytes: the input is BER as it is".to_string()));
+    }
+    let Some(text) = text_content(bytes) else {
+        return Ok(InputData::read(bytes.to_vec(), "Detected raw bytes: the input is not text".to_string()));
+    };
+    if let Some((entries, language)) = std::str::from_utf8(bytes).ok().and_then(document_values) {
+        let report = format!("Detected {}: {} DER or PEM value(s) in its strings", language, entries.len());
+        return Ok(InputData { entries, ..InputData::read(bytes.to_vec(), report) });
+    }
+
+    let hex = std::str::from_utf8(&text).ok().filter(|text| is_valid_hex(text)).map(hex_decode);
+    let base64 = base64_decode(&text).ok().filter(|_| !text.iter().all(u8::is_ascii_whitespace));
+    match (hex, base64) {
+        (Some(Ok((hex, _))), Some((data, variant))) if !is_complete_ber(&hex) && is_complete_ber(&data) => {
+            Ok(InputData::read(data, format!("Detected {}: it is BER once decoded, as hex it is not", variant)))
+        }
+        (Some(Ok((data, layout))), base64) => {
+            let also = if base64.is_some() { ", which are valid base64 too" } else { "" };
+            Ok(InputData::read(data, format!("Detected hex: {}{}", layout, also)))
+        }
+        (_, Some((data, variant))) => Ok(InputData::read(data, format!("Detected {}: the text is in the base64 alphabet", variant))),
+        (Some(Err(error)), None) => Err(error.into()),
+        (None, None) => Ok(InputData::read(bytes.to_vec(), "Detected raw bytes: the text is not hex or base64".to_string())),
+    }
+}
 
-    for line in reader.lines() {
-        let line = line?;
-        if !line.starts_with("----") {
-            result.push_str(&line);
+/// Reads each file of an archive as the input is read: decompressed and in the format detected.
+/// A PEM bundle or a container in the archive gives an entry for each of its blocks or structures.
+fn open_files(files: Vec<Entry>) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    for file in files {
+        let Ok(input) = decompress(file.data.clone()).and_then(|(data, _)| detect(&data)) else {
+            entries.push(file);
+            continue;
+        };
+        if !input.entries.is_empty() {
+            entries.extend(input.entries.into_iter().map(|entry| Entry { name: format!("{} {}", file.name, entry.name), data: entry.data }));
+        } else if !input.pem.is_empty() {
+            entries.extend(input.pem.into_iter().map(|block| Entry { name: format!("{} {}", file.name, block.label), data: block.body }));
+        } else {
+            entries.push(Entry { name: file.name, data: input.data });
         }
     }
+    entries
+}
 
-    Ok(Vec::from(result))
+/// Returns the lines of text input without `----` lines such as the markers of SSH2 public keys,
+/// `None` when the input is not text.
+fn text_content(data: &[u8]) -> Option<Vec<u8>> {
+    let text = std::str::from_utf8(data).ok()?;
+    let lines = text.lines().filter(|line| !line.starts_with("----")).collect::<Vec<&str>>();
+    Some(lines.join("\n").into_bytes())
 }
 
 fn read_file<P: AsRef<Path>>(path: P) -> io::Result<Vec<u8>> {
@@ -52,38 +343,201 @@ fn read_stdin() -> io::Result<Vec<u8>> {
     Ok(buffer)
 }
 
+/// XER and JER documents are decoded as they are read.
+fn is_text_input(cli: &Cli) -> bool {
+    cli.encoding.is_some_and(Encoding::is_text)
+}
+
 fn is_input_from_stdin() -> bool {
     !io::stdin().is_terminal()
 }
 
-pub(crate) fn get_input() -> Result<Vec<u8>, InputError> {
-    let cli = Cli::parse();
-
-    if let Some(asn1_base64) = cli.asn1 {
-        Ok(Vec::from(asn1_base64))
-    } else if let Some(file) = cli.file {
-        Ok(read_content(InputSource::File(&file))?)
+/// Reads the input from the command line, a file or standard input. A file or standard input
+/// compressed with gzip, zstd, bzip2 or xz is decompressed.
+fn read_content(cli: &Cli) -> Result<(Vec<u8>, Option<Compression>), InputError> {
+    let bytes = if let Some(asn1_base64) = &cli.asn1 {
+        return Ok((Vec::from(asn1_base64.as_str()), None));
+    } else if let Some(file) = &cli.file {
+        read_file(file)?
     } else if is_input_from_stdin() {
-        Ok(read_content(InputSource::Stdin)?)
+        read_stdin()?
+    } else {
+        return Err(InputError::NoInputProvided);
+    };
+    decompress(bytes)
+}
+
+pub(crate) fn get_input(cli: &Cli) -> Result<RawInput, InputError> {
+    let (bytes, compression) = read_content(cli)?;
+    Ok(RawInput {
+        bytes,
+        text: is_text_input(cli),
+        header: cli.cdr_header.unwrap_or(0),
+        offset: cli.offset.unwrap_or(0),
+        length: cli.length,
+        frame: cli.frame,
+        compression,
+    })
+}
+
+pub(crate) fn get_input_data(cli: &Cli) -> Result<InputData, InputError> {
+    get_input(cli)?.decode(cli.input_format)
+}
+
+/// Reads the passphrase for encrypted keys from a file or an environment variable.
+pub(crate) fn get_passphrase(cli: &Cli) -> Result<Option<String>, InputError> {
+    if let Some(file) = &cli.passphrase_file {
+        let content = read_file(file)?;
+        let text = String::from_utf8_lossy(&content);
+        Ok(text.lines().next().map(str::to_string))
+    } else if let Some(name) = &cli.passphrase_env {
+        match std::env::var(name) {
+            Ok(passphrase) => Ok(Some(passphrase)),
+            Err(_) => Err(InputError::MissingEnvVar(name.clone())),
+        }
     } else {
-        Err(InputError::NoInputProvided)
+        Ok(None)
+    }
+}
+
+pub(crate) fn get_oid_map(cli: &Cli) -> Result<OidMap, InputError> {
+    match &cli.oid_map {
+        Some(file) => Ok(OidMap::parse(&String::from_utf8_lossy(&read_file(file)?))),
+        None => Ok(OidMap::default()),
     }
 }
 
-pub(crate) fn get_input_data() -> Result<Vec<u8>, InputError> {
-    let input = get_input()?;
+pub(crate) fn get_tag_dictionary(cli: &Cli) -> Result<TagDictionary, InputError> {
+    let mut dictionary = TagDictionary::default();
+    for file in &cli.tag_dictionary {
+        dictionary.load(&String::from_utf8_lossy(&read_file(file)?));
+    }
+    Ok(dictionary)
+}
 
-    Ok(get_raw_data(input)?)
+/// Reads eMRTD data group files, decoding them like the input when they are base64 or hex.
+pub(crate) fn get_data_groups(cli: &Cli) -> Result<Vec<Vec<u8>>, InputError> {
+    cli.data_group.iter().map(|file| Ok(detect(&read_file(file)?)?.data)).collect()
 }
 
-fn get_raw_data(input: Vec<u8>) -> Result<Vec<u8>, InputError> {
-    if is_valid_base64(input.clone()) {
-        return Ok(base64_decode(input)?);
+/// Loads the ASN.1 modules and checks that they define the type the input is decoded with.
+pub(crate) fn get_type_decoding(cli: &Cli) -> Result<Option<TypeDecoding>, InputError> {
+    let encoding = match (cli.encoding, &cli.type_name) {
+        (Some(encoding), _) => encoding,
+        (None, Some(_)) => Encoding::Aper,
+        (None, None) => return Ok(None),
+    };
+    if cli.type_name.is_none() && !encoding.is_text() {
+        return Err(InputError::MissingType);
     }
+    let mut modules = Modules::default();
+    for file in &cli.module {
+        let text = String::from_utf8_lossy(&read_file(file)?).to_string();
+        let name = file.display().to_string();
+        modules.load(&text).map_err(|error| InputError::InvalidModule(name, error))?;
+    }
+    modules.link().map_err(|error| InputError::InvalidModule("objects".to_string(), error))?;
+    if let Some(type_name) = cli.type_name.as_ref().filter(|type_name| !modules.has_type(type_name)) {
+        return Err(InputError::UnknownType(type_name.clone()));
+    }
+    Ok(Some(TypeDecoding { modules, type_name: cli.type_name.clone(), encoding }))
+}
 
-    if is_valid_hex(&input) {
-        return Ok(hex_decode(input)?);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extract::zip::stored_archive;
+
+    fn raw(bytes: &[u8], offset: usize, length: Option<usize>) -> RawInput {
+        RawInput { bytes: bytes.to_vec(), offset, length, ..RawInput::default() }
+    }
+
+    #[test]
+    fn slices_the_decoded_input() {
+        let input = raw(b"3003020105", 2, Some(3)).decode(InputFormat::Hex).unwrap();
+        assert_eq!(input.data, [0x02, 0x01, 0x05]);
+        assert!(matches!(raw(b"3003020105", 2, Some(4)).decode(InputFormat::Hex), Err(InputError::OutOfRange { .. })));
     }
 
-    Ok(Vec::from(input))
+    #[test]
+    fn does_not_slice_entries() {
+        let pem = b"-----BEGIN A-----\nMAMCAQU=\n-----END A-----\n-----BEGIN B-----\nMAMCAQU=\n-----END B-----\n";
+        assert!(matches!(raw(pem, 2, None).decode(InputFormat::Auto), Err(InputError::SlicedEntries(2))));
+        assert_eq!(raw(pem, 0, None).decode(InputFormat::Auto).unwrap().pem.len(), 2);
+    }
+
+    #[test]
+    fn detects_the_format() {
+        let jar = stored_archive(&[("META-INF/MANIFEST.MF", b"Manifest-Version: 1.0\n"), ("META-INF/CERT.RSA", &[0x30, 0x00])]);
+        let zip = stored_archive(&[("a.der", &[0x30, 0x00]), ("b.txt", b"hello")]);
+        let cases: [(&[u8], &str, &[u8]); 13] = [
+            (&jar, "Detected a signed JAR or APK: 1 structure(s) taken out of it", &jar),
+            (&zip, "Detected a ZIP archive: 2 file(s)", &zip),
+            (b"-----BEGIN A-----\nMAMCAQU=\n-----END A-----\n", "Detected PEM: BEGIN and END lines", &[0x30, 0x03, 0x02, 0x01, 0x05]),
+            (&[0x30, 0x03, 0x02, 0x01, 0x05], "Detected raw bytes: the input is BER as it is", &[0x30, 0x03, 0x02, 0x01, 0x05]),
+            // text characters which are BER stay raw instead of being read as base64
+            (b"0\x020\x00", "Detected raw bytes: the input is BER as it is", b"0\x020\x00"),
+            (&[0xff, 0xfe, 0x80], "Detected raw bytes: the input is not text", &[0xff, 0xfe, 0x80]),
+            (br#"{"cert": "MAMCAQU="}"#, "Detected JSON: 1 DER or PEM value(s) in its strings", br#"{"cert": "MAMCAQU="}"#),
+            (b"cert: MAMCAQU=\n", "Detected YAML: 1 DER or PEM value(s) in its strings", b"cert: MAMCAQU=\n"),
+            (b"3003020105\n", "Detected hex: hex digits", &[0x30, 0x03, 0x02, 0x01, 0x05]),
+            // hex and base64 which are both not BER, hex is taken
+            (b"3030", "Detected hex: hex digits, which are valid base64 too", &[0x30, 0x30]),
+            // only the base64 of the text is BER
+            (b"BAEA", "Detected base64: it is BER once decoded, as hex it is not", &[0x04, 0x01, 0x00]),
+            (b"MAMCAQU=", "Detected base64: the text is in the base64 alphabet", &[0x30, 0x03, 0x02, 0x01, 0x05]),
+            (b"hello world!", "Detected raw bytes: the text is not hex or base64", b"hello world!"),
+        ];
+        for (bytes, report, data) in cases {
+            let input = detect(bytes).unwrap();
+            assert_eq!(input.report.as_deref(), Some(report), "{:?}", String::from_utf8_lossy(bytes));
+            assert_eq!(input.data, data, "{}", report);
+        }
+        assert!(matches!(detect(b"30 03 0g!"), Err(InputError::DecodeHexError(_))));
+    }
+
+    fn framed(bytes: &[u8], frame: Framing, format: InputFormat) -> InputData {
+        RawInput { bytes: bytes.to_vec(), frame: Some(frame), ..RawInput::default() }.decode(format).unwrap()
+    }
+
+    fn entries(input: &InputData) -> Vec<(&str, String)> {
+        input.entries.iter().map(|entry| (entry.name.as_str(), hex::encode(&entry.data))).collect()
+    }
+
+    #[test]
+    fn splits_length_prefixed_records() {
+        let input = framed(&hex::decode("0005300302010500023000000101").unwrap(), Framing::U16be, InputFormat::Raw);
+        assert_eq!(input.report.as_deref(), Some("Read as raw bytes, framed by 16-bit big-endian lengths"));
+        assert_eq!(entries(&input), [("record 1 at 0x0", "3003020105".to_string()), ("record 2 at 0x7", "3000".to_string()), ("record 3 at 0xb", "01".to_string())]);
+
+        // records are split once the input is decoded, a truncated last record is kept as it is
+        let input = framed(b"00000005 3003020105\n00000010 3000", Framing::U32be, InputFormat::Hex);
+        assert_eq!(input.report.as_deref(), Some("Read as hex, hex digits, framed by 32-bit big-endian lengths"));
+        assert_eq!(entries(&input), [("record 1 at 0x0", "3003020105".to_string()), ("6 byte(s) left at 0x9", "000000103000".to_string())]);
+
+        let input = framed(&hex::decode("00053003020105000530").unwrap(), Framing::U16be, InputFormat::Raw);
+        assert_eq!(entries(&input)[1], ("3 byte(s) left at 0x7", "000530".to_string()));
+        let input = framed(&[0x00], Framing::U32be, InputFormat::Raw);
+        assert_eq!(entries(&input), [("1 byte(s) left at 0x0", "00".to_string())]);
+    }
+
+    #[test]
+    fn decodes_each_line() {
+        let input = framed(b"MAMCAQU=\r\n3003020105\n\nnot hex!\nMAMCAQ", Framing::Lines, InputFormat::Auto);
+        assert_eq!(input.report.as_deref(), Some("Read lines, the format of each one is detected"));
+        assert_eq!(
+            entries(&input),
+            [
+                ("line 1", "3003020105".to_string()),
+                ("line 2", "3003020105".to_string()),
+                ("line 4", hex::encode("not hex!")),
+                ("line 5", "30030201".to_string()),
+            ]
+        );
+
+        // a line which is not in the format is kept as it is
+        let input = framed(b"3003020105\nMAMCAQU=\n", Framing::Lines, InputFormat::Hex);
+        assert_eq!(input.report.as_deref(), Some("Read lines as hex"));
+        assert_eq!(entries(&input), [("line 1", "3003020105".to_string()), ("line 2", hex::encode("MAMCAQU="))]);
+    }
 }
\ No newline at end of file
@@ -1,9 +1,12 @@
 extern crate core;
 
+use clap::Parser;
 use log::error;
 
 use crate::app::App;
-use crate::input::utils::get_input_data;
+use crate::cli::Cli;
+use crate::input::utils::{get_data_groups, get_input_data, get_oid_map, get_passphrase, get_tag_dictionary, get_type_decoding};
+use crate::schema::SchemaOptions;
 use crate::terminal::init_terminal_app;
 
 mod input;
@@ -11,11 +14,17 @@ mod asn1_der;
 mod app;
 mod terminal;
 mod cli;
+mod decrypt;
+mod schema;
+mod asn1_module;
+mod encoding;
+mod extract;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let _log2 = log2::open("log.txt").start();
+    let cli = Cli::parse();
 
-    let input= match get_input_data() {
+    let input = match get_input_data(&cli) {
         Ok(input) => {
             input
         }
@@ -25,7 +34,50 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     };
 
-    let app = match App::new(input) {
+    let oid_map = match get_oid_map(&cli) {
+        Ok(oid_map) => oid_map,
+        Err(err) => {
+            error!("oid map error: {:?}", err);
+            return Err(Box::new(err))
+        }
+    };
+
+    let tag_dictionary = match get_tag_dictionary(&cli) {
+        Ok(tag_dictionary) => tag_dictionary,
+        Err(err) => {
+            error!("tag dictionary error: {:?}", err);
+            return Err(Box::new(err))
+        }
+    };
+
+    let data_groups = match get_data_groups(&cli) {
+        Ok(data_groups) => data_groups,
+        Err(err) => {
+            error!("data group error: {:?}", err);
+            return Err(Box::new(err))
+        }
+    };
+
+    let decoding = match get_type_decoding(&cli) {
+        Ok(decoding) => decoding,
+        Err(err) => {
+            error!("module error: {:?}", err);
+            return Err(Box::new(err))
+        }
+    };
+
+    let options = SchemaOptions {
+        show_secrets: cli.show_secrets,
+        oid_map,
+        cdr: cli.cdr,
+        tlv: cli.tlv,
+        tag_dictionary,
+        data_groups,
+        decoding,
+        scan: cli.scan.then_some(cli.min_size),
+    };
+
+    let mut app = match App::new(input, &options) {
         Ok(app) => app,
         Err(err) => {
             error!("app error: {:?}", err);
@@ -33,11 +85,20 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     };
 
+    match get_passphrase(&cli) {
+        Ok(Some(passphrase)) => app.decrypt(&passphrase),
+        Ok(None) => {}
+        Err(err) => {
+            error!("passphrase error: {:?}", err);
+            return Err(Box::new(err))
+        }
+    }
+
     match init_terminal_app(app) {
         Ok(_) => Ok(()),
         Err(err) => {
             error!("terminal error: {:?}", err);
-            return Err(err)
+            Err(err)
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,341 @@
+use std::time::Duration;
+use der::asn1::ObjectIdentifier;
+use der::{DateTime, Tag};
+use crate::asn1_der::{ASN1Node, ASN1Value};
+use crate::schema::{label_children, show_as_bytes, show_as_value};
+
+/// Extension of an attestation certificate holding the `KeyDescription`.
+pub(crate) const ID_KEY_DESCRIPTION: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.6.1.4.1.11129.2.1.17");
+
+const SECURITY_LEVELS: &[(u128, &str)] = &[(0, "Software"), (1, "TrustedEnvironment"), (2, "StrongBox")];
+const VERIFIED_BOOT_STATES: &[(u128, &str)] = &[(0, "Verified"), (1, "SelfSigned"), (2, "Unverified"), (3, "Failed")];
+
+/// `AuthorizationList` fields by their `[n] EXPLICIT` tag number.
+const AUTHORIZATION_TAGS: &[(u32, &str)] = &[
+    (1, "purpose"),
+    (2, "algorithm"),
+    (3, "keySize"),
+    (4, "blockMode"),
+    (5, "digest"),
+    (6, "padding"),
+    (7, "callerNonce"),
+    (8, "minMacLength"),
+    (10, "ecCurve"),
+    (200, "rsaPublicExponent"),
+    (203, "mgfDigest"),
+    (303, "rollbackResistance"),
+    (305, "earlyBootOnly"),
+    (400, "activeDateTime"),
+    (401, "originationExpireDateTime"),
+    (402, "usageExpireDateTime"),
+    (405, "usageCountLimit"),
+    (503, "noAuthRequired"),
+    (504, "userAuthType"),
+    (505, "authTimeout"),
+    (506, "allowWhileOnBody"),
+    (507, "trustedUserPresenceRequired"),
+    (508, "trustedConfirmationRequired"),
+    (509, "unlockedDeviceRequired"),
+    (600, "allApplications"),
+    (601, "applicationId"),
+    (701, "creationDateTime"),
+    (702, "origin"),
+    (703, "rollbackResistant"),
+    (704, "rootOfTrust"),
+    (705, "osVersion"),
+    (706, "osPatchLevel"),
+    (709, "attestationApplicationId"),
+    (710, "attestationIdBrand"),
+    (711, "attestationIdDevice"),
+    (712, "attestationIdProduct"),
+    (713, "attestationIdSerial"),
+    (714, "attestationIdImei"),
+    (715, "attestationIdMeid"),
+    (716, "attestationIdManufacturer"),
+    (717, "attestationIdModel"),
+    (718, "vendorPatchLevel"),
+    (719, "bootPatchLevel"),
+    (720, "deviceUniqueAttestation"),
+    (723, "attestationIdSecondImei"),
+];
+
+const PURPOSES: &[(u128, &str)] = &[
+    (0, "ENCRYPT"),
+    (1, "DECRYPT"),
+    (2, "SIGN"),
+    (3, "VERIFY"),
+    (5, "WRAP_KEY"),
+    (6, "AGREE_KEY"),
+    (7, "ATTEST_KEY"),
+];
+const ALGORITHMS: &[(u128, &str)] = &[(1, "RSA"), (3, "EC"), (32, "AES"), (33, "TRIPLE_DES"), (128, "HMAC")];
+const BLOCK_MODES: &[(u128, &str)] = &[(1, "ECB"), (2, "CBC"), (3, "CTR"), (32, "GCM")];
+const DIGESTS: &[(u128, &str)] = &[
+    (0, "NONE"),
+    (1, "MD5"),
+    (2, "SHA1"),
+    (3, "SHA_2_224"),
+    (4, "SHA_2_256"),
+    (5, "SHA_2_384"),
+    (6, "SHA_2_512"),
+];
+const PADDINGS: &[(u128, &str)] = &[
+    (1, "NONE"),
+    (2, "RSA_OAEP"),
+    (3, "RSA_PSS"),
+    (4, "RSA_PKCS1_1_5_ENCRYPT"),
+    (5, "RSA_PKCS1_1_5_SIGN"),
+    (64, "PKCS7"),
+];
+const EC_CURVES: &[(u128, &str)] = &[(0, "P_224"), (1, "P_256"), (2, "P_384"), (3, "P_521"), (4, "CURVE_25519")];
+const ORIGINS: &[(u128, &str)] = &[
+    (0, "GENERATED"),
+    (1, "DERIVED"),
+    (2, "IMPORTED"),
+    (3, "RESERVED"),
+    (4, "SECURELY_IMPORTED"),
+];
+const USER_AUTH_TYPES: &[(u128, &str)] = &[(1, "PASSWORD"), (2, "FINGERPRINT")];
+
+/// Recognizes a bare Android key attestation `KeyDescription` at the node.
+pub(crate) fn recognize(node: &mut ASN1Node) -> bool {
+    if !is_key_description(node) {
+        return false;
+    }
+    label_key_description(node);
+    true
+}
+
+/// `KeyDescription ::= SEQUENCE { attestationVersion, attestationSecurityLevel, keyMintVersion,
+/// keyMintSecurityLevel, attestationChallenge, uniqueId, softwareEnforced, hardwareEnforced }`
+pub(crate) fn is_key_description(node: &ASN1Node) -> bool {
+    let tags = node.children().iter().map(|child| child.tag).collect::<Vec<Tag>>();
+    node.tag == Tag::Sequence
+        && tags == [
+            Tag::Integer,
+            Tag::Enumerated,
+            Tag::Integer,
+            Tag::Enumerated,
+            Tag::OctetString,
+            Tag::OctetString,
+            Tag::Sequence,
+            Tag::Sequence,
+        ]
+}
+
+pub(crate) fn label_key_description(node: &mut ASN1Node) {
+    node.set_label("KeyDescription");
+    label_children(node, &[
+        "attestationVersion",
+        "attestationSecurityLevel",
+        "keyMintVersion",
+        "keyMintSecurityLevel",
+        "attestationChallenge",
+        "uniqueId",
+        "softwareEnforced",
+        "hardwareEnforced",
+    ]);
+    let children = node.children_mut();
+    for level in [1, 3] {
+        annotate_name(&mut children[level], SECURITY_LEVELS);
+    }
+    show_as_value(&mut children[4]);
+    show_as_value(&mut children[5]);
+    for list in &mut children[6..] {
+        label_authorization_list(list);
+    }
+
+    let version = node.children()[0].as_u128().unwrap_or_default();
+    let level = node.children()[1].annotation.clone().unwrap_or_default();
+    node.set_annotation(format!("version {}, {}", version, level));
+}
+
+/// Labels the `[n] EXPLICIT` fields of an `AuthorizationList` by their tag numbers.
+fn label_authorization_list(node: &mut ASN1Node) {
+    for field in node.children_mut() {
+        if !field.tag.is_context_specific() {
+            continue;
+        }
+        let number = field.tag_number();
+        let Some(&(_, name)) = AUTHORIZATION_TAGS.iter().find(|(known, _)| *known == number) else {
+            continue;
+        };
+        field.set_label(name);
+        if let Some(annotation) = label_value(field, name) {
+            field.set_annotation(annotation);
+        }
+    }
+    node.set_annotation(format!("{} tag(s)", node.children().len()));
+}
+
+/// Labels the value inside an authorization field and returns the annotation of the field.
+fn label_value(field: &mut ASN1Node, name: &str) -> Option<String> {
+    let value = field.children_mut().first_mut()?;
+    match name {
+        "purpose" => annotate_set(value, PURPOSES),
+        "blockMode" => annotate_set(value, BLOCK_MODES),
+        "digest" | "mgfDigest" => annotate_set(value, DIGESTS),
+        "padding" => annotate_set(value, PADDINGS),
+        "algorithm" => annotate_name(value, ALGORITHMS),
+        "ecCurve" => annotate_name(value, EC_CURVES),
+        "origin" => annotate_name(value, ORIGINS),
+        "userAuthType" => {
+            let types = value.as_u128()?;
+            let names = USER_AUTH_TYPES
+                .iter()
+                .filter(|(bit, _)| types & bit != 0)
+                .map(|(_, name)| *name)
+                .collect::<Vec<&str>>();
+            Some(if names.is_empty() { "NONE".to_string() } else { names.join(" | ") })
+        }
+        "activeDateTime" | "originationExpireDateTime" | "usageExpireDateTime" | "creationDateTime" => {
+            let millis = u64::try_from(value.as_u128()?).ok()?;
+            DateTime::from_unix_duration(Duration::from_millis(millis)).ok().map(|time| time.to_string())
+        }
+        "osVersion" => {
+            let version = value.as_u128()?;
+            Some(format!("{}.{}.{}", version / 10000, version / 100 % 100, version % 100))
+        }
+        "osPatchLevel" | "vendorPatchLevel" | "bootPatchLevel" => patch_level(value.as_u128()?),
+        "rootOfTrust" => label_root_of_trust(value),
+        "attestationApplicationId" => label_application_id(value.children_mut().first_mut()?),
+        "applicationId" => {
+            show_as_value(value);
+            None
+        }
+        name if name.starts_with("attestationId") => {
+            show_as_value(value);
+            value.as_string()
+        }
+        _ => None,
+    }
+}
+
+/// Formats a patch level given as `YYYYMM` or `YYYYMMDD`.
+fn patch_level(level: u128) -> Option<String> {
+    match level {
+        100000..=999999 => Some(format!("{}-{:02}", level / 100, level % 100)),
+        10000000..=99999999 => Some(format!("{}-{:02}-{:02}", level / 10000, level / 100 % 100, level % 100)),
+        _ => None,
+    }
+}
+
+fn named(value: u128, names: &[(u128, &str)]) -> String {
+    names
+        .iter()
+        .find(|(known, _)| *known == value)
+        .map(|(_, name)| name.to_string())
+        .unwrap_or_else(|| value.to_string())
+}
+
+/// Annotates an INTEGER or ENUMERATED with its name and returns the name.
+fn annotate_name(node: &mut ASN1Node, names: &[(u128, &str)]) -> Option<String> {
+    let name = named(node.as_u128()?, names);
+    node.set_annotation(name.clone());
+    Some(name)
+}
+
+/// Annotates each INTEGER of a `SET OF INTEGER` with its name and returns the names joined.
+fn annotate_set(node: &mut ASN1Node, names: &[(u128, &str)]) -> Option<String> {
+    let names = node
+        .children_mut()
+        .iter_mut()
+        .filter_map(|item| annotate_name(item, names))
+        .collect::<Vec<String>>();
+    Some(names.join(", "))
+}
+
+/// `RootOfTrust ::= SEQUENCE { verifiedBootKey, deviceLocked BOOLEAN, verifiedBootState, verifiedBootHash }`
+fn label_root_of_trust(node: &mut ASN1Node) -> Option<String> {
+    node.set_label("RootOfTrust");
+    label_children(node, &["verifiedBootKey", "deviceLocked", "verifiedBootState", "verifiedBootHash"]);
+    let children = node.children_mut();
+    for child in children.iter_mut().filter(|child| child.tag == Tag::OctetString) {
+        show_as_bytes(child);
+    }
+    let state = annotate_name(children.get_mut(2)?, VERIFIED_BOOT_STATES)?;
+    let locked = matches!(children[1].value, ASN1Value::Boolean(true));
+    Some(format!("{}, {}", state, if locked { "locked" } else { "unlocked" }))
+}
+
+/// `AttestationApplicationId ::= SEQUENCE { packageInfos SET OF AttestationPackageInfo, signatureDigests SET OF OCTET STRING }`
+fn label_application_id(node: &mut ASN1Node) -> Option<String> {
+    if node.tag != Tag::Sequence {
+        return None;
+    }
+    node.set_label("AttestationApplicationId");
+    label_children(node, &["packageInfos", "signatureDigests"]);
+    let children = node.children_mut();
+    let mut packages = vec![];
+    for package in children.first_mut()?.children_mut() {
+        package.set_label("AttestationPackageInfo");
+        label_children(package, &["packageName", "version"]);
+        let fields = package.children_mut();
+        if let Some(name) = fields.first_mut() {
+            show_as_value(name);
+        }
+        let name = fields.first().and_then(ASN1Node::as_string).unwrap_or_default();
+        let version = fields.get(1).and_then(ASN1Node::as_u128).unwrap_or_default();
+        package.set_annotation(format!("{} {}", name, version));
+        packages.push(name);
+    }
+    if let Some(digests) = children.get_mut(1) {
+        for digest in digests.children_mut() {
+            digest.set_label("signatureDigest");
+            show_as_bytes(digest);
+        }
+    }
+    let packages = packages.join(", ");
+    node.set_annotation(packages.clone());
+    Some(packages)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::asn1_der::{parse_asn1, view_lines};
+    use crate::schema::{annotate, SchemaOptions};
+
+    fn annotated(data: &[u8]) -> Vec<String> {
+        let mut root = parse_asn1(data, 0).unwrap();
+        annotate(&mut root, &SchemaOptions::default());
+        view_lines(&root)
+    }
+
+    #[test]
+    fn labels_key_descriptions() {
+        let lines = annotated(include_bytes!("../../tests/data/key-description.der"));
+        assert_eq!(lines[0], "KeyDescription: SEQUENCE (field(s): 8) (version 200, TrustedEnvironment)");
+        assert_eq!(lines[2], "attestationSecurityLevel: ENUMERATED 1 (TrustedEnvironment)");
+        assert_eq!(lines[5], "attestationChallenge: OCTET STRING \"challenge\"");
+        assert_eq!(lines[7], "softwareEnforced: SEQUENCE (field(s): 2) (2 tag(s))");
+        assert_eq!(lines[8], "creationDateTime: CONTEXT-SPECIFIC [701] (constructed)  (2023-11-14T22:13:20Z)");
+        assert_eq!(lines[12], "AttestationApplicationId: SEQUENCE (field(s): 2) (com.example.app)");
+        assert_eq!(lines[14], "AttestationPackageInfo: SEQUENCE (field(s): 2) (com.example.app 42)");
+    }
+
+    #[test]
+    fn labels_authorization_lists() {
+        let lines = annotated(include_bytes!("../../tests/data/key-description.der"));
+        assert_eq!(lines[19], "hardwareEnforced: SEQUENCE (field(s): 12) (12 tag(s))");
+        assert_eq!(lines[20], "purpose: CONTEXT-SPECIFIC [1] (constructed)  (SIGN, VERIFY)");
+        assert_eq!(lines[24], "algorithm: CONTEXT-SPECIFIC [2] (constructed)  (EC)");
+        assert_eq!(lines[28], "digest: CONTEXT-SPECIFIC [5] (constructed)  (SHA_2_256)");
+        assert_eq!(lines[31], "ecCurve: CONTEXT-SPECIFIC [10] (constructed)  (P_256)");
+        assert_eq!(lines[33], "noAuthRequired: CONTEXT-SPECIFIC [503] (constructed) ");
+        assert_eq!(lines[35], "origin: CONTEXT-SPECIFIC [702] (constructed)  (GENERATED)");
+        assert_eq!(lines[37], "rootOfTrust: CONTEXT-SPECIFIC [704] (constructed)  (Verified, locked)");
+        assert_eq!(lines[41], "verifiedBootState: ENUMERATED 0 (Verified)");
+        assert_eq!(lines[43], "osVersion: CONTEXT-SPECIFIC [705] (constructed)  (13.0.0)");
+        assert_eq!(lines[45], "osPatchLevel: CONTEXT-SPECIFIC [706] (constructed)  (2023-05)");
+        assert_eq!(lines[47], "vendorPatchLevel: CONTEXT-SPECIFIC [718] (constructed)  (2023-05-01)");
+    }
+
+    #[test]
+    fn labels_attestation_extensions() {
+        let lines = annotated(include_bytes!("../../tests/data/attestation.der"));
+        assert_eq!(lines[32], "keyDescription: SEQUENCE (field(s): 2)");
+        assert_eq!(lines[33], "extnID: OBJECT IDENTIFIER 1.3.6.1.4.1.11129.2.1.17 (keyDescription)");
+        assert_eq!(lines[35], "KeyDescription: SEQUENCE (field(s): 8) (version 200, TrustedEnvironment)");
+        assert_eq!(lines[54], "hardwareEnforced: SEQUENCE (field(s): 12) (12 tag(s))");
+    }
+}
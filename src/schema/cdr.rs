@@ -0,0 +1,366 @@
+use crate::asn1_der::{ASN1Node, ASN1Value};
+use crate::schema::implicit_octets;
+
+/// `MOCallRecord ::= SET { recordType [0], servedIMSI [1], ... }` field names indexed by tag number.
+const MO_CALL_FIELDS: &[&str] = &[
+    "recordType", "servedIMSI", "servedIMEI", "servedMSISDN", "callingNumber", "calledNumber",
+    "translatedNumber", "connectedNumber", "roamingNumber", "recordingEntity", "mscIncomingTKGP",
+    "mscOutgoingTKGP", "location", "changeOfLocation", "basicService", "transparencyIndicator",
+    "changeOfService", "supplServicesUsed", "aocParameters", "changeOfAOCParms", "msClassmark",
+    "changeOfClassmark", "seizureTime", "answerTime", "releaseTime", "callDuration", "dataVolume",
+    "radioChanRequested", "radioChanUsed", "changeOfRadioChan", "causeForTerm", "diagnostics",
+    "callReference", "sequenceNumber",
+];
+
+const MT_CALL_FIELDS: &[&str] = &[
+    "recordType", "servedIMSI", "servedIMEI", "servedMSISDN", "callingNumber", "connectedNumber",
+    "msrn", "recordingEntity", "mscIncomingTKGP", "mscOutgoingTKGP", "location", "changeOfLocation",
+    "basicService", "transparencyIndicator", "changeOfService", "supplServicesUsed", "aocParameters",
+    "changeOfAOCParms", "msClassmark", "changeOfClassmark", "seizureTime", "answerTime", "releaseTime",
+    "callDuration", "dataVolume", "radioChanRequested", "radioChanUsed", "changeOfRadioChan",
+    "causeForTerm", "diagnostics", "callReference", "sequenceNumber",
+];
+
+const SGSN_PDP_FIELDS: &[&str] = &[
+    "recordType", "networkInitiation", "", "servedIMSI", "servedIMEI", "sgsnAddress",
+    "msNetworkCapability", "routingArea", "locationAreaCode", "cellIdentifier", "chargingID",
+    "ggsnAddressUsed", "accessPointNameNI", "pdpType", "servedPDPAddress", "listOfTrafficVolumes",
+    "recordOpeningTime", "duration", "sgsnChange", "causeForRecClosing", "diagnostics",
+    "recordSequenceNumber", "nodeID", "recordExtensions", "localSequenceNumber", "apnSelectionMode",
+    "accessPointNameOI", "servedMSISDN", "chargingCharacteristics", "rATType",
+];
+
+const GGSN_PDP_FIELDS: &[&str] = &[
+    "recordType", "networkInitiation", "", "servedIMSI", "ggsnAddress", "chargingID", "sgsnAddress",
+    "accessPointNameNI", "pdpType", "servedPDPAddress", "", "dynamicAddressFlag", "listOfTrafficVolumes",
+    "recordOpeningTime", "duration", "causeForRecClosing", "diagnostics", "recordSequenceNumber",
+    "nodeID", "recordExtensions", "localSequenceNumber", "apnSelectionMode", "servedMSISDN",
+    "chargingCharacteristics", "chChSelectionMode",
+];
+
+const SGW_FIELDS: &[&str] = &[
+    "recordType", "", "", "servedIMSI", "s-GWAddress", "chargingID", "servingNodeAddress",
+    "accessPointNameNI", "pdpPDNType", "servedPDPPDNAddress", "", "dynamicAddressFlag",
+    "listOfTrafficVolumes", "recordOpeningTime", "duration", "causeForRecClosing", "diagnostics",
+    "recordSequenceNumber", "nodeID", "recordExtensions", "localSequenceNumber", "apnSelectionMode",
+    "servedMSISDN", "chargingCharacteristics", "chChSelectionMode", "iMSsignalingContext", "",
+    "servingNodePLMNIdentifier", "", "servedIMEISV", "rATType", "mSTimeZone", "userLocationInformation",
+    "", "sGWChange", "servingNodeType", "p-GWAddressUsed", "p-GWPLMNIdentifier", "startTime", "stopTime",
+    "pDNConnectionChargingID",
+];
+
+const PGW_FIELDS: &[&str] = &[
+    "recordType", "", "", "servedIMSI", "p-GWAddress", "chargingID", "servingNodeAddress",
+    "accessPointNameNI", "pdpPDNType", "servedPDPPDNAddress", "", "dynamicAddressFlag", "",
+    "recordOpeningTime", "duration", "causeForRecClosing", "diagnostics", "recordSequenceNumber",
+    "nodeID", "recordExtensions", "localSequenceNumber", "apnSelectionMode", "servedMSISDN",
+    "chargingCharacteristics", "chChSelectionMode", "iMSsignalingContext", "externalChargingID",
+    "servingNodePLMNIdentifier", "pSFurnishChargingInformation", "servedIMEISV", "rATType",
+    "mSTimeZone", "userLocationInformation", "cAMELChargingInformation", "listOfServiceData",
+    "servingNodeType", "servedMNNAI", "p-GWPLMNIdentifier", "startTime", "stopTime",
+    "served3gpp2MEID", "pDNConnectionChargingID",
+];
+
+/// Alternatives of the TS 32.298 `CallEventRecord` (CS) and `GPRSRecord` (PS) CHOICE by tag number.
+const RECORD_TYPES: &[(u32, &str, &[&str])] = &[
+    (0, "moCallRecord", MO_CALL_FIELDS),
+    (1, "mtCallRecord", MT_CALL_FIELDS),
+    (2, "roamingRecord", &[]),
+    (3, "incGatewayRecord", &[]),
+    (4, "outGatewayRecord", &[]),
+    (5, "transitRecord", &[]),
+    (6, "moSMSRecord", &[]),
+    (7, "mtSMSRecord", &[]),
+    (8, "moSMSIWRecord", &[]),
+    (9, "mtSMSGWRecord", &[]),
+    (10, "ssActionRecord", &[]),
+    (11, "hlrIntRecord", &[]),
+    (12, "locUpdateHLRRecord", &[]),
+    (13, "locUpdateVLRRecord", &[]),
+    (14, "commonEquipRecord", &[]),
+    (20, "sgsnPDPRecord", SGSN_PDP_FIELDS),
+    (21, "ggsnPDPRecord", GGSN_PDP_FIELDS),
+    (22, "sgsnMMRecord", &[]),
+    (23, "sgsnSMORecord", &[]),
+    (24, "sgsnSMTRecord", &[]),
+    (25, "sgsnMTLCSRecord", &[]),
+    (26, "sgsnMOLCSRecord", &[]),
+    (27, "sgsnNILCSRecord", &[]),
+    (28, "sgsnMBMSRecord", &[]),
+    (29, "ggsnMBMSRecord", &[]),
+    (70, "egsnPDPRecord", GGSN_PDP_FIELDS),
+    (78, "sGWRecord", SGW_FIELDS),
+    (79, "pGWRecord", PGW_FIELDS),
+];
+
+const CLOSING_CAUSES: &[(u128, &str)] = &[
+    (0, "normalRelease"),
+    (1, "partialRecord"),
+    (2, "partialRecordCallReestablishment"),
+    (3, "unsuccessfulCallAttempt"),
+    (4, "abnormalRelease"),
+    (5, "cAMELInitCallRelease"),
+    (16, "volumeLimit"),
+    (17, "timeLimit"),
+    (18, "servingNodeChange"),
+    (19, "maxChangeCond"),
+    (20, "managementIntervention"),
+    (21, "intraSGSNIntersystemChange"),
+    (22, "rATChange"),
+    (23, "mSTimeZoneChange"),
+    (24, "sGSNPLMNIDChange"),
+];
+
+const RAT_TYPES: [&str; 11] = [
+    "reserved", "UTRAN", "GERAN", "WLAN", "GAN", "HSPA Evolution", "EUTRAN", "Virtual", "EUTRAN-NB-IoT",
+    "LTE-M", "NR",
+];
+
+/// Recognizes a TS 32.298 charging data record and labels its fields.
+pub(crate) fn recognize(node: &mut ASN1Node) -> bool {
+    let Some(&(_, name, fields)) = record_type(node) else {
+        return false;
+    };
+    node.set_label(name);
+    for field in node.children_mut() {
+        if !field.tag.is_context_specific() {
+            continue;
+        }
+        let Some(&field_name) = fields.get(field.tag_number() as usize).filter(|name| !name.is_empty()) else {
+            continue;
+        };
+        field.set_label(field_name);
+        if let Some(annotation) = decode_field(field, field_name) {
+            field.set_annotation(annotation);
+        }
+    }
+    if let Some(summary) = summary(node) {
+        node.set_annotation(summary);
+    }
+    true
+}
+
+/// Prefixes the labels of the records of a CDR file with their position, e.g. `#12 pGWRecord`.
+pub(crate) fn number_records(records: &mut [ASN1Node]) {
+    for (i, record) in records.iter_mut().enumerate() {
+        let name = record.label.take().unwrap_or_else(|| "record".to_string());
+        record.set_label(&format!("#{} {}", i + 1, name));
+    }
+}
+
+fn record_type(node: &ASN1Node) -> Option<&'static (u32, &'static str, &'static [&'static str])> {
+    if !node.tag.is_context_specific() || !node.tag.is_constructed() {
+        return None;
+    }
+    RECORD_TYPES.iter().find(|(number, _, _)| *number == node.tag_number())
+}
+
+/// Decodes the value of a field and returns the annotation of the field.
+fn decode_field(field: &mut ASN1Node, name: &str) -> Option<String> {
+    match name {
+        "servedIMSI" | "servedIMEI" | "servedIMEISV" => show_raw(field).map(|bytes| tbcd(&bytes)),
+        "servedMSISDN" | "callingNumber" | "calledNumber" | "connectedNumber" | "translatedNumber"
+        | "roamingNumber" | "msrn" => show_raw(field).and_then(|bytes| address_string(&bytes)),
+        "recordOpeningTime" | "seizureTime" | "answerTime" | "releaseTime" | "startTime" | "stopTime" => {
+            show_raw(field).and_then(|bytes| time_stamp(&bytes))
+        }
+        "duration" | "callDuration" => show_integer(field).map(duration),
+        "recordType" | "chargingID" | "recordSequenceNumber" | "localSequenceNumber" | "sequenceNumber" => {
+            show_integer(field);
+            None
+        }
+        "causeForRecClosing" | "causeForTerm" => show_integer(field).map(|cause| {
+            CLOSING_CAUSES
+                .iter()
+                .find(|(known, _)| *known == cause)
+                .map(|(_, name)| name.to_string())
+                .unwrap_or_else(|| cause.to_string())
+        }),
+        "rATType" => show_integer(field).map(|rat| {
+            RAT_TYPES.get(rat as usize).map(|name| name.to_string()).unwrap_or_else(|| rat.to_string())
+        }),
+        "accessPointNameNI" | "accessPointNameOI" | "nodeID" | "recordingEntity" => {
+            show_raw(field).map(|bytes| String::from_utf8_lossy(&bytes).to_string())
+        }
+        "servingNodePLMNIdentifier" | "p-GWPLMNIdentifier" => show_raw(field).and_then(|bytes| plmn_id(&bytes)),
+        "sgsnAddress" | "ggsnAddress" | "ggsnAddressUsed" | "s-GWAddress" | "p-GWAddress" | "p-GWAddressUsed"
+        | "servingNodeAddress" | "servedPDPAddress" | "servedPDPPDNAddress" => {
+            let addresses = ip_addresses(field);
+            (!addresses.is_empty()).then(|| addresses.join(", "))
+        }
+        _ => None,
+    }
+}
+
+/// Replaces the raw content of an IMPLICIT tagged primitive field by hex and returns it.
+fn show_raw(field: &mut ASN1Node) -> Option<Vec<u8>> {
+    let bytes = implicit_octets(field)?.to_vec();
+    field.value = ASN1Value::Bytes(bytes.clone());
+    field.expandable = false;
+    Some(bytes)
+}
+
+fn show_integer(field: &mut ASN1Node) -> Option<u128> {
+    let bytes = implicit_octets(field)?;
+    if bytes.is_empty() || bytes.len() > 16 {
+        return None;
+    }
+    let value = bytes.iter().fold(0u128, |value, &byte| value << 8 | byte as u128);
+    field.value = ASN1Value::Integer(value);
+    field.expandable = false;
+    Some(value)
+}
+
+/// Decodes TBCD digits, two per octet with the first digit in the low nibble and `F` as filler.
+fn tbcd(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .flat_map(|&byte| [byte & 0x0f, byte >> 4])
+        .take_while(|&digit| digit != 0x0f)
+        .map(|digit| match digit {
+            0..=9 => (b'0' + digit) as char,
+            0x0a => '*',
+            0x0b => '#',
+            _ => (b'a' + digit - 0x0c) as char,
+        })
+        .collect()
+}
+
+/// Decodes an `AddressString`: the nature of address and numbering plan octet followed by TBCD digits.
+fn address_string(bytes: &[u8]) -> Option<String> {
+    let (&nature, digits) = bytes.split_first()?;
+    let international = (nature >> 4) & 0x07 == 1;
+    Some(format!("{}{}", if international { "+" } else { "" }, tbcd(digits)))
+}
+
+/// Decodes a `TimeStamp ::= OCTET STRING (SIZE(9))` holding BCD `YYMMDDhhmmss` and a `ShhMM` UTC offset.
+fn time_stamp(bytes: &[u8]) -> Option<String> {
+    let [year, month, day, hour, minute, second, sign, offset_hour, offset_minute] = bytes else {
+        return None;
+    };
+    Some(format!(
+        "20{:02x}-{:02x}-{:02x} {:02x}:{:02x}:{:02x} {}{:02x}:{:02x}",
+        year, month, day, hour, minute, second, *sign as char, offset_hour, offset_minute
+    ))
+}
+
+fn duration(seconds: u128) -> String {
+    format!("{}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+}
+
+/// Decodes a `PLMN-Id` holding the MCC and MNC as TBCD digits.
+fn plmn_id(bytes: &[u8]) -> Option<String> {
+    let [first, second, third] = bytes else {
+        return None;
+    };
+    let digit = |nibble: u8| char::from_digit(nibble as u32, 10);
+    let mcc: String = [first & 0x0f, first >> 4, second & 0x0f].into_iter().filter_map(digit).collect();
+    let mnc: String = [third & 0x0f, third >> 4, second >> 4].into_iter().filter_map(digit).collect();
+    Some(format!("MCC {} MNC {}", mcc, mnc))
+}
+
+/// Collects the `iPBinV4Address [0]` and `iPBinV6Address [1]` values below a `GSNAddress` or `PDPAddress`.
+fn ip_addresses(node: &mut ASN1Node) -> Vec<String> {
+    let mut addresses = Vec::new();
+    for child in node.children_mut() {
+        match implicit_octets(child).map(<[u8]>::to_vec) {
+            Some(bytes) if bytes.len() == 4 => {
+                let address = bytes.iter().map(u8::to_string).collect::<Vec<String>>().join(".");
+                show_raw(child);
+                child.set_annotation(address.clone());
+                addresses.push(address);
+            }
+            Some(bytes) if bytes.len() == 16 => {
+                let address = std::net::Ipv6Addr::from(<[u8; 16]>::try_from(bytes).unwrap_or_default()).to_string();
+                show_raw(child);
+                child.set_annotation(address.clone());
+                addresses.push(address);
+            }
+            Some(_) => {}
+            None => addresses.extend(ip_addresses(child)),
+        }
+    }
+    addresses
+}
+
+/// Summarizes the key fields of a record: subscriber, start time and duration.
+fn summary(node: &ASN1Node) -> Option<String> {
+    let field = |names: &[&str]| {
+        node.children()
+            .iter()
+            .find(|field| field.label.as_deref().map(|label| names.contains(&label)).unwrap_or(false))
+            .and_then(|field| field.annotation.clone())
+    };
+    let parts: Vec<String> = [
+        field(&["servedIMSI"]).map(|imsi| format!("IMSI {}", imsi)),
+        field(&["servedMSISDN"]).map(|msisdn| format!("MSISDN {}", msisdn)),
+        field(&["recordOpeningTime", "seizureTime", "answerTime", "startTime"]),
+        field(&["duration", "callDuration"]).map(|duration| format!("duration {}", duration)),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    (!parts.is_empty()).then(|| parts.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asn1_der::{parse_asn1, view_lines};
+    use crate::schema::{annotate, SchemaOptions};
+
+    #[test]
+    fn decodes_tbcd_digits() {
+        assert_eq!(tbcd(&[0x62, 0x02, 0x91, 0x78, 0x56, 0x34, 0x12, 0xf0]), "262019876543210");
+        assert_eq!(tbcd(&[0x21, 0x43]), "1234");
+        assert_eq!(tbcd(&[0x21, 0xf3, 0x65]), "123");
+        assert_eq!(tbcd(&[0xba, 0xdc, 0xfe]), "*#abc");
+        assert_eq!(tbcd(&[0xff]), "");
+    }
+
+    #[test]
+    fn decodes_address_strings() {
+        assert_eq!(address_string(&[0x91, 0x94, 0x51, 0x11, 0x32, 0x54, 0x76, 0xf8]).unwrap(), "+4915112345678");
+        assert_eq!(address_string(&[0x81, 0x30, 0x10, 0x32, 0x54, 0xf6]).unwrap(), "030123456");
+        assert_eq!(address_string(&[0x91]).unwrap(), "+");
+        assert_eq!(address_string(&[]), None);
+    }
+
+    #[test]
+    fn decodes_time_stamps() {
+        assert_eq!(time_stamp(&hex::decode("2403151020302b0100").unwrap()).unwrap(), "2024-03-15 10:20:30 +01:00");
+        assert_eq!(time_stamp(&hex::decode("2403160900002d0500").unwrap()).unwrap(), "2024-03-16 09:00:00 -05:00");
+        assert_eq!(time_stamp(&hex::decode("240315102030").unwrap()), None);
+    }
+
+    #[test]
+    fn decodes_plmn_ids() {
+        assert_eq!(plmn_id(&[0x00, 0xf1, 0x10]).unwrap(), "MCC 001 MNC 01");
+        assert_eq!(plmn_id(&[0x62, 0xf2, 0x10]).unwrap(), "MCC 262 MNC 01");
+        assert_eq!(plmn_id(&[0x13, 0x00, 0x14]).unwrap(), "MCC 310 MNC 410");
+        assert_eq!(plmn_id(&[0x62, 0xf2]), None);
+    }
+
+    #[test]
+    fn labels_records() {
+        let data = concat!(
+            "a030800101810862029178563412f0830891945111325476f885068130103254f696092403160800002b000099013b9e",
+            "0100",
+        );
+        let mut root = parse_asn1(&hex::decode(data).unwrap(), 0).unwrap();
+        annotate(&mut root, &SchemaOptions { cdr: true, ..SchemaOptions::default() });
+        assert_eq!(
+            view_lines(&root),
+            [
+                "moCallRecord: CONTEXT-SPECIFIC [0] (constructed)  (IMSI 262019876543210, MSISDN +4915112345678, 2024-03-16 08:00:00 +00:00, duration 0:00:59)",
+                "recordType: CONTEXT-SPECIFIC [0] (primitive) 1",
+                "servedIMSI: CONTEXT-SPECIFIC [1] (primitive) 62029178563412f0 (262019876543210)",
+                "servedMSISDN: CONTEXT-SPECIFIC [3] (primitive) 91945111325476f8 (+4915112345678)",
+                "calledNumber: CONTEXT-SPECIFIC [5] (primitive) 8130103254f6 (030123456)",
+                "seizureTime: CONTEXT-SPECIFIC [22] (primitive) 2403160800002b0000 (2024-03-16 08:00:00 +00:00)",
+                "callDuration: CONTEXT-SPECIFIC [25] (primitive) 59 (0:00:59)",
+                "causeForTerm: CONTEXT-SPECIFIC [30] (primitive) 0 (normalRelease)",
+            ]
+        );
+    }
+}
@@ -0,0 +1,585 @@
+use der::Tag;
+use crate::asn1_der::ASN1Node;
+use crate::schema::{is_context, label_children, show_as_bytes};
+use crate::schema::cms::label_attributes;
+use crate::schema::keys::label_algorithm_identifier;
+use crate::schema::oid::short_oid_name;
+use crate::schema::tsp::label_pki_status_info;
+use crate::schema::x509::{format_general_name, format_name, is_certificate, label_certificate, label_extensions, label_name, label_public_key_info};
+
+/// `PKIBody` choices, indexed by their `[n] EXPLICIT` tag number.
+const PKI_BODY: [&str; 27] = [
+    "ir",
+    "ip",
+    "cr",
+    "cp",
+    "p10cr",
+    "popdecc",
+    "popdecr",
+    "kur",
+    "kup",
+    "krr",
+    "krp",
+    "rr",
+    "rp",
+    "ccr",
+    "ccp",
+    "ckuann",
+    "cann",
+    "rann",
+    "crlann",
+    "pkiconf",
+    "nested",
+    "genm",
+    "genp",
+    "error",
+    "certConf",
+    "pollReq",
+    "pollRep",
+];
+
+const PKI_STATUSES: [&str; 7] = [
+    "accepted",
+    "grantedWithMods",
+    "rejection",
+    "waiting",
+    "revocationWarning",
+    "revocationNotification",
+    "keyUpdateWarning",
+];
+
+/// Optional `PKIHeader` fields, indexed by their `[n] EXPLICIT` tag number.
+const PKI_HEADER: [&str; 9] = [
+    "messageTime",
+    "protectionAlg",
+    "senderKID",
+    "recipKID",
+    "transactionID",
+    "senderNonce",
+    "recipNonce",
+    "freeText",
+    "generalInfo",
+];
+
+/// `CertTemplate` fields, indexed by their `[n] IMPLICIT` tag number.
+const CERT_TEMPLATE: [&str; 10] = [
+    "version",
+    "serialNumber",
+    "signingAlg",
+    "issuer",
+    "validity",
+    "subject",
+    "publicKey",
+    "issuerUID",
+    "subjectUID",
+    "extensions",
+];
+
+const PROOF_OF_POSSESSION: [&str; 4] = ["raVerified", "signature", "keyEncipherment", "keyAgreement"];
+
+/// Recognizes a CMP (RFC 4210 / RFC 9480) `PKIMessage` at the node.
+pub(crate) fn recognize(node: &mut ASN1Node) -> bool {
+    if !is_pki_message(node) {
+        return false;
+    }
+    label_pki_message(node);
+    true
+}
+
+/// `PKIMessage ::= SEQUENCE { header PKIHeader, body PKIBody, protection [0] OPTIONAL, extraCerts [1] OPTIONAL }`
+fn is_pki_message(node: &ASN1Node) -> bool {
+    let children = node.children();
+    node.tag == Tag::Sequence
+        && (2..=4).contains(&children.len())
+        && is_pki_header(&children[0])
+        && children[1].tag.is_context_specific()
+        && children[1].tag.is_constructed()
+        && (children[1].tag_number() as usize) < PKI_BODY.len()
+        && children[2..].iter().all(|field| is_context(field, 0) || is_context(field, 1))
+}
+
+/// `PKIHeader ::= SEQUENCE { pvno INTEGER, sender GeneralName, recipient GeneralName, ... }`
+fn is_pki_header(node: &ASN1Node) -> bool {
+    let children = node.children();
+    node.tag == Tag::Sequence
+        && children.len() >= 3
+        && children[0].as_u128().map(|pvno| (1..=3).contains(&pvno)).unwrap_or(false)
+        && children[1..].iter().all(|field| field.tag.is_context_specific())
+}
+
+fn label_pki_message(node: &mut ASN1Node) {
+    node.set_label("PKIMessage");
+    let mut summary = None;
+    for field in node.children_mut() {
+        match field.tag {
+            Tag::Sequence => label_pki_header(field),
+            _ if is_context(field, 0) && summary.is_some() => field.set_label("protection"),
+            _ if is_context(field, 1) && summary.is_some() => {
+                field.set_label("extraCerts");
+                if let Some(certificates) = field.children_mut().first_mut() {
+                    label_certificates(certificates);
+                }
+            }
+            _ => summary = Some(label_body(field)),
+        }
+    }
+    if let Some(summary) = summary {
+        node.set_annotation(summary);
+    }
+}
+
+fn label_pki_header(node: &mut ASN1Node) {
+    node.set_label("header");
+    label_children(node, &["pvno", "sender", "recipient"]);
+    let mut names = vec![];
+    for (i, field) in node.children_mut().iter_mut().enumerate() {
+        if i == 1 || i == 2 {
+            if let Some(name) = format_general_name(field) {
+                field.set_annotation(name.clone());
+                names.push(name);
+            }
+            continue;
+        }
+        let Some(&name) = PKI_HEADER.get(field.tag_number() as usize).filter(|_| i > 2) else {
+            continue;
+        };
+        field.set_label(name);
+        let Some(value) = field.children_mut().first_mut() else {
+            continue;
+        };
+        match name {
+            "protectionAlg" => {
+                label_algorithm_identifier(value, "AlgorithmIdentifier");
+                let algorithm = value.children().first().and_then(ASN1Node::as_oid);
+                if let Some(algorithm) = algorithm {
+                    field.set_annotation(short_oid_name(&algorithm));
+                }
+            }
+            "senderKID" | "recipKID" | "transactionID" | "senderNonce" | "recipNonce" => {
+                show_as_bytes(value);
+                let octets = value.octets().map(hex::encode);
+                if let Some(octets) = octets {
+                    field.set_annotation(octets);
+                }
+            }
+            "messageTime" => {
+                let time = value.as_string();
+                if let Some(time) = time {
+                    field.set_annotation(time);
+                }
+            }
+            "freeText" => {
+                let text = free_text(value);
+                field.set_annotation(text);
+            }
+            "generalInfo" => label_info_type_and_values(value),
+            _ => {}
+        }
+    }
+    if let [sender, recipient] = names.as_slice() {
+        node.set_annotation(format!("{} -> {}", sender, recipient));
+    }
+}
+
+/// Labels the `PKIBody` choice and returns the annotation of the message.
+fn label_body(node: &mut ASN1Node) -> String {
+    let name = PKI_BODY[node.tag_number() as usize];
+    node.set_label(name);
+    let Some(value) = node.children_mut().first_mut() else {
+        return name.to_string();
+    };
+    let summary = match name {
+        "ir" | "cr" | "kur" | "ccr" => label_cert_req_messages(value),
+        "ip" | "cp" | "kup" | "ccp" => label_cert_rep_message(value),
+        "p10cr" => label_certification_request(value),
+        "rr" => label_rev_req_content(value),
+        "rp" => label_rev_rep_content(value),
+        "certConf" => label_cert_confirm_content(value),
+        "error" => label_error_msg_content(value),
+        "genm" | "genp" => {
+            label_info_type_and_values(value);
+            None
+        }
+        "pollReq" | "pollRep" => label_poll_content(value),
+        "nested" => {
+            value.set_label("NestedMessageContent");
+            for message in value.children_mut().iter_mut().filter(|message| is_pki_message(message)) {
+                label_pki_message(message);
+            }
+            Some(format!("{} message(s)", value.children().len()))
+        }
+        _ => None,
+    };
+    if let Some(summary) = &summary {
+        node.set_annotation(summary.clone());
+    }
+    match summary {
+        Some(summary) => format!("{}: {}", name, summary),
+        None => name.to_string(),
+    }
+}
+
+/// `CertReqMessages ::= SEQUENCE OF CertReqMsg`, returns the requested subjects.
+fn label_cert_req_messages(node: &mut ASN1Node) -> Option<String> {
+    node.set_label("CertReqMessages");
+    let mut subjects = vec![];
+    for message in node.children_mut() {
+        subjects.extend(label_cert_req_msg(message));
+    }
+    Some(subjects.join("; ")).filter(|subjects| !subjects.is_empty())
+}
+
+/// `CertReqMsg ::= SEQUENCE { certReq CertRequest, popo ProofOfPossession OPTIONAL, regInfo OPTIONAL }`
+fn label_cert_req_msg(node: &mut ASN1Node) -> Option<String> {
+    node.set_label("CertReqMsg");
+    let mut subject = None;
+    for (i, field) in node.children_mut().iter_mut().enumerate() {
+        match field.tag {
+            Tag::Sequence if i == 0 => {
+                field.set_label("certReq");
+                subject = label_cert_request(field);
+            }
+            Tag::Sequence => field.set_label("regInfo"),
+            _ => {
+                field.set_label("popo");
+                if let Some(&choice) = PROOF_OF_POSSESSION.get(field.tag_number() as usize) {
+                    field.set_annotation(choice);
+                }
+            }
+        }
+    }
+    if let Some(subject) = &subject {
+        node.set_annotation(subject.clone());
+    }
+    subject
+}
+
+/// `CertRequest ::= SEQUENCE { certReqId INTEGER, certTemplate CertTemplate, controls Controls OPTIONAL }`
+fn label_cert_request(node: &mut ASN1Node) -> Option<String> {
+    label_children(node, &["certReqId", "certTemplate", "controls"]);
+    let children = node.children_mut();
+    let subject = children.get_mut(1).and_then(label_cert_template);
+    if let Some(controls) = children.get_mut(2) {
+        label_info_type_and_values(controls);
+    }
+    subject
+}
+
+/// Labels the `[n] IMPLICIT` fields of a `CertTemplate` and returns the subject.
+fn label_cert_template(node: &mut ASN1Node) -> Option<String> {
+    let mut subject = None;
+    for field in node.children_mut() {
+        let Some(&name) = CERT_TEMPLATE.get(field.tag_number() as usize).filter(|_| field.tag.is_context_specific()) else {
+            continue;
+        };
+        match name {
+            "signingAlg" => label_algorithm_identifier(field, name),
+            "publicKey" => label_public_key_info(field),
+            "extensions" => label_extensions(field),
+            "validity" => {
+                for time in field.children_mut() {
+                    time.set_label(if is_context(time, 0) { "notBefore" } else { "notAfter" });
+                }
+            }
+            // Name is a CHOICE, so the tag stays EXPLICIT
+            "issuer" | "subject" => {
+                let name = field.children().first().map(format_name);
+                if let Some(name) = name {
+                    if field.tag_number() == 5 {
+                        subject = Some(name.clone());
+                    }
+                    field.set_annotation(name);
+                }
+            }
+            _ => {}
+        }
+        field.set_label(name);
+    }
+    subject
+}
+
+/// `CertRepMessage ::= SEQUENCE { caPubs [1] SEQUENCE OF CMPCertificate OPTIONAL, response SEQUENCE OF CertResponse }`
+fn label_cert_rep_message(node: &mut ASN1Node) -> Option<String> {
+    node.set_label("CertRepMessage");
+    let mut statuses = vec![];
+    for field in node.children_mut() {
+        if is_context(field, 1) {
+            field.set_label("caPubs");
+            if let Some(certificates) = field.children_mut().first_mut() {
+                label_certificates(certificates);
+            }
+            continue;
+        }
+        field.set_label("response");
+        for response in field.children_mut() {
+            statuses.extend(label_cert_response(response));
+        }
+    }
+    Some(statuses.join("; ")).filter(|statuses| !statuses.is_empty())
+}
+
+/// `CertResponse ::= SEQUENCE { certReqId INTEGER, status PKIStatusInfo, certifiedKeyPair OPTIONAL, rspInfo OPTIONAL }`
+fn label_cert_response(node: &mut ASN1Node) -> Option<String> {
+    node.set_label("CertResponse");
+    label_children(node, &["certReqId", "status"]);
+    let children = node.children_mut();
+    let status = children.get_mut(1).map(|status| label_pki_status_info(status, &PKI_STATUSES));
+    for field in children.iter_mut().skip(2) {
+        match field.tag {
+            Tag::Sequence => label_certified_key_pair(field),
+            _ => field.set_label("rspInfo"),
+        }
+    }
+    if let Some(status) = &status {
+        node.set_annotation(status.clone());
+    }
+    status
+}
+
+/// `CertifiedKeyPair ::= SEQUENCE { certOrEncCert, privateKey [0] OPTIONAL, publicationInfo [1] OPTIONAL }`
+fn label_certified_key_pair(node: &mut ASN1Node) {
+    node.set_label("certifiedKeyPair");
+    for (i, field) in node.children_mut().iter_mut().enumerate() {
+        match i {
+            0 => {
+                field.set_label("certOrEncCert");
+                // certOrEncCert ::= CHOICE { certificate [0] CMPCertificate, encryptedCert [1] EncryptedKey }
+                if is_context(field, 0) {
+                    field.set_annotation("certificate");
+                    if let Some(certificate) = field.children_mut().first_mut().filter(|certificate| is_certificate(certificate)) {
+                        label_certificate(certificate);
+                        certificate.set_label("certificate");
+                    }
+                } else {
+                    field.set_annotation("encryptedCert");
+                }
+            }
+            _ if is_context(field, 0) => field.set_label("privateKey"),
+            _ => field.set_label("publicationInfo"),
+        }
+    }
+}
+
+/// `CertificationRequest ::= SEQUENCE { certificationRequestInfo, signatureAlgorithm, signature BIT STRING }`
+fn label_certification_request(node: &mut ASN1Node) -> Option<String> {
+    node.set_label("CertificationRequest");
+    let children = node.children_mut();
+    if let Some(algorithm) = children.get_mut(1) {
+        label_algorithm_identifier(algorithm, "signatureAlgorithm");
+    }
+    if let Some(signature) = children.get_mut(2) {
+        signature.set_label("signature");
+    }
+    let info = children.first_mut()?;
+    info.set_label("certificationRequestInfo");
+    // CertificationRequestInfo ::= SEQUENCE { version, subject Name, subjectPKInfo, attributes [0] }
+    for (i, field) in info.children_mut().iter_mut().enumerate() {
+        match i {
+            0 => field.set_label("version"),
+            1 => label_name(field, "subject"),
+            2 => label_public_key_info(field),
+            _ => label_attributes(field, "attributes"),
+        }
+    }
+    info.children().get(1).and_then(|subject| subject.annotation.clone())
+}
+
+/// `RevReqContent ::= SEQUENCE OF RevDetails`, `RevDetails ::= SEQUENCE { certDetails CertTemplate, crlEntryDetails OPTIONAL }`
+fn label_rev_req_content(node: &mut ASN1Node) -> Option<String> {
+    node.set_label("RevReqContent");
+    for details in node.children_mut() {
+        details.set_label("RevDetails");
+        label_children(details, &["certDetails", "crlEntryDetails"]);
+        let subject = details.children_mut().first_mut().and_then(label_cert_template);
+        if let Some(subject) = subject {
+            details.set_annotation(subject);
+        }
+    }
+    Some(format!("{} revocation(s)", node.children().len()))
+}
+
+/// `RevRepContent ::= SEQUENCE { status SEQUENCE OF PKIStatusInfo, revCerts [0] OPTIONAL, crls [1] OPTIONAL }`
+fn label_rev_rep_content(node: &mut ASN1Node) -> Option<String> {
+    node.set_label("RevRepContent");
+    let mut statuses = vec![];
+    for field in node.children_mut() {
+        match field.tag {
+            Tag::Sequence => {
+                field.set_label("status");
+                for status in field.children_mut() {
+                    statuses.push(label_pki_status_info(status, &PKI_STATUSES));
+                }
+            }
+            _ if is_context(field, 0) => field.set_label("revCerts"),
+            _ => field.set_label("crls"),
+        }
+    }
+    Some(statuses.join("; ")).filter(|statuses| !statuses.is_empty())
+}
+
+/// `CertConfirmContent ::= SEQUENCE OF CertStatus`
+fn label_cert_confirm_content(node: &mut ASN1Node) -> Option<String> {
+    node.set_label("CertConfirmContent");
+    let mut statuses = vec![];
+    for status in node.children_mut() {
+        // CertStatus ::= SEQUENCE { certHash OCTET STRING, certReqId INTEGER, statusInfo OPTIONAL, hashAlg [0] OPTIONAL }
+        status.set_label("CertStatus");
+        for field in status.children_mut() {
+            match field.tag {
+                Tag::OctetString => {
+                    field.set_label("certHash");
+                    show_as_bytes(field);
+                }
+                Tag::Integer => field.set_label("certReqId"),
+                Tag::Sequence => {
+                    let name = label_pki_status_info(field, &PKI_STATUSES);
+                    field.set_label("statusInfo");
+                    statuses.push(name);
+                }
+                _ => {
+                    field.set_label("hashAlg");
+                    if let Some(algorithm) = field.children_mut().first_mut() {
+                        label_algorithm_identifier(algorithm, "hashAlg");
+                    }
+                }
+            }
+        }
+    }
+    match statuses.is_empty() {
+        true => Some(format!("{} certificate(s) confirmed", node.children().len())),
+        false => Some(statuses.join("; ")),
+    }
+}
+
+/// `ErrorMsgContent ::= SEQUENCE { pKIStatusInfo, errorCode INTEGER OPTIONAL, errorDetails PKIFreeText OPTIONAL }`
+fn label_error_msg_content(node: &mut ASN1Node) -> Option<String> {
+    node.set_label("ErrorMsgContent");
+    let children = node.children_mut();
+    let mut summary = vec![label_pki_status_info(children.first_mut()?, &PKI_STATUSES)];
+    children[0].set_label("pKIStatusInfo");
+    for field in &mut children[1..] {
+        match field.tag {
+            Tag::Integer => {
+                field.set_label("errorCode");
+                summary.push(format!("error code {}", field.display_value()));
+            }
+            _ => {
+                field.set_label("errorDetails");
+                summary.push(free_text(field));
+            }
+        }
+    }
+    Some(summary.join(", "))
+}
+
+/// `PollReqContent` or `PollRepContent ::= SEQUENCE OF SEQUENCE { certReqId, checkAfter INTEGER, reason OPTIONAL }`
+fn label_poll_content(node: &mut ASN1Node) -> Option<String> {
+    for entry in node.children_mut() {
+        label_children(entry, &["certReqId", "checkAfter", "reason"]);
+        let check_after = entry.children().get(1).and_then(ASN1Node::as_u128);
+        if let Some(seconds) = check_after {
+            entry.set_annotation(format!("check after {} s", seconds));
+        }
+    }
+    node.children().first().and_then(|entry| entry.annotation.clone())
+}
+
+/// `SEQUENCE OF InfoTypeAndValue`, `InfoTypeAndValue ::= SEQUENCE { infoType OBJECT IDENTIFIER, infoValue ANY OPTIONAL }`
+fn label_info_type_and_values(node: &mut ASN1Node) {
+    for info in node.children_mut() {
+        let Some(oid) = info.children().first().and_then(ASN1Node::as_oid) else {
+            continue;
+        };
+        info.set_label(&short_oid_name(&oid));
+        label_children(info, &["infoType", "infoValue"]);
+    }
+}
+
+/// Labels `SEQUENCE OF CMPCertificate` as individually browsable certificates.
+fn label_certificates(node: &mut ASN1Node) {
+    let mut count = 0;
+    for certificate in node.children_mut().iter_mut().filter(|certificate| is_certificate(certificate)) {
+        count += 1;
+        label_certificate(certificate);
+        certificate.set_label(&format!("certificate #{}", count));
+    }
+}
+
+/// Joins the strings of `PKIFreeText ::= SEQUENCE OF UTF8String`.
+fn free_text(node: &ASN1Node) -> String {
+    node.children().iter().filter_map(ASN1Node::as_string).collect::<Vec<String>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::asn1_der::{parse_asn1, view_lines};
+    use crate::schema::{annotate, SchemaOptions};
+
+    fn annotated(data: &[u8]) -> Vec<String> {
+        let mut root = parse_asn1(data, 0).unwrap();
+        annotate(&mut root, &SchemaOptions::default());
+        view_lines(&root)
+    }
+
+    #[test]
+    fn labels_headers() {
+        let lines = annotated(include_bytes!("../../tests/data/cmp-ir.der"));
+        assert_eq!(lines[1], "header: SEQUENCE (field(s): 9) (CN=client -> CN=CA)");
+        assert_eq!(lines[3], "sender: CONTEXT-SPECIFIC [4] (constructed)  (CN=client)");
+        assert_eq!(lines[9], "recipient: CONTEXT-SPECIFIC [4] (constructed)  (CN=CA)");
+        assert_eq!(lines[15], "messageTime: CONTEXT-SPECIFIC [0] (constructed)  (2024-01-01T12:00:00Z)");
+        assert_eq!(lines[17], "protectionAlg: CONTEXT-SPECIFIC [1] (constructed)  (hmacWithSHA256)");
+        assert_eq!(lines[22], "transactionID: CONTEXT-SPECIFIC [4] (constructed)  (000102030405060708090a0b0c0d0e0f)");
+        assert_eq!(lines[26], "freeText: CONTEXT-SPECIFIC [7] (constructed)  (hello)");
+    }
+
+    #[test]
+    fn labels_certificate_requests() {
+        let lines = annotated(include_bytes!("../../tests/data/cmp-ir.der"));
+        assert_eq!(lines[0], "PKIMessage: SEQUENCE (field(s): 3) (ir: CN=device-1)");
+        assert_eq!(lines[29], "ir: CONTEXT-SPECIFIC [0] (constructed)  (CN=device-1)");
+        assert_eq!(lines[31], "CertReqMsg: SEQUENCE (field(s): 2) (CN=device-1)");
+        assert_eq!(lines[34], "certTemplate: SEQUENCE (field(s): 3)");
+        assert_eq!(lines[35], "subject: CONTEXT-SPECIFIC [5] (constructed)  (CN=device-1)");
+        assert_eq!(lines[41], "publicKey: CONTEXT-SPECIFIC [6] (constructed) ");
+        assert_eq!(lines[47], "notAfter: CONTEXT-SPECIFIC [1] (constructed) ");
+        assert_eq!(lines[49], "popo: CONTEXT-SPECIFIC [0] (primitive)  (raVerified)");
+        assert_eq!(lines[51], "protection: CONTEXT-SPECIFIC [0] (constructed) ");
+    }
+
+    #[test]
+    fn labels_certificate_responses() {
+        let lines = annotated(include_bytes!("../../tests/data/cmp-ip.der"));
+        assert_eq!(lines[0], "PKIMessage: SEQUENCE (field(s): 4) (ip: accepted)");
+        assert_eq!(lines[32], "CertResponse: SEQUENCE (field(s): 3) (accepted)");
+        assert_eq!(lines[34], "status: SEQUENCE (field(s): 1) (accepted)");
+        assert_eq!(lines[37], "certOrEncCert: CONTEXT-SPECIFIC [0] (constructed)  (certificate)");
+        assert_eq!(lines[38], "certificate: SEQUENCE (field(s): 3) (CN=device-1)");
+        assert_eq!(lines[68], "extraCerts: CONTEXT-SPECIFIC [1] (constructed) ");
+        assert_eq!(lines[70], "certificate #1: SEQUENCE (field(s): 3) (CN=device-1)");
+    }
+
+    #[test]
+    fn labels_errors() {
+        let lines = annotated(include_bytes!("../../tests/data/cmp-error.der"));
+        assert_eq!(
+            lines[0],
+            "PKIMessage: SEQUENCE (field(s): 2) (error: rejection (certConfirmed): bad template, error code 42, detail)"
+        );
+        assert_eq!(
+            &lines[29..],
+            [
+                "error: CONTEXT-SPECIFIC [23] (constructed)  (rejection (certConfirmed): bad template, error code 42, detail)",
+                "ErrorMsgContent: SEQUENCE (field(s): 3)",
+                "pKIStatusInfo: SEQUENCE (field(s): 3) (rejection (certConfirmed): bad template)",
+                "status: INTEGER 2",
+                "statusString: SEQUENCE (field(s): 1)",
+                "UTF8String \"bad template\"",
+                "failInfo: BIT STRING 0000000000010000 (certConfirmed)",
+                "errorCode: INTEGER 42",
+                "errorDetails: SEQUENCE (field(s): 1)",
+                "UTF8String \"detail\"",
+            ]
+        );
+    }
+}
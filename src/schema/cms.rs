@@ -0,0 +1,391 @@
+use der::asn1::ObjectIdentifier;
+use der::Tag;
+use crate::asn1_der::{ASN1Node, ASN1Value};
+use crate::schema::{implicit_octets, is_context};
+use crate::schema::keys::{label_algorithm_identifier, label_pbes2_params, PBES2};
+use crate::schema::oid::short_oid_name;
+use crate::schema::receipt::{is_receipt, label_receipt};
+use crate::schema::scep::{attribute_value_name, message_summary};
+use crate::schema::tsp::label_tst_info;
+use crate::schema::x509::{format_name, is_certificate, label_certificate, label_name};
+
+const ID_DATA: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.7.1");
+const ID_SIGNED_DATA: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.7.2");
+const ID_ENVELOPED_DATA: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.7.3");
+const ID_DIGESTED_DATA: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.7.5");
+const ID_ENCRYPTED_DATA: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.7.6");
+const ID_CT_TST_INFO: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.16.1.4");
+
+/// Content types of PKCS#7 (1.2.840.113549.1.7) and S/MIME (1.2.840.113549.1.9.16.1).
+const CONTENT_TYPE_ARCS: [&str; 2] = ["1.2.840.113549.1.7.", "1.2.840.113549.1.9.16.1."];
+
+/// Recognizes a CMS / PKCS#7 `ContentInfo` at the node.
+pub(crate) fn recognize(node: &mut ASN1Node) -> bool {
+    if !is_content_info(node) {
+        return false;
+    }
+    label_content_info(node);
+    true
+}
+
+/// `ContentInfo ::= SEQUENCE { contentType OBJECT IDENTIFIER, content [0] EXPLICIT ANY OPTIONAL }`
+pub(crate) fn is_content_info(node: &ASN1Node) -> bool {
+    let children = node.children();
+    node.tag == Tag::Sequence
+        && (1..=2).contains(&children.len())
+        && children[0].as_oid().map(|oid| is_content_type(&oid)).unwrap_or(false)
+        && children.get(1).map(|content| is_context(content, 0)).unwrap_or(true)
+}
+
+fn is_content_type(oid: &ObjectIdentifier) -> bool {
+    let dotted = oid.to_string();
+    CONTENT_TYPE_ARCS.iter().any(|arc| dotted.starts_with(arc))
+}
+
+pub(crate) fn label_content_info(node: &mut ASN1Node) {
+    node.set_label("ContentInfo");
+    let Some(content_type) = node.children()[0].as_oid() else {
+        return;
+    };
+    node.set_annotation(short_oid_name(&content_type));
+
+    let children = node.children_mut();
+    children[0].set_label("contentType");
+    let Some(content) = children.get_mut(1) else {
+        return;
+    };
+    content.set_label("content");
+    if let Some(inner) = content.children_mut().first_mut() {
+        label_content(&content_type, inner);
+    }
+}
+
+/// Labels the content of a `ContentInfo` or the encapsulated content of `SignedData`.
+fn label_content(content_type: &ObjectIdentifier, node: &mut ASN1Node) {
+    match *content_type {
+        ID_DATA if is_receipt(node) => label_receipt(node),
+        ID_DATA if is_content_info(node) => label_content_info(node),
+        ID_DATA => node.set_label("data"),
+        ID_SIGNED_DATA => label_signed_data(node),
+        ID_ENVELOPED_DATA => label_enveloped_data(node),
+        ID_DIGESTED_DATA => label_digested_data(node),
+        ID_ENCRYPTED_DATA => label_encrypted_data(node),
+        ID_CT_TST_INFO => label_tst_info(node),
+        _ => {}
+    }
+}
+
+fn label_signed_data(node: &mut ASN1Node) {
+    node.set_label("SignedData");
+    let mut certificates = 0;
+    let mut signers = 0;
+    let mut scep = None;
+    for (i, child) in node.children_mut().iter_mut().enumerate() {
+        match (i, child.tag) {
+            (0, _) => child.set_label("version"),
+            (1, _) => {
+                child.set_label("digestAlgorithms");
+                for algorithm in child.children_mut() {
+                    label_algorithm_identifier(algorithm, "digestAlgorithm");
+                }
+            }
+            (2, _) => label_encapsulated_content_info(child),
+            (_, Tag::ContextSpecific { number, .. }) if number.value() == 0 => {
+                child.set_label("certificates");
+                certificates = label_certificates(child);
+            }
+            (_, Tag::ContextSpecific { number, .. }) if number.value() == 1 => child.set_label("crls"),
+            (_, Tag::Set) => {
+                child.set_label("signerInfos");
+                for signer in child.children_mut() {
+                    signers += 1;
+                    label_signer_info(signer, signers);
+                    scep = scep.or_else(|| {
+                        let attributes = signer.children().iter().find(|field| field.label.as_deref() == Some("signedAttrs"));
+                        attributes.and_then(message_summary)
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    let summary = format!("{} certificate(s), {} signer(s)", certificates, signers);
+    match scep {
+        Some(scep) => node.set_annotation(format!("{}, {}", scep, summary)),
+        None => node.set_annotation(summary),
+    }
+}
+
+/// Labels embedded certificates as individually browsable items, returns their number.
+fn label_certificates(node: &mut ASN1Node) -> usize {
+    let mut count = 0;
+    for certificate in node.children_mut() {
+        if is_certificate(certificate) {
+            count += 1;
+            label_certificate(certificate);
+            certificate.set_label(&format!("certificate #{}", count));
+        }
+    }
+    count
+}
+
+/// `EncapsulatedContentInfo ::= SEQUENCE { eContentType, eContent [0] EXPLICIT OCTET STRING OPTIONAL }`
+fn label_encapsulated_content_info(node: &mut ASN1Node) {
+    node.set_label("encapContentInfo");
+    let content_type = node.children().first().and_then(ASN1Node::as_oid);
+    let children = node.children_mut();
+    if let Some(kind) = children.get_mut(0) {
+        kind.set_label("eContentType");
+    }
+    let Some(content) = children.get_mut(1) else {
+        return;
+    };
+    content.set_label("eContent");
+    let (Some(content_type), Some(octets)) = (content_type, content.children_mut().first_mut()) else {
+        return;
+    };
+    if let ASN1Value::OctetString(inner, _) = &mut octets.value {
+        label_content(&content_type, inner);
+        if inner.label.is_none() {
+            inner.set_label(&short_oid_name(&content_type));
+        }
+    }
+}
+
+fn label_signer_info(node: &mut ASN1Node, number: usize) {
+    node.set_label(&format!("signerInfo #{}", number));
+    let mut fields = ["version", "sid", "digestAlgorithm", "signatureAlgorithm", "signature"].iter();
+    let mut signer = None;
+    for child in node.children_mut() {
+        // signedAttrs [0] follows digestAlgorithm, unsignedAttrs [1] follows the signature
+        if is_context(child, 0) && fields.len() == 2 {
+            label_attributes(child, "signedAttrs");
+            continue;
+        }
+        if is_context(child, 1) && fields.len() == 0 {
+            label_attributes(child, "unsignedAttrs");
+            continue;
+        }
+        match fields.next().copied().unwrap_or_default() {
+            "" => {}
+            "sid" => signer = Some(label_signer_identifier(child)),
+            name if name.ends_with("Algorithm") => label_algorithm_identifier(child, name),
+            name => child.set_label(name),
+        }
+    }
+    if let Some(signer) = signer {
+        node.set_annotation(signer);
+    }
+}
+
+/// `SignerIdentifier ::= CHOICE { issuerAndSerialNumber, subjectKeyIdentifier [0] }`, returns a summary.
+pub(crate) fn label_signer_identifier(node: &mut ASN1Node) -> String {
+    if is_context(node, 0) {
+        node.set_label("subjectKeyIdentifier");
+        let key_id = implicit_octets(node).map(hex::encode).unwrap_or_default();
+        return format!("keyId {}", key_id);
+    }
+
+    node.set_label("issuerAndSerialNumber");
+    let children = node.children_mut();
+    if let Some(issuer) = children.get_mut(0) {
+        label_name(issuer, "issuer");
+    }
+    if let Some(serial) = children.get_mut(1) {
+        serial.set_label("serialNumber");
+    }
+    match node.children() {
+        [issuer, serial] => format!("{}, serial {}", format_name(issuer), serial.display_value()),
+        _ => String::new(),
+    }
+}
+
+/// Labels `SET OF Attribute` with attribute names and annotates well-known values.
+pub(crate) fn label_attributes(node: &mut ASN1Node, label: &str) {
+    node.set_label(label);
+    for attribute in node.children_mut() {
+        label_attribute(attribute);
+    }
+}
+
+/// `Attribute ::= SEQUENCE { attrType OBJECT IDENTIFIER, attrValues SET OF AttributeValue }`
+pub(crate) fn label_attribute(node: &mut ASN1Node) {
+    let Some(oid) = node.children().first().and_then(ASN1Node::as_oid) else {
+        return;
+    };
+    node.set_label(&short_oid_name(&oid));
+    let summary = node
+        .children()
+        .get(1)
+        .and_then(|values| values.children().first())
+        .and_then(|value| attribute_summary(&oid, value));
+    if let Some(summary) = summary {
+        node.set_annotation(summary);
+    }
+    let children = node.children_mut();
+    children[0].set_label("attrType");
+    if let Some(values) = children.get_mut(1) {
+        values.set_label("attrValues");
+    }
+}
+
+fn attribute_summary(kind: &ObjectIdentifier, value: &ASN1Node) -> Option<String> {
+    if let Some(oid) = value.as_oid() {
+        return Some(short_oid_name(&oid));
+    }
+    if let Some(text) = value.as_string() {
+        return Some(attribute_value_name(kind, &text).unwrap_or(text));
+    }
+    value.octets().map(hex::encode)
+}
+
+fn label_enveloped_data(node: &mut ASN1Node) {
+    node.set_label("EnvelopedData");
+    let mut recipients = 0;
+    for (i, child) in node.children_mut().iter_mut().enumerate() {
+        match child.tag {
+            _ if i == 0 => child.set_label("version"),
+            Tag::ContextSpecific { number, .. } if number.value() == 0 => child.set_label("originatorInfo"),
+            Tag::ContextSpecific { number, .. } if number.value() == 1 => label_attributes(child, "unprotectedAttrs"),
+            Tag::Set => {
+                child.set_label("recipientInfos");
+                for recipient in child.children_mut() {
+                    recipients += 1;
+                    label_recipient_info(recipient, recipients);
+                }
+            }
+            Tag::Sequence => label_encrypted_content_info(child),
+            _ => {}
+        }
+    }
+    node.set_annotation(format!("{} recipient(s)", recipients));
+}
+
+fn label_recipient_info(node: &mut ASN1Node, number: usize) {
+    // context tagged optional fields of kari and pwri are matched by tag, the rest by position
+    let (kind, fields): (&str, &[&str]) = match node.tag {
+        Tag::Sequence => ("ktri", &["version", "rid", "keyEncryptionAlgorithm", "encryptedKey"]),
+        Tag::ContextSpecific { number, .. } => match number.value() {
+            1 => ("kari", &["version", "keyEncryptionAlgorithm", "recipientEncryptedKeys"]),
+            2 => ("kekri", &["version", "kekid", "keyEncryptionAlgorithm", "encryptedKey"]),
+            3 => ("pwri", &["version", "keyEncryptionAlgorithm", "encryptedKey"]),
+            4 => ("ori", &["oriType", "oriValue"]),
+            _ => ("unknown", &[]),
+        },
+        _ => ("unknown", &[]),
+    };
+    node.set_label(&format!("recipientInfo #{}", number));
+    node.set_annotation(kind);
+
+    let mut names = fields.iter();
+    for child in node.children_mut() {
+        let name = match (kind, child.tag) {
+            ("kari", Tag::ContextSpecific { number, .. }) if number.value() == 0 => "originator",
+            ("kari", Tag::ContextSpecific { .. }) => "ukm",
+            ("pwri", Tag::ContextSpecific { .. }) => "keyDerivationAlgorithm",
+            _ => names.next().copied().unwrap_or_default(),
+        };
+        match name {
+            "" => {}
+            "rid" => {
+                label_signer_identifier(child);
+                child.set_label(name);
+            }
+            _ if name.ends_with("Algorithm") && child.tag == Tag::Sequence => label_algorithm_identifier(child, name),
+            _ => child.set_label(name),
+        }
+    }
+}
+
+/// `EncryptedContentInfo ::= SEQUENCE { contentType, contentEncryptionAlgorithm, encryptedContent [0] IMPLICIT OPTIONAL }`
+pub(crate) fn label_encrypted_content_info(node: &mut ASN1Node) {
+    node.set_label("encryptedContentInfo");
+    let children = node.children_mut();
+    if let Some(kind) = children.get_mut(0) {
+        kind.set_label("contentType");
+    }
+    if let Some(algorithm) = children.get_mut(1) {
+        let is_pbes2 = algorithm.children().first().and_then(ASN1Node::as_oid) == Some(PBES2);
+        label_algorithm_identifier(algorithm, "contentEncryptionAlgorithm");
+        if let Some(params) = algorithm.children_mut().get_mut(1).filter(|_| is_pbes2) {
+            label_pbes2_params(params);
+        }
+    }
+    if let Some(content) = children.get_mut(2) {
+        content.set_label("encryptedContent");
+    }
+}
+
+fn label_digested_data(node: &mut ASN1Node) {
+    node.set_label("DigestedData");
+    let children = node.children_mut();
+    if let [version, algorithm, content, digest, ..] = children {
+        version.set_label("version");
+        label_algorithm_identifier(algorithm, "digestAlgorithm");
+        label_encapsulated_content_info(content);
+        digest.set_label("digest");
+    }
+}
+
+fn label_encrypted_data(node: &mut ASN1Node) {
+    node.set_label("EncryptedData");
+    for (i, child) in node.children_mut().iter_mut().enumerate() {
+        match i {
+            0 => child.set_label("version"),
+            1 => label_encrypted_content_info(child),
+            _ if is_context(child, 1) => label_attributes(child, "unprotectedAttrs"),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::asn1_der::{parse_asn1, view_lines};
+    use crate::schema::{annotate, SchemaOptions};
+
+    fn annotated(data: &[u8]) -> Vec<String> {
+        let mut root = parse_asn1(data, 0).unwrap();
+        annotate(&mut root, &SchemaOptions::default());
+        view_lines(&root)
+    }
+
+    #[test]
+    fn labels_signed_data() {
+        let lines = annotated(include_bytes!("../../tests/data/signed.p7s"));
+        assert_eq!(lines[0], "ContentInfo: SEQUENCE (field(s): 2) (signedData)");
+        assert_eq!(lines[3], "SignedData: SEQUENCE (field(s): 5) (1 certificate(s), 1 signer(s))");
+        assert_eq!(lines[9], "eContentType: OBJECT IDENTIFIER 1.2.840.113549.1.7.1 (id-data)");
+        assert_eq!(lines[13], "certificate #1: SEQUENCE (field(s): 3) (CN=Signer, O=Example)");
+        assert_eq!(
+            lines[68],
+            "signerInfo #1: SEQUENCE (field(s): 6) (CN=Signer, O=Example, serial 0x75822cc7b107a1d10fb40fa23349e2c035d27cd0)"
+        );
+        assert_eq!(lines[70], "issuerAndSerialNumber: SEQUENCE (field(s): 2)");
+        assert_eq!(lines[83], "signedAttrs: CONTEXT-SPECIFIC [0] (constructed) ");
+    }
+
+    #[test]
+    fn labels_signer_attributes() {
+        let lines = annotated(include_bytes!("../../tests/data/signed.p7s"));
+        assert_eq!(lines[84], "contentType: SEQUENCE (field(s): 2) (data)");
+        assert_eq!(lines[88], "signingTime: SEQUENCE (field(s): 2) (2026-10-18T20:24:30Z)");
+        assert_eq!(
+            lines[92],
+            "messageDigest: SEQUENCE (field(s): 2) (cd2eca3535741f27a8ae40c31b0c41d4057a7a7b912b33b9aed86485d1c84676)"
+        );
+        assert_eq!(lines[96], "smimeCapabilities: SEQUENCE (field(s): 2)");
+    }
+
+    #[test]
+    fn labels_enveloped_data() {
+        let lines = annotated(include_bytes!("../../tests/data/enveloped.p7m"));
+        assert_eq!(lines[0], "ContentInfo: SEQUENCE (field(s): 2) (envelopedData)");
+        assert_eq!(lines[3], "EnvelopedData: SEQUENCE (field(s): 3) (1 recipient(s))");
+        assert_eq!(lines[6], "recipientInfo #1: SEQUENCE (field(s): 4) (ktri)");
+        assert_eq!(lines[9], "issuer: SEQUENCE (field(s): 2) (CN=Test CA, O=Example)");
+        assert_eq!(lines[20], "algorithm: OBJECT IDENTIFIER 1.2.840.113549.1.1.1 (rsaEncryption)");
+        assert_eq!(lines[23], "encryptedContentInfo: SEQUENCE (field(s): 3)");
+        assert_eq!(lines[27], "parameters: OCTET STRING 1c8e637c8d28701cc60d6d2363fb7eec");
+    }
+}
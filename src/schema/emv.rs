@@ -0,0 +1,443 @@
+use crate::asn1_der::{ASN1Node, ASN1Value};
+use crate::schema::{hide_secret, SchemaOptions};
+
+/// EMV Book 3 Annex A data elements.
+const EMV_TAGS: &[(u32, &str)] = &[
+    (0x42, "Issuer Identification Number (IIN)"),
+    (0x4F, "Application Identifier (AID)"),
+    (0x50, "Application Label"),
+    (0x56, "Track 1 Data"),
+    (0x57, "Track 2 Equivalent Data"),
+    (0x5A, "Application PAN"),
+    (0x5F20, "Cardholder Name"),
+    (0x5F24, "Application Expiration Date"),
+    (0x5F25, "Application Effective Date"),
+    (0x5F28, "Issuer Country Code"),
+    (0x5F2A, "Transaction Currency Code"),
+    (0x5F2D, "Language Preference"),
+    (0x5F30, "Service Code"),
+    (0x5F34, "Application PAN Sequence Number"),
+    (0x5F36, "Transaction Currency Exponent"),
+    (0x5F50, "Issuer URL"),
+    (0x61, "Application Template"),
+    (0x6F, "FCI Template"),
+    (0x70, "READ RECORD Response Message Template"),
+    (0x71, "Issuer Script Template 1"),
+    (0x72, "Issuer Script Template 2"),
+    (0x73, "Directory Discretionary Template"),
+    (0x77, "Response Message Template Format 2"),
+    (0x80, "Response Message Template Format 1"),
+    (0x81, "Amount, Authorised (Binary)"),
+    (0x82, "Application Interchange Profile"),
+    (0x83, "Command Template"),
+    (0x84, "Dedicated File (DF) Name"),
+    (0x86, "Issuer Script Command"),
+    (0x87, "Application Priority Indicator"),
+    (0x88, "Short File Identifier (SFI)"),
+    (0x89, "Authorisation Code"),
+    (0x8A, "Authorisation Response Code"),
+    (0x8C, "CDOL1"),
+    (0x8D, "CDOL2"),
+    (0x8E, "CVM List"),
+    (0x8F, "Certification Authority Public Key Index"),
+    (0x90, "Issuer Public Key Certificate"),
+    (0x91, "Issuer Authentication Data"),
+    (0x92, "Issuer Public Key Remainder"),
+    (0x93, "Signed Static Application Data"),
+    (0x94, "Application File Locator (AFL)"),
+    (0x95, "Terminal Verification Results"),
+    (0x97, "TDOL"),
+    (0x98, "Transaction Certificate (TC) Hash Value"),
+    (0x99, "Transaction PIN Data"),
+    (0x9A, "Transaction Date"),
+    (0x9B, "Transaction Status Information"),
+    (0x9C, "Transaction Type"),
+    (0x9D, "Directory Definition File (DDF) Name"),
+    (0x9F01, "Acquirer Identifier"),
+    (0x9F02, "Amount, Authorised (Numeric)"),
+    (0x9F03, "Amount, Other (Numeric)"),
+    (0x9F04, "Amount, Other (Binary)"),
+    (0x9F05, "Application Discretionary Data"),
+    (0x9F06, "Application Identifier (AID) - terminal"),
+    (0x9F07, "Application Usage Control"),
+    (0x9F08, "Application Version Number"),
+    (0x9F09, "Application Version Number - terminal"),
+    (0x9F0B, "Cardholder Name Extended"),
+    (0x9F0D, "Issuer Action Code - Default"),
+    (0x9F0E, "Issuer Action Code - Denial"),
+    (0x9F0F, "Issuer Action Code - Online"),
+    (0x9F10, "Issuer Application Data"),
+    (0x9F11, "Issuer Code Table Index"),
+    (0x9F12, "Application Preferred Name"),
+    (0x9F13, "Last Online ATC Register"),
+    (0x9F14, "Lower Consecutive Offline Limit"),
+    (0x9F15, "Merchant Category Code"),
+    (0x9F16, "Merchant Identifier"),
+    (0x9F17, "PIN Try Counter"),
+    (0x9F18, "Issuer Script Identifier"),
+    (0x9F1A, "Terminal Country Code"),
+    (0x9F1B, "Terminal Floor Limit"),
+    (0x9F1C, "Terminal Identification"),
+    (0x9F1D, "Terminal Risk Management Data"),
+    (0x9F1E, "Interface Device (IFD) Serial Number"),
+    (0x9F1F, "Track 1 Discretionary Data"),
+    (0x9F20, "Track 2 Discretionary Data"),
+    (0x9F21, "Transaction Time"),
+    (0x9F22, "Certification Authority Public Key Index - terminal"),
+    (0x9F23, "Upper Consecutive Offline Limit"),
+    (0x9F26, "Application Cryptogram"),
+    (0x9F27, "Cryptogram Information Data"),
+    (0x9F2D, "ICC PIN Encipherment Public Key Certificate"),
+    (0x9F2E, "ICC PIN Encipherment Public Key Exponent"),
+    (0x9F2F, "ICC PIN Encipherment Public Key Remainder"),
+    (0x9F32, "Issuer Public Key Exponent"),
+    (0x9F33, "Terminal Capabilities"),
+    (0x9F34, "CVM Results"),
+    (0x9F35, "Terminal Type"),
+    (0x9F36, "Application Transaction Counter (ATC)"),
+    (0x9F37, "Unpredictable Number"),
+    (0x9F38, "PDOL"),
+    (0x9F39, "POS Entry Mode"),
+    (0x9F3A, "Amount, Reference Currency"),
+    (0x9F3B, "Application Reference Currency"),
+    (0x9F3C, "Transaction Reference Currency Code"),
+    (0x9F3D, "Transaction Reference Currency Exponent"),
+    (0x9F40, "Additional Terminal Capabilities"),
+    (0x9F41, "Transaction Sequence Counter"),
+    (0x9F42, "Application Currency Code"),
+    (0x9F43, "Application Reference Currency Exponent"),
+    (0x9F44, "Application Currency Exponent"),
+    (0x9F45, "Data Authentication Code"),
+    (0x9F46, "ICC Public Key Certificate"),
+    (0x9F47, "ICC Public Key Exponent"),
+    (0x9F48, "ICC Public Key Remainder"),
+    (0x9F49, "DDOL"),
+    (0x9F4A, "Static Data Authentication Tag List"),
+    (0x9F4B, "Signed Dynamic Application Data"),
+    (0x9F4C, "ICC Dynamic Number"),
+    (0x9F4D, "Log Entry"),
+    (0x9F4E, "Merchant Name and Location"),
+    (0x9F4F, "Log Format"),
+    (0x9F66, "Terminal Transaction Qualifiers (TTQ)"),
+    (0x9F6B, "Track 2 Data"),
+    (0x9F6C, "Card Transaction Qualifiers (CTQ)"),
+    (0x9F6E, "Form Factor Indicator"),
+    (0xA5, "FCI Proprietary Template"),
+    (0xBF0C, "FCI Issuer Discretionary Data"),
+];
+
+/// Registered application provider identifiers, the first five bytes of an AID.
+const RIDS: &[([u8; 5], &str)] = &[
+    ([0xA0, 0x00, 0x00, 0x00, 0x03], "Visa"),
+    ([0xA0, 0x00, 0x00, 0x00, 0x04], "Mastercard"),
+    ([0xA0, 0x00, 0x00, 0x00, 0x25], "American Express"),
+    ([0xA0, 0x00, 0x00, 0x00, 0x65], "JCB"),
+    ([0xA0, 0x00, 0x00, 0x01, 0x52], "Discover"),
+    ([0xA0, 0x00, 0x00, 0x02, 0x77], "Interac"),
+    ([0xA0, 0x00, 0x00, 0x03, 0x33], "UnionPay"),
+];
+
+/// ISO 4217 numeric codes of common transaction currencies.
+const CURRENCIES: &[(u16, &str)] = &[
+    (36, "AUD"),
+    (124, "CAD"),
+    (156, "CNY"),
+    (392, "JPY"),
+    (643, "RUB"),
+    (752, "SEK"),
+    (756, "CHF"),
+    (826, "GBP"),
+    (840, "USD"),
+    (978, "EUR"),
+    (985, "PLN"),
+];
+
+const AIP_BITS: [(u8, &str); 7] = [
+    (0x40, "SDA"),
+    (0x20, "DDA"),
+    (0x10, "cardholder verification"),
+    (0x08, "terminal risk management"),
+    (0x04, "issuer authentication"),
+    (0x02, "on device cardholder verification"),
+    (0x01, "CDA"),
+];
+
+/// Tag names loaded from user-provided dictionary files, taking precedence over the EMV names.
+#[derive(Clone, Debug, Default)]
+pub struct TagDictionary {
+    names: Vec<(u32, String)>,
+}
+
+impl TagDictionary {
+    /// Reads lines holding a hex tag followed by its name, such as `9F02 Amount, Authorised`.
+    pub(crate) fn load(&mut self, text: &str) {
+        for line in text.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#')) {
+            let (tag, name) = line.split_once(|c: char| c.is_whitespace() || c == ',' || c == '=').unwrap_or((line, ""));
+            let name = name.trim_start_matches(|c: char| c.is_whitespace() || c == ',' || c == '=');
+            if let (Ok(tag), false) = (u32::from_str_radix(tag, 16), name.is_empty()) {
+                self.names.push((tag, name.to_string()));
+            }
+        }
+    }
+
+    fn lookup(&self, tag: u32) -> Option<&str> {
+        self.names.iter().rev().find(|(known, _)| *known == tag).map(|(_, name)| name.as_str())
+    }
+}
+
+/// Names BER-TLV data objects from the tag dictionaries and decodes well-known EMV values.
+pub(crate) fn recognize(node: &mut ASN1Node, options: &SchemaOptions) -> bool {
+    let Some(tag) = node.tlv_tag else {
+        return false;
+    };
+    if let Some(name) = tag_name(tag, options) {
+        node.set_label(name);
+    }
+    if let ASN1Value::Bytes(bytes) = &node.value {
+        if let Some(annotation) = decode_value(tag, bytes, options) {
+            node.set_annotation(annotation);
+        }
+        if matches!(tag, 0x56 | 0x57 | 0x5A | 0x9F1F | 0x9F20 | 0x9F6B) {
+            hide_secret(node, options);
+        }
+    }
+    for child in node.children_mut() {
+        recognize(child, options);
+    }
+    true
+}
+
+fn tag_name(tag: u32, options: &SchemaOptions) -> Option<&str> {
+    options.tag_dictionary
+        .lookup(tag)
+        .or_else(|| EMV_TAGS.iter().find(|(known, _)| *known == tag).map(|(_, name)| *name))
+}
+
+fn decode_value(tag: u32, bytes: &[u8], options: &SchemaOptions) -> Option<String> {
+    match tag {
+        0x50 | 0x5F20 | 0x5F2D | 0x5F50 | 0x9F12 | 0x9F4E => text(bytes),
+        0x4F | 0x84 | 0x9F06 => {
+            let rid = RIDS.iter().find(|(rid, _)| bytes.starts_with(rid)).map(|(_, name)| *name)?;
+            Some(rid.to_string())
+        }
+        0x5A => Some(mask_pan(&bcd(bytes), options)),
+        0x57 | 0x9F6B => {
+            let track = bcd(bytes);
+            let (pan, rest) = track.split_once('d')?;
+            Some(format!("{}={}", mask_pan(pan, options), rest.get(..4).map(expiry).unwrap_or_default()))
+        }
+        0x5F24 | 0x5F25 | 0x9A => {
+            let date = bcd(bytes);
+            (date.len() == 6).then(|| format!("20{}-{}-{}", &date[..2], &date[2..4], &date[4..]))
+        }
+        0x9F21 => {
+            let time = bcd(bytes);
+            (time.len() == 6).then(|| format!("{}:{}:{}", &time[..2], &time[2..4], &time[4..]))
+        }
+        0x9F02 | 0x9F03 => {
+            let amount = bcd(bytes).trim_start_matches('0').to_string();
+            let amount = format!("{:0>3}", amount);
+            Some(format!("{}.{}", &amount[..amount.len() - 2], &amount[amount.len() - 2..]))
+        }
+        0x81 | 0x9F04 | 0x9F36 | 0x9F13 | 0x9F17 | 0x5F34 | 0x9F41 => {
+            (bytes.len() <= 8).then(|| bytes.iter().fold(0u64, |value, &byte| value << 8 | byte as u64).to_string())
+        }
+        0x5F2A | 0x9F42 | 0x5F28 | 0x9F1A | 0x9F3C => {
+            let code: u16 = bcd(bytes).parse().ok()?;
+            let currency = matches!(tag, 0x5F2A | 0x9F42 | 0x9F3C);
+            let name = CURRENCIES.iter().find(|(known, _)| currency && *known == code).map(|(_, name)| *name);
+            Some(name.map(|name| format!("{} {}", code, name)).unwrap_or_else(|| code.to_string()))
+        }
+        0x9C => match bytes {
+            [0x00] => Some("purchase".to_string()),
+            [0x01] => Some("cash".to_string()),
+            [0x09] => Some("purchase with cashback".to_string()),
+            [0x20] => Some("refund".to_string()),
+            _ => None,
+        },
+        0x82 => {
+            let first = *bytes.first()?;
+            let bits: Vec<&str> = AIP_BITS.iter().filter(|(bit, _)| first & bit != 0).map(|(_, name)| *name).collect();
+            Some(bits.join(", "))
+        }
+        0x8E => cvm_list(bytes),
+        0x94 => {
+            let entries: Vec<String> = bytes
+                .chunks_exact(4)
+                .map(|entry| format!("SFI {} records {}-{}, {} for ODA", entry[0] >> 3, entry[1], entry[2], entry[3]))
+                .collect();
+            Some(entries.join("; "))
+        }
+        0x8C | 0x8D | 0x97 | 0x9F38 | 0x9F49 => dol(bytes, options),
+        0x9F27 => Some(match bytes.first()? & 0xc0 {
+            0x00 => "AAC",
+            0x40 => "TC",
+            0x80 => "ARQC",
+            _ => "RFU",
+        }.to_string()),
+        _ => None,
+    }
+}
+
+/// Decodes packed BCD digits, the `F` padding nibbles are dropped.
+fn bcd(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .flat_map(|&byte| [byte >> 4, byte & 0x0f])
+        .filter(|&digit| digit != 0x0f)
+        .map(|digit| char::from_digit(digit as u32, 16).unwrap_or('?'))
+        .collect()
+}
+
+/// Masks all but the first six and the last four digits of a primary account number, shorter
+/// numbers keep only their last four digits and numbers of four digits or less are masked fully.
+fn mask_pan(pan: &str, options: &SchemaOptions) -> String {
+    match pan.len() {
+        _ if options.show_secrets => pan.to_string(),
+        0..=4 => "*".repeat(pan.len()),
+        5..=10 => format!("{}{}", "*".repeat(pan.len() - 4), &pan[pan.len() - 4..]),
+        _ => format!("{}{}{}", &pan[..6], "*".repeat(pan.len() - 10), &pan[pan.len() - 4..]),
+    }
+}
+
+fn expiry(date: &str) -> String {
+    format!("expires 20{}-{}", &date[..2], &date[2..])
+}
+
+fn text(bytes: &[u8]) -> Option<String> {
+    let text = std::str::from_utf8(bytes).ok()?;
+    (!text.chars().any(char::is_control)).then(|| text.to_string())
+}
+
+/// Decodes a `CVM List`: amounts X and Y followed by two byte cardholder verification rules.
+fn cvm_list(bytes: &[u8]) -> Option<String> {
+    let rules = bytes.get(8..)?;
+    let rules: Vec<String> = rules
+        .chunks_exact(2)
+        .map(|rule| {
+            let method = match rule[0] & 0x3f {
+                0x00 => "fail CVM".to_string(),
+                0x01 => "plaintext PIN by ICC".to_string(),
+                0x02 => "enciphered PIN online".to_string(),
+                0x03 => "plaintext PIN by ICC and signature".to_string(),
+                0x04 => "enciphered PIN by ICC".to_string(),
+                0x05 => "enciphered PIN by ICC and signature".to_string(),
+                0x1e => "signature".to_string(),
+                0x1f => "no CVM required".to_string(),
+                other => format!("method {:02X}", other),
+            };
+            let condition = match rule[1] {
+                0x00 => "always".to_string(),
+                0x01 => "if unattended cash".to_string(),
+                0x02 => "if not unattended cash, manual cash or cashback".to_string(),
+                0x03 => "if terminal supports the CVM".to_string(),
+                0x04 => "if manual cash".to_string(),
+                0x05 => "if purchase with cashback".to_string(),
+                0x06 => "if under X".to_string(),
+                0x07 => "if over X".to_string(),
+                0x08 => "if under Y".to_string(),
+                0x09 => "if over Y".to_string(),
+                other => format!("condition {:02X}", other),
+            };
+            let next = if rule[0] & 0x40 != 0 { ", else next" } else { "" };
+            format!("{} {}{}", method, condition, next)
+        })
+        .collect();
+    Some(rules.join("; "))
+}
+
+/// Decodes a data object list of tags and lengths such as the `PDOL`.
+fn dol(bytes: &[u8], options: &SchemaOptions) -> Option<String> {
+    let mut entries = Vec::new();
+    let mut rest = bytes;
+    while let Some(&first) = rest.first() {
+        let identifier = match first & 0x1f {
+            0x1f => 2 + rest[1..].iter().take_while(|&&byte| byte & 0x80 != 0).count(),
+            _ => 1,
+        };
+        let length = *rest.get(identifier)?;
+        let tag = rest[..identifier].iter().fold(0u32, |tag, &byte| tag << 8 | byte as u32);
+        entries.push(match tag_name(tag, options) {
+            Some(name) => format!("{:02X} {} ({})", tag, name, length),
+            None => format!("{:02X} ({})", tag, length),
+        });
+        rest = &rest[identifier + 1..];
+    }
+    Some(entries.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asn1_der::{parse_tlv_records, view_lines};
+
+    fn recognized(data: &str, options: &SchemaOptions) -> Vec<String> {
+        let mut records = parse_tlv_records(&hex::decode(data).unwrap()).unwrap();
+        records
+            .iter_mut()
+            .flat_map(|record| {
+                recognize(record, options);
+                view_lines(record)
+            })
+            .collect()
+    }
+
+    const FCI: &str = concat!(
+        "6f4c8407a0000000031010a541500b56495341204352454449548701019f38189f66049f02069f03069f1a0295055f2a",
+        "029a039c019f37045f2d04656e6465bf0c0c9f5a053108400840df0101aa9000",
+    );
+    const RECORD: &str = concat!(
+        "70615a0847617390010101195f24032512315f25031901015f280208405f3401018e0e000000000000000042031e03",
+        "1f0357104761739001010119d25122011234560f5f200f43415244484f4c4445522f56495341940808010100100103",
+        "00820239009000",
+    );
+
+    #[test]
+    fn names_data_objects() {
+        let lines = recognized(FCI, &SchemaOptions::default());
+        assert_eq!(lines.len(), 11);
+        assert_eq!(lines[1], "Dedicated File (DF) Name: 84 a0000000031010 (Visa)");
+        assert_eq!(lines[3], "Application Label: 50 5649534120435245444954 (VISA CREDIT)");
+        assert!(lines[5].starts_with("PDOL: 9F38 9f66049f02069f03069f1a0295055f2a029a039c019f3704 (9F66 Terminal Transaction Qualifiers (TTQ) (4), "));
+        assert_eq!(lines[8..], ["9F5A 3108400840", "DF01 aa", "SW1-SW2: OCTET STRING 9000 (success)"]);
+
+        let lines = recognized(RECORD, &SchemaOptions::default());
+        assert_eq!(lines[2], "Application Expiration Date: 5F24 251231 (2025-12-31)");
+        assert_eq!(lines[6], "CVM List: 8E 000000000000000042031e031f03 (enciphered PIN online if terminal supports the CVM, else next; signature if terminal supports the CVM; no CVM required if terminal supports the CVM)");
+        assert_eq!(lines[10], "Application Interchange Profile: 82 3900 (DDA, cardholder verification, terminal risk management, CDA)");
+    }
+
+    #[test]
+    fn names_tags_from_dictionaries() {
+        let mut options = SchemaOptions::default();
+        options.tag_dictionary.load("# tag names\n9F5A Application Program Identifier\n\nDF01, Custom tag\n5F2D=Languages\nzz Ignored\n");
+        let lines = recognized(FCI, &options);
+        assert_eq!(lines[6], "Languages: 5F2D 656e6465 (ende)");
+        assert_eq!(lines[8..10], ["Application Program Identifier: 9F5A 3108400840", "Custom tag: DF01 aa"]);
+    }
+
+    #[test]
+    fn masks_account_numbers() {
+        let lines = recognized(RECORD, &SchemaOptions::default());
+        assert_eq!(lines[1], "Application PAN: 5A <redacted, 8 bytes> (476173******0119)");
+        assert_eq!(lines[7], "Track 2 Equivalent Data: 57 <redacted, 16 bytes> (476173******0119=expires 2025-12)");
+
+        let options = SchemaOptions { show_secrets: true, ..SchemaOptions::default() };
+        let lines = recognized(RECORD, &options);
+        assert_eq!(lines[1], "Application PAN: 5A 4761739001010119 (4761739001010119)");
+        assert_eq!(lines[7], "Track 2 Equivalent Data: 57 4761739001010119d25122011234560f (4761739001010119=expires 2025-12)");
+    }
+
+    #[test]
+    fn masks_short_account_numbers() {
+        let options = SchemaOptions::default();
+        assert_eq!(mask_pan("4761739001010119", &options), "476173******0119");
+        assert_eq!(mask_pan("12345678901", &options), "123456*8901");
+        assert_eq!(mask_pan("1234567890", &options), "******7890");
+        assert_eq!(mask_pan("12345", &options), "*2345");
+        assert_eq!(mask_pan("1234", &options), "****");
+        assert_eq!(mask_pan("", &options), "");
+        let track = hex::decode("1234567890d2512f").unwrap();
+        assert_eq!(decode_value(0x57, &track, &options).as_deref(), Some("******7890=expires 2025-12"));
+    }
+}
@@ -0,0 +1,527 @@
+use der::Tag;
+use crate::asn1_der::{ASN1Node, ASN1Value};
+use crate::schema::{flag_names, hide_secret, is_context, signed_integer, SchemaOptions};
+
+/// Field names of the RFC 4120 structures, indexed by their context-specific tag number.
+const TICKET: &[&str] = &["tkt-vno", "realm", "sname", "enc-part"];
+const AUTHENTICATOR: &[&str] = &[
+    "authenticator-vno", "crealm", "cname", "cksum", "cusec", "ctime", "subkey", "seq-number", "authorization-data",
+];
+const ENC_TICKET_PART: &[&str] = &[
+    "flags", "key", "crealm", "cname", "transited", "authtime", "starttime", "endtime", "renew-till", "caddr",
+    "authorization-data",
+];
+const KDC_REQ: &[&str] = &["", "pvno", "msg-type", "padata", "req-body"];
+const KDC_REQ_BODY: &[&str] = &[
+    "kdc-options", "cname", "realm", "sname", "from", "till", "rtime", "nonce", "etype", "addresses",
+    "enc-authorization-data", "additional-tickets",
+];
+const KDC_REP: &[&str] = &["pvno", "msg-type", "padata", "crealm", "cname", "ticket", "enc-part"];
+const ENC_KDC_REP_PART: &[&str] = &[
+    "key", "last-req", "nonce", "key-expiration", "flags", "authtime", "starttime", "endtime", "renew-till",
+    "srealm", "sname", "caddr", "encrypted-pa-data",
+];
+const AP_REQ: &[&str] = &["pvno", "msg-type", "ap-options", "ticket", "authenticator"];
+const AP_REP: &[&str] = &["pvno", "msg-type", "enc-part"];
+const ENC_AP_REP_PART: &[&str] = &["ctime", "cusec", "subkey", "seq-number"];
+const KRB_SAFE: &[&str] = &["pvno", "msg-type", "safe-body", "cksum"];
+const KRB_SAFE_BODY: &[&str] = &["user-data", "timestamp", "usec", "seq-number", "s-address", "r-address"];
+const KRB_PRIV: &[&str] = &["pvno", "msg-type", "", "enc-part"];
+const KRB_CRED: &[&str] = &["pvno", "msg-type", "tickets", "enc-part"];
+const ENC_KRB_PRIV_PART: &[&str] = &["user-data", "timestamp", "usec", "seq-number", "s-address", "r-address"];
+const ENC_KRB_CRED_PART: &[&str] = &["ticket-info", "nonce", "timestamp", "usec", "s-address", "r-address"];
+const KRB_ERROR: &[&str] = &[
+    "pvno", "msg-type", "ctime", "cusec", "stime", "susec", "error-code", "crealm", "cname", "realm", "sname",
+    "e-text", "e-data",
+];
+const PRINCIPAL_NAME: &[&str] = &["name-type", "name-string"];
+const ENCRYPTED_DATA: &[&str] = &["etype", "kvno", "cipher"];
+const ENCRYPTION_KEY: &[&str] = &["keytype", "keyvalue"];
+const CHECKSUM: &[&str] = &["cksumtype", "checksum"];
+const PA_DATA: &[&str] = &["", "padata-type", "padata-value"];
+const HOST_ADDRESS: &[&str] = &["addr-type", "address"];
+const AUTHORIZATION_DATA: &[&str] = &["ad-type", "ad-data"];
+const TRANSITED_ENCODING: &[&str] = &["tr-type", "contents"];
+const LAST_REQ: &[&str] = &["lr-type", "lr-value"];
+const ETYPE_INFO_ENTRY: &[&str] = &["etype", "salt", "s2kparams"];
+const PA_PAC_REQUEST: &[&str] = &["include-pac"];
+const PA_FOR_USER: &[&str] = &["userName", "userRealm", "cksum", "auth-package"];
+
+const ENCRYPTION_TYPES: &[(i128, &str)] = &[
+    (1, "des-cbc-crc"),
+    (2, "des-cbc-md4"),
+    (3, "des-cbc-md5"),
+    (16, "des3-cbc-sha1-kd"),
+    (17, "aes128-cts-hmac-sha1-96"),
+    (18, "aes256-cts-hmac-sha1-96"),
+    (19, "aes128-cts-hmac-sha256-128"),
+    (20, "aes256-cts-hmac-sha384-192"),
+    (23, "rc4-hmac"),
+    (24, "rc4-hmac-exp"),
+    (25, "camellia128-cts-cmac"),
+    (26, "camellia256-cts-cmac"),
+    // Microsoft private types, sent by Windows clients
+    (-128, "rc4-md4"),
+    (-133, "rc4-hmac-old"),
+    (-135, "rc4-hmac-old-exp"),
+];
+
+const CHECKSUM_TYPES: &[(i128, &str)] = &[
+    (1, "CRC32"),
+    (2, "rsa-md4"),
+    (3, "rsa-md4-des"),
+    (7, "rsa-md5"),
+    (8, "rsa-md5-des"),
+    (12, "hmac-sha1-des3-kd"),
+    (15, "hmac-sha1-96-aes128"),
+    (16, "hmac-sha1-96-aes256"),
+    (19, "hmac-sha256-128-aes128"),
+    (20, "hmac-sha384-192-aes256"),
+    (-138, "hmac-md5"),
+];
+
+const NAME_TYPES: &[(i128, &str)] = &[
+    (0, "NT-UNKNOWN"),
+    (1, "NT-PRINCIPAL"),
+    (2, "NT-SRV-INST"),
+    (3, "NT-SRV-HST"),
+    (4, "NT-SRV-XHST"),
+    (5, "NT-UID"),
+    (6, "NT-X500-PRINCIPAL"),
+    (7, "NT-SMTP-NAME"),
+    (10, "NT-ENTERPRISE"),
+    (11, "NT-WELLKNOWN"),
+];
+
+const PA_DATA_TYPES: &[(i128, &str)] = &[
+    (1, "PA-TGS-REQ"),
+    (2, "PA-ENC-TIMESTAMP"),
+    (3, "PA-PW-SALT"),
+    (11, "PA-ETYPE-INFO"),
+    (16, "PA-PK-AS-REQ"),
+    (17, "PA-PK-AS-REP"),
+    (19, "PA-ETYPE-INFO2"),
+    (128, "PA-PAC-REQUEST"),
+    (129, "PA-FOR-USER"),
+    (133, "PA-FX-COOKIE"),
+    (136, "PA-FX-FAST"),
+    (137, "PA-FX-ERROR"),
+    (138, "PA-ENCRYPTED-CHALLENGE"),
+    (149, "PA-REQ-ENC-PA-REP"),
+    (165, "PA-SUPPORTED-ENCTYPES"),
+];
+
+const ERROR_CODES: &[(i128, &str)] = &[
+    (0, "KDC_ERR_NONE"),
+    (6, "KDC_ERR_C_PRINCIPAL_UNKNOWN"),
+    (7, "KDC_ERR_S_PRINCIPAL_UNKNOWN"),
+    (12, "KDC_ERR_POLICY"),
+    (14, "KDC_ERR_ETYPE_NOSUPP"),
+    (18, "KDC_ERR_CLIENT_REVOKED"),
+    (23, "KDC_ERR_KEY_EXPIRED"),
+    (24, "KDC_ERR_PREAUTH_FAILED"),
+    (25, "KDC_ERR_PREAUTH_REQUIRED"),
+    (31, "KRB_AP_ERR_BAD_INTEGRITY"),
+    (32, "KRB_AP_ERR_TKT_EXPIRED"),
+    (34, "KRB_AP_ERR_REPEAT"),
+    (37, "KRB_AP_ERR_SKEW"),
+    (41, "KRB_AP_ERR_MODIFIED"),
+    (52, "KRB_ERR_RESPONSE_TOO_BIG"),
+    (60, "KRB_ERR_GENERIC"),
+    (68, "KDC_ERR_WRONG_REALM"),
+];
+
+const TICKET_FLAGS: &[&str] = &[
+    "reserved", "forwardable", "forwarded", "proxiable", "proxy", "may-postdate", "postdated", "invalid",
+    "renewable", "initial", "pre-authent", "hw-authent", "transited-policy-checked", "ok-as-delegate", "",
+    "name-canonicalize",
+];
+const KDC_OPTIONS: &[&str] = &[
+    "reserved", "forwardable", "forwarded", "proxiable", "proxy", "allow-postdate", "postdated", "", "renewable",
+    "", "", "opt-hardware-auth", "", "", "request-anonymous", "canonicalize", "", "", "", "", "", "", "", "", "", "",
+    "disable-transited-check", "renewable-ok", "enc-tkt-in-skey", "", "renew", "validate",
+];
+const AP_OPTIONS: &[&str] = &["reserved", "use-session-key", "mutual-required"];
+
+/// Bits of the `PA-SUPPORTED-ENCTYPES` little-endian mask (MS-KILE 2.2.7).
+const SUPPORTED_ENCTYPE_BITS: &[&str] = &["des-cbc-crc", "des-cbc-md5", "rc4-hmac", "aes128-cts-hmac-sha1-96", "aes256-cts-hmac-sha1-96", "aes256-sk"];
+
+/// Recognizes a Kerberos (RFC 4120) message or a bare `METHOD-DATA` at the node.
+pub(crate) fn recognize(node: &mut ASN1Node, options: &SchemaOptions) -> bool {
+    if application_fields(node).is_some() {
+        label_message(node, options);
+        true
+    } else if is_method_data(node) {
+        node.set_label("METHOD-DATA");
+        label_padata_list(node, options);
+        true
+    } else {
+        false
+    }
+}
+
+/// Returns the name and the fields of a `[APPLICATION n]` Kerberos message.
+fn application_fields(node: &ASN1Node) -> Option<(&'static str, &'static [&'static str])> {
+    let Tag::Application { number, .. } = node.tag else {
+        return None;
+    };
+    if !matches!(node.children(), [inner] if inner.tag == Tag::Sequence) {
+        return None;
+    }
+    Some(match number.value() {
+        1 => ("Ticket", TICKET),
+        2 => ("Authenticator", AUTHENTICATOR),
+        3 => ("EncTicketPart", ENC_TICKET_PART),
+        10 => ("AS-REQ", KDC_REQ),
+        11 => ("AS-REP", KDC_REP),
+        12 => ("TGS-REQ", KDC_REQ),
+        13 => ("TGS-REP", KDC_REP),
+        14 => ("AP-REQ", AP_REQ),
+        15 => ("AP-REP", AP_REP),
+        20 => ("KRB-SAFE", KRB_SAFE),
+        21 => ("KRB-PRIV", KRB_PRIV),
+        22 => ("KRB-CRED", KRB_CRED),
+        25 => ("EncASRepPart", ENC_KDC_REP_PART),
+        26 => ("EncTGSRepPart", ENC_KDC_REP_PART),
+        27 => ("EncAPRepPart", ENC_AP_REP_PART),
+        28 => ("EncKrbPrivPart", ENC_KRB_PRIV_PART),
+        29 => ("EncKrbCredPart", ENC_KRB_CRED_PART),
+        30 => ("KRB-ERROR", KRB_ERROR),
+        _ => return None,
+    })
+}
+
+/// `METHOD-DATA ::= SEQUENCE OF PA-DATA`
+fn is_method_data(node: &ASN1Node) -> bool {
+    node.tag == Tag::Sequence && !node.children().is_empty() && node.children().iter().all(is_pa_data)
+}
+
+/// `PA-DATA ::= SEQUENCE { padata-type [1] Int32, padata-value [2] OCTET STRING }`
+fn is_pa_data(node: &ASN1Node) -> bool {
+    match node.children() {
+        [kind, value] => {
+            node.tag == Tag::Sequence
+                && is_context(kind, 1)
+                && is_context(value, 2)
+                && kind.children().first().map(|kind| kind.tag == Tag::Integer).unwrap_or(false)
+                && value.children().first().map(|value| value.tag == Tag::OctetString).unwrap_or(false)
+        }
+        _ => false,
+    }
+}
+
+/// Labels an application tagged message and returns a one line summary of it.
+fn label_message(node: &mut ASN1Node, options: &SchemaOptions) -> Option<String> {
+    let (name, fields) = application_fields(node)?;
+    node.set_label(name);
+    let inner = &mut node.children_mut()[0];
+    label_fields(inner, fields, options);
+
+    let summary = match name {
+        "Ticket" => principal_at_realm(inner, "sname", "realm"),
+        "AS-REQ" | "TGS-REQ" => {
+            let body = field(inner, "req-body")?.children().first()?;
+            let client = principal_at_realm(body, "cname", "realm");
+            let server = principal_at_realm(body, "sname", "realm");
+            Some([client, server].into_iter().flatten().collect::<Vec<String>>().join(" -> "))
+        }
+        "AS-REP" | "TGS-REP" => principal_at_realm(inner, "cname", "crealm"),
+        "AP-REQ" => field(inner, "ticket").and_then(|ticket| ticket.annotation.clone()),
+        "KRB-ERROR" => {
+            let error = field(inner, "error-code").and_then(|code| code.annotation.clone());
+            let text = field(inner, "e-text").and_then(|text| text.children().first()?.as_string());
+            Some([error, text].into_iter().flatten().collect::<Vec<String>>().join(": "))
+        }
+        _ => None,
+    }
+    .filter(|summary| !summary.is_empty());
+    if let Some(summary) = &summary {
+        node.set_annotation(summary.clone());
+    }
+    summary.or_else(|| Some(name.to_string()))
+}
+
+/// Labels the `[n] EXPLICIT` fields of a SEQUENCE with the names at index `n`.
+fn label_fields(node: &mut ASN1Node, fields: &[&str], options: &SchemaOptions) {
+    for field in node.children_mut() {
+        let Tag::ContextSpecific { number, .. } = field.tag else {
+            continue;
+        };
+        let Some(&name) = fields.get(number.value() as usize).filter(|name| !name.is_empty()) else {
+            continue;
+        };
+        field.set_label(name);
+        if let Some(annotation) = label_value(field, name, options) {
+            field.set_annotation(annotation);
+        }
+    }
+}
+
+/// Labels the value inside a field and returns the annotation of the field.
+fn label_value(field: &mut ASN1Node, name: &str, options: &SchemaOptions) -> Option<String> {
+    let value = field.children_mut().first_mut()?;
+    match name {
+        "sname" | "cname" | "userName" => label_principal_name(value, options),
+        "enc-part" | "authenticator" | "enc-authorization-data" => label_encrypted_data(value, options),
+        "key" | "subkey" => {
+            value.set_label("EncryptionKey");
+            label_fields(value, ENCRYPTION_KEY, options);
+            if let Some(key) = value.children_mut().get_mut(1).and_then(|key| key.children_mut().first_mut()) {
+                hide_secret(key, options);
+            }
+            field_integer(value, "keytype").map(encryption_type)
+        }
+        "cksum" => {
+            value.set_label("Checksum");
+            label_fields(value, CHECKSUM, options);
+            None
+        }
+        "padata" | "encrypted-pa-data" => {
+            label_padata_list(value, options);
+            Some(format!("{} item(s)", value.children().len()))
+        }
+        "req-body" => {
+            value.set_label("KDC-REQ-BODY");
+            label_fields(value, KDC_REQ_BODY, options);
+            None
+        }
+        "safe-body" => {
+            value.set_label("KRB-SAFE-BODY");
+            label_fields(value, KRB_SAFE_BODY, options);
+            None
+        }
+        "ticket" => label_message(value, options),
+        "tickets" | "additional-tickets" => {
+            for ticket in value.children_mut() {
+                label_message(ticket, options);
+            }
+            Some(format!("{} ticket(s)", value.children().len()))
+        }
+        "etype" if value.tag == Tag::Sequence => Some(
+            value
+                .children()
+                .iter()
+                .filter_map(signed_integer)
+                .map(encryption_type)
+                .collect::<Vec<String>>()
+                .join(", "),
+        ),
+        "etype" | "keytype" => signed_integer(value).map(encryption_type),
+        "cksumtype" => signed_integer(value).map(|kind| lookup(CHECKSUM_TYPES, kind)),
+        "name-type" => signed_integer(value).map(|kind| lookup(NAME_TYPES, kind)),
+        "padata-type" => signed_integer(value).map(|kind| lookup(PA_DATA_TYPES, kind)),
+        "error-code" => signed_integer(value).map(|code| lookup(ERROR_CODES, code)),
+        "msg-type" => signed_integer(value).and_then(message_type),
+        "flags" => flag_names(value, TICKET_FLAGS),
+        "kdc-options" => flag_names(value, KDC_OPTIONS),
+        "ap-options" => flag_names(value, AP_OPTIONS),
+        "caddr" | "addresses" | "s-address" | "r-address" => {
+            let addresses = if value.tag == Tag::Sequence && value.children().iter().all(|item| item.tag == Tag::Sequence) {
+                value.children_mut()
+            } else {
+                std::slice::from_mut(value)
+            };
+            for address in addresses {
+                address.set_label("HostAddress");
+                label_fields(address, HOST_ADDRESS, options);
+            }
+            None
+        }
+        "authorization-data" => {
+            for item in value.children_mut() {
+                item.set_label("AuthorizationData");
+                label_fields(item, AUTHORIZATION_DATA, options);
+            }
+            None
+        }
+        "transited" => {
+            value.set_label("TransitedEncoding");
+            label_fields(value, TRANSITED_ENCODING, options);
+            None
+        }
+        "last-req" => {
+            for item in value.children_mut() {
+                item.set_label("LastReq");
+                label_fields(item, LAST_REQ, options);
+            }
+            None
+        }
+        "e-data" => {
+            let ASN1Value::OctetString(inner, _) = &mut value.value else {
+                return None;
+            };
+            if is_method_data(inner) {
+                inner.set_label("METHOD-DATA");
+                label_padata_list(inner, options);
+            }
+            None
+        }
+        "ticket-info" => {
+            for info in value.children_mut() {
+                info.set_label("KrbCredInfo");
+            }
+            None
+        }
+        _ => None,
+    }
+}
+
+fn label_padata_list(node: &mut ASN1Node, options: &SchemaOptions) {
+    for padata in node.children_mut() {
+        if is_pa_data(padata) {
+            label_pa_data(padata, options);
+        }
+    }
+}
+
+/// Labels a `PA-DATA` with its type name and decodes the value of well-known types.
+fn label_pa_data(node: &mut ASN1Node, options: &SchemaOptions) {
+    label_fields(node, PA_DATA, options);
+    let kind = node.children()[0].children().first().and_then(signed_integer);
+    node.set_label(&kind.map(|kind| lookup(PA_DATA_TYPES, kind)).unwrap_or_else(|| "PA-DATA".to_string()));
+
+    let value_field = &mut node.children_mut()[1];
+    let value = &mut value_field.children_mut()[0];
+    let annotation = match (kind, &mut value.value) {
+        (Some(1), ASN1Value::OctetString(inner, _)) => label_message(inner, options),
+        (Some(2) | Some(138), ASN1Value::OctetString(inner, _)) => label_encrypted_data(inner, options),
+        (Some(11) | Some(19), ASN1Value::OctetString(inner, _)) => {
+            let mut etypes = Vec::new();
+            inner.set_label(if kind == Some(19) { "ETYPE-INFO2" } else { "ETYPE-INFO" });
+            for entry in inner.children_mut() {
+                entry.set_label(if kind == Some(19) { "ETYPE-INFO2-ENTRY" } else { "ETYPE-INFO-ENTRY" });
+                label_fields(entry, ETYPE_INFO_ENTRY, options);
+                etypes.extend(field(entry, "etype").and_then(|etype| etype.annotation.clone()));
+                let salt = field(entry, "salt").and_then(|salt| salt.children().first()?.as_string());
+                if let Some(salt) = salt {
+                    entry.set_annotation(format!("salt {}", salt));
+                }
+            }
+            Some(etypes.join(", "))
+        }
+        (Some(128), ASN1Value::OctetString(inner, _)) => {
+            inner.set_label("KERB-PA-PAC-REQUEST");
+            label_fields(inner, PA_PAC_REQUEST, options);
+            field(inner, "include-pac")
+                .and_then(|include| include.children().first())
+                .map(|include| format!("include-pac: {}", include.display_value()))
+        }
+        (Some(129), ASN1Value::OctetString(inner, _)) => {
+            inner.set_label("PA-FOR-USER");
+            label_fields(inner, PA_FOR_USER, options);
+            principal_at_realm(inner, "userName", "userRealm")
+        }
+        (Some(165), ASN1Value::Bytes(bytes)) if bytes.len() == 4 => {
+            let mask = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+            Some(
+                SUPPORTED_ENCTYPE_BITS
+                    .iter()
+                    .enumerate()
+                    .filter(|(bit, _)| mask & (1 << bit) != 0)
+                    .map(|(_, name)| name.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", "),
+            )
+        }
+        _ => None,
+    };
+    if let Some(annotation) = annotation.filter(|annotation| !annotation.is_empty()) {
+        node.set_annotation(annotation);
+    }
+}
+
+/// `PrincipalName ::= SEQUENCE { name-type [0] Int32, name-string [1] SEQUENCE OF KerberosString }`
+fn label_principal_name(node: &mut ASN1Node, options: &SchemaOptions) -> Option<String> {
+    node.set_label("PrincipalName");
+    label_fields(node, PRINCIPAL_NAME, options);
+    let name = principal_name(node)?;
+    node.set_annotation(name.clone());
+    Some(name)
+}
+
+fn principal_name(node: &ASN1Node) -> Option<String> {
+    let parts = field(node, "name-string")?.children().first()?;
+    Some(parts.children().iter().filter_map(ASN1Node::as_string).collect::<Vec<String>>().join("/"))
+}
+
+/// Formats `name@REALM` from a PrincipalName field and a realm field of the SEQUENCE.
+fn principal_at_realm(node: &ASN1Node, name: &str, realm: &str) -> Option<String> {
+    let name = principal_name(field(node, name)?.children().first()?)?;
+    match field(node, realm).and_then(|realm| realm.children().first()?.as_string()) {
+        Some(realm) => Some(format!("{}@{}", name, realm)),
+        None => Some(name),
+    }
+}
+
+/// `EncryptedData ::= SEQUENCE { etype [0] Int32, kvno [1] UInt32 OPTIONAL, cipher [2] OCTET STRING }`
+fn label_encrypted_data(node: &mut ASN1Node, options: &SchemaOptions) -> Option<String> {
+    node.set_label("EncryptedData");
+    label_fields(node, ENCRYPTED_DATA, options);
+    let etype = field(node, "etype")?.annotation.clone()?;
+    node.set_annotation(etype.clone());
+    Some(etype)
+}
+
+fn field<'a>(node: &'a ASN1Node, name: &str) -> Option<&'a ASN1Node> {
+    node.children().iter().find(|child| child.label.as_deref() == Some(name))
+}
+
+fn field_integer(node: &ASN1Node, name: &str) -> Option<i128> {
+    signed_integer(field(node, name)?.children().first()?)
+}
+
+fn lookup(table: &[(i128, &str)], value: i128) -> String {
+    table
+        .iter()
+        .find(|(known, _)| *known == value)
+        .map(|(_, name)| name.to_string())
+        .unwrap_or_else(|| value.to_string())
+}
+
+fn encryption_type(etype: i128) -> String {
+    lookup(ENCRYPTION_TYPES, etype)
+}
+
+fn message_type(kind: i128) -> Option<String> {
+    let name = match kind {
+        10 => "KRB_AS_REQ",
+        11 => "KRB_AS_REP",
+        12 => "KRB_TGS_REQ",
+        13 => "KRB_TGS_REP",
+        14 => "KRB_AP_REQ",
+        15 => "KRB_AP_REP",
+        20 => "KRB_SAFE",
+        21 => "KRB_PRIV",
+        22 => "KRB_CRED",
+        30 => "KRB_ERROR",
+        _ => return None,
+    };
+    Some(name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::asn1_der::{flatten_nodes, parse_asn1};
+    use crate::schema::{annotate, SchemaOptions};
+
+    #[test]
+    fn names_negative_encryption_types() {
+        // an AS-REQ whose etype list ends with the Microsoft private type -128
+        let data = hex::decode(concat!(
+            "6a81dd3081daa103020105a20302010aa3533051303ca103020102a23504333031a003020112a22a0428111111111111",
+            "111111111111111111111111111111111111111111111111111111111111111111113011a10402020080a20904073005",
+            "a0030101ffa4793077a00703050040810010a1123010a003020101a10930071b05616c696365a20d1b0b4558414d504c",
+            "452e434f4da320301ea003020102a11730151b066b72627467741b0b4558414d504c452e434f4da511180f3230333730",
+            "3931333032343830355aa70402023039a80e300c020112020111020117020180",
+        ))
+        .unwrap();
+        let mut root = parse_asn1(&data, 0).unwrap();
+        annotate(&mut root, &SchemaOptions::default());
+
+        let etype = flatten_nodes(&root)
+            .into_iter()
+            .rfind(|node| node.label.as_deref() == Some("etype"))
+            .and_then(|node| node.annotation);
+        assert_eq!(etype.as_deref(), Some("aes256-cts-hmac-sha1-96, aes128-cts-hmac-sha1-96, rc4-hmac, rc4-md4"));
+    }
+}
@@ -0,0 +1,247 @@
+use der::asn1::ObjectIdentifier;
+use der::Tag;
+use crate::asn1_der::{ASN1Node, ASN1Value};
+use crate::schema::{hide_secret, integer_bits, is_context, label_children, SchemaOptions};
+use crate::schema::oid::oid_name;
+
+const RSA_ENCRYPTION: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.1");
+const EC_PUBLIC_KEY: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.2.1");
+pub(crate) const PBES2: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.5.13");
+const PBKDF2: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.5.12");
+const SCRYPT: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.6.1.4.1.11591.4.11");
+
+/// PKCS#5 and PKCS#12 password based encryption algorithms start with one of these arcs.
+const PBE_ARCS: [&str; 2] = ["1.2.840.113549.1.5.", "1.2.840.113549.1.12.1."];
+
+/// Recognizes PKCS#1, PKCS#8 (plain and encrypted) and SEC1 private keys.
+pub(crate) fn recognize(node: &mut ASN1Node, options: &SchemaOptions) -> bool {
+    if is_encrypted_private_key_info(node) {
+        label_encrypted_private_key_info(node);
+    } else if is_private_key_info(node) {
+        label_private_key_info(node, options);
+    } else if is_rsa_private_key(node) {
+        label_rsa_private_key(node, options);
+    } else if is_ec_private_key(node) {
+        label_ec_private_key(node, options);
+    } else {
+        return false;
+    }
+    true
+}
+
+fn is_integer(node: &ASN1Node) -> bool {
+    matches!(node.value, ASN1Value::Integer(_) | ASN1Value::BigInteger(_))
+}
+
+fn is_octet_string(node: &ASN1Node) -> bool {
+    node.tag == Tag::OctetString
+}
+
+fn algorithm_oid(node: &ASN1Node) -> Option<ObjectIdentifier> {
+    if node.tag != Tag::Sequence {
+        return None;
+    }
+    node.children().first().and_then(ASN1Node::as_oid)
+}
+
+fn is_rsa_private_key(node: &ASN1Node) -> bool {
+    let children = node.children();
+    node.tag == Tag::Sequence
+        && children.len() >= 9
+        && matches!(children[0].as_u128(), Some(0) | Some(1))
+        && children[..9].iter().all(is_integer)
+}
+
+fn is_ec_private_key(node: &ASN1Node) -> bool {
+    let children = node.children();
+    node.tag == Tag::Sequence
+        && (2..=4).contains(&children.len())
+        && children[0].as_u128() == Some(1)
+        && is_octet_string(&children[1])
+        && children[2..].iter().all(|child| (is_context(child, 0) || is_context(child, 1)) && child.children().len() == 1)
+}
+
+fn is_private_key_info(node: &ASN1Node) -> bool {
+    let children = node.children();
+    node.tag == Tag::Sequence
+        && (3..=5).contains(&children.len())
+        && matches!(children[0].as_u128(), Some(0) | Some(1))
+        && algorithm_oid(&children[1]).is_some()
+        && is_octet_string(&children[2])
+}
+
+pub(crate) fn is_encrypted_private_key_info(node: &ASN1Node) -> bool {
+    let children = node.children();
+    node.tag == Tag::Sequence
+        && children.len() == 2
+        && is_octet_string(&children[1])
+        && algorithm_oid(&children[0])
+            .map(|oid| is_pbe_algorithm(&oid))
+            .unwrap_or(false)
+}
+
+pub(crate) fn is_pbe_algorithm(oid: &ObjectIdentifier) -> bool {
+    let dotted = oid.to_string();
+    PBE_ARCS.iter().any(|arc| dotted.starts_with(arc))
+}
+
+fn label_rsa_private_key(node: &mut ASN1Node, options: &SchemaOptions) {
+    node.set_label("RSAPrivateKey");
+    let bits = integer_bits(&node.children()[1]);
+    if let Some(bits) = bits {
+        node.set_annotation(format!("RSA {}-bit private key", bits));
+    }
+    label_children(node, &[
+        "version",
+        "modulus",
+        "publicExponent",
+        "privateExponent",
+        "prime1",
+        "prime2",
+        "exponent1",
+        "exponent2",
+        "coefficient",
+        "otherPrimeInfos",
+    ]);
+    for child in node.children_mut().iter_mut().skip(3) {
+        hide_secret(child, options);
+    }
+}
+
+fn label_ec_private_key(node: &mut ASN1Node, options: &SchemaOptions) {
+    node.set_label("ECPrivateKey");
+    node.set_annotation("SEC1 EC private key");
+    let children = node.children_mut();
+    children[0].set_label("version");
+    children[1].set_label("privateKey");
+    hide_secret(&mut children[1], options);
+    for child in children.iter_mut().skip(2) {
+        match child.tag {
+            Tag::ContextSpecific { number, .. } if number.value() == 0 => {
+                child.set_label("parameters");
+                if let Some(curve) = child.children_mut().first_mut() {
+                    curve.set_label("namedCurve");
+                }
+            }
+            Tag::ContextSpecific { number, .. } if number.value() == 1 => {
+                child.set_label("publicKey");
+                if let Some(point) = child.children_mut().first_mut() {
+                    point.set_label("ecPoint");
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn label_private_key_info(node: &mut ASN1Node, options: &SchemaOptions) {
+    let one_asymmetric_key = node.children()[0].as_u128() == Some(1);
+    node.set_label(if one_asymmetric_key { "OneAsymmetricKey" } else { "PrivateKeyInfo" });
+
+    let algorithm = algorithm_oid(&node.children()[1]);
+    let curve = curve_name(&node.children()[1]);
+    let children = node.children_mut();
+    children[0].set_label("version");
+    label_algorithm_identifier(&mut children[1], "privateKeyAlgorithm");
+    children[2].set_label("privateKey");
+    for child in children.iter_mut().skip(3) {
+        match child.tag {
+            Tag::ContextSpecific { number, .. } if number.value() == 0 => child.set_label("attributes"),
+            Tag::ContextSpecific { number, .. } if number.value() == 1 => child.set_label("publicKey"),
+            _ => {}
+        }
+    }
+
+    let mut key_type = algorithm
+        .as_ref()
+        .and_then(oid_name)
+        .unwrap_or("unknown algorithm")
+        .to_string();
+    let private_key = &mut children[2];
+    let inner = match &mut private_key.value {
+        ASN1Value::OctetString(inner, _) => Some(inner.as_mut()),
+        _ => None,
+    };
+    match (algorithm, inner) {
+        (Some(RSA_ENCRYPTION), Some(inner)) if is_rsa_private_key(inner) => {
+            label_rsa_private_key(inner, options);
+            key_type = inner.annotation.clone().unwrap_or(key_type);
+        }
+        (Some(EC_PUBLIC_KEY), Some(inner)) if is_ec_private_key(inner) => {
+            label_ec_private_key(inner, options);
+            key_type = format!("EC private key{}", curve.map(|name| format!(" on {}", name)).unwrap_or_default());
+        }
+        _ => hide_secret(private_key, options),
+    }
+    node.set_annotation(format!("PKCS#8, {}", key_type));
+}
+
+fn curve_name(algorithm: &ASN1Node) -> Option<String> {
+    let curve = algorithm.children().get(1)?.as_oid()?;
+    Some(oid_name(&curve).map(str::to_string).unwrap_or_else(|| curve.to_string()))
+}
+
+fn label_encrypted_private_key_info(node: &mut ASN1Node) {
+    node.set_label("EncryptedPrivateKeyInfo");
+    let algorithm = algorithm_oid(&node.children()[0]);
+    let children = node.children_mut();
+    label_algorithm_identifier(&mut children[0], "encryptionAlgorithm");
+    children[1].set_label("encryptedData");
+    if algorithm == Some(PBES2) {
+        if let Some(params) = children[0].children_mut().get_mut(1) {
+            label_pbes2_params(params);
+        }
+    }
+    let name = algorithm.as_ref().and_then(oid_name).unwrap_or("unknown algorithm");
+    node.set_annotation(format!("PKCS#8, encrypted with {}", name));
+}
+
+pub(crate) fn label_algorithm_identifier(node: &mut ASN1Node, label: &str) {
+    node.set_label(label);
+    label_children(node, &["algorithm", "parameters"]);
+}
+
+/// Labels PBES2-params together with the PBKDF2 or scrypt parameters inside.
+pub(crate) fn label_pbes2_params(node: &mut ASN1Node) {
+    node.set_label("PBES2-params");
+    let children = node.children_mut();
+    if let Some(kdf) = children.get_mut(0) {
+        let kdf_algorithm = algorithm_oid(kdf);
+        label_algorithm_identifier(kdf, "keyDerivationFunc");
+        if let Some(params) = kdf.children_mut().get_mut(1) {
+            match kdf_algorithm {
+                Some(PBKDF2) => label_pbkdf2_params(params),
+                Some(SCRYPT) => label_scrypt_params(params),
+                _ => {}
+            }
+        }
+    }
+    if let Some(scheme) = children.get_mut(1) {
+        label_algorithm_identifier(scheme, "encryptionScheme");
+        if let Some(iv) = scheme.children_mut().get_mut(1) {
+            iv.set_label("iv");
+        }
+    }
+}
+
+fn label_pbkdf2_params(node: &mut ASN1Node) {
+    node.set_label("PBKDF2-params");
+    let mut iteration_count_seen = false;
+    for child in node.children_mut() {
+        match child.tag {
+            Tag::OctetString => child.set_label("salt"),
+            Tag::Integer if !iteration_count_seen => {
+                iteration_count_seen = true;
+                child.set_label("iterationCount");
+            }
+            Tag::Integer => child.set_label("keyLength"),
+            Tag::Sequence => label_algorithm_identifier(child, "prf"),
+            _ => {}
+        }
+    }
+}
+
+fn label_scrypt_params(node: &mut ASN1Node) {
+    node.set_label("scrypt-params");
+    label_children(node, &["salt", "costParameter", "blockSize", "parallelizationParameter", "keyLength"]);
+}
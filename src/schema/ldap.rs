@@ -0,0 +1,622 @@
+use der::Tag;
+use crate::asn1_der::{ASN1Node, ASN1Value};
+use crate::schema::{hide_secret, implicit_octets, is_context, label_children, show_as_text, show_as_value, text, SchemaOptions};
+
+const RESULT_CODES: &[(u128, &str)] = &[
+    (0, "success"),
+    (1, "operationsError"),
+    (2, "protocolError"),
+    (3, "timeLimitExceeded"),
+    (4, "sizeLimitExceeded"),
+    (5, "compareFalse"),
+    (6, "compareTrue"),
+    (7, "authMethodNotSupported"),
+    (8, "strongerAuthRequired"),
+    (10, "referral"),
+    (11, "adminLimitExceeded"),
+    (12, "unavailableCriticalExtension"),
+    (13, "confidentialityRequired"),
+    (14, "saslBindInProgress"),
+    (16, "noSuchAttribute"),
+    (17, "undefinedAttributeType"),
+    (18, "inappropriateMatching"),
+    (19, "constraintViolation"),
+    (20, "attributeOrValueExists"),
+    (21, "invalidAttributeSyntax"),
+    (32, "noSuchObject"),
+    (33, "aliasProblem"),
+    (34, "invalidDNSyntax"),
+    (36, "aliasDereferencingProblem"),
+    (48, "inappropriateAuthentication"),
+    (49, "invalidCredentials"),
+    (50, "insufficientAccessRights"),
+    (51, "busy"),
+    (52, "unavailable"),
+    (53, "unwillingToPerform"),
+    (54, "loopDetect"),
+    (64, "namingViolation"),
+    (65, "objectClassViolation"),
+    (66, "notAllowedOnNonLeaf"),
+    (67, "notAllowedOnRDN"),
+    (68, "entryAlreadyExists"),
+    (69, "objectClassModsProhibited"),
+    (71, "affectsMultipleDSAs"),
+    (80, "other"),
+];
+
+const SEARCH_SCOPES: [&str; 3] = ["baseObject", "singleLevel", "wholeSubtree"];
+const DEREF_ALIASES: [&str; 4] = ["neverDerefAliases", "derefInSearching", "derefFindingBaseObj", "derefAlways"];
+const MODIFY_OPERATIONS: [&str; 4] = ["add", "delete", "replace", "increment"];
+
+/// Recognizes an RFC 4511 `LDAPMessage` at the node.
+pub(crate) fn recognize(node: &mut ASN1Node, options: &SchemaOptions) -> bool {
+    if !is_ldap_message(node) {
+        return false;
+    }
+    label_ldap_message(node, options);
+    true
+}
+
+/// `LDAPMessage ::= SEQUENCE { messageID INTEGER, protocolOp CHOICE { [APPLICATION n] ... }, controls [0] OPTIONAL }`
+fn is_ldap_message(node: &ASN1Node) -> bool {
+    let children = node.children();
+    node.tag == Tag::Sequence
+        && (2..=3).contains(&children.len())
+        && children[0].tag == Tag::Integer
+        && operation_name(&children[1]).is_some()
+        && children.get(2).map(|controls| is_context(controls, 0)).unwrap_or(true)
+}
+
+fn operation_name(node: &ASN1Node) -> Option<&'static str> {
+    let Tag::Application { number, constructed } = node.tag else {
+        return None;
+    };
+    let (name, primitive) = match number.value() {
+        0 => ("BindRequest", false),
+        1 => ("BindResponse", false),
+        2 => ("UnbindRequest", true),
+        3 => ("SearchRequest", false),
+        4 => ("SearchResultEntry", false),
+        5 => ("SearchResultDone", false),
+        6 => ("ModifyRequest", false),
+        7 => ("ModifyResponse", false),
+        8 => ("AddRequest", false),
+        9 => ("AddResponse", false),
+        10 => ("DelRequest", true),
+        11 => ("DelResponse", false),
+        12 => ("ModifyDNRequest", false),
+        13 => ("ModifyDNResponse", false),
+        14 => ("CompareRequest", false),
+        15 => ("CompareResponse", false),
+        16 => ("AbandonRequest", true),
+        19 => ("SearchResultReference", false),
+        23 => ("ExtendedRequest", false),
+        24 => ("ExtendedResponse", false),
+        25 => ("IntermediateResponse", false),
+        _ => return None,
+    };
+    (constructed != primitive).then_some(name)
+}
+
+fn label_ldap_message(node: &mut ASN1Node, options: &SchemaOptions) {
+    node.set_label("LDAPMessage");
+    let message_id = node.children()[0].display_value();
+    let children = node.children_mut();
+    children[0].set_label("messageID");
+
+    let operation = &mut children[1];
+    let name = operation_name(operation).unwrap_or_default();
+    operation.set_label(name);
+    let summary = label_operation(operation, name, options);
+    if let Some(summary) = &summary {
+        operation.set_annotation(summary.clone());
+    }
+
+    if let Some(controls) = children.get_mut(2) {
+        label_controls(controls);
+    }
+    node.set_annotation(match summary {
+        Some(summary) => format!("#{} {} {}", message_id, name, summary),
+        None => format!("#{} {}", message_id, name),
+    });
+}
+
+/// Labels the fields of a protocol operation and returns a short summary of it.
+fn label_operation(node: &mut ASN1Node, name: &str, options: &SchemaOptions) -> Option<String> {
+    match name {
+        "BindRequest" => label_bind_request(node, options),
+        "SearchRequest" => label_search_request(node),
+        "SearchResultEntry" => {
+            label_children(node, &["objectName", "attributes"]);
+            let fields = node.children_mut();
+            show_as_text(fields.first_mut()?);
+            let count = fields.get_mut(1).map(label_attribute_list).unwrap_or_default();
+            Some(format!("{} ({} attribute(s))", text(fields.first()?), count))
+        }
+        "ModifyRequest" => {
+            label_children(node, &["object", "changes"]);
+            let fields = node.children_mut();
+            show_as_text(fields.first_mut()?);
+            let changes = fields.get_mut(1)?;
+            for change in changes.children_mut() {
+                label_change(change);
+            }
+            Some(text(node.children().first()?))
+        }
+        "AddRequest" => {
+            label_children(node, &["entry", "attributes"]);
+            let fields = node.children_mut();
+            show_as_text(fields.first_mut()?);
+            if let Some(attributes) = fields.get_mut(1) {
+                label_attribute_list(attributes);
+            }
+            Some(text(node.children().first()?))
+        }
+        "DelRequest" => {
+            let entry = node.children_mut().first_mut()?;
+            show_as_text(entry);
+            entry.set_label("entry");
+            Some(text(entry))
+        }
+        "ModifyDNRequest" => {
+            label_children(node, &["entry", "newrdn", "deleteoldrdn"]);
+            for field in node.children_mut() {
+                if is_context(field, 0) {
+                    field.set_label("newSuperior");
+                    implicit_text(field);
+                } else {
+                    show_as_text(field);
+                }
+            }
+            let fields = node.children();
+            Some(format!("{} -> {}", text(fields.first()?), text(fields.get(1)?)))
+        }
+        "CompareRequest" => {
+            label_children(node, &["entry", "ava"]);
+            let fields = node.children_mut();
+            show_as_text(fields.first_mut()?);
+            let ava = fields.get_mut(1)?;
+            label_children(ava, &["attributeDesc", "assertionValue"]);
+            for field in ava.children_mut() {
+                show_as_text(field);
+            }
+            let ava = node.children().get(1)?;
+            Some(format!("{} {}={}", text(node.children().first()?), text(ava.children().first()?), text(ava.children().get(1)?)))
+        }
+        "AbandonRequest" => {
+            let id = implicit_octets(node)?.iter().fold(0u128, |id, &byte| id << 8 | byte as u128);
+            node.children_mut().first_mut()?.set_label("messageID");
+            Some(format!("message #{}", id))
+        }
+        "SearchResultReference" => {
+            for uri in node.children_mut() {
+                show_as_text(uri);
+                uri.set_label("uri");
+            }
+            None
+        }
+        "ExtendedRequest" => {
+            for field in node.children_mut() {
+                if is_context(field, 0) {
+                    field.set_label("requestName");
+                    implicit_text(field);
+                } else if is_context(field, 1) {
+                    field.set_label("requestValue");
+                }
+            }
+            node.children().first().and_then(|name| name.annotation.clone())
+        }
+        "IntermediateResponse" => {
+            for field in node.children_mut() {
+                if is_context(field, 0) {
+                    field.set_label("responseName");
+                    implicit_text(field);
+                } else if is_context(field, 1) {
+                    field.set_label("responseValue");
+                }
+            }
+            None
+        }
+        "UnbindRequest" => None,
+        _ => label_ldap_result(node),
+    }
+}
+
+/// `BindRequest ::= [APPLICATION 0] SEQUENCE { version, name LDAPDN, authentication CHOICE { simple [0], sasl [3] } }`
+fn label_bind_request(node: &mut ASN1Node, options: &SchemaOptions) -> Option<String> {
+    label_children(node, &["version", "name", "authentication"]);
+    let fields = node.children_mut();
+    show_as_text(fields.get_mut(1)?);
+    let authentication = fields.get_mut(2)?;
+    let method = if is_context(authentication, 0) {
+        authentication.set_label("simple");
+        hide_secret(authentication, options);
+        "simple".to_string()
+    } else if is_context(authentication, 3) {
+        authentication.set_label("sasl");
+        label_children(authentication, &["mechanism", "credentials"]);
+        let parts = authentication.children_mut();
+        show_as_text(parts.first_mut()?);
+        if let Some(credentials) = parts.get_mut(1) {
+            hide_secret(credentials, options);
+        }
+        format!("SASL {}", text(authentication.children().first()?))
+    } else {
+        return None;
+    };
+    Some(format!("{} ({})", text(node.children().get(1)?), method))
+}
+
+/// `SearchRequest ::= [APPLICATION 3] SEQUENCE { baseObject, scope, derefAliases, sizeLimit, timeLimit, typesOnly, filter, attributes }`
+fn label_search_request(node: &mut ASN1Node) -> Option<String> {
+    label_children(node, &["baseObject", "scope", "derefAliases", "sizeLimit", "timeLimit", "typesOnly", "filter", "attributes"]);
+    let fields = node.children_mut();
+    show_as_text(fields.first_mut()?);
+    annotate_enumerated(fields.get_mut(1)?, &SEARCH_SCOPES);
+    annotate_enumerated(fields.get_mut(2)?, &DEREF_ALIASES);
+    let filter = fields.get_mut(6)?;
+    label_filter(filter);
+    filter.set_label("filter");
+    if let Some(attributes) = fields.get_mut(7) {
+        for attribute in attributes.children_mut() {
+            show_as_text(attribute);
+        }
+    }
+    let fields = node.children();
+    Some(format!(
+        "{} {} {}",
+        text(&fields[0]),
+        fields[1].annotation.clone().unwrap_or_default(),
+        fields[6].annotation.clone().unwrap_or_default()
+    ))
+}
+
+/// `LDAPResult ::= SEQUENCE { resultCode ENUMERATED, matchedDN, diagnosticMessage, referral [3] OPTIONAL }`
+fn label_ldap_result(node: &mut ASN1Node) -> Option<String> {
+    let mut code = None;
+    for (i, field) in node.children_mut().iter_mut().enumerate() {
+        match i {
+            0 => {
+                field.set_label("resultCode");
+                code = field.as_u128().map(result_code);
+                if let Some(code) = &code {
+                    field.set_annotation(code.clone());
+                }
+            }
+            1 => {
+                field.set_label("matchedDN");
+                show_as_text(field);
+            }
+            2 => {
+                field.set_label("diagnosticMessage");
+                show_as_text(field);
+            }
+            _ if is_context(field, 3) => field.set_label("referral"),
+            _ if is_context(field, 7) => field.set_label("serverSaslCreds"),
+            _ if is_context(field, 10) => {
+                field.set_label("responseName");
+                implicit_text(field);
+            }
+            _ if is_context(field, 11) => field.set_label("responseValue"),
+            _ => {}
+        }
+    }
+    let message = node.children().get(2).map(text).filter(|message| !message.is_empty());
+    match (code, message) {
+        (Some(code), Some(message)) => Some(format!("{}: {}", code, message)),
+        (code, _) => code,
+    }
+}
+
+fn result_code(code: u128) -> String {
+    RESULT_CODES
+        .iter()
+        .find(|(known, _)| *known == code)
+        .map(|(_, name)| name.to_string())
+        .unwrap_or_else(|| code.to_string())
+}
+
+fn annotate_enumerated(node: &mut ASN1Node, names: &[&str]) {
+    if let Some(name) = node.as_u128().and_then(|value| names.get(value as usize)) {
+        node.set_annotation(*name);
+    }
+}
+
+/// Labels a list of `PartialAttribute ::= SEQUENCE { type, vals SET OF value }` and returns its length.
+fn label_attribute_list(node: &mut ASN1Node) -> usize {
+    for attribute in node.children_mut() {
+        label_attribute(attribute);
+    }
+    node.children().len()
+}
+
+/// Labels an attribute with its type and annotates it with the values.
+fn label_attribute(node: &mut ASN1Node) {
+    let fields = node.children_mut();
+    let Some(kind) = fields.first_mut() else {
+        return;
+    };
+    show_as_text(kind);
+    kind.set_label("type");
+    let kind = text(kind);
+    if let Some(values) = fields.get_mut(1) {
+        values.set_label("vals");
+        for value in values.children_mut() {
+            show_as_value(value);
+        }
+    }
+    let values = node
+        .children()
+        .get(1)
+        .map(|values| values.children().iter().map(text).collect::<Vec<String>>().join(", "))
+        .unwrap_or_default();
+    node.set_label(&kind);
+    node.set_annotation(values);
+}
+
+/// `change ::= SEQUENCE { operation ENUMERATED { add, delete, replace, increment }, modification PartialAttribute }`
+fn label_change(node: &mut ASN1Node) {
+    node.set_label("change");
+    label_children(node, &["operation", "modification"]);
+    let fields = node.children_mut();
+    if let Some(operation) = fields.first_mut() {
+        annotate_enumerated(operation, &MODIFY_OPERATIONS);
+    }
+    if let Some(modification) = fields.get_mut(1) {
+        label_attribute(modification);
+    }
+    let operation = node.children().first().and_then(|operation| operation.annotation.clone());
+    let attribute = node.children().get(1).and_then(|modification| modification.label.clone());
+    if let (Some(operation), Some(attribute)) = (operation, attribute) {
+        node.set_annotation(format!("{} {}", operation, attribute));
+    }
+}
+
+/// `Controls ::= SEQUENCE OF Control { controlType LDAPOID, criticality BOOLEAN DEFAULT FALSE, controlValue OCTET STRING OPTIONAL }`
+fn label_controls(node: &mut ASN1Node) {
+    node.set_label("controls");
+    for control in node.children_mut() {
+        control.set_label("Control");
+        for (i, field) in control.children_mut().iter_mut().enumerate() {
+            match field.tag {
+                _ if i == 0 => {
+                    field.set_label("controlType");
+                    show_as_text(field);
+                }
+                Tag::Boolean => field.set_label("criticality"),
+                Tag::OctetString => field.set_label("controlValue"),
+                _ => {}
+            }
+        }
+        if let Some(control_type) = control.children().first().map(text) {
+            control.set_annotation(control_type);
+        }
+    }
+}
+
+/// Labels a search filter and annotates it with its RFC 4515 string form.
+fn label_filter(node: &mut ASN1Node) {
+    let Tag::ContextSpecific { number, .. } = node.tag else {
+        return;
+    };
+    let kind = match number.value() {
+        0 => "and",
+        1 => "or",
+        2 => "not",
+        3 => "equalityMatch",
+        4 => "substrings",
+        5 => "greaterOrEqual",
+        6 => "lessOrEqual",
+        7 => "present",
+        8 => "approxMatch",
+        9 => "extensibleMatch",
+        _ => return,
+    };
+    node.set_label(kind);
+    match number.value() {
+        0..=2 => node.children_mut().iter_mut().for_each(label_filter),
+        3 | 5 | 6 | 8 => {
+            label_children(node, &["attributeDesc", "assertionValue"]);
+            node.children_mut().iter_mut().for_each(show_as_text);
+        }
+        4 => label_substrings(node),
+        7 => implicit_text(node),
+        _ => label_matching_rule_assertion(node),
+    }
+    if let Some(filter) = filter_string(node) {
+        node.set_annotation(filter);
+    }
+}
+
+fn label_substrings(node: &mut ASN1Node) {
+    label_children(node, &["type", "substrings"]);
+    let fields = node.children_mut();
+    if let Some(kind) = fields.first_mut() {
+        show_as_text(kind);
+    }
+    if let Some(substrings) = fields.get_mut(1) {
+        for substring in substrings.children_mut() {
+            let label = match substring.tag {
+                Tag::ContextSpecific { number, .. } if number.value() == 0 => "initial",
+                Tag::ContextSpecific { number, .. } if number.value() == 1 => "any",
+                _ => "final",
+            };
+            substring.set_label(label);
+        }
+    }
+}
+
+/// `MatchingRuleAssertion ::= SEQUENCE { matchingRule [1], type [2], matchValue [3], dnAttributes [4] BOOLEAN }`
+fn label_matching_rule_assertion(node: &mut ASN1Node) {
+    for field in node.children_mut() {
+        let Tag::ContextSpecific { number, .. } = field.tag else {
+            continue;
+        };
+        match number.value() {
+            1 => field.set_label("matchingRule"),
+            2 => field.set_label("type"),
+            3 => field.set_label("matchValue"),
+            4 => {
+                field.set_label("dnAttributes");
+                let value = implicit_octets(field).map(|value| value.iter().any(|&byte| byte != 0));
+                if let Some(value) = value {
+                    field.set_annotation(value.to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Formats a `Filter` as an RFC 4515 string such as `(&(objectClass=user)(cn=J*))`.
+fn filter_string(node: &ASN1Node) -> Option<String> {
+    let Tag::ContextSpecific { number, .. } = node.tag else {
+        return None;
+    };
+    let fields = node.children();
+    let assertion = |operator: &str| -> Option<String> {
+        let [description, value] = fields else {
+            return None;
+        };
+        Some(format!("({}{}{})", text(description), operator, escape(description_bytes(value)?)))
+    };
+    match number.value() {
+        0 | 1 => {
+            let operator = if number.value() == 0 { '&' } else { '|' };
+            let inner = fields.iter().map(filter_string).collect::<Option<Vec<String>>>()?;
+            Some(format!("({}{})", operator, inner.concat()))
+        }
+        2 => Some(format!("(!{})", filter_string(fields.first()?)?)),
+        3 => assertion("="),
+        5 => assertion(">="),
+        6 => assertion("<="),
+        8 => assertion("~="),
+        4 => {
+            let [kind, substrings] = fields else {
+                return None;
+            };
+            let mut pattern = String::new();
+            let parts = substrings.children();
+            if !parts.first().map(|part| is_context(part, 0)).unwrap_or(false) {
+                pattern.push('*');
+            }
+            for part in parts {
+                pattern.push_str(&escape(implicit_octets(part)?));
+                if !is_context(part, 2) {
+                    pattern.push('*');
+                }
+            }
+            Some(format!("({}={})", text(kind), pattern))
+        }
+        7 => Some(format!("({}=*)", String::from_utf8_lossy(implicit_octets(node)?))),
+        9 => {
+            let part = |number: u8| fields.iter().find(|field| is_context(field, number)).and_then(implicit_octets);
+            let mut filter = String::from("(");
+            if let Some(kind) = part(2) {
+                filter.push_str(&String::from_utf8_lossy(kind));
+            }
+            if part(4).map(|dn| dn.iter().any(|&byte| byte != 0)).unwrap_or(false) {
+                filter.push_str(":dn");
+            }
+            if let Some(rule) = part(1) {
+                filter.push(':');
+                filter.push_str(&String::from_utf8_lossy(rule));
+            }
+            filter.push_str(":=");
+            filter.push_str(&escape(part(3)?));
+            filter.push(')');
+            Some(filter)
+        }
+        _ => None,
+    }
+}
+
+fn description_bytes(node: &ASN1Node) -> Option<&[u8]> {
+    match &node.value {
+        ASN1Value::Utf8String(bytes) => Some(bytes),
+        _ => node.octets(),
+    }
+}
+
+/// Escapes an assertion value as required by RFC 4515.
+fn escape(value: &[u8]) -> String {
+    match std::str::from_utf8(value) {
+        Ok(value) => value
+            .chars()
+            .map(|c| match c {
+                '*' | '(' | ')' | '\\' | '\0' => format!("\\{:02x}", c as u32),
+                c => c.to_string(),
+            })
+            .collect(),
+        Err(_) => value.iter().map(|byte| format!("\\{:02x}", byte)).collect(),
+    }
+}
+
+/// Shows the content of an IMPLICIT tagged string as the annotation of the node.
+fn implicit_text(node: &mut ASN1Node) {
+    if let Some(value) = implicit_octets(node).map(|bytes| String::from_utf8_lossy(bytes).to_string()) {
+        node.set_annotation(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::asn1_der::{parse_asn1, view_lines};
+    use crate::schema::{annotate, SchemaOptions};
+
+    fn annotated(data: &str, options: &SchemaOptions) -> Vec<String> {
+        let mut root = parse_asn1(&hex::decode(data).unwrap(), 0).unwrap();
+        annotate(&mut root, options);
+        view_lines(&root)
+    }
+
+    const BIND: &str = "302c0201016027020103041a636e3d61646d696e2c64633d6578616d706c652c64633d636f6d8006736563726574";
+    const SASL_BIND: &str = "301f020107601a0201030400a3130405504c41494e040a00757365720070617373";
+
+    #[test]
+    fn renders_search_filters() {
+        let search = concat!(
+            "3081bc02010263818e041164633d6578616d706c652c64633d636f6d0a01020a0100020100020100010100a05ea31504",
+            "0b6f626a656374436c6173730406706572736f6ea118a4100402636e300a80024a6f81016882016e87046d61696ca213",
+            "a51104097569644e756d626572040431303030a9168108322e352e31332e358202636e8303612a628401ff300a040263",
+            "6e04046d61696ca02630240416312e322e3834302e3131333535362e312e342e333139010101040730050201640400",
+        );
+        let lines = annotated(search, &SchemaOptions::default());
+        assert_eq!(
+            lines[0],
+            "LDAPMessage: SEQUENCE (field(s): 3) (#2 SearchRequest dc=example,dc=com wholeSubtree \
+             (&(objectClass=person)(|(cn=Jo*h*n)(mail=*))(!(uidNumber>=1000))(cn:dn:2.5.13.5:=a\\2ab)))"
+        );
+        assert_eq!(lines[14], "substrings: CONTEXT-SPECIFIC [4] (constructed)  ((cn=Jo*h*n))");
+        assert_eq!(lines[29], "extensibleMatch: CONTEXT-SPECIFIC [9] (constructed)  ((cn:dn:2.5.13.5:=a\\2ab))");
+        assert_eq!(lines[42], "Control: SEQUENCE (field(s): 3) (1.2.840.113556.1.4.319)");
+
+        let search = concat!(
+            "306d020103636804000a01000a010302010a020105010101a053a20d870b6465736372697074696f6ea30e0402636e04",
+            "08612862292a635c00a817040f63726561746554696d657374616d70040432303234a609040361676504023330a90e82",
+            "026f75830553616c65738401ff3000",
+        );
+        let lines = annotated(search, &SchemaOptions::default());
+        assert_eq!(
+            lines[9],
+            "filter: CONTEXT-SPECIFIC [0] (constructed)  \
+             ((&(!(description=*))(cn=a\\28b\\29\\2ac\\5c\\00)(createTimestamp~=2024)(age<=30)(ou:dn:=Sales)))"
+        );
+    }
+
+    #[test]
+    fn masks_bind_credentials() {
+        let lines = annotated(BIND, &SchemaOptions::default());
+        assert_eq!(lines[0], "LDAPMessage: SEQUENCE (field(s): 2) (#1 BindRequest cn=admin,dc=example,dc=com (simple))");
+        assert_eq!(lines[5..], ["simple: CONTEXT-SPECIFIC [0] (primitive) <redacted>", "UTF8String <redacted>"]);
+        let lines = annotated(SASL_BIND, &SchemaOptions::default());
+        assert_eq!(lines[0], "LDAPMessage: SEQUENCE (field(s): 2) (#7 BindRequest  (SASL PLAIN))");
+        assert_eq!(lines[7], "credentials: OCTET STRING <redacted, 10 bytes>");
+
+        let options = SchemaOptions { show_secrets: true, ..SchemaOptions::default() };
+        let lines = annotated(BIND, &options);
+        assert_eq!(lines[6], "UTF8String \"secret\"");
+        let lines = annotated(SASL_BIND, &options);
+        assert_eq!(lines[7], "credentials: OCTET STRING 00757365720070617373");
+    }
+}
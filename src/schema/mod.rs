@@ -0,0 +1,197 @@
+use der::Class;
+use crate::asn1_der::{ASN1Node, ASN1Value};
+use crate::encoding::TypeDecoding;
+use crate::schema::emv::TagDictionary;
+use crate::schema::oid::{oid_name, OidMap};
+
+pub mod android;
+pub mod cdr;
+pub mod cmp;
+pub mod cms;
+pub mod emv;
+pub mod kerberos;
+pub mod keys;
+pub mod ldap;
+pub mod mrtd;
+pub mod ocsp;
+pub mod oid;
+pub mod pkcs12;
+pub mod receipt;
+pub mod scep;
+pub mod snmp;
+pub mod tsp;
+pub mod x509;
+
+/// Options which change how recognized structures are annotated.
+#[derive(Clone, Debug, Default)]
+pub struct SchemaOptions {
+    /// Show secret components (private exponents, private keys, ...) instead of masking them.
+    pub(crate) show_secrets: bool,
+    /// Names for object identifiers provided by the user, such as MIB objects in SNMP varbinds.
+    pub(crate) oid_map: OidMap,
+    /// Read the input as 3GPP charging data records.
+    pub(crate) cdr: bool,
+    /// Read the input as BER-TLV data objects named from tag dictionaries instead of ASN.1 types.
+    pub(crate) tlv: bool,
+    /// Tag names loaded from user-provided dictionary files for the BER-TLV mode.
+    pub(crate) tag_dictionary: TagDictionary,
+    /// Contents of eMRTD data group files whose hashes are compared with the EF.SOD.
+    pub(crate) data_groups: Vec<Vec<u8>>,
+    /// Module type and encoding the input is decoded with instead of reading it as BER.
+    pub(crate) decoding: Option<TypeDecoding>,
+    /// Smallest DER structure listed when binary input is scanned for DER structures.
+    pub(crate) scan: Option<usize>,
+}
+
+/// Recognizes well-known structures in the parsed tree and labels their fields.
+pub(crate) fn annotate(root: &mut ASN1Node, options: &SchemaOptions) {
+    let _ = (options.tlv && emv::recognize(root, options))
+        || (options.cdr && cdr::recognize(root))
+        || keys::recognize(root, options)
+        || pkcs12::recognize(root, options)
+        || mrtd::recognize(root, options)
+        || cms::recognize(root)
+        || x509::recognize(root)
+        || cmp::recognize(root)
+        || ocsp::recognize(root)
+        || tsp::recognize(root)
+        || android::recognize(root)
+        || kerberos::recognize(root, options)
+        || ldap::recognize(root, options)
+        || snmp::recognize(root, options);
+    name_oids(root);
+}
+
+/// Hides the value of the node unless secrets were requested to be shown.
+pub(crate) fn hide_secret(node: &mut ASN1Node, options: &SchemaOptions) {
+    if !options.show_secrets {
+        node.mark_secret();
+    }
+}
+
+/// Applies labels to the children of the node by position, extra children or labels are left over.
+/// Structures with optional fields in between are labelled by their tags instead.
+pub(crate) fn label_children(node: &mut ASN1Node, labels: &[&str]) {
+    for (child, label) in node.children_mut().iter_mut().zip(labels) {
+        child.set_label(label);
+    }
+}
+
+/// Returns true if the node has the context-specific tag `[number]`.
+pub(crate) fn is_context(node: &ASN1Node, number: u8) -> bool {
+    node.tag.is_context_specific() && node.tag_number() == u32::from(number)
+}
+
+/// Returns the content octets of an IMPLICIT tagged primitive value such as `[0] IMPLICIT OCTET STRING`
+/// or `[APPLICATION 16] IMPLICIT INTEGER`.
+pub(crate) fn implicit_octets(node: &ASN1Node) -> Option<&[u8]> {
+    match node.children() {
+        [inner] if node.tag.class() != Class::Universal && !node.tag.is_constructed() => match &inner.value {
+            ASN1Value::Utf8String(bytes) => Some(bytes),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Returns the text of a string node, or the hex of its octets when they are not a string.
+pub(crate) fn text(node: &ASN1Node) -> String {
+    node.as_string()
+        .or_else(|| node.octets().map(hex::encode))
+        .unwrap_or_default()
+}
+
+/// Returns the value of an INTEGER node, including negative values kept as two's complement bytes.
+pub(crate) fn signed_integer(node: &ASN1Node) -> Option<i128> {
+    match &node.value {
+        ASN1Value::Integer(value) => i128::try_from(*value).ok(),
+        ASN1Value::BigInteger(bytes) if !bytes.is_empty() && bytes.len() <= 16 => {
+            let fill = if bytes[0] & 0x80 != 0 { 0xff } else { 0x00 };
+            let mut buffer = [fill; 16];
+            buffer[16 - bytes.len()..].copy_from_slice(bytes);
+            Some(i128::from_be_bytes(buffer))
+        }
+        _ => None,
+    }
+}
+
+/// Shows an OCTET STRING holding printable UTF-8 text as a string instead of hex.
+pub(crate) fn show_as_text(node: &mut ASN1Node) {
+    let Some(bytes) = node.octets() else {
+        return;
+    };
+    let printable = std::str::from_utf8(bytes).map(|text| !text.chars().any(char::is_control)).unwrap_or(false);
+    if printable {
+        node.value = ASN1Value::Utf8String(bytes.to_vec());
+        node.expandable = false;
+    }
+}
+
+/// Shows an OCTET STRING value as text, or as hex when it is binary instead of parsing it as nested DER.
+pub(crate) fn show_as_value(node: &mut ASN1Node) {
+    show_as_text(node);
+    show_as_bytes(node);
+}
+
+/// Shows an OCTET STRING value as hex, for binary values such as digests which may look like DER or text.
+pub(crate) fn show_as_bytes(node: &mut ASN1Node) {
+    if let ASN1Value::OctetString(_, bytes) = &node.value {
+        node.value = ASN1Value::Bytes(bytes.clone());
+        node.expandable = false;
+    }
+}
+
+/// Returns the number of significant bits of a big-endian unsigned integer node.
+pub(crate) fn integer_bits(node: &ASN1Node) -> Option<usize> {
+    let bytes = match &node.value {
+        ASN1Value::BigInteger(bytes) => bytes.clone(),
+        ASN1Value::Integer(value) => value.to_be_bytes().to_vec(),
+        _ => return None,
+    };
+    let first = bytes.iter().position(|&byte| byte != 0)?;
+    Some((bytes.len() - first) * 8 - bytes[first].leading_zeros() as usize)
+}
+
+/// Names the bits set in a named BIT STRING such as `KerberosFlags` or `PKIFailureInfo`.
+pub(crate) fn flag_names(node: &ASN1Node, names: &[&str]) -> Option<String> {
+    let ASN1Value::BitString(bits) = &node.value else {
+        return None;
+    };
+    let flags = bits
+        .raw_bytes()
+        .iter()
+        .flat_map(|byte| (0..8).rev().map(move |bit| byte & (1 << bit) != 0))
+        .enumerate()
+        .filter(|(_, set)| *set)
+        .map(|(bit, _)| names.get(bit).filter(|name| !name.is_empty()).map(|name| name.to_string()).unwrap_or_else(|| format!("bit {}", bit)))
+        .collect::<Vec<String>>();
+    Some(flags.join(", "))
+}
+
+fn name_oids(node: &mut ASN1Node) {
+    if node.annotation.is_none() {
+        if let Some(name) = node.as_oid().as_ref().and_then(oid_name) {
+            node.set_annotation(name);
+        }
+    }
+    for child in node.children_mut() {
+        name_oids(child);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::asn1_der::parse_asn1;
+    use super::signed_integer;
+
+    #[test]
+    fn reads_signed_integers() {
+        let integer = |data: &[u8]| signed_integer(&parse_asn1(data, 0).unwrap());
+        assert_eq!(integer(&[0x02, 0x01, 0x05]), Some(5));
+        assert_eq!(integer(&[0x02, 0x01, 0x80]), Some(-128));
+        assert_eq!(integer(&[0x02, 0x02, 0xff, 0x76]), Some(-138));
+        // a positive value needing a leading zero byte is not negative
+        assert_eq!(integer(&[0x02, 0x05, 0x00, 0x80, 0x00, 0x00, 0x00]), Some(1 << 31));
+        assert_eq!(integer(&[0x02, 0x04, 0x80, 0x00, 0x00, 0x00]), Some(-(1 << 31)));
+    }
+}
@@ -0,0 +1,664 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+use der::asn1::ObjectIdentifier;
+use der::{Class, Tag};
+use sha1::{Digest, Sha1};
+use sha2::{Sha224, Sha256, Sha384, Sha512};
+use crate::asn1_der::{ASN1Node, ASN1Value};
+use crate::schema::{implicit_octets, label_children, show_as_bytes, SchemaOptions};
+use crate::schema::cms::{is_content_info, label_content_info};
+use crate::schema::keys::label_algorithm_identifier;
+use crate::schema::oid::{oid_name, short_oid_name};
+use crate::schema::x509::label_public_key_info;
+
+const ID_LDS_SECURITY_OBJECT: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.23.136.1.1.1");
+
+const SHA1: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.14.3.2.26");
+const SHA224: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.2.4");
+const SHA256: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.2.1");
+const SHA384: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.2.2");
+const SHA512: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.2.3");
+
+/// Data elements of the ICAO 9303 LDS by the number of their `[APPLICATION n]` tag, such as `5F1F` for the MRZ.
+const ELEMENTS: &[(u32, &str)] = &[
+    (1, "ldsVersion"),
+    (14, "fullName"),
+    (15, "otherName"),
+    (16, "personalNumber"),
+    (17, "placeOfBirth"),
+    (18, "telephone"),
+    (19, "profession"),
+    (20, "title"),
+    (21, "personalSummary"),
+    (22, "proofOfCitizenship"),
+    (23, "otherTravelDocuments"),
+    (24, "custodyInformation"),
+    (25, "issuingAuthority"),
+    (27, "endorsements"),
+    (28, "tagList"),
+    (29, "frontImage"),
+    (30, "rearImage"),
+    (31, "mrz"),
+    (38, "dateOfIssue"),
+    (43, "dateOfBirth"),
+    (46, "biometricDataBlock"),
+    (54, "unicodeVersion"),
+    (66, "permanentAddress"),
+    (67, "displayedSignature"),
+    (85, "personalizationTime"),
+    (86, "personalizationDevice"),
+    (96, "biometricInfoTemplate"),
+    (97, "biometricInfoGroupTemplate"),
+];
+
+/// Fields of the biometric header template `A1`, indexed by their context-specific tag number.
+const BIOMETRIC_HEADER: [&str; 9] = [
+    "icaoHeaderVersion",
+    "biometricType",
+    "biometricSubtype",
+    "creationDateTime",
+    "",
+    "validityPeriod",
+    "creator",
+    "formatOwner",
+    "formatType",
+];
+
+const BIOMETRIC_TYPES: &[(u8, &str)] = &[(0x02, "facial features"), (0x08, "fingerprint"), (0x10, "iris")];
+
+/// Recognizes an eMRTD elementary file (EF.COM, DG1-DG16 or EF.SOD) or a bare document security object.
+pub(crate) fn recognize(node: &mut ASN1Node, options: &SchemaOptions) -> bool {
+    if is_security_object_document(node) {
+        label_security_object_document(node, options);
+        return true;
+    }
+    if !is_lds_file(node) {
+        return false;
+    }
+    let number = node.tag_number();
+    node.set_label(&file_name(number));
+    let summary = match number {
+        23 => {
+            let content_info = node.children_mut().first_mut();
+            content_info.and_then(|content_info| label_security_object_document(content_info, options))
+        }
+        14 => node.children_mut().first_mut().and_then(label_security_infos),
+        15 => {
+            let key = node.children_mut().first_mut();
+            key.and_then(|key| {
+                label_public_key_info(key);
+                key.set_label("activeAuthenticationPublicKey");
+                let algorithm = key.children().first()?.children().first()?.as_oid()?;
+                Some(format!("{} key", short_oid_name(&algorithm)))
+            })
+        }
+        _ => label_elements(node),
+    };
+    if let Some(summary) = summary {
+        node.set_annotation(summary);
+    }
+    true
+}
+
+/// Names an elementary file from the number of its `[APPLICATION n]` tag.
+fn file_name(number: u32) -> String {
+    match number {
+        0 => "EF.COM".to_string(),
+        23 => "EF.SOD".to_string(),
+        _ => data_group_number(number).map(|number| format!("DG{}", number)).unwrap_or_default(),
+    }
+}
+
+/// Returns the data group number of an `[APPLICATION n]` tag number, DG2 and DG4 use tags `75` and `76`.
+fn data_group_number(number: u32) -> Option<u32> {
+    match number {
+        1 | 3 | 5..=16 => Some(number),
+        21 => Some(2),
+        22 => Some(4),
+        _ => None,
+    }
+}
+
+fn is_lds_file(node: &ASN1Node) -> bool {
+    let Tag::Application { constructed: true, .. } = node.tag else {
+        return false;
+    };
+    let children = node.children();
+    let is_element = |child: &ASN1Node| child.tag.class() != Class::Universal || child.tag == Tag::Integer;
+    match node.tag_number() {
+        0 => children.iter().any(|child| child.tag.class() == Class::Application && child.tag_number() == 1),
+        1 => children.iter().any(|child| child.tag.class() == Class::Application && child.tag_number() == 31),
+        14 => matches!(children, [infos] if infos.tag == Tag::Set),
+        15 => matches!(children, [key] if matches!(key.children(), [algorithm, bits] if algorithm.tag == Tag::Sequence && bits.tag == Tag::BitString)),
+        23 => matches!(children, [content_info] if is_content_info(content_info)),
+        number => data_group_number(number).is_some() && !children.is_empty() && children.iter().all(is_element),
+    }
+}
+
+/// Labels the data elements of a file and returns the annotation of the file.
+fn label_elements(node: &mut ASN1Node) -> Option<String> {
+    let mut summary = None;
+    for element in node.children_mut() {
+        let number = element.tag_number();
+        let element_summary = match element.tag.class() {
+            Class::Application => {
+                let Some(&(_, name)) = ELEMENTS.iter().find(|(known, _)| *known == number) else {
+                    continue;
+                };
+                element.set_label(name);
+                label_element(element, name)
+            }
+            Class::ContextSpecific if number == 1 && element.tag.is_constructed() => {
+                element.set_label("biometricHeaderTemplate");
+                label_biometric_header(element)
+            }
+            Class::ContextSpecific => label_elements(element),
+            _ if element.tag == Tag::Integer => {
+                element.set_label("numberOfInstances");
+                None
+            }
+            _ => None,
+        };
+        summary = summary.or(element_summary);
+    }
+    summary
+}
+
+/// Decodes the value of a data element and returns its annotation.
+fn label_element(node: &mut ASN1Node, name: &str) -> Option<String> {
+    if node.tag.is_constructed() {
+        let summary = label_elements(node);
+        if let Some(summary) = &summary {
+            node.set_annotation(summary.clone());
+        }
+        return summary;
+    }
+    let annotation = match name {
+        "mrz" => return label_mrz(node),
+        "tagList" => Some(tag_list(&show_raw(node)?)),
+        "biometricDataBlock" => biometric_data_block(&show_raw(node)?),
+        "frontImage" | "rearImage" | "displayedSignature" => Some(format!("{} bytes", show_raw(node)?.len())),
+        "personalizationTime" => {
+            let bytes = show_raw(node)?;
+            Some(bytes.iter().map(|byte| format!("{:02x}", byte)).collect())
+        }
+        "ldsVersion" | "unicodeVersion" => {
+            let version = show_text(node)?;
+            let parts = version.as_bytes().chunks(2).map(|part| String::from_utf8_lossy(part).trim_start_matches('0').to_string());
+            Some(parts.map(|part| if part.is_empty() { "0".to_string() } else { part }).collect::<Vec<String>>().join("."))
+        }
+        "dateOfBirth" | "dateOfIssue" => {
+            let date = show_text(node)?;
+            Some(match date.len() {
+                8 => format!("{}-{}-{}", &date[..4], &date[4..6], &date[6..]),
+                _ => date,
+            })
+        }
+        _ => {
+            let text = show_text(node)?;
+            Some(text.replace("<<", ", ").replace('<', " "))
+        }
+    };
+    if let Some(annotation) = &annotation {
+        node.set_annotation(annotation.clone());
+    }
+    None
+}
+
+/// Replaces the content of a primitive element with bytes and returns them.
+fn show_raw(node: &mut ASN1Node) -> Option<Vec<u8>> {
+    let bytes = implicit_octets(node)?.to_vec();
+    node.value = ASN1Value::Bytes(bytes.clone());
+    node.expandable = false;
+    Some(bytes)
+}
+
+/// Replaces the content of a primitive element with text and returns it.
+fn show_text(node: &mut ASN1Node) -> Option<String> {
+    let bytes = implicit_octets(node)?.to_vec();
+    let text = String::from_utf8_lossy(&bytes).to_string();
+    node.value = ASN1Value::Utf8String(bytes);
+    node.expandable = false;
+    Some(text)
+}
+
+/// Names the tags of a tag list, `61 75` in EF.COM or `5F0E 5F2B` in DG11 and DG12.
+fn tag_list(bytes: &[u8]) -> String {
+    let mut names = vec![];
+    let mut rest = bytes;
+    while let Some((&first, tail)) = rest.split_first() {
+        match (first, tail.split_first()) {
+            (0x5F | 0x7F, Some((&second, tail))) => {
+                let name = ELEMENTS.iter().find(|(known, _)| *known == u32::from(second)).map(|(_, name)| *name);
+                names.push(name.map(str::to_string).unwrap_or_else(|| format!("{:02X}{:02X}", first, second)));
+                rest = tail;
+            }
+            _ => {
+                let name = Some(file_name(u32::from(first & 0x1f))).filter(|name| !name.is_empty() && first & 0xe0 == 0x60);
+                names.push(name.unwrap_or_else(|| format!("{:02X}", first)));
+                rest = tail;
+            }
+        }
+    }
+    names.join(", ")
+}
+
+/// Describes an ISO/IEC 19794 biometric data block by its record format and image type.
+fn biometric_data_block(bytes: &[u8]) -> Option<String> {
+    let format = match bytes.get(..4)? {
+        b"FAC\0" => "ISO/IEC 19794-5 face",
+        b"FIR\0" => "ISO/IEC 19794-4 finger",
+        b"IIR\0" => "ISO/IEC 19794-6 iris",
+        _ => "biometric data",
+    };
+    let image = if bytes.windows(3).any(|window| window == [0xff, 0xd8, 0xff]) {
+        ", JPEG"
+    } else if bytes.windows(4).any(|window| window == b"jP  ") {
+        ", JPEG 2000"
+    } else {
+        ""
+    };
+    Some(format!("{}{}, {} bytes", format, image, bytes.len()))
+}
+
+/// Labels the fields of a biometric header template and returns the biometric type.
+fn label_biometric_header(node: &mut ASN1Node) -> Option<String> {
+    let mut kind = None;
+    for field in node.children_mut() {
+        let Some(&name) = BIOMETRIC_HEADER.get(field.tag_number() as usize).filter(|name| !name.is_empty()) else {
+            continue;
+        };
+        field.set_label(name);
+        let Some(bytes) = show_raw(field) else {
+            continue;
+        };
+        let annotation = match (name, bytes.as_slice()) {
+            ("biometricType", [.., last]) => BIOMETRIC_TYPES.iter().find(|(known, _)| known == last).map(|(_, name)| name.to_string()),
+            ("formatOwner", [0x01, 0x01]) => Some("ISO/IEC JTC 1/SC 37".to_string()),
+            ("formatType", [0x00, 0x07]) => Some("finger image".to_string()),
+            ("formatType", [0x00, 0x08]) => Some("face image".to_string()),
+            ("formatType", [0x00, 0x09]) => Some("iris image".to_string()),
+            _ => None,
+        };
+        if let Some(annotation) = annotation {
+            if name == "biometricType" {
+                kind = Some(annotation.clone());
+            }
+            field.set_annotation(annotation);
+        }
+    }
+    if let Some(kind) = &kind {
+        node.set_annotation(kind.clone());
+    }
+    kind
+}
+
+/// Shows the machine readable zone as lines and decoded fields, returns a one line summary.
+fn label_mrz(node: &mut ASN1Node) -> Option<String> {
+    let text = String::from_utf8_lossy(implicit_octets(node)?).replace(['\r', '\n', ' '], "");
+    let width = match text.len() {
+        88 => 44,
+        72 => 36,
+        90 => 30,
+        _ => {
+            show_text(node);
+            return None;
+        }
+    };
+    let lines = text.as_bytes().chunks(width).map(|line| String::from_utf8_lossy(line).to_string()).collect::<Vec<String>>();
+    let mrz = Mrz::parse(&lines);
+
+    let level = node.level + 1;
+    let mut children = vec![];
+    for (i, line) in lines.iter().enumerate() {
+        children.push(text_node(&format!("line {}", i + 1), line, level, None));
+    }
+    let fields = [
+        ("documentCode", mrz.document_code.clone(), None),
+        ("issuingState", mrz.issuing_state.clone(), None),
+        ("name", mrz.name.clone(), None),
+        ("documentNumber", mrz.document_number.0.clone(), Some(mrz.document_number.1)),
+        ("nationality", mrz.nationality.clone(), None),
+        ("dateOfBirth", mrz.date_of_birth.0.clone(), Some(mrz.date_of_birth.1)),
+        ("sex", mrz.sex.clone(), None),
+        ("dateOfExpiry", mrz.date_of_expiry.0.clone(), Some(mrz.date_of_expiry.1)),
+    ];
+    let mut mismatches = vec![];
+    for (label, value, check) in fields {
+        let annotation = check.map(|valid| if valid { "check digit ok" } else { "check digit mismatch" });
+        if check == Some(false) {
+            mismatches.push(label);
+        }
+        children.push(text_node(label, &value, level, annotation));
+    }
+    node.value = ASN1Value::Application(children);
+
+    let mut summary = format!(
+        "{} {} {}, document {}, born {}, {}, expires {}",
+        mrz.document_code,
+        mrz.issuing_state,
+        mrz.name,
+        mrz.document_number.0,
+        mrz.date_of_birth.0,
+        mrz.sex,
+        mrz.date_of_expiry.0
+    );
+    if !mismatches.is_empty() {
+        summary.push_str(&format!(", check digit mismatch: {}", mismatches.join(", ")));
+    }
+    node.set_annotation(summary.clone());
+    Some(summary)
+}
+
+fn text_node(label: &str, text: &str, level: u32, annotation: Option<&str>) -> ASN1Node {
+    let mut node = ASN1Node::new(Tag::Utf8String, ASN1Value::Utf8String(text.as_bytes().to_vec()), level, false, false);
+    node.set_label(label);
+    if let Some(annotation) = annotation {
+        node.set_annotation(annotation);
+    }
+    node
+}
+
+/// Fields of a TD1, TD2 or TD3 machine readable zone, checked fields carry the result of their check digit.
+struct Mrz {
+    document_code: String,
+    issuing_state: String,
+    name: String,
+    document_number: (String, bool),
+    nationality: String,
+    date_of_birth: (String, bool),
+    sex: String,
+    date_of_expiry: (String, bool),
+}
+
+impl Mrz {
+    fn parse(lines: &[String]) -> Mrz {
+        let field = |line: usize, start: usize, end: usize| lines[line].get(start..end).unwrap_or_default().to_string();
+        let checked = |line: usize, start: usize, end: usize| {
+            let value = field(line, start, end);
+            let valid = field(line, end, end + 1) == check_digit(&value);
+            (value, valid)
+        };
+        // TD1 has three lines of 30 characters, TD2 and TD3 share the layout of their second line
+        let (document_number, data, name) = match lines.len() {
+            3 => (checked(0, 5, 14), (1, 0, 7, 8, 15), field(2, 0, 30)),
+            _ => (checked(1, 0, 9), (1, 13, 20, 21, 10), field(0, 5, lines[0].len())),
+        };
+        let (line, birth, sex, expiry, nationality) = data;
+        let (date_of_birth, birth_valid) = checked(line, birth, birth + 6);
+        let (date_of_expiry, expiry_valid) = checked(line, expiry, expiry + 6);
+        Mrz {
+            document_code: field(0, 0, 2).trim_end_matches('<').to_string(),
+            issuing_state: field(0, 2, 5).trim_end_matches('<').to_string(),
+            name: mrz_name(&name),
+            document_number: (document_number.0.trim_end_matches('<').to_string(), document_number.1),
+            nationality: field(line, nationality, nationality + 3).trim_end_matches('<').to_string(),
+            date_of_birth: (mrz_date(&date_of_birth, false), birth_valid),
+            sex: field(line, sex, sex + 1).replace('<', "X"),
+            date_of_expiry: (mrz_date(&date_of_expiry, true), expiry_valid),
+        }
+    }
+}
+
+/// Computes the ICAO 9303 check digit with the weights 7, 3, 1.
+fn check_digit(value: &str) -> String {
+    let sum = value
+        .bytes()
+        .map(|byte| match byte {
+            b'0'..=b'9' => u32::from(byte - b'0'),
+            b'A'..=b'Z' => u32::from(byte - b'A') + 10,
+            _ => 0,
+        })
+        .zip([7, 3, 1].iter().cycle())
+        .map(|(value, weight)| value * weight)
+        .sum::<u32>();
+    (sum % 10).to_string()
+}
+
+/// Formats `SURNAME<<GIVEN<NAMES` as `SURNAME, GIVEN NAMES`.
+fn mrz_name(name: &str) -> String {
+    let name = name.trim_end_matches('<');
+    match name.split_once("<<") {
+        Some((surname, given)) => format!("{}, {}", surname.replace('<', " "), given.replace('<', " ")),
+        None => name.replace('<', " "),
+    }
+}
+
+/// Formats a `YYMMDD` date, dates of birth in the future belong to the previous century.
+fn mrz_date(date: &str, expiry: bool) -> String {
+    let Ok(year) = date.get(..2).unwrap_or_default().parse::<u64>() else {
+        return date.to_string();
+    };
+    if date.len() != 6 || !date.bytes().all(|byte| byte.is_ascii_digit()) {
+        return date.to_string();
+    }
+    let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map(|now| now.as_secs()).unwrap_or_default();
+    let this_year = (1970 + seconds / 31_556_952) % 100;
+    let century = if expiry || year <= this_year { 20 } else { 19 };
+    format!("{}{}-{}-{}", century, &date[..2], &date[2..4], &date[4..])
+}
+
+/// `SecurityInfos ::= SET OF SecurityInfo`, each `SEQUENCE { protocol OBJECT IDENTIFIER, requiredData, optionalData }`
+fn label_security_infos(node: &mut ASN1Node) -> Option<String> {
+    node.set_label("SecurityInfos");
+    let mut protocols = vec![];
+    for info in node.children_mut() {
+        let Some(protocol) = info.children().first().and_then(ASN1Node::as_oid) else {
+            continue;
+        };
+        let name = oid_name(&protocol).map(|name| name.trim_start_matches("id-").to_string()).unwrap_or_else(|| protocol.to_string());
+        let fields = match name.split('-').next().unwrap_or_default() {
+            "PK" => ["protocol", "chipAuthenticationPublicKey", "keyId"],
+            "CA" => ["protocol", "version", "keyId"],
+            "AA" => ["protocol", "version", "signatureAlgorithm"],
+            "TA" => ["protocol", "version", "efCVCA"],
+            "PACE" => ["protocol", "version", "parameterId"],
+            _ => ["protocol", "requiredData", "optionalData"],
+        };
+        label_children(info, &fields);
+        if let Some(key) = info.children_mut().get_mut(1).filter(|key| key.tag == Tag::Sequence && fields[1] != "requiredData") {
+            label_public_key_info(key);
+            key.set_label(fields[1]);
+        }
+        info.set_label(&name);
+        protocols.push(name);
+    }
+    let summary = protocols.join(", ");
+    node.set_annotation(summary.clone());
+    Some(summary)
+}
+
+/// An EF.SOD `ContentInfo` whose signed content is an `LDSSecurityObject`.
+fn is_security_object_document(node: &ASN1Node) -> bool {
+    is_content_info(node) && security_object(node).is_some()
+}
+
+/// Follows `ContentInfo` > `SignedData` > `encapContentInfo` to the content of an `LDSSecurityObject`.
+fn security_object(node: &ASN1Node) -> Option<&ASN1Node> {
+    let encapsulated = node.children().get(1)?.children().first()?.children().get(2)?;
+    let content_type = encapsulated.children().first()?.as_oid()?;
+    let octets = encapsulated.children().get(1)?.children().first()?;
+    (content_type == ID_LDS_SECURITY_OBJECT).then(|| octets.children().first()).flatten()
+}
+
+fn security_object_mut(node: &mut ASN1Node) -> Option<&mut ASN1Node> {
+    let encapsulated = node.children_mut().get_mut(1)?.children_mut().first_mut()?.children_mut().get_mut(2)?;
+    let octets = encapsulated.children_mut().get_mut(1)?.children_mut().first_mut()?;
+    octets.children_mut().first_mut()
+}
+
+/// Labels the EF.SOD `ContentInfo` and its `LDSSecurityObject`, returns a summary of the data group hashes.
+fn label_security_object_document(node: &mut ASN1Node, options: &SchemaOptions) -> Option<String> {
+    label_content_info(node);
+    security_object(node)?;
+    let summary = label_security_object(security_object_mut(node)?, options);
+    node.set_annotation(summary.clone());
+    Some(summary)
+}
+
+/// `LDSSecurityObject ::= SEQUENCE { version, hashAlgorithm, dataGroupHashValues SEQUENCE OF DataGroupHash, ldsVersionInfo OPTIONAL }`
+fn label_security_object(node: &mut ASN1Node, options: &SchemaOptions) -> String {
+    node.set_label("LDSSecurityObject");
+    label_children(node, &["version", "hashAlgorithm", "dataGroupHashValues", "ldsVersionInfo"]);
+    let algorithm = node.children().get(1).and_then(|algorithm| algorithm.children().first()).and_then(ASN1Node::as_oid);
+    let children = node.children_mut();
+    if let Some(algorithm) = children.get_mut(1) {
+        label_algorithm_identifier(algorithm, "hashAlgorithm");
+    }
+    if let Some(version_info) = children.get_mut(3) {
+        label_children(version_info, &["ldsVersion", "unicodeVersion"]);
+    }
+    let Some(hashes) = children.get_mut(2) else {
+        return String::new();
+    };
+    let (mut matched, mut mismatched) = (0, 0);
+    for hash in hashes.children_mut() {
+        // DataGroupHash ::= SEQUENCE { dataGroupNumber INTEGER, dataGroupHashValue OCTET STRING }
+        label_children(hash, &["dataGroupNumber", "dataGroupHashValue"]);
+        let Some(number) = hash.children().first().and_then(ASN1Node::as_u128) else {
+            continue;
+        };
+        hash.set_label(&format!("DG{}", number));
+        let Some(value) = hash.children_mut().get_mut(1) else {
+            continue;
+        };
+        show_as_bytes(value);
+        let Some(expected) = value.octets().map(<[u8]>::to_vec) else {
+            continue;
+        };
+        let file = options.data_groups.iter().find(|file| {
+            let tag = u32::from(file.first().copied().unwrap_or_default());
+            tag & 0xe0 == 0x60 && data_group_number(tag & 0x1f).map(u128::from) == Some(number)
+        });
+        let annotation = match file.map(|file| algorithm.as_ref().and_then(|algorithm| digest(algorithm, file))) {
+            Some(Some(actual)) if actual == expected => {
+                matched += 1;
+                "hash matches the DG file".to_string()
+            }
+            Some(Some(actual)) => {
+                mismatched += 1;
+                format!("hash MISMATCH, DG file hash {}", hex::encode(actual))
+            }
+            Some(None) => "unsupported hash algorithm".to_string(),
+            None => continue,
+        };
+        hash.set_annotation(annotation);
+    }
+    let count = hashes.children().len();
+    let summary = format!("{} data group hash(es)", count);
+    if options.data_groups.is_empty() {
+        return summary;
+    }
+    format!("{}, {} matched, {} mismatched", summary, matched, mismatched)
+}
+
+fn digest(algorithm: &ObjectIdentifier, data: &[u8]) -> Option<Vec<u8>> {
+    match *algorithm {
+        SHA1 => Some(Sha1::digest(data).to_vec()),
+        SHA224 => Some(Sha224::digest(data).to_vec()),
+        SHA256 => Some(Sha256::digest(data).to_vec()),
+        SHA384 => Some(Sha384::digest(data).to_vec()),
+        SHA512 => Some(Sha512::digest(data).to_vec()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asn1_der::{parse_asn1, view_lines};
+    use crate::schema::annotate;
+
+    const TD3: &str = "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<L898902C36UTO7408122F1204159ZE184226B<<<<<10";
+
+    fn dg1(mrz: &str) -> Vec<u8> {
+        [&[0x61, mrz.len() as u8 + 3, 0x5f, 0x1f, mrz.len() as u8][..], mrz.as_bytes()].concat()
+    }
+
+    fn annotated(data: &[u8], options: &SchemaOptions) -> Vec<String> {
+        let mut root = parse_asn1(data, 0).unwrap();
+        annotate(&mut root, options);
+        view_lines(&root)
+    }
+
+    #[test]
+    fn computes_check_digits() {
+        assert_eq!(check_digit("L898902C3"), "6");
+        assert_eq!(check_digit("D23145890"), "7");
+        assert_eq!(check_digit("740812"), "2");
+        assert_eq!(check_digit("120415"), "9");
+        assert_eq!(check_digit("ZE184226B<<<<<"), "1");
+        assert_eq!(check_digit("<<<<<<<<<"), "0");
+    }
+
+    #[test]
+    fn parses_td3_zones() {
+        assert_eq!(
+            annotated(&dg1(TD3), &SchemaOptions::default()),
+            [
+                "DG1: APPLICATION [1] (constructed)  (P UTO ERIKSSON, ANNA MARIA, document L898902C3, born 1974-08-12, F, expires 2012-04-15)",
+                "mrz: APPLICATION [31] (primitive)  (P UTO ERIKSSON, ANNA MARIA, document L898902C3, born 1974-08-12, F, expires 2012-04-15)",
+                "line 1: UTF8String \"P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\"",
+                "line 2: UTF8String \"L898902C36UTO7408122F1204159ZE184226B<<<<<10\"",
+                "documentCode: UTF8String \"P\"",
+                "issuingState: UTF8String \"UTO\"",
+                "name: UTF8String \"ERIKSSON, ANNA MARIA\"",
+                "documentNumber: UTF8String \"L898902C3\" (check digit ok)",
+                "nationality: UTF8String \"UTO\"",
+                "dateOfBirth: UTF8String \"1974-08-12\" (check digit ok)",
+                "sex: UTF8String \"F\"",
+                "dateOfExpiry: UTF8String \"2012-04-15\" (check digit ok)",
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_td1_zones() {
+        let mrz = concat!("I<UTOD231458907<<<<<<<<<<<<<<<", "7408122F1204159UTO<<<<<<<<<<<6", "ERIKSSON<<ANNA<MARIA<<<<<<<<<<");
+        let lines = annotated(&dg1(mrz), &SchemaOptions::default());
+        assert_eq!(
+            lines[0],
+            "DG1: APPLICATION [1] (constructed)  (I UTO ERIKSSON, ANNA MARIA, document D23145890, born 1974-08-12, F, expires 2012-04-15)"
+        );
+        assert_eq!(lines[4], "line 3: UTF8String \"ERIKSSON<<ANNA<MARIA<<<<<<<<<<\"");
+        assert_eq!(lines[8], "documentNumber: UTF8String \"D23145890\" (check digit ok)");
+        assert_eq!(lines[12], "dateOfExpiry: UTF8String \"2012-04-15\" (check digit ok)");
+    }
+
+    #[test]
+    fn reports_check_digit_mismatches() {
+        let lines = annotated(&dg1(&TD3.replace("7408122", "7408132")), &SchemaOptions::default());
+        assert_eq!(
+            lines[0],
+            "DG1: APPLICATION [1] (constructed)  (P UTO ERIKSSON, ANNA MARIA, document L898902C3, born 1974-08-13, F, expires 2012-04-15, check digit mismatch: dateOfBirth)"
+        );
+        assert_eq!(lines[7], "documentNumber: UTF8String \"L898902C3\" (check digit ok)");
+        assert_eq!(lines[9], "dateOfBirth: UTF8String \"1974-08-13\" (check digit mismatch)");
+    }
+
+    #[test]
+    fn labels_security_objects() {
+        let lines = annotated(include_bytes!("../../tests/data/mrtd-sod.bin"), &SchemaOptions::default());
+        assert_eq!(lines[0], "EF.SOD: APPLICATION [23] (constructed)  (3 data group hash(es))");
+        assert_eq!(lines[10], "eContentType: OBJECT IDENTIFIER 2.23.136.1.1.1 (ldsSecurityObject)");
+        assert_eq!(lines[13], "LDSSecurityObject: SEQUENCE (field(s): 3)");
+        assert_eq!(lines[18], "DG1: SEQUENCE (field(s): 2)");
+        assert_eq!(lines[24], "DG15: SEQUENCE (field(s): 2)");
+        assert_eq!(lines[28], "signerInfo #1: SEQUENCE (field(s): 5) (CN=DS, serial 5)");
+    }
+
+    #[test]
+    fn checks_data_group_hashes() {
+        let options = SchemaOptions {
+            data_groups: vec![dg1(TD3), vec![0x75, 0x03, 0x02, 0x01, 0x01]],
+            ..SchemaOptions::default()
+        };
+        let lines = annotated(include_bytes!("../../tests/data/mrtd-sod.bin"), &options);
+        assert_eq!(lines[0], "EF.SOD: APPLICATION [23] (constructed)  (3 data group hash(es), 1 matched, 1 mismatched)");
+        assert_eq!(lines[18], "DG1: SEQUENCE (field(s): 2) (hash matches the DG file)");
+        assert_eq!(
+            lines[21],
+            "DG2: SEQUENCE (field(s): 2) (hash MISMATCH, DG file hash 9eb472158fd28fe864a03c6a515f93432382c396fed6a720f209cfaa32ef176a)"
+        );
+        assert_eq!(lines[24], "DG15: SEQUENCE (field(s): 2)");
+    }
+}
@@ -0,0 +1,414 @@
+use der::asn1::ObjectIdentifier;
+use der::Tag;
+use crate::asn1_der::{ASN1Node, ASN1Value};
+use crate::schema::is_context;
+use crate::schema::keys::label_algorithm_identifier;
+use crate::schema::x509::{is_certificate, label_certificate, label_extensions, label_name};
+
+const ID_PKIX_OCSP_BASIC: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.6.1.5.5.7.48.1.1");
+
+const RESPONSE_STATUSES: [&str; 7] = [
+    "successful",
+    "malformedRequest",
+    "internalError",
+    "tryLater",
+    "",
+    "sigRequired",
+    "unauthorized",
+];
+
+const CRL_REASONS: [&str; 11] = [
+    "unspecified",
+    "keyCompromise",
+    "cACompromise",
+    "affiliationChanged",
+    "superseded",
+    "cessationOfOperation",
+    "certificateHold",
+    "",
+    "removeFromCRL",
+    "privilegeWithdrawn",
+    "aACompromise",
+];
+
+/// Recognizes an OCSP request, response or a bare `BasicOCSPResponse` (RFC 6960) at the node.
+pub(crate) fn recognize(node: &mut ASN1Node) -> bool {
+    if is_ocsp_response(node) {
+        label_ocsp_response(node);
+    } else if is_ocsp_request(node) {
+        label_ocsp_request(node);
+    } else if is_basic_ocsp_response(node) {
+        label_basic_ocsp_response(node);
+    } else {
+        return false;
+    }
+    true
+}
+
+/// `OCSPResponse ::= SEQUENCE { responseStatus ENUMERATED, responseBytes [0] EXPLICIT ResponseBytes OPTIONAL }`
+fn is_ocsp_response(node: &ASN1Node) -> bool {
+    let children = node.children();
+    node.tag == Tag::Sequence
+        && (1..=2).contains(&children.len())
+        && children[0].tag == Tag::Enumerated
+        && children.get(1).map(|bytes| is_context(bytes, 0)).unwrap_or(true)
+}
+
+/// `OCSPRequest ::= SEQUENCE { tbsRequest TBSRequest, optionalSignature [0] EXPLICIT Signature OPTIONAL }`
+fn is_ocsp_request(node: &ASN1Node) -> bool {
+    let children = node.children();
+    node.tag == Tag::Sequence
+        && (1..=2).contains(&children.len())
+        && children.get(1).map(|signature| is_context(signature, 0)).unwrap_or(true)
+        && children[0].tag == Tag::Sequence
+        && children[0]
+            .children()
+            .iter()
+            .find(|field| field.tag == Tag::Sequence)
+            .map(|requests| !requests.children().is_empty() && requests.children().iter().all(is_request))
+            .unwrap_or(false)
+}
+
+/// `Request ::= SEQUENCE { reqCert CertID, singleRequestExtensions [0] EXPLICIT Extensions OPTIONAL }`
+fn is_request(node: &ASN1Node) -> bool {
+    node.tag == Tag::Sequence && node.children().first().map(is_cert_id).unwrap_or(false)
+}
+
+/// `CertID ::= SEQUENCE { hashAlgorithm, issuerNameHash OCTET STRING, issuerKeyHash OCTET STRING, serialNumber }`
+fn is_cert_id(node: &ASN1Node) -> bool {
+    match node.children() {
+        [algorithm, name_hash, key_hash, serial] => {
+            node.tag == Tag::Sequence
+                && algorithm.tag == Tag::Sequence
+                && name_hash.tag == Tag::OctetString
+                && key_hash.tag == Tag::OctetString
+                && serial.tag == Tag::Integer
+        }
+        _ => false,
+    }
+}
+
+/// `BasicOCSPResponse ::= SEQUENCE { tbsResponseData, signatureAlgorithm, signature BIT STRING, certs [0] OPTIONAL }`
+fn is_basic_ocsp_response(node: &ASN1Node) -> bool {
+    let children = node.children();
+    node.tag == Tag::Sequence
+        && (3..=4).contains(&children.len())
+        && children[1].tag == Tag::Sequence
+        && children[2].tag == Tag::BitString
+        && is_response_data(&children[0])
+}
+
+/// `ResponseData ::= SEQUENCE { version [0] DEFAULT v1, responderID, producedAt GeneralizedTime, responses, ... }`
+fn is_response_data(node: &ASN1Node) -> bool {
+    let fields: Vec<&ASN1Node> = node.children().iter().filter(|field| !is_context(field, 0)).collect();
+    node.tag == Tag::Sequence
+        && fields.len() >= 3
+        && (is_context(fields[0], 1) || is_context(fields[0], 2))
+        && fields[1].tag == Tag::GeneralizedTime
+        && fields[2].tag == Tag::Sequence
+}
+
+fn label_ocsp_response(node: &mut ASN1Node) {
+    node.set_label("OCSPResponse");
+    let status = node.children()[0].as_u128().map(response_status).unwrap_or_default();
+    node.set_annotation(status.clone());
+
+    let children = node.children_mut();
+    children[0].set_label("responseStatus");
+    children[0].set_annotation(status);
+    let Some(response_bytes) = children.get_mut(1) else {
+        return;
+    };
+    response_bytes.set_label("responseBytes");
+    let Some(response_bytes) = response_bytes.children_mut().first_mut() else {
+        return;
+    };
+    response_bytes.set_label("ResponseBytes");
+    let response_type = response_bytes.children().first().and_then(ASN1Node::as_oid);
+    let fields = response_bytes.children_mut();
+    if let Some(kind) = fields.get_mut(0) {
+        kind.set_label("responseType");
+    }
+    let Some(response) = fields.get_mut(1) else {
+        return;
+    };
+    response.set_label("response");
+    if let (Some(ID_PKIX_OCSP_BASIC), ASN1Value::OctetString(inner, _)) = (response_type, &mut response.value) {
+        if is_basic_ocsp_response(inner) {
+            label_basic_ocsp_response(inner);
+        }
+    }
+}
+
+fn response_status(status: u128) -> String {
+    RESPONSE_STATUSES
+        .get(status as usize)
+        .filter(|name| !name.is_empty())
+        .map(|name| name.to_string())
+        .unwrap_or_else(|| format!("unknown status {}", status))
+}
+
+fn crl_reason(reason: u128) -> String {
+    CRL_REASONS
+        .get(reason as usize)
+        .filter(|name| !name.is_empty())
+        .map(|name| name.to_string())
+        .unwrap_or_else(|| format!("unknown reason {}", reason))
+}
+
+fn label_ocsp_request(node: &mut ASN1Node) {
+    node.set_label("OCSPRequest");
+    let children = node.children_mut();
+    children[0].set_label("tbsRequest");
+    let mut requests = 0;
+    for field in children[0].children_mut() {
+        match field.tag {
+            Tag::ContextSpecific { number, .. } => match number.value() {
+                0 => field.set_label("version"),
+                1 => field.set_label("requestorName"),
+                2 => {
+                    field.set_label("requestExtensions");
+                    if let Some(extensions) = field.children_mut().first_mut() {
+                        label_extensions(extensions);
+                    }
+                }
+                _ => {}
+            },
+            Tag::Sequence => {
+                field.set_label("requestList");
+                for (i, request) in field.children_mut().iter_mut().enumerate() {
+                    label_request(request, i + 1);
+                    requests += 1;
+                }
+            }
+            _ => {}
+        }
+    }
+    if let Some(signature) = children.get_mut(1) {
+        signature.set_label("optionalSignature");
+        if let Some(signature) = signature.children_mut().first_mut() {
+            label_signature(signature);
+        }
+    }
+    node.set_annotation(format!("{} certificate(s)", requests));
+}
+
+fn label_request(node: &mut ASN1Node, number: usize) {
+    node.set_label(&format!("request #{}", number));
+    let children = node.children_mut();
+    let serial = label_cert_id(&mut children[0]);
+    children[0].set_label("reqCert");
+    if let Some(extensions) = children.get_mut(1) {
+        extensions.set_label("singleRequestExtensions");
+        if let Some(extensions) = extensions.children_mut().first_mut() {
+            label_extensions(extensions);
+        }
+    }
+    node.set_annotation(format!("serial {}", serial));
+}
+
+/// Labels a `CertID` and returns the serial number of the certificate.
+fn label_cert_id(node: &mut ASN1Node) -> String {
+    let children = node.children_mut();
+    label_algorithm_identifier(&mut children[0], "hashAlgorithm");
+    children[1].set_label("issuerNameHash");
+    children[2].set_label("issuerKeyHash");
+    children[3].set_label("serialNumber");
+    children[3].display_value()
+}
+
+/// `Signature ::= SEQUENCE { signatureAlgorithm, signature BIT STRING, certs [0] EXPLICIT SEQUENCE OF Certificate OPTIONAL }`
+fn label_signature(node: &mut ASN1Node) {
+    node.set_label("Signature");
+    let children = node.children_mut();
+    if let Some(algorithm) = children.get_mut(0) {
+        label_algorithm_identifier(algorithm, "signatureAlgorithm");
+    }
+    if let Some(signature) = children.get_mut(1) {
+        signature.set_label("signature");
+    }
+    if let Some(certs) = children.get_mut(2) {
+        label_certs(certs);
+    }
+}
+
+fn label_certs(node: &mut ASN1Node) {
+    node.set_label("certs");
+    let Some(certificates) = node.children_mut().first_mut() else {
+        return;
+    };
+    for (i, certificate) in certificates.children_mut().iter_mut().enumerate() {
+        if is_certificate(certificate) {
+            certificate.set_label(&format!("certificate #{}", i + 1));
+            label_certificate(certificate);
+        }
+    }
+}
+
+fn label_basic_ocsp_response(node: &mut ASN1Node) {
+    node.set_label("BasicOCSPResponse");
+    let children = node.children_mut();
+    let produced_at = label_response_data(&mut children[0]);
+    label_algorithm_identifier(&mut children[1], "signatureAlgorithm");
+    children[2].set_label("signature");
+    if let Some(certs) = children.get_mut(3) {
+        label_certs(certs);
+    }
+    if let Some(produced_at) = produced_at {
+        node.set_annotation(format!("produced at {}", produced_at));
+    }
+}
+
+/// Labels `ResponseData` and returns its `producedAt` time.
+fn label_response_data(node: &mut ASN1Node) -> Option<String> {
+    node.set_label("tbsResponseData");
+    let mut produced_at = None;
+    let mut seen_responder = false;
+    for field in node.children_mut() {
+        match field.tag {
+            Tag::ContextSpecific { number, .. } if number.value() == 0 => field.set_label("version"),
+            Tag::ContextSpecific { number, .. } if !seen_responder => {
+                seen_responder = true;
+                field.set_label("responderID");
+                if let Some(inner) = field.children_mut().first_mut() {
+                    match number.value() {
+                        1 => label_name(inner, "byName"),
+                        _ => inner.set_label("byKey"),
+                    }
+                }
+            }
+            Tag::ContextSpecific { .. } => {
+                field.set_label("responseExtensions");
+                if let Some(extensions) = field.children_mut().first_mut() {
+                    label_extensions(extensions);
+                }
+            }
+            Tag::GeneralizedTime => {
+                field.set_label("producedAt");
+                produced_at = field.as_string();
+            }
+            Tag::Sequence => {
+                field.set_label("responses");
+                for (i, response) in field.children_mut().iter_mut().enumerate() {
+                    label_single_response(response, i + 1);
+                }
+            }
+            _ => {}
+        }
+    }
+    produced_at
+}
+
+/// `SingleResponse ::= SEQUENCE { certID, certStatus, thisUpdate, nextUpdate [0] OPTIONAL, singleExtensions [1] OPTIONAL }`
+fn label_single_response(node: &mut ASN1Node, number: usize) {
+    node.set_label(&format!("SingleResponse #{}", number));
+    let mut serial = None;
+    let mut status = None;
+    for (i, field) in node.children_mut().iter_mut().enumerate() {
+        match i {
+            0 if is_cert_id(field) => {
+                serial = Some(label_cert_id(field));
+                field.set_label("certID");
+            }
+            1 => status = Some(label_cert_status(field)),
+            2 => field.set_label("thisUpdate"),
+            _ if is_context(field, 0) => field.set_label("nextUpdate"),
+            _ if is_context(field, 1) => {
+                field.set_label("singleExtensions");
+                if let Some(extensions) = field.children_mut().first_mut() {
+                    label_extensions(extensions);
+                }
+            }
+            _ => {}
+        }
+    }
+    let annotation = [serial.map(|serial| format!("serial {}", serial)), status]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<String>>()
+        .join(", ");
+    node.set_annotation(annotation);
+}
+
+/// `CertStatus ::= CHOICE { good [0] IMPLICIT NULL, revoked [1] IMPLICIT RevokedInfo, unknown [2] IMPLICIT UnknownInfo }`
+fn label_cert_status(node: &mut ASN1Node) -> String {
+    node.set_label("certStatus");
+    let status = match node.tag {
+        Tag::ContextSpecific { number, .. } if number.value() == 0 => "good".to_string(),
+        Tag::ContextSpecific { number, .. } if number.value() == 1 => label_revoked_info(node),
+        Tag::ContextSpecific { number, .. } if number.value() == 2 => "unknown".to_string(),
+        _ => return String::new(),
+    };
+    node.set_annotation(status.clone());
+    status
+}
+
+/// `RevokedInfo ::= SEQUENCE { revocationTime GeneralizedTime, revocationReason [0] EXPLICIT CRLReason OPTIONAL }`
+fn label_revoked_info(node: &mut ASN1Node) -> String {
+    let mut status = "revoked".to_string();
+    for field in node.children_mut() {
+        if field.tag == Tag::GeneralizedTime {
+            field.set_label("revocationTime");
+            if let Some(time) = field.as_string() {
+                status.push_str(&format!(" at {}", time));
+            }
+        } else if is_context(field, 0) {
+            field.set_label("revocationReason");
+            let reason = field.children().first().and_then(ASN1Node::as_u128).map(crl_reason);
+            if let Some(reason) = reason {
+                field.set_annotation(reason.clone());
+                status.push_str(&format!(" ({})", reason));
+            }
+        }
+    }
+    status
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::asn1_der::{parse_asn1, view_lines};
+    use crate::schema::{annotate, SchemaOptions};
+
+    fn annotated(data: &[u8]) -> Vec<String> {
+        let mut root = parse_asn1(data, 0).unwrap();
+        annotate(&mut root, &SchemaOptions::default());
+        view_lines(&root)
+    }
+
+    #[test]
+    fn labels_basic_responses() {
+        let lines = annotated(include_bytes!("../../tests/data/ocsp-response.der"));
+        assert_eq!(lines[0], "OCSPResponse: SEQUENCE (field(s): 2) (successful)");
+        assert_eq!(lines[1], "responseStatus: ENUMERATED 0 (successful)");
+        assert_eq!(lines[4], "responseType: OBJECT IDENTIFIER 1.3.6.1.5.5.7.48.1.1 (id-pkix-ocsp-basic)");
+        assert_eq!(lines[6], "BasicOCSPResponse: SEQUENCE (field(s): 4) (produced at 2026-10-18T20:38:48Z)");
+        assert_eq!(lines[9], "byName: SEQUENCE (field(s): 2) (CN=Signer, O=Example)");
+        assert_eq!(lines[40], "certificate #1: SEQUENCE (field(s): 3) (CN=Signer, O=Example)");
+    }
+
+    #[test]
+    fn labels_revoked_certificates() {
+        let lines = annotated(include_bytes!("../../tests/data/ocsp-response.der"));
+        assert_eq!(
+            lines[20],
+            "SingleResponse #1: SEQUENCE (field(s): 4) (serial 0x75822cc7b107a1d10fb40fa23349e2c035d27cd0, revoked at 2026-10-18T00:00:00Z (keyCompromise))"
+        );
+        assert_eq!(lines[25], "issuerNameHash: OCTET STRING b62169c1fcc0a22f86af478136cd1fd9dcce9e82");
+        assert_eq!(
+            lines[28],
+            "certStatus: CONTEXT-SPECIFIC [1] (constructed)  (revoked at 2026-10-18T00:00:00Z (keyCompromise))"
+        );
+        assert_eq!(lines[29], "revocationTime: GeneralizedTime \"2026-10-18T00:00:00Z\"");
+        assert_eq!(lines[30], "revocationReason: CONTEXT-SPECIFIC [0] (constructed)  (keyCompromise)");
+        assert_eq!(lines[33], "nextUpdate: CONTEXT-SPECIFIC [0] (constructed) ");
+    }
+
+    #[test]
+    fn labels_requests() {
+        let lines = annotated(include_bytes!("../../tests/data/ocsp-request.der"));
+        assert_eq!(lines[0], "OCSPRequest: SEQUENCE (field(s): 1) (1 certificate(s))");
+        assert_eq!(lines[3], "request #1: SEQUENCE (field(s): 1) (serial 0x75822cc7b107a1d10fb40fa23349e2c035d27cd0)");
+        assert_eq!(lines[4], "reqCert: SEQUENCE (field(s): 4)");
+        assert_eq!(lines[9], "issuerKeyHash: OCTET STRING 19f4c04cca1b573aeced1b5b62037eca0c08ef22");
+    }
+}
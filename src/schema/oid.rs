@@ -0,0 +1,142 @@
+use const_oid::db::DB;
+use der::asn1::ObjectIdentifier;
+
+/// Names for object identifiers which are missing from the `const-oid` database.
+const EXTRA_NAMES: &[(&str, &str)] = &[
+    ("1.2.840.113549.1.5.3", "pbeWithMD5AndDES-CBC"),
+    ("1.2.840.113549.1.5.10", "pbeWithSHA1AndDES-CBC"),
+    ("1.2.840.113549.1.5.12", "id-PBKDF2"),
+    ("1.2.840.113549.1.5.13", "id-PBES2"),
+    ("1.3.6.1.4.1.11591.4.11", "id-scrypt"),
+    ("1.2.840.113549.2.7", "id-hmacWithSHA1"),
+    ("1.2.840.113549.2.9", "id-hmacWithSHA256"),
+    ("1.2.840.113549.2.10", "id-hmacWithSHA384"),
+    ("1.2.840.113549.2.11", "id-hmacWithSHA512"),
+    ("2.16.840.1.101.3.4.1.2", "id-aes128-CBC"),
+    ("2.16.840.1.101.3.4.1.22", "id-aes192-CBC"),
+    ("2.16.840.1.101.3.4.1.42", "id-aes256-CBC"),
+    ("1.2.840.113549.1.9.16.1.4", "id-ct-TSTInfo"),
+    ("1.2.840.113549.1.9.20", "friendlyName"),
+    ("1.2.840.113549.1.9.21", "localKeyId"),
+    ("1.2.840.113549.1.9.22.1", "x509Certificate"),
+    ("1.2.840.113549.1.9.22.2", "sdsiCertificate"),
+    ("1.2.840.113549.1.9.23.1", "x509CRL"),
+    ("1.2.840.113549.1.12.1.1", "pbeWithSHAAnd128BitRC4"),
+    ("1.2.840.113549.1.12.1.2", "pbeWithSHAAnd40BitRC4"),
+    ("1.2.840.113549.1.12.1.3", "pbeWithSHAAnd3-KeyTripleDES-CBC"),
+    ("1.2.840.113549.1.12.1.4", "pbeWithSHAAnd2-KeyTripleDES-CBC"),
+    ("1.2.840.113549.1.12.1.5", "pbeWithSHAAnd128BitRC2-CBC"),
+    ("1.2.840.113549.1.12.1.6", "pbeWithSHAAnd40BitRC2-CBC"),
+    ("1.2.840.113549.1.12.10.1.1", "keyBag"),
+    ("1.2.840.113549.1.12.10.1.2", "pkcs8ShroudedKeyBag"),
+    ("1.2.840.113549.1.12.10.1.3", "certBag"),
+    ("1.2.840.113549.1.12.10.1.4", "crlBag"),
+    ("1.2.840.113549.1.12.10.1.5", "secretBag"),
+    ("1.2.840.113549.1.12.10.1.6", "safeContentsBag"),
+    ("1.3.6.1.2.1.1.3", "sysUpTime"),
+    ("1.3.6.1.6.3.1.1.4.1", "snmpTrapOID"),
+    ("1.3.6.1.6.3.1.1.5.1", "coldStart"),
+    ("1.3.6.1.6.3.1.1.5.2", "warmStart"),
+    ("1.3.6.1.6.3.1.1.5.3", "linkDown"),
+    ("1.3.6.1.6.3.1.1.5.4", "linkUp"),
+    ("1.3.6.1.6.3.1.1.5.5", "authenticationFailure"),
+    ("1.3.6.1.4.1.11129.2.1.17", "keyDescription"),
+    ("2.16.840.1.113733.1.9.2", "messageType"),
+    ("2.16.840.1.113733.1.9.3", "pkiStatus"),
+    ("2.16.840.1.113733.1.9.4", "failInfo"),
+    ("2.16.840.1.113733.1.9.5", "senderNonce"),
+    ("2.16.840.1.113733.1.9.6", "recipientNonce"),
+    ("2.16.840.1.113733.1.9.7", "transactionID"),
+    ("1.3.6.1.5.5.7.24.1", "failInfoText"),
+    ("2.23.136.1.1.1", "ldsSecurityObject"),
+    ("2.23.136.1.1.5", "id-AA"),
+    ("0.4.0.127.0.7.2.2.1.1", "id-PK-DH"),
+    ("0.4.0.127.0.7.2.2.1.2", "id-PK-ECDH"),
+    ("0.4.0.127.0.7.2.2.2", "id-TA"),
+    ("0.4.0.127.0.7.2.2.3.1.1", "id-CA-DH-3DES-CBC-CBC"),
+    ("0.4.0.127.0.7.2.2.3.1.2", "id-CA-DH-AES-CBC-CMAC-128"),
+    ("0.4.0.127.0.7.2.2.3.1.3", "id-CA-DH-AES-CBC-CMAC-192"),
+    ("0.4.0.127.0.7.2.2.3.1.4", "id-CA-DH-AES-CBC-CMAC-256"),
+    ("0.4.0.127.0.7.2.2.3.2.1", "id-CA-ECDH-3DES-CBC-CBC"),
+    ("0.4.0.127.0.7.2.2.3.2.2", "id-CA-ECDH-AES-CBC-CMAC-128"),
+    ("0.4.0.127.0.7.2.2.3.2.3", "id-CA-ECDH-AES-CBC-CMAC-192"),
+    ("0.4.0.127.0.7.2.2.3.2.4", "id-CA-ECDH-AES-CBC-CMAC-256"),
+    ("0.4.0.127.0.7.2.2.4.1.2", "id-PACE-DH-GM-AES-CBC-CMAC-128"),
+    ("0.4.0.127.0.7.2.2.4.2.2", "id-PACE-ECDH-GM-AES-CBC-CMAC-128"),
+    ("0.4.0.127.0.7.2.2.4.2.3", "id-PACE-ECDH-GM-AES-CBC-CMAC-192"),
+    ("0.4.0.127.0.7.2.2.4.2.4", "id-PACE-ECDH-GM-AES-CBC-CMAC-256"),
+    ("0.4.0.127.0.7.2.2.4.6.2", "id-PACE-ECDH-CAM-AES-CBC-CMAC-128"),
+    ("0.4.0.127.0.7.2.2.4.6.3", "id-PACE-ECDH-CAM-AES-CBC-CMAC-192"),
+    ("0.4.0.127.0.7.2.2.4.6.4", "id-PACE-ECDH-CAM-AES-CBC-CMAC-256"),
+];
+
+/// Names for object identifiers loaded from a user-provided file, such as MIB object names.
+#[derive(Clone, Debug, Default)]
+pub struct OidMap {
+    names: Vec<(String, String)>,
+}
+
+impl OidMap {
+    /// Reads lines holding a dotted OID and a name in either order, such as `snmptranslate -Tz` output.
+    pub(crate) fn parse(text: &str) -> Self {
+        let names = text
+            .lines()
+            .filter(|line| !line.trim_start().starts_with('#'))
+            .filter_map(|line| {
+                let tokens: Vec<&str> = line
+                    .split(|c: char| c.is_whitespace() || c == '=' || c == ',')
+                    .map(|token| token.trim_matches('"'))
+                    .filter(|token| !token.is_empty())
+                    .collect();
+                let [first, second] = tokens[..] else {
+                    return None;
+                };
+                let (oid, name) = if is_dotted(first) { (first, second) } else { (second, first) };
+                is_dotted(oid).then(|| (oid.trim_start_matches('.').to_string(), name.to_string()))
+            })
+            .collect();
+        OidMap { names }
+    }
+
+    /// Returns the name of the longest mapped prefix of the OID followed by the remaining arcs, such as `ifDescr.2`.
+    pub(crate) fn lookup(&self, oid: &ObjectIdentifier) -> Option<String> {
+        let dotted = oid.to_string();
+        self.names
+            .iter()
+            .filter_map(|(prefix, name)| {
+                let rest = dotted.strip_prefix(prefix.as_str())?;
+                (rest.is_empty() || rest.starts_with('.')).then(|| (prefix.len(), format!("{}{}", name, rest)))
+            })
+            .max_by_key(|(length, _)| *length)
+            .map(|(_, name)| name)
+    }
+}
+
+fn is_dotted(token: &str) -> bool {
+    let token = token.trim_start_matches('.');
+    !token.is_empty() && token.split('.').all(|arc| !arc.is_empty() && arc.bytes().all(|byte| byte.is_ascii_digit()))
+}
+
+/// Returns a human readable name of the object identifier if it is known.
+pub(crate) fn oid_name(oid: &ObjectIdentifier) -> Option<&'static str> {
+    let dotted = oid.to_string();
+    EXTRA_NAMES
+        .iter()
+        .find(|(known, _)| *known == dotted)
+        .map(|(_, name)| *name)
+        .or_else(|| DB.by_oid(oid))
+}
+
+/// Returns the name of the object identifier without the `id-xx-` prefix, or the dotted form.
+pub(crate) fn short_oid_name(oid: &ObjectIdentifier) -> String {
+    match oid_name(oid) {
+        Some(name) => {
+            let name = name.strip_prefix("id-").unwrap_or(name);
+            match name.split_once('-') {
+                Some((prefix, rest)) if prefix.len() <= 3 && !rest.is_empty() => rest.to_string(),
+                _ => name.to_string(),
+            }
+        }
+        None => oid.to_string(),
+    }
+}
@@ -0,0 +1,175 @@
+use der::Tag;
+use crate::asn1_der::{ASN1Node, ASN1Value};
+use crate::schema::{is_context, SchemaOptions};
+use crate::schema::cms::{is_content_info, label_attributes, label_content_info};
+use crate::schema::keys;
+use crate::schema::keys::label_algorithm_identifier;
+use crate::schema::oid::short_oid_name;
+use crate::schema::x509;
+
+/// Recognizes a PKCS#12 `PFX` at the node.
+pub(crate) fn recognize(node: &mut ASN1Node, options: &SchemaOptions) -> bool {
+    if !is_pfx(node) {
+        return false;
+    }
+    label_pfx(node, options);
+    true
+}
+
+/// `PFX ::= SEQUENCE { version INTEGER {v3(3)}, authSafe ContentInfo, macData MacData OPTIONAL }`
+pub(crate) fn is_pfx(node: &ASN1Node) -> bool {
+    let children = node.children();
+    node.tag == Tag::Sequence
+        && (2..=3).contains(&children.len())
+        && children[0].as_u128() == Some(3)
+        && is_content_info(&children[1])
+}
+
+fn label_pfx(node: &mut ASN1Node, options: &SchemaOptions) {
+    node.set_label("PFX");
+    node.set_annotation("PKCS#12");
+    let children = node.children_mut();
+    children[0].set_label("version");
+
+    let auth_safe = &mut children[1];
+    label_content_info(auth_safe);
+    auth_safe.set_label("authSafe");
+    if let Some(safe) = content_octets_child(auth_safe) {
+        label_authenticated_safe(safe, options);
+    }
+
+    if let Some(mac_data) = children.get_mut(2) {
+        label_mac_data(mac_data);
+    }
+}
+
+/// Returns the structure nested in the OCTET STRING of an `id-data` ContentInfo.
+fn content_octets_child(content_info: &mut ASN1Node) -> Option<&mut ASN1Node> {
+    let content = content_info.children_mut().get_mut(1)?;
+    let octets = content.children_mut().first_mut()?;
+    match &mut octets.value {
+        ASN1Value::OctetString(inner, _) => Some(inner.as_mut()),
+        _ => None,
+    }
+}
+
+/// `AuthenticatedSafe ::= SEQUENCE OF ContentInfo`, the content is plain, encrypted or enveloped SafeContents.
+fn label_authenticated_safe(node: &mut ASN1Node, options: &SchemaOptions) {
+    node.set_label("AuthenticatedSafe");
+    for (i, content_info) in node.children_mut().iter_mut().enumerate() {
+        if !is_content_info(content_info) {
+            continue;
+        }
+        label_content_info(content_info);
+        content_info.set_label(&format!("safe #{}", i + 1));
+        if let Some(safe_contents) = content_octets_child(content_info) {
+            label_safe_contents(safe_contents, options);
+        } else if let Some(safe_contents) = decrypted_safe_contents(content_info) {
+            label_safe_contents(safe_contents, options);
+        }
+    }
+}
+
+/// Finds SafeContents decrypted from `EncryptedData.encryptedContentInfo.encryptedContent`.
+fn decrypted_safe_contents(content_info: &mut ASN1Node) -> Option<&mut ASN1Node> {
+    let encrypted_data = content_info.children_mut().get_mut(1)?.children_mut().first_mut()?;
+    let content_info = encrypted_data.children_mut().get_mut(1)?;
+    let encrypted_content = content_info.children_mut().get_mut(2)?;
+    match encrypted_content.children_mut() {
+        [inner] if inner.tag == Tag::Sequence => Some(inner),
+        _ => None,
+    }
+}
+
+/// `SafeContents ::= SEQUENCE OF SafeBag`
+fn label_safe_contents(node: &mut ASN1Node, options: &SchemaOptions) {
+    node.set_label("SafeContents");
+    for (i, bag) in node.children_mut().iter_mut().enumerate() {
+        label_safe_bag(bag, i + 1, options);
+    }
+}
+
+/// `SafeBag ::= SEQUENCE { bagId OBJECT IDENTIFIER, bagValue [0] EXPLICIT, bagAttributes SET OPTIONAL }`
+fn label_safe_bag(node: &mut ASN1Node, number: usize, options: &SchemaOptions) {
+    let Some(bag_id) = node.children().first().and_then(ASN1Node::as_oid) else {
+        return;
+    };
+    let bag_type = short_oid_name(&bag_id);
+    node.set_label(&format!("{} #{}", bag_type, number));
+
+    let children = node.children_mut();
+    children[0].set_label("bagId");
+    if let Some(value) = children.get_mut(1) {
+        value.set_label("bagValue");
+        if let Some(inner) = value.children_mut().first_mut() {
+            match bag_type.as_str() {
+                "keyBag" | "pkcs8ShroudedKeyBag" => {
+                    keys::recognize(inner, options);
+                }
+                "certBag" => label_cert_bag(inner),
+                "safeContentsBag" => label_safe_contents(inner, options),
+                _ => {}
+            }
+        }
+    }
+    if let Some(attributes) = children.get_mut(2) {
+        label_attributes(attributes, "bagAttributes");
+    }
+
+    let summary = node
+        .children()
+        .get(2)
+        .map(|attributes| {
+            attributes
+                .children()
+                .iter()
+                .filter_map(|attribute| Some(format!("{}: {}", attribute.label.as_ref()?, attribute.annotation.as_ref()?)))
+                .collect::<Vec<String>>()
+                .join(", ")
+        })
+        .unwrap_or_default();
+    if !summary.is_empty() {
+        node.set_annotation(summary);
+    }
+}
+
+/// `CertBag ::= SEQUENCE { certId OBJECT IDENTIFIER, certValue [0] EXPLICIT OCTET STRING }`
+fn label_cert_bag(node: &mut ASN1Node) {
+    node.set_label("CertBag");
+    let children = node.children_mut();
+    if let Some(cert_id) = children.get_mut(0) {
+        cert_id.set_label("certId");
+    }
+    let Some(value) = children.get_mut(1).filter(|value| is_context(value, 0)) else {
+        return;
+    };
+    value.set_label("certValue");
+    let Some(octets) = value.children_mut().first_mut() else {
+        return;
+    };
+    if let ASN1Value::OctetString(certificate, _) = &mut octets.value {
+        x509::recognize(certificate);
+    }
+}
+
+/// `MacData ::= SEQUENCE { mac DigestInfo, macSalt OCTET STRING, iterations INTEGER DEFAULT 1 }`
+fn label_mac_data(node: &mut ASN1Node) {
+    node.set_label("macData");
+    let children = node.children_mut();
+    if let Some(mac) = children.get_mut(0) {
+        mac.set_label("mac");
+        let fields = mac.children_mut();
+        if let Some(algorithm) = fields.get_mut(0) {
+            label_algorithm_identifier(algorithm, "digestAlgorithm");
+        }
+        if let Some(digest) = fields.get_mut(1) {
+            digest.set_label("digest");
+        }
+    }
+    if let Some(salt) = children.get_mut(1) {
+        salt.set_label("macSalt");
+    }
+    if let Some(iterations) = children.get_mut(2) {
+        iterations.set_label("iterations");
+    }
+}
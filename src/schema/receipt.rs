@@ -0,0 +1,154 @@
+use der::Tag;
+use crate::asn1_der::ASN1Node;
+use crate::schema::{label_children, show_as_bytes};
+
+/// Attribute types of the App Store receipt payload.
+const RECEIPT_FIELDS: &[(u128, &str)] = &[
+    (2, "bundle_id"),
+    (3, "application_version"),
+    (4, "opaque_value"),
+    (5, "sha1_hash"),
+    (12, "receipt_creation_date"),
+    (17, "in_app"),
+    (19, "original_application_version"),
+    (21, "expiration_date"),
+];
+
+/// Attribute types of an in-app purchase receipt.
+const IN_APP_FIELDS: &[(u128, &str)] = &[
+    (1701, "quantity"),
+    (1702, "product_id"),
+    (1703, "transaction_id"),
+    (1704, "purchase_date"),
+    (1705, "original_transaction_id"),
+    (1706, "original_purchase_date"),
+    (1708, "expires_date"),
+    (1711, "web_order_line_item_id"),
+    (1712, "cancellation_date"),
+    (1713, "is_trial_period"),
+    (1719, "is_in_intro_offer_period"),
+];
+
+/// `Payload ::= SET OF ReceiptAttribute`
+pub(crate) fn is_receipt(node: &ASN1Node) -> bool {
+    node.tag == Tag::Set && !node.children().is_empty() && node.children().iter().all(is_receipt_attribute)
+}
+
+/// `ReceiptAttribute ::= SEQUENCE { type INTEGER, version INTEGER, value OCTET STRING }`
+fn is_receipt_attribute(node: &ASN1Node) -> bool {
+    match node.children() {
+        [kind, version, value] => {
+            node.tag == Tag::Sequence
+                && kind.as_u128().is_some()
+                && version.tag == Tag::Integer
+                && value.tag == Tag::OctetString
+        }
+        _ => false,
+    }
+}
+
+/// Labels the attributes of an App Store receipt and annotates it with the bundle id and version.
+pub(crate) fn label_receipt(node: &mut ASN1Node) {
+    node.set_label("Receipt");
+    let mut purchases = 0;
+    for attribute in node.children_mut() {
+        if label_attribute(attribute, RECEIPT_FIELDS) == Some("in_app") {
+            purchases += 1;
+            attribute.set_label(&format!("in_app #{}", purchases));
+        }
+    }
+    let summary = [field_text(node, "bundle_id"), field_text(node, "application_version")]
+        .into_iter()
+        .flatten()
+        .chain(Some(format!("{} in-app purchase(s)", purchases)))
+        .collect::<Vec<String>>();
+    node.set_annotation(summary.join(", "));
+}
+
+/// `InAppReceipt ::= SET OF ReceiptAttribute`
+fn label_in_app_receipt(node: &mut ASN1Node) -> Option<String> {
+    if !is_receipt(node) {
+        return None;
+    }
+    node.set_label("InAppReceipt");
+    for attribute in node.children_mut() {
+        label_attribute(attribute, IN_APP_FIELDS);
+    }
+    let summary = [field_text(node, "product_id"), field_text(node, "purchase_date")]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<String>>()
+        .join(", ");
+    node.set_annotation(summary.clone());
+    Some(summary)
+}
+
+/// Labels a receipt attribute with the name of its type and annotates it with its value, returns the name.
+fn label_attribute(node: &mut ASN1Node, fields: &[(u128, &'static str)]) -> Option<&'static str> {
+    label_children(node, &["type", "version", "value"]);
+    let kind = node.children()[0].as_u128()?;
+    let name = fields.iter().find(|(known, _)| *known == kind).map(|(_, name)| *name);
+    node.set_label(&name.map(str::to_string).unwrap_or_else(|| format!("attribute {}", kind)));
+
+    let value = &mut node.children_mut()[2];
+    let annotation = match name {
+        Some("in_app") => value.children_mut().first_mut().and_then(label_in_app_receipt),
+        Some("opaque_value" | "sha1_hash") => {
+            show_as_bytes(value);
+            None
+        }
+        _ => value.children().first().and_then(|inner| inner.as_string().or_else(|| inner.as_u128().map(|n| n.to_string()))),
+    };
+    if let Some(annotation) = annotation {
+        node.set_annotation(annotation);
+    }
+    name
+}
+
+/// Returns the text of the named attribute of a receipt.
+fn field_text(node: &ASN1Node, name: &str) -> Option<String> {
+    node.children()
+        .iter()
+        .find(|attribute| attribute.label.as_deref() == Some(name))
+        .and_then(|attribute| attribute.annotation.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::asn1_der::{parse_asn1, view_lines};
+    use crate::schema::{annotate, SchemaOptions};
+
+    fn annotated(data: &[u8]) -> Vec<String> {
+        let mut root = parse_asn1(data, 0).unwrap();
+        annotate(&mut root, &SchemaOptions::default());
+        view_lines(&root)
+    }
+
+    #[test]
+    fn labels_receipts() {
+        let lines = annotated(include_bytes!("../../tests/data/receipt.p7"));
+        assert_eq!(lines[12], "Receipt: SET (item(s): 9) (com.example.app, 1.2, 2 in-app purchase(s))");
+        assert_eq!(lines[13], "bundle_id: SEQUENCE (field(s): 3) (com.example.app)");
+        assert_eq!(lines[14], "type: INTEGER 2");
+        assert_eq!(lines[16], "value: OCTET STRING ");
+        assert_eq!(lines[18], "application_version: SEQUENCE (field(s): 3) (1.2)");
+        assert_eq!(lines[26], "value: OCTET STRING 300102");
+        assert_eq!(lines[31], "receipt_creation_date: SEQUENCE (field(s): 3) (2023-05-02T00:00:00Z)");
+        assert_eq!(lines[106], "original_application_version: SEQUENCE (field(s): 3) (1.0)");
+        assert_eq!(lines[111], "attribute 8: SEQUENCE (field(s): 3) (x)");
+    }
+
+    #[test]
+    fn labels_in_app_purchases() {
+        let lines = annotated(include_bytes!("../../tests/data/receipt.p7"));
+        assert_eq!(lines[36], "in_app #1: SEQUENCE (field(s): 3) (com.example.coins, 2023-05-01T10:00:00Z)");
+        assert_eq!(lines[40], "InAppReceipt: SET (item(s): 6) (com.example.coins, 2023-05-01T10:00:00Z)");
+        assert_eq!(lines[41], "quantity: SEQUENCE (field(s): 3) (1)");
+        assert_eq!(lines[46], "product_id: SEQUENCE (field(s): 3) (com.example.coins)");
+        assert_eq!(lines[51], "transaction_id: SEQUENCE (field(s): 3) (1000000123)");
+        assert_eq!(lines[56], "purchase_date: SEQUENCE (field(s): 3) (2023-05-01T10:00:00Z)");
+        assert_eq!(lines[61], "is_in_intro_offer_period: SEQUENCE (field(s): 3) (0)");
+        assert_eq!(lines[66], "attribute 1730: SEQUENCE (field(s): 3) (7)");
+        assert_eq!(lines[71], "in_app #2: SEQUENCE (field(s): 3) (com.example.coins, 2023-05-01T10:00:00Z)");
+    }
+}
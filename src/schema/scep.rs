@@ -0,0 +1,95 @@
+use der::asn1::ObjectIdentifier;
+use crate::asn1_der::ASN1Node;
+
+const ID_MESSAGE_TYPE: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.113733.1.9.2");
+const ID_PKI_STATUS: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.113733.1.9.3");
+const ID_FAIL_INFO: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.113733.1.9.4");
+
+/// SCEP (RFC 8894) `messageType` values, sent as printable decimal strings.
+const MESSAGE_TYPES: &[(&str, &str)] = &[
+    ("3", "CertRep"),
+    ("17", "RenewalReq"),
+    ("18", "UpdateReq"),
+    ("19", "PKCSReq"),
+    ("20", "CertPoll"),
+    ("21", "GetCert"),
+    ("22", "GetCRL"),
+];
+const PKI_STATUSES: &[(&str, &str)] = &[("0", "SUCCESS"), ("2", "FAILURE"), ("3", "PENDING")];
+const FAIL_INFOS: &[(&str, &str)] = &[
+    ("0", "badAlg"),
+    ("1", "badMessageCheck"),
+    ("2", "badRequest"),
+    ("3", "badTime"),
+    ("4", "badCertId"),
+];
+
+/// Names the value of a SCEP `messageType`, `pkiStatus` or `failInfo` authenticated attribute.
+pub(crate) fn attribute_value_name(oid: &ObjectIdentifier, value: &str) -> Option<String> {
+    let names = match *oid {
+        ID_MESSAGE_TYPE => MESSAGE_TYPES,
+        ID_PKI_STATUS => PKI_STATUSES,
+        ID_FAIL_INFO => FAIL_INFOS,
+        _ => return None,
+    };
+    let name = names.iter().find(|(known, _)| *known == value).map(|(_, name)| *name)?;
+    Some(format!("{} ({})", name, value))
+}
+
+/// Summarizes a SCEP `pkiMessage` from the labelled `signedAttrs` of its signer, such as `CertRep FAILURE badRequest`.
+pub(crate) fn message_summary(signed_attributes: &ASN1Node) -> Option<String> {
+    let value = |name: &str| {
+        signed_attributes
+            .children()
+            .iter()
+            .find(|attribute| attribute.label.as_deref() == Some(name))
+            .and_then(|attribute| attribute.annotation.as_deref())
+            .and_then(|annotation| annotation.split(' ').next())
+            .map(str::to_string)
+    };
+    let message_type = value("messageType")?;
+    let summary = [Some(message_type), value("pkiStatus"), value("failInfo")]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<String>>()
+        .join(" ");
+    Some(format!("SCEP {}", summary))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asn1_der::{parse_asn1, view_lines};
+    use crate::schema::{annotate, SchemaOptions};
+
+    fn annotated(data: &[u8]) -> Vec<String> {
+        let mut root = parse_asn1(data, 0).unwrap();
+        annotate(&mut root, &SchemaOptions::default());
+        view_lines(&root)
+    }
+
+    #[test]
+    fn names_attribute_values() {
+        assert_eq!(attribute_value_name(&ID_MESSAGE_TYPE, "19").unwrap(), "PKCSReq (19)");
+        assert_eq!(attribute_value_name(&ID_PKI_STATUS, "3").unwrap(), "PENDING (3)");
+        assert_eq!(attribute_value_name(&ID_FAIL_INFO, "4").unwrap(), "badCertId (4)");
+        assert_eq!(attribute_value_name(&ID_PKI_STATUS, "1"), None);
+        assert_eq!(attribute_value_name(&ID_MESSAGE_TYPE.parent().unwrap(), "3"), None);
+    }
+
+    #[test]
+    fn labels_cert_rep_messages() {
+        let lines = annotated(include_bytes!("../../tests/data/scep-certrep.p7"));
+        assert_eq!(
+            lines[3],
+            "SignedData: SEQUENCE (field(s): 5) (SCEP CertRep FAILURE badRequest, 1 certificate(s), 1 signer(s))"
+        );
+        assert_eq!(lines[12], "ContentInfo: SEQUENCE (field(s): 2) (envelopedData)");
+        assert_eq!(lines[55], "signerInfo #1: SEQUENCE (field(s): 6) (CN=CA, serial 5)");
+        assert_eq!(lines[71], "messageType: SEQUENCE (field(s): 2) (CertRep (3))");
+        assert_eq!(lines[75], "pkiStatus: SEQUENCE (field(s): 2) (FAILURE (2))");
+        assert_eq!(lines[79], "failInfo: SEQUENCE (field(s): 2) (badRequest (2))");
+        assert_eq!(lines[83], "transactionID: SEQUENCE (field(s): 2) (ABC123)");
+        assert_eq!(lines[87], "senderNonce: SEQUENCE (field(s): 2) (05050505050505050505050505050505)");
+    }
+}
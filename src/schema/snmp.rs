@@ -0,0 +1,584 @@
+use der::asn1::ObjectIdentifier;
+use der::Tag;
+use crate::asn1_der::{ASN1Node, ASN1Value};
+use crate::schema::oid::oid_name;
+use crate::schema::{label_children, show_as_text, show_as_value, signed_integer, text, SchemaOptions};
+
+const VERSIONS: [(u128, &str); 3] = [(0, "SNMPv1"), (1, "SNMPv2c"), (3, "SNMPv3")];
+
+const ERROR_STATUSES: [&str; 19] = [
+    "noError",
+    "tooBig",
+    "noSuchName",
+    "badValue",
+    "readOnly",
+    "genErr",
+    "noAccess",
+    "wrongType",
+    "wrongLength",
+    "wrongEncoding",
+    "wrongValue",
+    "noCreation",
+    "inconsistentValue",
+    "resourceUnavailable",
+    "commitFailed",
+    "undoFailed",
+    "authorizationError",
+    "notWritable",
+    "inconsistentName",
+];
+
+const GENERIC_TRAPS: [&str; 7] = [
+    "coldStart",
+    "warmStart",
+    "linkDown",
+    "linkUp",
+    "authenticationFailure",
+    "egpNeighborLoss",
+    "enterpriseSpecific",
+];
+
+const SECURITY_MODELS: [(u128, &str); 4] = [(1, "SNMPv1"), (2, "SNMPv2c"), (3, "USM"), (4, "TSM")];
+
+const SNMP_TRAP_OID: &str = "1.3.6.1.6.3.1.1.4.1.0";
+
+/// Recognizes an SNMPv1/v2c `Message` or an SNMPv3 `SNMPv3Message` at the node.
+pub(crate) fn recognize(node: &mut ASN1Node, options: &SchemaOptions) -> bool {
+    if is_community_message(node) {
+        label_community_message(node, options);
+    } else if is_v3_message(node) {
+        label_v3_message(node, options);
+    } else {
+        return false;
+    }
+    true
+}
+
+/// `Message ::= SEQUENCE { version INTEGER { version-1(0), version-2c(1) }, community OCTET STRING, data PDUs }`
+fn is_community_message(node: &ASN1Node) -> bool {
+    match node.children() {
+        [version, community, pdu] => {
+            node.tag == Tag::Sequence
+                && matches!(version.as_u128(), Some(0 | 1))
+                && community.tag == Tag::OctetString
+                && pdu_name(pdu).is_some()
+        }
+        _ => false,
+    }
+}
+
+/// `SNMPv3Message ::= SEQUENCE { msgVersion, msgGlobalData HeaderData, msgSecurityParameters OCTET STRING, msgData ScopedPduData }`
+fn is_v3_message(node: &ASN1Node) -> bool {
+    match node.children() {
+        [version, header, parameters, data] => {
+            node.tag == Tag::Sequence
+                && version.as_u128() == Some(3)
+                && header.tag == Tag::Sequence
+                && header.children().len() == 4
+                && parameters.tag == Tag::OctetString
+                && matches!(data.tag, Tag::Sequence | Tag::OctetString)
+        }
+        _ => false,
+    }
+}
+
+fn pdu_name(node: &ASN1Node) -> Option<&'static str> {
+    let Tag::ContextSpecific { number, constructed: true } = node.tag else {
+        return None;
+    };
+    match number.value() {
+        0 => Some("GetRequest"),
+        1 => Some("GetNextRequest"),
+        2 => Some("Response"),
+        3 => Some("SetRequest"),
+        4 => Some("Trap"),
+        5 => Some("GetBulkRequest"),
+        6 => Some("InformRequest"),
+        7 => Some("SNMPv2-Trap"),
+        8 => Some("Report"),
+        _ => None,
+    }
+}
+
+fn label_community_message(node: &mut ASN1Node, options: &SchemaOptions) {
+    node.set_label("Message");
+    label_children(node, &["version", "community", "data"]);
+    let fields = node.children_mut();
+    let version = annotate_version(&mut fields[0]);
+    show_as_text(&mut fields[1]);
+    let community = text(&fields[1]);
+    let summary = label_pdu(&mut fields[2], options);
+    node.set_annotation(format!("{} community {:?}, {}", version, community, summary));
+}
+
+fn label_v3_message(node: &mut ASN1Node, options: &SchemaOptions) {
+    node.set_label("SNMPv3Message");
+    label_children(node, &["msgVersion", "msgGlobalData", "msgSecurityParameters", "msgData"]);
+    let fields = node.children_mut();
+    annotate_version(&mut fields[0]);
+    let level = label_header_data(&mut fields[1]);
+
+    let mut user = None;
+    if let Some(usm) = fields[2].children_mut().first_mut() {
+        user = label_usm_security_parameters(usm);
+    }
+
+    let data = &mut fields[3];
+    let summary = if data.tag == Tag::OctetString {
+        data.set_label("encryptedPDU");
+        "encrypted PDU".to_string()
+    } else {
+        data.set_label("plaintext");
+        label_scoped_pdu(data, options)
+    };
+    node.set_annotation(match user {
+        Some(user) => format!("SNMPv3 user {:?} {}, {}", user, level, summary),
+        None => format!("SNMPv3 {}, {}", level, summary),
+    });
+}
+
+fn annotate_version(node: &mut ASN1Node) -> &'static str {
+    let name = node.as_u128().and_then(|version| {
+        VERSIONS.iter().find(|(known, _)| *known == version).map(|(_, name)| *name)
+    });
+    let name = name.unwrap_or("SNMP");
+    node.set_annotation(name);
+    name
+}
+
+/// `HeaderData ::= SEQUENCE { msgID, msgMaxSize, msgFlags OCTET STRING (SIZE(1)), msgSecurityModel }`
+fn label_header_data(node: &mut ASN1Node) -> String {
+    label_children(node, &["msgID", "msgMaxSize", "msgFlags", "msgSecurityModel"]);
+    let fields = node.children_mut();
+    let flags = fields[2].octets().and_then(|flags| flags.first().copied()).unwrap_or_default();
+    let level = match flags & 0x03 {
+        0x03 => "authPriv",
+        0x01 => "authNoPriv",
+        _ => "noAuthNoPriv",
+    };
+    let flags_text = if flags & 0x04 != 0 { format!("{}, reportable", level) } else { level.to_string() };
+    fields[2].set_annotation(flags_text);
+    let model = fields[3].as_u128().and_then(|model| {
+        SECURITY_MODELS.iter().find(|(known, _)| *known == model).map(|(_, name)| *name)
+    });
+    if let Some(model) = model {
+        fields[3].set_annotation(model);
+    }
+    level.to_string()
+}
+
+/// `UsmSecurityParameters ::= SEQUENCE { msgAuthoritativeEngineID, ...Boots, ...Time, msgUserName, msgAuthenticationParameters, msgPrivacyParameters }`
+fn label_usm_security_parameters(node: &mut ASN1Node) -> Option<String> {
+    if node.tag != Tag::Sequence || node.children().len() != 6 {
+        return None;
+    }
+    node.set_label("UsmSecurityParameters");
+    label_children(node, &[
+        "msgAuthoritativeEngineID",
+        "msgAuthoritativeEngineBoots",
+        "msgAuthoritativeEngineTime",
+        "msgUserName",
+        "msgAuthenticationParameters",
+        "msgPrivacyParameters",
+    ]);
+    let fields = node.children_mut();
+    annotate_engine_id(&mut fields[0]);
+    show_as_text(&mut fields[3]);
+    let user = text(&fields[3]);
+    node.set_annotation(format!("user {:?}", user));
+    Some(user)
+}
+
+/// Annotates an RFC 3411 `SnmpEngineID` with the private enterprise number of its vendor.
+fn annotate_engine_id(node: &mut ASN1Node) {
+    show_as_value(node);
+    let enterprise = match node.octets() {
+        Some([first, second, third, fourth, _, ..]) => {
+            u32::from_be_bytes([*first, *second, *third, *fourth]) & 0x7fff_ffff
+        }
+        _ => return,
+    };
+    node.set_annotation(format!("enterprise {}", enterprise));
+}
+
+/// `ScopedPDU ::= SEQUENCE { contextEngineID OCTET STRING, contextName OCTET STRING, data ANY }`
+fn label_scoped_pdu(node: &mut ASN1Node, options: &SchemaOptions) -> String {
+    label_children(node, &["contextEngineID", "contextName", "data"]);
+    let fields = node.children_mut();
+    if let Some(engine) = fields.first_mut() {
+        annotate_engine_id(engine);
+    }
+    if let Some(context) = fields.get_mut(1) {
+        show_as_text(context);
+    }
+    match fields.get_mut(2) {
+        Some(pdu) if pdu_name(pdu).is_some() => label_pdu(pdu, options),
+        _ => String::new(),
+    }
+}
+
+/// Labels a PDU and returns a short summary of it.
+fn label_pdu(node: &mut ASN1Node, options: &SchemaOptions) -> String {
+    let name = pdu_name(node).unwrap_or_default();
+    node.set_label(name);
+    let summary = match name {
+        "Trap" => label_trap_pdu(node, options),
+        "GetBulkRequest" => {
+            if let Some(id) = node.children_mut().first_mut() {
+                annotate_signed(id);
+            }
+            label_children(node, &["request-id", "non-repeaters", "max-repetitions", "variable-bindings"]);
+            let count = label_variable_bindings(node, 3, options);
+            format!("GetBulkRequest #{}, {} varbind(s)", request_id(node), count)
+        }
+        _ => {
+            if let Some(id) = node.children_mut().first_mut() {
+                annotate_signed(id);
+            }
+            label_children(node, &["request-id", "error-status", "error-index", "variable-bindings"]);
+            let count = label_variable_bindings(node, 3, options);
+            let status = node.children_mut().get_mut(1).and_then(|status| {
+                let name = status.as_u128().and_then(|code| ERROR_STATUSES.get(code as usize))?;
+                status.set_annotation(*name);
+                Some(*name)
+            });
+            let index = node.children().get(2).and_then(ASN1Node::as_u128).unwrap_or_default();
+            let mut summary = format!("{} #{}", name, request_id(node));
+            match status {
+                Some("noError") | None => {}
+                Some(status) => summary.push_str(&format!(" {} at {}", status, index)),
+            }
+            summary.push_str(&format!(", {} varbind(s)", count));
+            if name == "SNMPv2-Trap" || name == "InformRequest" {
+                if let Some(trap) = trap_oid(node, options) {
+                    summary.push_str(&format!(", {}", trap));
+                }
+            }
+            summary
+        }
+    };
+    node.set_annotation(summary.clone());
+    summary
+}
+
+/// `Trap-PDU ::= [4] IMPLICIT SEQUENCE { enterprise, agent-addr, generic-trap, specific-trap, time-stamp, variable-bindings }`
+fn label_trap_pdu(node: &mut ASN1Node, options: &SchemaOptions) -> String {
+    label_children(node, &["enterprise", "agent-addr", "generic-trap", "specific-trap", "time-stamp", "variable-bindings"]);
+    let count = label_variable_bindings(node, 5, options);
+    let fields = node.children_mut();
+    if let Some(enterprise) = fields.first_mut() {
+        annotate_oid(enterprise, options);
+    }
+    let agent = fields.get_mut(1).and_then(decode_smi_value).map(|(_, agent)| agent).unwrap_or_default();
+    let generic = fields.get_mut(2).and_then(|generic| {
+        let name = generic.as_u128().and_then(|code| GENERIC_TRAPS.get(code as usize))?;
+        generic.set_annotation(*name);
+        Some(*name)
+    });
+    if let Some(time) = fields.get_mut(4) {
+        decode_smi_value(time);
+    }
+    let specific = node.children().get(3).and_then(ASN1Node::as_u128).unwrap_or_default();
+    let generic = match generic {
+        Some("enterpriseSpecific") | None => format!("enterpriseSpecific {}", specific),
+        Some(generic) => generic.to_string(),
+    };
+    format!("Trap {} from {}, {} varbind(s)", generic, agent, count)
+}
+
+/// `VarBindList ::= SEQUENCE OF VarBind` where `VarBind ::= SEQUENCE { name ObjectName, value ObjectSyntax }`
+fn label_variable_bindings(node: &mut ASN1Node, position: usize, options: &SchemaOptions) -> usize {
+    let Some(list) = node.children_mut().get_mut(position) else {
+        return 0;
+    };
+    for varbind in list.children_mut() {
+        label_varbind(varbind, options);
+    }
+    list.children().len()
+}
+
+fn label_varbind(node: &mut ASN1Node, options: &SchemaOptions) {
+    node.set_label("VarBind");
+    label_children(node, &["name", "value"]);
+    let fields = node.children_mut();
+    let Some(oid) = fields.first().and_then(ASN1Node::as_oid) else {
+        return;
+    };
+    let name = mib_name(&oid, options);
+    annotate_oid(&mut fields[0], options);
+    let value = fields.get_mut(1).map(|value| render_value(value, options)).unwrap_or_default();
+    node.set_annotation(format!("{} = {}", name, value));
+}
+
+/// Converts a varbind value for display and returns it in `OID = value` form.
+fn render_value(node: &mut ASN1Node, options: &SchemaOptions) -> String {
+    match node.tag {
+        Tag::Integer => {
+            annotate_signed(node);
+            signed_integer(node).map(|value| value.to_string()).unwrap_or_else(|| node.display_value())
+        }
+        Tag::OctetString => {
+            show_as_value(node);
+            node.display_value()
+        }
+        Tag::ObjectIdentifier => {
+            annotate_oid(node, options);
+            node.as_oid().map(|oid| mib_name(&oid, options)).unwrap_or_default()
+        }
+        Tag::Null => "NULL".to_string(),
+        Tag::Application { .. } => match decode_smi_value(node) {
+            Some((kind, text)) => {
+                node.set_label(kind);
+                text
+            }
+            None => node.display_value(),
+        },
+        Tag::ContextSpecific { number, constructed: false } => {
+            let exception = match number.value() {
+                0 => "noSuchObject",
+                1 => "noSuchInstance",
+                2 => "endOfMibView",
+                _ => return node.display_value(),
+            };
+            node.set_label(exception);
+            node.value = ASN1Value::Null;
+            node.expandable = false;
+            exception.to_string()
+        }
+        _ => node.display_value(),
+    }
+}
+
+/// Decodes an SMI application type such as `Counter32` or `TimeTicks` and returns its name and text.
+fn decode_smi_value(node: &mut ASN1Node) -> Option<(&'static str, String)> {
+    let Tag::Application { number, constructed: false } = node.tag else {
+        return None;
+    };
+    let bytes = match &node.children().first()?.value {
+        ASN1Value::Utf8String(bytes) => bytes.clone(),
+        _ => return None,
+    };
+    let unsigned = (bytes.len() <= 9).then(|| bytes.iter().fold(0u128, |value, &byte| value << 8 | byte as u128));
+    let (kind, text) = match (number.value(), unsigned) {
+        (0, _) if bytes.len() == 4 => {
+            let address = bytes.iter().map(u8::to_string).collect::<Vec<String>>().join(".");
+            node.set_annotation(address.clone());
+            node.value = ASN1Value::Bytes(bytes);
+            ("IpAddress", address)
+        }
+        (4, _) => {
+            node.value = ASN1Value::Bytes(bytes.clone());
+            ("Opaque", hex::encode(bytes))
+        }
+        (1 | 2 | 3 | 6, Some(value)) => {
+            node.value = ASN1Value::Integer(value);
+            match number.value() {
+                1 => ("Counter32", value.to_string()),
+                2 => ("Gauge32", value.to_string()),
+                6 => ("Counter64", value.to_string()),
+                _ => {
+                    node.set_annotation(time_ticks(value));
+                    ("TimeTicks", time_ticks(value))
+                }
+            }
+        }
+        _ => return None,
+    };
+    node.expandable = false;
+    Some((kind, text))
+}
+
+/// Formats hundredths of a second as `[<days>d ]h:mm:ss.cc`.
+fn time_ticks(ticks: u128) -> String {
+    let (days, rest) = (ticks / 8_640_000, ticks % 8_640_000);
+    let time = format!(
+        "{}:{:02}:{:02}.{:02}",
+        rest / 360_000,
+        rest / 6_000 % 60,
+        rest / 100 % 60,
+        rest % 100
+    );
+    if days > 0 {
+        format!("{}d {}", days, time)
+    } else {
+        time
+    }
+}
+
+/// Returns the value of `snmpTrapOID.0` from the variable bindings of a notification.
+fn trap_oid(node: &ASN1Node, options: &SchemaOptions) -> Option<String> {
+    node.children().get(3)?.children().iter().find_map(|varbind| match varbind.children() {
+        [name, value] if name.as_oid()?.to_string() == SNMP_TRAP_OID => value.as_oid().map(|oid| mib_name(&oid, options)),
+        _ => None,
+    })
+}
+
+fn request_id(node: &ASN1Node) -> String {
+    node.children()
+        .first()
+        .map(|id| signed_integer(id).map(|id| id.to_string()).unwrap_or_else(|| id.display_value()))
+        .unwrap_or_default()
+}
+
+/// Annotates an INTEGER holding a negative value, which is otherwise shown as two's complement hex.
+fn annotate_signed(node: &mut ASN1Node) {
+    if let (ASN1Value::BigInteger(_), Some(value)) = (&node.value, signed_integer(node)) {
+        node.set_annotation(value.to_string());
+    }
+}
+
+/// Annotates an OBJECT IDENTIFIER with its MIB name when one is known.
+fn annotate_oid(node: &mut ASN1Node, options: &SchemaOptions) {
+    let Some(oid) = node.as_oid() else {
+        return;
+    };
+    let name = mib_name(&oid, options);
+    if name != oid.to_string() {
+        node.set_annotation(name);
+    }
+}
+
+/// Names an OID from the user-provided OID map, or the built-in names with the instance suffix kept.
+fn mib_name(oid: &ObjectIdentifier, options: &SchemaOptions) -> String {
+    if let Some(name) = options.oid_map.lookup(oid) {
+        return name;
+    }
+    if let Some(name) = oid_name(oid) {
+        return name.to_string();
+    }
+    let dotted = oid.to_string();
+    let parent = dotted
+        .rsplit_once('.')
+        .and_then(|(parent, instance)| Some((ObjectIdentifier::new(parent).ok()?, instance)));
+    match parent {
+        Some((parent, instance)) => match oid_name(&parent) {
+            Some(name) => format!("{}.{}", name, instance),
+            None => dotted,
+        },
+        None => dotted,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asn1_der::{parse_asn1, view_lines};
+    use crate::schema::annotate;
+    use crate::schema::oid::OidMap;
+
+    // GetResponse carrying each SMI application type
+    const RESPONSE: &str = concat!(
+        "3081f102010104067075626c6963a281e302014d0201020201023081d7301d06082b0601020101010004114c696e7578",
+        "20726f7574657220352e3130301006082b060102010103004304075bcd153013060a2b060102010202010a02410500ff",
+        "ffffff3015060b2b060102011f010101060246060100000000053015060d2b06010201041401010a00000140040a0000",
+        "013012060a2b06010201020201050242043b9aca00301606082b06010201010200060a2b06010401bf0803020a300e06",
+        "0a2b06010201010901020180003014060a2b0601020102020106020406001122334455300f060a2b0601020102020107",
+        "020201ff",
+    );
+
+    fn annotated(data: &str, options: &SchemaOptions) -> Vec<String> {
+        let mut root = parse_asn1(&hex::decode(data).unwrap(), 0).unwrap();
+        annotate(&mut root, options);
+        view_lines(&root)
+    }
+
+    #[test]
+    fn labels_v1_traps() {
+        let lines = annotated(
+            concat!(
+                "303b02010004067075626c6963a42e06082b06010401bf08044004c0a801050201020201004303057e403011300f060a",
+                "2b060102010202010102020102",
+            ),
+            &SchemaOptions::default(),
+        );
+        assert_eq!(lines[0], "Message: SEQUENCE (field(s): 3) (SNMPv1 community \"public\", Trap linkDown from 192.168.1.5, 1 varbind(s))");
+        assert_eq!(lines[1], "version: INTEGER 0 (SNMPv1)");
+        assert_eq!(lines[5], "agent-addr: APPLICATION [0] (primitive) c0a80105 (192.168.1.5)");
+        assert_eq!(lines[6], "generic-trap: INTEGER 2 (linkDown)");
+        assert_eq!(lines[8], "time-stamp: APPLICATION [3] (primitive) 360000 (1:00:00.00)");
+    }
+
+    #[test]
+    fn labels_v2c_traps() {
+        let lines = annotated(
+            concat!(
+                "3044020101040770726976617465a736020163020100020100302b301006082b060102010103004304018b8264301706",
+                "0a2b06010603010104010006092b0601060301010503",
+            ),
+            &SchemaOptions::default(),
+        );
+        assert_eq!(lines[0], "Message: SEQUENCE (field(s): 3) (SNMPv2c community \"private\", SNMPv2-Trap #99, 2 varbind(s), linkDown)");
+        assert_eq!(lines[3], "SNMPv2-Trap: CONTEXT-SPECIFIC [7] (constructed)  (SNMPv2-Trap #99, 2 varbind(s), linkDown)");
+        assert_eq!(lines[12], "name: OBJECT IDENTIFIER 1.3.6.1.6.3.1.1.4.1.0 (snmpTrapOID.0)");
+        assert_eq!(lines[13], "value: OBJECT IDENTIFIER 1.3.6.1.6.3.1.1.5.3 (linkDown)");
+    }
+
+    #[test]
+    fn labels_v3_usm_messages() {
+        let lines = annotated(
+            concat!(
+                "3073020103300f02021092020300ffe3040107020103042f302d040d80001f88800101010101010101020105020204d2",
+                "0405616c696365040c0000000000000000000000000400302c040d80001f888001010101010101010400a01902010102",
+                "0100020100300e300c06082b060102010105000500",
+            ),
+            &SchemaOptions::default(),
+        );
+        assert_eq!(lines[0], "SNMPv3Message: SEQUENCE (field(s): 4) (SNMPv3 user \"alice\" authPriv, GetRequest #1, 1 varbind(s))");
+        assert_eq!(lines[5], "msgFlags: OCTET STRING 07 (authPriv, reportable)");
+        assert_eq!(lines[6], "msgSecurityModel: INTEGER 3 (USM)");
+        assert_eq!(lines[8], "UsmSecurityParameters: SEQUENCE (field(s): 6) (user \"alice\")");
+        assert_eq!(lines[9], "msgAuthoritativeEngineID: OCTET STRING 80001f88800101010101010101 (enterprise 8072)");
+        assert_eq!(lines[18], "GetRequest: CONTEXT-SPECIFIC [0] (constructed)  (GetRequest #1, 1 varbind(s))");
+    }
+
+    #[test]
+    fn labels_encrypted_v3_messages() {
+        let lines = annotated(
+            concat!(
+                "3057020103300f02021093020300ffe3040103020103042f302d040d80001f88800101010101010101020105020204d2",
+                "0405616c696365040c00000000000000000000000004000410deadbeefdeadbeefdeadbeefdeadbeef",
+            ),
+            &SchemaOptions::default(),
+        );
+        assert_eq!(lines[0], "SNMPv3Message: SEQUENCE (field(s): 4) (SNMPv3 user \"alice\" authPriv, encrypted PDU)");
+        assert_eq!(lines.last().unwrap(), "encryptedPDU: OCTET STRING deadbeefdeadbeefdeadbeefdeadbeef");
+    }
+
+    #[test]
+    fn decodes_smi_values() {
+        let lines = annotated(RESPONSE, &SchemaOptions::default());
+        assert_eq!(lines[3], "Response: CONTEXT-SPECIFIC [2] (constructed)  (Response #77 noSuchName at 2, 10 varbind(s))");
+        assert_eq!(lines[13], "TimeTicks: APPLICATION [3] (primitive) 123456789 (14d 6:56:07.89)");
+        assert_eq!(lines[16], "Counter32: APPLICATION [1] (primitive) 4294967295");
+        assert_eq!(lines[19], "Counter64: APPLICATION [6] (primitive) 1099511627781");
+        assert_eq!(lines[22], "IpAddress: APPLICATION [0] (primitive) 0a000001 (10.0.0.1)");
+        assert_eq!(lines[25], "Gauge32: APPLICATION [2] (primitive) 1000000000");
+        assert_eq!(lines[31], "noSuchObject: CONTEXT-SPECIFIC [0] (primitive) Null");
+        assert_eq!(lines[37], "value: INTEGER 0xff (-1)");
+    }
+
+    #[test]
+    fn formats_time_ticks() {
+        assert_eq!(time_ticks(0), "0:00:00.00");
+        assert_eq!(time_ticks(360000), "1:00:00.00");
+        assert_eq!(time_ticks(25920100), "3d 0:00:01.00");
+        assert_eq!(time_ticks(u32::MAX as u128), "497d 2:27:52.95");
+    }
+
+    #[test]
+    fn names_instances() {
+        let oid = |text: &str| text.parse::<ObjectIdentifier>().unwrap();
+        let options = SchemaOptions {
+            oid_map: OidMap::parse("1.3.6.1.2.1.2.2.1.10 ifInOctets\nifHCInOctets = .1.3.6.1.2.1.31.1.1.1.6\n"),
+            ..SchemaOptions::default()
+        };
+        assert_eq!(mib_name(&oid("1.3.6.1.2.1.2.2.1.10.2"), &options), "ifInOctets.2");
+        assert_eq!(mib_name(&oid("1.3.6.1.2.1.31.1.1.1.6.12.1"), &options), "ifHCInOctets.12.1");
+        assert_eq!(mib_name(&oid("1.3.6.1.2.1.1.3.0"), &options), "sysUpTime.0");
+        assert_eq!(mib_name(&oid("1.3.6.1.2.1.2.2.1.10.2"), &SchemaOptions::default()), "1.3.6.1.2.1.2.2.1.10.2");
+
+        let lines = annotated(RESPONSE, &options);
+        assert_eq!(lines[14], "VarBind: SEQUENCE (field(s): 2) (ifInOctets.2 = 4294967295)");
+        assert_eq!(lines[17], "VarBind: SEQUENCE (field(s): 2) (ifHCInOctets.2 = 1099511627781)");
+    }
+}
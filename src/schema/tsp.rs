@@ -0,0 +1,331 @@
+use der::Tag;
+use crate::asn1_der::ASN1Node;
+use crate::schema::cms::{is_content_info, label_content_info};
+use crate::schema::{flag_names, implicit_octets, is_context};
+use crate::schema::keys::label_algorithm_identifier;
+use crate::schema::oid::short_oid_name;
+use crate::schema::x509::{format_general_name, label_extensions};
+
+const PKI_STATUSES: [&str; 6] = [
+    "granted",
+    "grantedWithMods",
+    "rejection",
+    "waiting",
+    "revocationWarning",
+    "revocationNotification",
+];
+
+/// Bits of `PKIFailureInfo` shared by RFC 3161 and CMP (RFC 4210).
+const PKI_FAILURE_INFO: [&str; 27] = [
+    "badAlg",
+    "badMessageCheck",
+    "badRequest",
+    "badTime",
+    "badCertId",
+    "badDataFormat",
+    "wrongAuthority",
+    "incorrectData",
+    "missingTimeStamp",
+    "badPOP",
+    "certRevoked",
+    "certConfirmed",
+    "wrongIntegrity",
+    "badRecipientNonce",
+    "timeNotAvailable",
+    "unacceptedPolicy",
+    "unacceptedExtension",
+    "addInfoNotAvailable",
+    "badSenderNonce",
+    "badCertTemplate",
+    "signerNotTrusted",
+    "transactionIdInUse",
+    "unsupportedVersion",
+    "notAuthorized",
+    "systemUnavail",
+    "systemFailure",
+    "duplicateCertReq",
+];
+
+/// Recognizes an RFC 3161 `TimeStampReq`, `TimeStampResp` or a bare `TSTInfo` at the node.
+pub(crate) fn recognize(node: &mut ASN1Node) -> bool {
+    if is_time_stamp_resp(node) {
+        label_time_stamp_resp(node);
+    } else if is_tst_info(node) {
+        label_tst_info(node);
+    } else if is_time_stamp_req(node) {
+        label_time_stamp_req(node);
+    } else {
+        return false;
+    }
+    true
+}
+
+/// `MessageImprint ::= SEQUENCE { hashAlgorithm AlgorithmIdentifier, hashedMessage OCTET STRING }`
+fn is_message_imprint(node: &ASN1Node) -> bool {
+    match node.children() {
+        [algorithm, hash] => node.tag == Tag::Sequence && algorithm.tag == Tag::Sequence && hash.tag == Tag::OctetString,
+        _ => false,
+    }
+}
+
+/// `TimeStampReq ::= SEQUENCE { version INTEGER {v1(1)}, messageImprint, reqPolicy, nonce, certReq, extensions [0] }`
+fn is_time_stamp_req(node: &ASN1Node) -> bool {
+    let children = node.children();
+    node.tag == Tag::Sequence
+        && children.len() >= 2
+        && children[0].as_u128() == Some(1)
+        && is_message_imprint(&children[1])
+}
+
+/// `TimeStampResp ::= SEQUENCE { status PKIStatusInfo, timeStampToken ContentInfo OPTIONAL }`
+fn is_time_stamp_resp(node: &ASN1Node) -> bool {
+    let children = node.children();
+    node.tag == Tag::Sequence
+        && (1..=2).contains(&children.len())
+        && is_pki_status_info(&children[0])
+        && children.get(1).map(is_content_info).unwrap_or(true)
+}
+
+/// `PKIStatusInfo ::= SEQUENCE { status PKIStatus, statusString PKIFreeText OPTIONAL, failInfo BIT STRING OPTIONAL }`
+fn is_pki_status_info(node: &ASN1Node) -> bool {
+    let children = node.children();
+    node.tag == Tag::Sequence
+        && (1..=3).contains(&children.len())
+        && children[0].as_u128().map(|status| status < PKI_STATUSES.len() as u128).unwrap_or(false)
+        && children[1..].iter().all(|field| matches!(field.tag, Tag::Sequence | Tag::BitString))
+}
+
+/// `TSTInfo ::= SEQUENCE { version, policy, messageImprint, serialNumber, genTime, accuracy, ordering, nonce, tsa [0], extensions [1] }`
+fn is_tst_info(node: &ASN1Node) -> bool {
+    let children = node.children();
+    node.tag == Tag::Sequence
+        && children.len() >= 5
+        && children[0].as_u128() == Some(1)
+        && children[1].tag == Tag::ObjectIdentifier
+        && is_message_imprint(&children[2])
+        && children[3].tag == Tag::Integer
+        && children[4].tag == Tag::GeneralizedTime
+}
+
+fn label_time_stamp_req(node: &mut ASN1Node) {
+    node.set_label("TimeStampReq");
+    let mut hash = None;
+    for (i, field) in node.children_mut().iter_mut().enumerate() {
+        match field.tag {
+            Tag::Integer if i == 0 => field.set_label("version"),
+            Tag::Sequence if i == 1 => hash = label_message_imprint(field),
+            Tag::ObjectIdentifier => field.set_label("reqPolicy"),
+            Tag::Integer => field.set_label("nonce"),
+            Tag::Boolean => field.set_label("certReq"),
+            _ if is_context(field, 0) => field.set_label("extensions"),
+            _ => {}
+        }
+    }
+    if let Some(hash) = hash {
+        node.set_annotation(format!("{} message imprint", hash));
+    }
+}
+
+/// Labels a `MessageImprint` and returns the name of its hash algorithm.
+fn label_message_imprint(node: &mut ASN1Node) -> Option<String> {
+    node.set_label("messageImprint");
+    let hash = node.children()[0].children().first().and_then(ASN1Node::as_oid).map(|oid| short_oid_name(&oid));
+    let children = node.children_mut();
+    label_algorithm_identifier(&mut children[0], "hashAlgorithm");
+    children[1].set_label("hashedMessage");
+    if let Some(hash) = &hash {
+        node.set_annotation(hash.clone());
+    }
+    hash
+}
+
+fn label_time_stamp_resp(node: &mut ASN1Node) {
+    node.set_label("TimeStampResp");
+    let children = node.children_mut();
+    let status = label_pki_status_info(&mut children[0], &PKI_STATUSES);
+    if let Some(token) = children.get_mut(1) {
+        label_content_info(token);
+        token.set_label("timeStampToken");
+    }
+    node.set_annotation(status);
+}
+
+/// Labels `PKIStatusInfo` with the given status names and returns the status name.
+pub(crate) fn label_pki_status_info(node: &mut ASN1Node, statuses: &[&str]) -> String {
+    node.set_label("status");
+    let status = node
+        .children()
+        .first()
+        .and_then(ASN1Node::as_u128)
+        .and_then(|status| statuses.get(status as usize))
+        .map(|status| status.to_string())
+        .unwrap_or_default();
+    let mut fail_info = None;
+    for field in node.children_mut() {
+        match field.tag {
+            Tag::Integer => field.set_label("status"),
+            Tag::Sequence => field.set_label("statusString"),
+            Tag::BitString => {
+                field.set_label("failInfo");
+                fail_info = flag_names(field, &PKI_FAILURE_INFO).filter(|names| !names.is_empty());
+                if let Some(names) = &fail_info {
+                    field.set_annotation(names.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+    let status = match fail_info {
+        Some(names) => format!("{} ({})", status, names),
+        None => status,
+    };
+    let text = node
+        .children()
+        .iter()
+        .find(|field| field.tag == Tag::Sequence)
+        .map(|text| text.children().iter().filter_map(ASN1Node::as_string).collect::<Vec<String>>().join(" "))
+        .filter(|text| !text.is_empty());
+    let status = match text {
+        Some(text) => format!("{}: {}", status, text),
+        None => status,
+    };
+    node.set_annotation(status.clone());
+    status
+}
+
+/// Labels the fields of a `TSTInfo` and annotates it with the time stamp.
+pub(crate) fn label_tst_info(node: &mut ASN1Node) {
+    if !is_tst_info(node) {
+        return;
+    }
+    node.set_label("TSTInfo");
+    let mut gen_time = None;
+    for (i, field) in node.children_mut().iter_mut().enumerate() {
+        match i {
+            0 => field.set_label("version"),
+            1 => field.set_label("policy"),
+            2 => {
+                label_message_imprint(field);
+            }
+            3 => field.set_label("serialNumber"),
+            4 => {
+                field.set_label("genTime");
+                gen_time = field.as_string();
+            }
+            _ => match field.tag {
+                Tag::Sequence => label_accuracy(field),
+                Tag::Boolean => field.set_label("ordering"),
+                Tag::Integer => field.set_label("nonce"),
+                _ if is_context(field, 0) => label_tsa(field),
+                _ if is_context(field, 1) => {
+                    field.set_label("extensions");
+                    label_extensions(field);
+                }
+                _ => {}
+            },
+        }
+    }
+    if let Some(gen_time) = gen_time {
+        node.set_annotation(format!("generated at {}", gen_time));
+    }
+}
+
+/// `tsa [0] GeneralName`, shown on one line.
+fn label_tsa(node: &mut ASN1Node) {
+    node.set_label("tsa");
+    let name = match node.children() {
+        [general_name] => format_general_name(general_name),
+        _ => None,
+    };
+    if let Some(name) = name {
+        node.set_annotation(name);
+    }
+}
+
+/// `Accuracy ::= SEQUENCE { seconds INTEGER OPTIONAL, millis [0] IMPLICIT INTEGER OPTIONAL, micros [1] IMPLICIT INTEGER OPTIONAL }`
+fn label_accuracy(node: &mut ASN1Node) {
+    node.set_label("accuracy");
+    let mut parts = Vec::new();
+    for field in node.children_mut() {
+        let (label, unit, value) = match field.tag {
+            Tag::Integer => ("seconds", "s", field.as_u128()),
+            _ if is_context(field, 0) => ("millis", "ms", implicit_integer(field)),
+            _ if is_context(field, 1) => ("micros", "us", implicit_integer(field)),
+            _ => continue,
+        };
+        field.set_label(label);
+        if let Some(value) = value {
+            parts.push(format!("{}{}", value, unit));
+        }
+    }
+    if !parts.is_empty() {
+        node.set_annotation(format!("±{}", parts.join(" ")));
+    }
+}
+
+fn implicit_integer(node: &ASN1Node) -> Option<u128> {
+    implicit_octets(node)
+        .filter(|bytes| bytes.len() <= 16)
+        .map(|bytes| bytes.iter().fold(0, |value, &byte| value << 8 | byte as u128))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::asn1_der::{parse_asn1, view_lines};
+    use crate::schema::{annotate, SchemaOptions};
+
+    fn annotated(data: &[u8]) -> Vec<String> {
+        let mut root = parse_asn1(data, 0).unwrap();
+        annotate(&mut root, &SchemaOptions::default());
+        view_lines(&root)
+    }
+
+    #[test]
+    fn labels_granted_responses() {
+        let lines = annotated(include_bytes!("../../tests/data/timestamp-response.der"));
+        assert_eq!(lines[0], "TimeStampResp: SEQUENCE (field(s): 2) (granted)");
+        assert_eq!(lines[1], "status: SEQUENCE (field(s): 1) (granted)");
+        assert_eq!(lines[3], "timeStampToken: SEQUENCE (field(s): 2) (signedData)");
+        assert_eq!(lines[13], "eContentType: OBJECT IDENTIFIER 1.2.840.113549.1.9.16.1.4 (id-ct-TSTInfo)");
+        assert_eq!(lines[108], "contentType: SEQUENCE (field(s): 2) (TSTInfo)");
+    }
+
+    #[test]
+    fn labels_tst_info() {
+        let lines = annotated(include_bytes!("../../tests/data/timestamp-response.der"));
+        assert_eq!(lines[16], "TSTInfo: SEQUENCE (field(s): 9) (generated at 2026-10-18T20:38:34Z)");
+        assert_eq!(lines[18], "policy: OBJECT IDENTIFIER 1.2.3.4.1");
+        assert_eq!(lines[19], "messageImprint: SEQUENCE (field(s): 2) (sha256)");
+        assert_eq!(lines[24], "serialNumber: INTEGER 2");
+        assert_eq!(lines[26], "accuracy: SEQUENCE (field(s): 3) (±1s 500ms 100us)");
+        assert_eq!(lines[28], "millis: CONTEXT-SPECIFIC [0] (primitive) ");
+        assert_eq!(lines[32], "ordering: BOOLEAN true");
+        assert_eq!(lines[33], "nonce: INTEGER 14449214698147338811");
+        assert_eq!(lines[34], "tsa: CONTEXT-SPECIFIC [0] (constructed)  (CN=TSA)");
+    }
+
+    #[test]
+    fn labels_rejected_responses() {
+        let data = hex::decode("30183016020102300d0c0b756e737570706f7274656403020780").unwrap();
+        assert_eq!(
+            annotated(&data),
+            [
+                "TimeStampResp: SEQUENCE (field(s): 1) (rejection (badAlg): unsupported)",
+                "status: SEQUENCE (field(s): 3) (rejection (badAlg): unsupported)",
+                "status: INTEGER 2",
+                "statusString: SEQUENCE (field(s): 1)",
+                "UTF8String \"unsupported\"",
+                "failInfo: BIT STRING 10000000 (badAlg)",
+            ]
+        );
+    }
+
+    #[test]
+    fn labels_requests() {
+        let lines = annotated(include_bytes!("../../tests/data/timestamp-query.der"));
+        assert_eq!(lines[0], "TimeStampReq: SEQUENCE (field(s): 4) (sha256 message imprint)");
+        assert_eq!(lines[2], "messageImprint: SEQUENCE (field(s): 2) (sha256)");
+        assert_eq!(lines[7], "nonce: INTEGER 14449214698147338811");
+        assert_eq!(lines[8], "certReq: BOOLEAN true");
+    }
+}
@@ -0,0 +1,191 @@
+use der::asn1::ObjectIdentifier;
+use der::Tag;
+use crate::asn1_der::ASN1Node;
+use crate::schema::android::{is_key_description, label_key_description, ID_KEY_DESCRIPTION};
+use crate::schema::keys::label_algorithm_identifier;
+use crate::schema::{implicit_octets, label_children};
+use crate::schema::oid::short_oid_name;
+
+/// Short attribute names used when a distinguished name is shown on a single line.
+const NAME_ATTRIBUTES: &[(&str, &str)] = &[
+    ("2.5.4.3", "CN"),
+    ("2.5.4.4", "SN"),
+    ("2.5.4.5", "serialNumber"),
+    ("2.5.4.6", "C"),
+    ("2.5.4.7", "L"),
+    ("2.5.4.8", "ST"),
+    ("2.5.4.9", "street"),
+    ("2.5.4.10", "O"),
+    ("2.5.4.11", "OU"),
+    ("2.5.4.12", "title"),
+    ("2.5.4.42", "GN"),
+    ("1.2.840.113549.1.9.1", "E"),
+    ("0.9.2342.19200300.100.1.25", "DC"),
+    ("0.9.2342.19200300.100.1.1", "UID"),
+];
+
+/// Recognizes an X.509 certificate at the node.
+pub(crate) fn recognize(node: &mut ASN1Node) -> bool {
+    if !is_certificate(node) {
+        return false;
+    }
+    node.set_label("Certificate");
+    label_certificate(node);
+    true
+}
+
+/// `Certificate ::= SEQUENCE { tbsCertificate, signatureAlgorithm, signatureValue BIT STRING }`
+pub(crate) fn is_certificate(node: &ASN1Node) -> bool {
+    match node.children() {
+        [tbs, algorithm, signature] => {
+            node.tag == Tag::Sequence
+                && tbs.tag == Tag::Sequence
+                && algorithm.tag == Tag::Sequence
+                && algorithm.children().first().and_then(ASN1Node::as_oid).is_some()
+                && signature.tag == Tag::BitString
+                && tbs.children().len() >= 6
+        }
+        _ => false,
+    }
+}
+
+/// Labels the fields of a certificate and annotates it with the subject name.
+pub(crate) fn label_certificate(node: &mut ASN1Node) {
+    let children = node.children_mut();
+    children[0].set_label("tbsCertificate");
+    label_tbs_certificate(&mut children[0]);
+    label_algorithm_identifier(&mut children[1], "signatureAlgorithm");
+    children[2].set_label("signatureValue");
+
+    let subject = node.children()[0]
+        .children()
+        .iter()
+        .find(|child| child.label.as_deref() == Some("subject"))
+        .map(format_name);
+    if let Some(subject) = subject {
+        node.set_annotation(subject);
+    }
+}
+
+fn label_tbs_certificate(node: &mut ASN1Node) {
+    let mut fields = ["serialNumber", "signature", "issuer", "validity", "subject", "subjectPublicKeyInfo"].iter();
+    for child in node.children_mut() {
+        match child.tag {
+            Tag::ContextSpecific { number, .. } => match number.value() {
+                0 => child.set_label("version"),
+                1 => child.set_label("issuerUniqueID"),
+                2 => child.set_label("subjectUniqueID"),
+                3 => {
+                    child.set_label("extensions");
+                    if let Some(extensions) = child.children_mut().first_mut() {
+                        label_extensions(extensions);
+                    }
+                }
+                _ => {}
+            },
+            _ => {
+                let Some(&name) = fields.next() else {
+                    continue;
+                };
+                match name {
+                    "signature" => label_algorithm_identifier(child, name),
+                    "issuer" | "subject" => label_name(child, name),
+                    "validity" => {
+                        child.set_label(name);
+                        label_children(child, &["notBefore", "notAfter"]);
+                    }
+                    "subjectPublicKeyInfo" => label_public_key_info(child),
+                    _ => child.set_label(name),
+                }
+            }
+        }
+    }
+}
+
+pub(crate) fn label_public_key_info(node: &mut ASN1Node) {
+    node.set_label("subjectPublicKeyInfo");
+    let children = node.children_mut();
+    if let Some(algorithm) = children.get_mut(0) {
+        label_algorithm_identifier(algorithm, "algorithm");
+    }
+    if let Some(key) = children.get_mut(1) {
+        key.set_label("subjectPublicKey");
+    }
+}
+
+/// `Extensions ::= SEQUENCE OF Extension`, each labelled with the extension name.
+pub(crate) fn label_extensions(node: &mut ASN1Node) {
+    for extension in node.children_mut() {
+        let Some(oid) = extension.children().first().and_then(ASN1Node::as_oid) else {
+            continue;
+        };
+        extension.set_label(&short_oid_name(&oid));
+        for field in extension.children_mut() {
+            match field.tag {
+                Tag::ObjectIdentifier => field.set_label("extnID"),
+                Tag::Boolean => field.set_label("critical"),
+                Tag::OctetString => {
+                    field.set_label("extnValue");
+                    label_extension_value(&oid, field);
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Labels the DER value of extensions with a dedicated decoder.
+fn label_extension_value(oid: &ObjectIdentifier, node: &mut ASN1Node) {
+    let Some(value) = node.children_mut().first_mut() else {
+        return;
+    };
+    if *oid == ID_KEY_DESCRIPTION && is_key_description(value) {
+        label_key_description(value);
+    }
+}
+
+/// Labels a distinguished name and annotates it with its one line form.
+pub(crate) fn label_name(node: &mut ASN1Node, label: &str) {
+    node.set_label(label);
+    let name = format_name(node);
+    if !name.is_empty() {
+        node.set_annotation(name);
+    }
+}
+
+/// Formats a `GeneralName` on one line, such as `directoryName [4]`, `rfc822Name [1]`, `dNSName [2]` or a URI `[6]`.
+pub(crate) fn format_general_name(node: &ASN1Node) -> Option<String> {
+    if !node.tag.is_context_specific() {
+        return None;
+    }
+    let prefix = match node.tag_number() {
+        4 => return node.children().first().map(format_name),
+        1 => "email:",
+        2 => "DNS:",
+        6 => "URI:",
+        _ => return None,
+    };
+    implicit_octets(node).map(|text| format!("{}{}", prefix, String::from_utf8_lossy(text)))
+}
+
+/// Formats `Name ::= SEQUENCE OF SET OF AttributeTypeAndValue` as `CN=..., O=...`.
+pub(crate) fn format_name(node: &ASN1Node) -> String {
+    node.children()
+        .iter()
+        .flat_map(|rdn| rdn.children())
+        .filter_map(|attribute| match attribute.children() {
+            [kind, value] => {
+                let oid = kind.as_oid()?;
+                let dotted = oid.to_string();
+                let name = NAME_ATTRIBUTES
+                    .iter()
+                    .find(|(known, _)| *known == dotted)
+                    .map(|(_, name)| name.to_string())
+                    .unwrap_or_else(|| short_oid_name(&oid));
+                Some(format!("{}={}", name, value.as_string().unwrap_or_else(|| value.display_value())))
+            }
+            _ => None,
+        })
+        .collect::<Vec<String>>()
+        .join(", ")
+}
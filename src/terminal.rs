@@ -3,7 +3,7 @@ use std::io;
 use std::time::{Duration, Instant};
 use clipboard::{ClipboardContext, ClipboardProvider};
 use crossterm::{event, execute};
-use crossterm::event::{DisableMouseCapture, EnableMouseCapture, Event, KeyCode};
+use crossterm::event::{DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind};
 use crossterm::terminal::{enable_raw_mode, disable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
 use log::trace;
 use tui::{Frame, Terminal};
@@ -42,10 +42,19 @@ pub fn run_terminal_app<B: tui::backend::Backend>(terminal: &mut Terminal<B>, mu
     let mut last_key_press = Instant::now();
     let cooldown_duration = Duration::from_millis(200);
 
+    if app.needs_passphrase() {
+        app.start_passphrase_prompt();
+    }
+
     loop {
         terminal.draw(|f| ui(f, &mut app))?;
 
         if let Event::Key(key) = event::read()? {
+            if app.passphrase_prompt.is_some() {
+                handle_prompt_key(&mut app, key);
+                continue;
+            }
+
             let now = Instant::now();
             if now.duration_since(last_key_press) >= cooldown_duration {
                 match key.code {
@@ -61,6 +70,10 @@ pub fn run_terminal_app<B: tui::backend::Backend>(terminal: &mut Terminal<B>, mu
                     },
                     KeyCode::Char('f') => app.first(),
                     KeyCode::Char('l') => app.last(),
+                    KeyCode::Char('n') => app.next_record(),
+                    KeyCode::Char('N') => app.prev_record(),
+                    KeyCode::Char('p') => app.start_passphrase_prompt(),
+                    KeyCode::Char('i') => app.next_input_format(),
                     _ => {}
                 }
                 last_key_press = now;
@@ -69,16 +82,37 @@ pub fn run_terminal_app<B: tui::backend::Backend>(terminal: &mut Terminal<B>, mu
     }
 }
 
+fn handle_prompt_key(app: &mut App, key: KeyEvent) {
+    if key.kind != KeyEventKind::Press {
+        return;
+    }
+    match key.code {
+        KeyCode::Enter => app.submit_passphrase_prompt(),
+        KeyCode::Esc => app.passphrase_prompt = None,
+        KeyCode::Backspace => {
+            if let Some(prompt) = app.passphrase_prompt.as_mut() {
+                prompt.pop();
+            }
+        }
+        KeyCode::Char(c) => {
+            if let Some(prompt) = app.passphrase_prompt.as_mut() {
+                prompt.push(c);
+            }
+        }
+        _ => {}
+    }
+}
+
 pub fn ui<B: tui::backend::Backend>(f: &mut Frame<B>, app: &mut App) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .margin(2)
-        .constraints([Constraint::Percentage(100)].as_ref())
+        .constraints([Constraint::Min(1), Constraint::Length(1)].as_ref())
         .split(f.size());
 
     let items: Vec<ListItem> = app.view
         .iter_mut()
-        .filter(|node| node.visible == true)
+        .filter(|node| node.visible)
         .map(|node| {
             let content = node.get_view_content();
             ListItem::new(vec![Spans::from(vec![Span::styled(content, Style::default())])])
@@ -92,7 +126,15 @@ pub fn ui<B: tui::backend::Backend>(f: &mut Frame<B>, app: &mut App) {
 
     f.render_stateful_widget(items, chunks[0], &mut app.state);
 
-    let instructions = Paragraph::new("↑↓: Navigate | f: to first | l: to last | Enter: Expand/Collapse | c: Copy Value | q: Quit")
+    let instructions = Paragraph::new("↑↓: Navigate | f: to first | l: to last | Enter: Expand/Collapse | n/N: Next/Prev record | c: Copy Value | p: Passphrase | i: Input format | q: Quit")
         .style(Style::default().fg(Color::Gray));
     f.render_widget(instructions, chunks[0]);
+
+    let status = match (&app.passphrase_prompt, &app.status) {
+        (Some(prompt), _) => format!("Passphrase (Enter: decrypt, Esc: cancel): {}", "*".repeat(prompt.chars().count())),
+        (None, Some(status)) => status.clone(),
+        (None, None) => String::new(),
+    };
+    let status = Paragraph::new(status).style(Style::default().fg(Color::Cyan));
+    f.render_widget(status, chunks[1]);
 }